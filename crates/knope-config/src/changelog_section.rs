@@ -14,6 +14,10 @@ pub struct ChangelogSection {
     pub(crate) types: Vec<CustomChangeType>,
 }
 
+/// Combine `changelog_sections` with the default sections.
+///
+/// Defaults which aren't overridden (by source or by name) come first, then every configured
+/// section in order. So listing a default section by name alone moves it to that position.
 pub fn convert_to_versioning(changelog_sections: Vec<ChangelogSection>) -> Sections {
     let mut defaults = Sections::defaults();
     let mut sections = Vec::with_capacity(changelog_sections.len());
@@ -1,6 +1,13 @@
-use std::ops::Not;
+use std::{collections::BTreeMap, ops::Not};
 
-use knope_versioning::{UnknownFile, VersionedFileConfig};
+use knope_versioning::{
+    changes::RequireScope,
+    release_notes,
+    release_notes::SectionName,
+    semver::{Version, VersionFormat, ZeroMajorBreaking},
+    versioned_file::FileType,
+    UnknownFile, VersionedFileConfig,
+};
 use relative_path::RelativePathBuf;
 use serde::{Deserialize, Serialize};
 use toml::Spanned;
@@ -13,33 +20,167 @@ pub struct Package {
     /// The files which define the current version of the package.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub versioned_files: Vec<Spanned<VersionedFile>>,
-    /// The path to the `CHANGELOG.md` file (if any) to be updated when running [`Step::PrepareRelease`].
-    pub changelog: Option<RelativePathBuf>,
+    /// The path to the `CHANGELOG.md` file(s) (if any) to be updated when running [`Step::PrepareRelease`].
+    pub changelog: Option<Changelogs>,
+    /// The path to a JSON file (if any) to write the details of each new release to when running
+    /// [`Step::PrepareRelease`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_json: Option<RelativePathBuf>,
+    /// Customize the notes of each new release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_notes: Option<ReleaseNotes>,
     /// Optional scopes that can be used to filter commits when running [`Step::PrepareRelease`].
     pub scopes: Option<Vec<String>>,
+    /// Whether conventional commits need a scope to apply to this package
+    #[serde(default, skip_serializing_if = "RequireScope::is_default")]
+    pub require_scope: RequireScope,
     /// Extra sections that should be added to the changelog from custom footers in commit messages.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extra_changelog_sections: Vec<ChangelogSection>,
     /// The assets, if any, to upload with each release
     pub assets: Option<Assets>,
+    /// Upload a file with the checksums of the `assets`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksums: Option<Checksums>,
+    /// Sign the `assets` (and checksums) with Sigstore, uploading a bundle next to each
+    #[serde(default, skip_serializing_if = "<&bool>::not")]
+    pub sigstore: bool,
     #[serde(default, skip_serializing_if = "<&bool>::not")]
     pub ignore_go_major_versioning: bool,
+    /// Whether a breaking change on a 0.x version bumps to 1.0.0 or only to the next minor version
+    #[serde(default, skip_serializing_if = "ZeroMajorBreaking::is_default")]
+    pub zero_major_breaking: ZeroMajorBreaking,
+    /// Only use commits which change files in `paths` (or the package's directory)
+    #[serde(default, skip_serializing_if = "<&bool>::not")]
+    pub filter_commits_by_path: bool,
+    /// Glob patterns of files belonging to this package, for `filter_commits_by_path`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    /// Every package in the same group is always released with the same new version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_group: Option<String>,
+}
+
+/// How to write the notes of each release, in changelogs and on forges.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReleaseNotes {
+    /// A file which lays out the notes of each release, with placeholders like `{{version}}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<RelativePathBuf>,
+    /// Values for custom placeholders in the `template`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Custom layouts for each change, the first one that applies is used
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub change_templates: Vec<ChangeTemplate>,
+    /// The format of each release title, like `{version} ({date})`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// A layout for each change in the release notes, like `- $summary ($commit_hash)`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ChangeTemplate {
+    /// Used for changes in every section
+    Global(String),
+    /// Only used for changes in `sections`
+    Scoped {
+        template: String,
+        sections: Vec<SectionName>,
+    },
+}
+
+impl From<ChangeTemplate> for release_notes::ChangeTemplate {
+    fn from(template: ChangeTemplate) -> Self {
+        match template {
+            ChangeTemplate::Global(template) => Self {
+                template,
+                sections: Vec::new(),
+            },
+            ChangeTemplate::Scoped { template, sections } => Self { template, sections },
+        }
+    }
+}
+
+/// One or more changelog files for a package.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Changelogs {
+    Single(RelativePathBuf),
+    Multiple(Vec<RelativePathBuf>),
+}
+
+impl Changelogs {
+    /// Use the simplest representation of `paths`, or `None` if there aren't any.
+    #[must_use]
+    pub fn from_paths(mut paths: Vec<RelativePathBuf>) -> Option<Self> {
+        match paths.len() {
+            0 => None,
+            1 => paths.pop().map(Self::Single),
+            _ => Some(Self::Multiple(paths)),
+        }
+    }
+}
+
+impl From<Changelogs> for Vec<RelativePathBuf> {
+    fn from(changelogs: Changelogs) -> Self {
+        match changelogs {
+            Changelogs::Single(path) => vec![path],
+            Changelogs::Multiple(paths) => paths,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum VersionedFile {
     Simple(RelativePathBuf),
+    /// A file where the version is written in a specific format, like PEP 440
+    Formatted {
+        path: RelativePathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        dependency: Option<String>,
+        version_format: VersionFormat,
+    },
     Dependency {
         path: RelativePathBuf,
         dependency: String,
     },
+    /// A file that Knope doesn't natively support, with a version stored at `key`
+    Custom {
+        path: RelativePathBuf,
+        file_type: FileType,
+        key: String,
+    },
+    /// Any file, where the version is found using a `search` regex containing `{version}`
+    Regex {
+        path: RelativePathBuf,
+        search: String,
+    },
 }
 
 impl From<VersionedFileConfig> for VersionedFile {
     fn from(config: VersionedFileConfig) -> Self {
-        let (path, dependency) = (config.as_path(), config.dependency);
-        if let Some(dependency) = dependency {
+        let path = config.as_path();
+        if let Some((file_type, key)) = config.custom_key() {
+            Self::Custom {
+                path,
+                file_type,
+                key: key.to_string(),
+            }
+        } else if let Some(search) = config.search() {
+            Self::Regex {
+                path,
+                search: search.to_string(),
+            }
+        } else if config.version_format() != VersionFormat::default() {
+            Self::Formatted {
+                path,
+                version_format: config.version_format(),
+                dependency: config.dependency,
+            }
+        } else if let Some(dependency) = config.dependency {
             Self::Dependency { path, dependency }
         } else {
             Self::Simple(path)
@@ -53,9 +194,21 @@ impl TryFrom<VersionedFile> for VersionedFileConfig {
     fn try_from(value: VersionedFile) -> Result<Self, Self::Error> {
         match value {
             VersionedFile::Simple(path) => VersionedFileConfig::new(path, None),
+            VersionedFile::Formatted {
+                path,
+                dependency,
+                version_format,
+            } => VersionedFileConfig::new(path, dependency)
+                .map(|config| config.with_version_format(version_format)),
             VersionedFile::Dependency { path, dependency } => {
                 VersionedFileConfig::new(path, Some(dependency))
             }
+            VersionedFile::Custom {
+                path,
+                file_type,
+                key,
+            } => VersionedFileConfig::custom(path, file_type, key),
+            VersionedFile::Regex { path, search } => VersionedFileConfig::regex(path, search),
         }
     }
 }
@@ -71,9 +224,51 @@ pub enum Assets {
 pub struct Asset {
     pub path: RelativePathBuf,
     pub name: Option<String>,
+    /// Strings to replace in `path` and `name`, and what to replace them with
+    #[allow(clippy::zero_sized_map_values)] // There will be more variables
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, AssetVariable>,
+}
+
+impl From<RelativePathBuf> for Asset {
+    #[allow(clippy::zero_sized_map_values)]
+    fn from(path: RelativePathBuf) -> Self {
+        Self {
+            path,
+            name: None,
+            variables: BTreeMap::new(),
+        }
+    }
+}
+
+/// A value which can replace part of an asset's `path` or `name`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AssetVariable {
+    /// The version being released
+    Version,
 }
 
 impl Asset {
+    /// Replace any `variables` in `path` and `name` for a release of `version`.
+    #[must_use]
+    #[allow(clippy::zero_sized_map_values)]
+    pub fn resolve_variables(&self, version: &Version) -> Self {
+        let mut path = self.path.to_string();
+        let mut name = self.name.clone();
+        for (variable, value) in &self.variables {
+            let value = match value {
+                AssetVariable::Version => version.to_string(),
+            };
+            path = path.replace(variable, &value);
+            name = name.map(|name| name.replace(variable, &value));
+        }
+        Self {
+            path: RelativePathBuf::from(path),
+            name,
+            variables: BTreeMap::new(),
+        }
+    }
+
     /// Get the name of the asset
     ///
     /// # Errors
@@ -93,6 +288,24 @@ impl Asset {
     }
 }
 
+/// A file of SHA-256 checksums for the assets of a release, in the format of `sha256sum`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Checksums {
+    /// The name of the checksums file, defaults to `SHA256SUMS`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Also upload a signature of the checksums file, named like it with `.sig` appended
+    #[serde(default, skip_serializing_if = "<&bool>::not")]
+    pub sign: bool,
+}
+
+impl Checksums {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("SHA256SUMS")
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error("No asset name set, and name could not be determined from path {path}")]
 pub struct AssetNameError {
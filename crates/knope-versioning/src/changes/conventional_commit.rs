@@ -1,35 +1,69 @@
-use git_conventional::{Commit, Footer, Type};
+use std::{num::NonZeroUsize, panic, thread};
+
+use git_conventional::{Footer, Type};
+use itertools::Itertools;
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use thiserror::Error;
 use tracing::debug;
 
-use super::{Change, ChangeSource, ChangeType};
-use crate::release_notes::Sections;
+use super::{Change, ChangeSource, ChangeType, Commit, Scope};
+use crate::release_notes::{SectionSource, Sections};
+
+/// Below this many commits, parsing isn't worth spreading across threads.
+const PARALLEL_THRESHOLD: usize = 256;
 
 /// Try to parse each commit message as a [conventional commit](https://www.conventionalcommits.org/).
 ///
+/// Large batches of commits are split across threads, the order of the changes is the same either way.
+///
 /// # Filtering
 ///
 /// 1. If the commit message doesn't follow the conventional commit format, it is ignored.
-/// 2. For non-standard change types, only those included will be considered.
+/// 2. For non-standard change types (like `perf`), only those included in `changelog_sections`
+///    will be considered.
 /// 3. For non-standard footers, only those included will be considered.
-pub(crate) fn changes_from_commit_messages<'a, Message: AsRef<str>>(
-    commit_messages: &'a [Message],
-    scopes: Option<&'a Vec<String>>,
-    changelog_sections: &'a Sections,
-) -> impl Iterator<Item = Change> + 'a {
+pub(crate) fn changes_from_commits(
+    commits: &[Commit],
+    scopes: Option<&[Scope]>,
+    changelog_sections: &Sections,
+) -> Vec<Change> {
     if let Some(scopes) = scopes {
         debug!("Only checking commits with scopes: {scopes:?}");
     }
-    commit_messages.iter().flat_map(move |message| {
-        changes_from_commit_message(message.as_ref(), scopes, changelog_sections).into_iter()
+    let parse = |commits: &[Commit]| {
+        commits
+            .iter()
+            .flat_map(|commit| changes_from_commit(commit, scopes, changelog_sections))
+            .collect_vec()
+    };
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    if commits.len() < PARALLEL_THRESHOLD || threads < 2 {
+        return parse(commits);
+    }
+    let chunk_size = (commits.len() + threads - 1) / threads;
+    thread::scope(|scope| {
+        let handles = commits
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || parse(chunk)))
+            .collect_vec();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| panic::resume_unwind(err))
+            })
+            .collect()
     })
 }
 
-fn changes_from_commit_message(
-    commit_message: &str,
-    scopes: Option<&Vec<String>>,
+fn changes_from_commit(
+    Commit { hash, message, .. }: &Commit,
+    scopes: Option<&[Scope]>,
     changelog_sections: &Sections,
 ) -> Vec<Change> {
-    let Some(commit) = Commit::parse(commit_message.trim()).ok() else {
+    let Some(commit) = git_conventional::Commit::parse(message.trim()).ok() else {
         return Vec::new();
     };
     let mut has_breaking_footer = false;
@@ -39,7 +73,7 @@ fn changes_from_commit_message(
         if let Some(scopes) = scopes {
             if !scopes
                 .iter()
-                .any(|s| s.eq_ignore_ascii_case(commit_scope.as_str()))
+                .any(|scope| scope.matches(commit_scope.as_str()))
             {
                 return Vec::new();
             }
@@ -56,10 +90,10 @@ fn changes_from_commit_message(
         changes.push(Change {
             change_type: footer.token().into(),
             description: footer.value().into(),
-            original_source: ChangeSource::ConventionalCommit(format_commit_footer(
-                &commit_summary,
-                footer,
-            )),
+            original_source: ChangeSource::ConventionalCommit {
+                summary: format_commit_footer(&commit_summary, footer),
+                hash: hash.clone(),
+            },
         });
     }
 
@@ -69,6 +103,8 @@ fn changes_from_commit_message(
         ChangeType::Feature
     } else if commit.type_() == Type::FIX {
         ChangeType::Fix
+    } else if let Some(custom_type) = changelog_sections.find_custom_type(commit.type_().as_str()) {
+        ChangeType::Custom(SectionSource::CustomChangeType(custom_type.clone()))
     } else {
         return changes; // The commit description isn't a change itself, only (maybe) footers were.
     };
@@ -76,13 +112,128 @@ fn changes_from_commit_message(
     changes.push(Change {
         change_type: commit_description_change_type,
         description: commit.description().into(),
-        original_source: ChangeSource::ConventionalCommit(commit_summary),
+        original_source: ChangeSource::ConventionalCommit {
+            summary: commit_summary,
+            hash: hash.clone(),
+        },
     });
 
     changes
 }
 
-fn format_commit_summary(commit: &Commit) -> String {
+/// Check that `commit` follows the conventional commit format.
+///
+/// Merge, revert, `fixup!`, and `squash!` commits created by Git are always valid.
+///
+/// # Errors
+///
+/// If the commit message isn't a conventional commit, with the reason why.
+pub fn validate(commit: &Commit) -> Result<(), InvalidCommit> {
+    let message = commit.message.trim();
+    if ["Merge ", "Revert ", "fixup! ", "squash! "]
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+    {
+        return Ok(());
+    }
+    git_conventional::Commit::parse(message)
+        .map(|_| ())
+        .map_err(|err| InvalidCommit::new(commit, err.to_string()))
+}
+
+/// Check that `commit` has a scope, if it's a conventional commit at all.
+///
+/// Commits which aren't conventional commits are never changes, so they're always valid here.
+///
+/// # Errors
+///
+/// If the commit is a conventional commit without a scope.
+pub fn validate_scope(commit: &Commit) -> Result<(), InvalidCommit> {
+    match git_conventional::Commit::parse(commit.message.trim()) {
+        Ok(parsed) if parsed.scope().is_none() => {
+            Err(InvalidCommit::new(commit, String::from("missing a scope")))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+#[error("{}{summary}: {reason}", hash.as_ref().map(|hash| format!("{hash} ")).unwrap_or_default())]
+pub struct InvalidCommit {
+    hash: Option<String>,
+    summary: String,
+    reason: String,
+}
+
+impl InvalidCommit {
+    fn new(commit: &Commit, reason: String) -> Self {
+        Self {
+            hash: commit
+                .hash
+                .as_deref()
+                .map(|hash| hash.chars().take(7).collect()),
+            summary: commit
+                .message
+                .trim()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            reason,
+        }
+    }
+}
+
+/// Split squash-merge commits into the commits that were squashed.
+///
+/// When a commit body contains a bulleted list of conventional commits (like GitHub's default
+/// squash message), each item becomes its own commit, replacing the title.
+/// Any other commit is kept as-is.
+#[must_use]
+pub fn split_squash_merges(commits: Vec<Commit>) -> Vec<Commit> {
+    commits
+        .into_iter()
+        .flat_map(|commit| {
+            let squashed = squashed_messages(&commit.message);
+            if squashed.is_empty() {
+                vec![commit]
+            } else {
+                squashed
+                    .into_iter()
+                    .map(|message| Commit {
+                        hash: commit.hash.clone(),
+                        author: commit.author.clone(),
+                        message,
+                    })
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Every bullet point in the body of `message` which is a conventional commit, including any
+/// lines following each bullet point (as its body and footers).
+fn squashed_messages(message: &str) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    let mut in_item = false;
+    for line in message.trim().lines().skip(1) {
+        if let Some(item) = line.strip_prefix("* ").or_else(|| line.strip_prefix("- ")) {
+            items.push(item.to_string());
+            in_item = true;
+        } else if let Some(current) = items.last_mut().filter(|_| in_item) {
+            current.push('\n');
+            current.push_str(line);
+        }
+    }
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| git_conventional::Commit::parse(item).is_ok())
+        .collect()
+}
+
+fn format_commit_summary(commit: &git_conventional::Commit) -> String {
     let commit_scope = commit
         .scope()
         .map(|s| s.to_string())
@@ -116,7 +267,6 @@ fn format_commit_footer(commit_summary: &str, footer: &Footer) -> String {
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use itertools::Itertools;
     use pretty_assertions::assert_eq;
 
     use super::*;
@@ -127,42 +277,48 @@ mod tests {
 
     #[test]
     fn commit_types() {
-        let commits = &[
+        let commits = [
             "fix: a bug",
             "fix!: a breaking bug fix",
             "feat!: add a feature",
             "feat: add another feature",
-        ];
-        let changes =
-            changes_from_commit_messages(commits, None, &Sections::default()).collect_vec();
+        ]
+        .map(Commit::from);
+        let changes = changes_from_commits(&commits, None, &Sections::default());
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Fix,
                     description: "a bug".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("fix: a bug")),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("fix: a bug"),
+                        hash: None
+                    },
                 },
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "a breaking bug fix".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "fix!: a breaking bug fix"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("fix!: a breaking bug fix"),
+                        hash: None
+                    },
                 },
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "add a feature".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "feat!: add a feature"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("feat!: add a feature"),
+                        hash: None
+                    },
                 },
                 Change {
                     change_type: ChangeType::Feature,
                     description: "add another feature".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "feat: add another feature"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("feat: add another feature"),
+                        hash: None
+                    },
                 }
             ]
         );
@@ -173,31 +329,31 @@ mod tests {
         let commits = [
             "fix: a bug\n\nBREAKING CHANGE: something broke",
             "feat: a features\n\nBREAKING CHANGE: something else broke",
-        ];
-        let changes =
-            changes_from_commit_messages(&commits, None, &Sections::default()).collect_vec();
+        ]
+        .map(Commit::from);
+        let changes = changes_from_commits(&commits, None, &Sections::default());
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "something broke".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("fix: a bug\n\tContaining footer BREAKING CHANGE: something broke")),
+                    original_source: ChangeSource::ConventionalCommit { summary: String::from("fix: a bug\n\tContaining footer BREAKING CHANGE: something broke"), hash: None },
                 },
                 Change {
                     change_type: ChangeType::Fix,
                     description: "a bug".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("fix: a bug")),
+                    original_source: ChangeSource::ConventionalCommit { summary: String::from("fix: a bug"), hash: None },
                 },
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "something else broke".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("feat: a features\n\tContaining footer BREAKING CHANGE: something else broke")),
+                    original_source: ChangeSource::ConventionalCommit { summary: String::from("feat: a features\n\tContaining footer BREAKING CHANGE: something else broke"), hash: None },
                 },
                 Change {
                     change_type: ChangeType::Feature,
                     description: "a features".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from("feat: a features")),
+                    original_source: ChangeSource::ConventionalCommit { summary: String::from("feat: a features"), hash: None },
                 },
             ]
         );
@@ -208,25 +364,27 @@ mod tests {
         let commits = [
             "feat(scope)!: Wrong scope breaking change!",
             "fix: No scope",
-        ];
-        let changes =
-            changes_from_commit_messages(&commits, None, &Sections::default()).collect_vec();
+        ]
+        .map(Commit::from);
+        let changes = changes_from_commits(&commits, None, &Sections::default());
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Breaking,
                     description: "Wrong scope breaking change!".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "feat(scope)!: Wrong scope breaking change!"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("feat(scope)!: Wrong scope breaking change!"),
+                        hash: None
+                    },
                 },
                 Change {
                     change_type: ChangeType::Fix,
                     description: "No scope".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "fix: No scope"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("fix: No scope"),
+                        hash: None
+                    },
                 }
             ]
         );
@@ -238,30 +396,32 @@ mod tests {
             "feat(wrong_scope)!: Wrong scope breaking change!",
             "feat(scope): Scoped feature",
             "fix: No scope",
-        ];
+        ]
+        .map(Commit::from);
 
-        let changes = changes_from_commit_messages(
+        let changes = changes_from_commits(
             &commits,
-            Some(&vec![String::from("scope")]),
+            Some(&[Scope::new(String::from("scope")).unwrap()]),
             &Sections::default(),
-        )
-        .collect_vec();
+        );
         assert_eq!(
             changes,
             vec![
                 Change {
                     change_type: ChangeType::Feature,
                     description: "Scoped feature".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "feat(scope): Scoped feature"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("feat(scope): Scoped feature"),
+                        hash: None
+                    },
                 },
                 Change {
                     change_type: ChangeType::Fix,
                     description: "No scope".into(),
-                    original_source: ChangeSource::ConventionalCommit(String::from(
-                        "fix: No scope"
-                    )),
+                    original_source: ChangeSource::ConventionalCommit {
+                        summary: String::from("fix: No scope"),
+                        hash: None
+                    },
                 },
             ]
         );
@@ -269,15 +429,15 @@ mod tests {
 
     #[test]
     fn custom_footers() {
-        let commits = ["chore: ignored type\n\nignored-footer: ignored\ncustom-footer: hello"];
+        let commits = ["chore: ignored type\n\nignored-footer: ignored\ncustom-footer: hello"]
+            .map(Commit::from);
         let changelog_sections = Sections(vec![(
             "custom section".into(),
             vec![ChangeType::Custom(SectionSource::CommitFooter(
                 "custom-footer".into(),
             ))],
         )]);
-        let changes =
-            changes_from_commit_messages(&commits, None, &changelog_sections).collect_vec();
+        let changes = changes_from_commits(&commits, None, &changelog_sections);
         assert_eq!(
             changes,
             vec![Change {
@@ -285,10 +445,95 @@ mod tests {
                     "custom-footer".into()
                 )),
                 description: "hello".into(),
-                original_source: ChangeSource::ConventionalCommit(String::from(
-                    "chore: ignored type\n\tContaining footer custom-footer: hello"
-                )),
+                original_source: ChangeSource::ConventionalCommit {
+                    summary: String::from(
+                        "chore: ignored type\n\tContaining footer custom-footer: hello"
+                    ),
+                    hash: None
+                },
             }]
         );
     }
+
+    #[test]
+    fn custom_types() {
+        let commits = ["perf: faster", "chore: ignored type"].map(Commit::from);
+        let changelog_sections = Sections(vec![(
+            "Performance".into(),
+            vec![ChangeType::Custom(SectionSource::CustomChangeType(
+                "perf".into(),
+            ))],
+        )]);
+        let changes = changes_from_commits(&commits, None, &changelog_sections);
+        assert_eq!(
+            changes,
+            vec![Change {
+                change_type: ChangeType::Custom(SectionSource::CustomChangeType("perf".into())),
+                description: "faster".into(),
+                original_source: ChangeSource::ConventionalCommit {
+                    summary: String::from("perf: faster"),
+                    hash: None
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn many_commits_keep_their_order() {
+        let commits = (0..PARALLEL_THRESHOLD * 2)
+            .map(|index| Commit::from(format!("fix: fix {index}").as_str()))
+            .collect_vec();
+        let changes = changes_from_commits(&commits, None, &Sections::default());
+        assert_eq!(
+            changes
+                .into_iter()
+                .map(|change| change.description.to_string())
+                .collect_vec(),
+            (0..PARALLEL_THRESHOLD * 2)
+                .map(|index| format!("fix {index}"))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn split_squash_merge() {
+        let commits = vec![
+            Commit::from(
+                "Some PR title (#12)\n\n* feat: a feature\n\n* fix: a fix\n\nChangelog-Note: a note\n\n* not conventional",
+            ),
+            Commit::from("feat: a regular commit\n\n- just a list\n- of things"),
+        ];
+        assert_eq!(
+            split_squash_merges(commits)
+                .into_iter()
+                .map(|commit| commit.message)
+                .collect_vec(),
+            vec![
+                "feat: a feature",
+                "fix: a fix\n\nChangelog-Note: a note",
+                "feat: a regular commit\n\n- just a list\n- of things",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_commits() {
+        assert!(validate(&Commit::from("feat(scope)!: valid")).is_ok());
+        assert!(validate(&Commit::from("Merge branch 'main' into feature")).is_ok());
+        let err = validate(&Commit {
+            hash: Some(String::from("0123456789abcdef")),
+            author: None,
+            message: String::from("Not conventional\n\nWith a body"),
+        })
+        .unwrap_err();
+        assert!(err.to_string().starts_with("0123456 Not conventional: "));
+    }
+
+    #[test]
+    fn validate_scopes() {
+        assert!(validate_scope(&Commit::from("feat(scope): scoped")).is_ok());
+        assert!(validate_scope(&Commit::from("Not conventional")).is_ok());
+        let err = validate_scope(&Commit::from("fix: no scope")).unwrap_err();
+        assert_eq!(err.to_string(), "fix: no scope: missing a scope");
+    }
 }
@@ -0,0 +1,108 @@
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use regex::Regex;
+use thiserror::Error;
+
+use super::Commit;
+
+/// Commits that shouldn't count as changes, like those from bots.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoredCommits {
+    authors: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl IgnoredCommits {
+    /// Ignore commits whose author name or email is in `authors`, or whose message matches any
+    /// of the regexes in `patterns`.
+    ///
+    /// # Errors
+    ///
+    /// If any of `patterns` is not a valid regex
+    pub fn new(authors: Vec<String>, patterns: &[String]) -> Result<Self, InvalidPattern> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|source| InvalidPattern {
+                    pattern: pattern.clone(),
+                    source,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { authors, patterns })
+    }
+
+    #[must_use]
+    pub fn is_ignored(&self, commit: &Commit) -> bool {
+        let author_ignored = commit.author.as_ref().is_some_and(|author| {
+            self.authors.iter().any(|ignored| {
+                ignored.eq_ignore_ascii_case(&author.name)
+                    || ignored.eq_ignore_ascii_case(&author.email)
+            })
+        });
+        author_ignored
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.is_match(&commit.message))
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+#[error("Invalid commit pattern {pattern}: {source}")]
+#[cfg_attr(
+    feature = "miette",
+    diagnostic(
+        code(changes::invalid_commit_pattern),
+        help("Each entry in `ignore_commit_patterns` must be a valid regular expression"),
+        url("https://knope.tech/reference/config-file/steps/prepare-release/")
+    )
+)]
+pub struct InvalidPattern {
+    pattern: String,
+    source: regex::Error,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::changes::Author;
+
+    fn commit(name: &str, email: &str, message: &str) -> Commit {
+        Commit {
+            hash: None,
+            author: Some(Author {
+                name: name.to_string(),
+                email: email.to_string(),
+            }),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn authors() {
+        let ignored = IgnoredCommits::new(
+            vec!["renovate[bot]".to_string(), "bot@example.com".to_string()],
+            &[],
+        )
+        .unwrap();
+        assert!(ignored.is_ignored(&commit("renovate[bot]", "r@example.com", "fix: deps")));
+        assert!(ignored.is_ignored(&commit("Some Bot", "bot@example.com", "fix: deps")));
+        assert!(!ignored.is_ignored(&commit("Human", "human@example.com", "fix: bug")));
+        assert!(!ignored.is_ignored(&Commit::from("fix: unknown author")));
+    }
+
+    #[test]
+    fn patterns() {
+        let ignored = IgnoredCommits::new(Vec::new(), &[r"^chore\(deps\)".to_string()]).unwrap();
+        assert!(ignored.is_ignored(&Commit::from("chore(deps): update")));
+        assert!(!ignored.is_ignored(&Commit::from("fix: not chore(deps)")));
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(IgnoredCommits::new(Vec::new(), &["(".to_string()]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Display, sync::Arc};
+use std::{collections::BTreeMap, fmt::Display, sync::Arc};
 
 pub use changeset::CHANGESET_DIR;
 use git_conventional::FooterToken;
@@ -10,6 +10,11 @@ use crate::{
 
 mod changeset;
 pub mod conventional_commit;
+mod ignore;
+mod scope;
+
+pub use ignore::{IgnoredCommits, InvalidPattern};
+pub use scope::{InvalidScope, RequireScope, Scope};
 
 /// A change to one or more packages.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -26,20 +31,51 @@ impl Change {
     ) -> impl Iterator<Item = Self> + 'a {
         releases
             .iter()
-            .find(|release| *package_name == release.package_name)
+            .find(|release| {
+                // `@changesets/cli` quotes package names like `"@scope/name": minor`
+                let name = release.package_name.trim_matches(|c| c == '"' || c == '\'');
+                package_name.as_ref() == name
+            })
             .into_iter()
-            .flat_map(|release_changes| release_changes.changes.clone().into_iter().map(Self::from))
+            .flat_map(move |release_changes| {
+                release_changes
+                    .changes
+                    .iter()
+                    .map(move |package_change| Self {
+                        change_type: package_change.change_type.clone().into(),
+                        description: package_change.summary.clone(),
+                        original_source: ChangeSource::ChangeFile {
+                            id: package_change.unique_id.clone(),
+                            metadata: metadata(&package_change.unique_id, releases),
+                        },
+                    })
+            })
     }
 }
 
-impl From<changesets::PackageChange> for Change {
-    fn from(package_change: changesets::PackageChange) -> Self {
-        Self {
-            change_type: package_change.change_type.into(),
-            description: package_change.summary,
-            original_source: ChangeSource::ChangeFile(package_change.unique_id),
-        }
-    }
+/// The prefix of front matter keys in a change file which are metadata, like `metadata.issue: 1234`,
+/// instead of the change type of a package.
+pub const METADATA_PREFIX: &str = "metadata.";
+
+/// The metadata in the front matter of the change file `unique_id`, without [`METADATA_PREFIX`].
+///
+/// The changesets crate reads every key as a package, so metadata shows up as "releases" of
+/// packages which start with [`METADATA_PREFIX`].
+fn metadata(
+    unique_id: &changesets::UniqueId,
+    releases: &[changesets::Release],
+) -> BTreeMap<String, String> {
+    releases
+        .iter()
+        .filter_map(|release| {
+            let key = release.package_name.strip_prefix(METADATA_PREFIX)?;
+            let change = release
+                .changes
+                .iter()
+                .find(|change| *change.unique_id == *unique_id)?;
+            Some((key.to_string(), change.change_type.to_string()))
+        })
+        .collect()
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
@@ -124,17 +160,49 @@ impl From<FooterToken<'_>> for ChangeType {
     }
 }
 
+/// A commit which might follow the conventional commit format.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Commit {
+    /// The full hash of the commit, if known, used to link to it from release notes
+    pub hash: Option<String>,
+    pub author: Option<Author>,
+    pub message: String,
+}
+
+impl From<&str> for Commit {
+    fn from(message: &str) -> Self {
+        Self {
+            hash: None,
+            author: None,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Author {
+    pub name: String,
+    pub email: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ChangeSource {
-    ConventionalCommit(String),
-    ChangeFile(Arc<changesets::UniqueId>),
+    ConventionalCommit {
+        summary: String,
+        hash: Option<String>,
+    },
+    ChangeFile {
+        id: Arc<changesets::UniqueId>,
+        /// The metadata in the change file's front matter, like `issue` for `metadata.issue: 1234`
+        metadata: BTreeMap<String, String>,
+    },
 }
 
 impl Display for ChangeSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::ConventionalCommit(commit) => write!(f, "commit {commit}"),
-            Self::ChangeFile(id) => write!(f, "changeset {}", id.to_file_name()),
+            Self::ConventionalCommit { summary, .. } => write!(f, "commit {summary}"),
+            Self::ChangeFile { id, .. } => write!(f, "changeset {}", id.to_file_name()),
         }
     }
 }
@@ -0,0 +1,191 @@
+use std::{fmt, fmt::Debug};
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// An entry in a package's `scopes`, which conventional commit scopes are matched against.
+///
+/// Entries are either:
+/// - An exact scope (like `api`), compared case-insensitively.
+/// - A glob pattern containing `*` or `?` (like `api-*`).
+/// - A regex wrapped in slashes (like `/^api-v\d+$/`).
+#[derive(Clone)]
+pub struct Scope {
+    raw: String,
+    pattern: Option<Regex>,
+}
+
+impl Scope {
+    /// Parse a `scopes` entry.
+    ///
+    /// # Errors
+    ///
+    /// If the entry is a regex or glob pattern which can't be compiled.
+    pub fn new(raw: String) -> Result<Self, InvalidScope> {
+        let pattern = if let Some(regex) = raw
+            .strip_prefix('/')
+            .and_then(|rest| rest.strip_suffix('/'))
+            .filter(|regex| !regex.is_empty())
+        {
+            Some(format!("(?i){regex}"))
+        } else if raw.contains(['*', '?']) {
+            Some(glob_to_regex(&raw))
+        } else {
+            None
+        };
+        let pattern = pattern
+            .map(|pattern| Regex::new(&pattern))
+            .transpose()
+            .map_err(|source| InvalidScope {
+                scope: raw.clone(),
+                source,
+            })?;
+        Ok(Self { raw, pattern })
+    }
+
+    #[must_use]
+    pub fn matches(&self, commit_scope: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => pattern.is_match(commit_scope),
+            None => self.raw.eq_ignore_ascii_case(commit_scope),
+        }
+    }
+}
+
+impl Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.raw, f)
+    }
+}
+
+/// What to do with conventional commits that have no scope, set by a package's `require_scope`.
+///
+/// In the config file, `true` is [`Self::Error`], `false` is [`Self::No`], and `"ignore"` is
+/// [`Self::Ignore`].
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(from = "RequireScopeValue", into = "RequireScopeValue")]
+pub enum RequireScope {
+    /// Unscoped commits apply to the package
+    #[default]
+    No,
+    /// Unscoped commits are an error
+    Error,
+    /// Unscoped commits are skipped
+    Ignore,
+}
+
+impl RequireScope {
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum RequireScopeValue {
+    Bool(bool),
+    Mode(RequireScopeMode),
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum RequireScopeMode {
+    Error,
+    Ignore,
+}
+
+impl From<RequireScopeValue> for RequireScope {
+    fn from(value: RequireScopeValue) -> Self {
+        match value {
+            RequireScopeValue::Bool(false) => Self::No,
+            RequireScopeValue::Bool(true) | RequireScopeValue::Mode(RequireScopeMode::Error) => {
+                Self::Error
+            }
+            RequireScopeValue::Mode(RequireScopeMode::Ignore) => Self::Ignore,
+        }
+    }
+}
+
+impl From<RequireScope> for RequireScopeValue {
+    fn from(value: RequireScope) -> Self {
+        match value {
+            RequireScope::No => Self::Bool(false),
+            RequireScope::Error => Self::Bool(true),
+            RequireScope::Ignore => Self::Mode(RequireScopeMode::Ignore),
+        }
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("(?i)^");
+    for char in glob.chars() {
+        match char {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            other => regex.push_str(&regex::escape(other.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+#[allow(clippy::module_name_repetitions)]
+#[error("Invalid scope {scope}: {source}")]
+#[cfg_attr(
+    feature = "miette",
+    diagnostic(
+        code(changes::invalid_scope),
+        help("Scopes wrapped in `/` must be valid regular expressions"),
+        url("https://knope.tech/reference/config-file/packages/#scopes")
+    )
+)]
+pub struct InvalidScope {
+    scope: String,
+    source: regex::Error,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        let scope = Scope::new(String::from("api")).unwrap();
+        assert!(scope.matches("api"));
+        assert!(scope.matches("API"));
+        assert!(!scope.matches("api-v2"));
+    }
+
+    #[test]
+    fn glob() {
+        let scope = Scope::new(String::from("api-*")).unwrap();
+        assert!(scope.matches("api-v2"));
+        assert!(scope.matches("api-"));
+        assert!(!scope.matches("api"));
+        assert!(!scope.matches("the-api-v2"));
+
+        let scope = Scope::new(String::from("v?.x")).unwrap();
+        assert!(scope.matches("v1.x"));
+        assert!(!scope.matches("v1.y"));
+    }
+
+    #[test]
+    fn regex() {
+        let scope = Scope::new(String::from(r"/^api-v\d+$/")).unwrap();
+        assert!(scope.matches("api-v12"));
+        assert!(!scope.matches("api-vx"));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        assert!(Scope::new(String::from("/(/")).is_err());
+    }
+}
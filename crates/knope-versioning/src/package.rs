@@ -14,12 +14,13 @@ use thiserror::Error;
 use tracing::debug;
 
 use crate::{
-    action::Action,
+    action::{Action, ReleaseTag},
     changes::{
-        conventional_commit::changes_from_commit_messages, Change, ChangeSource, CHANGESET_DIR,
+        conventional_commit::{changes_from_commits, validate_scope},
+        Change, ChangeSource, Commit, InvalidScope, RequireScope, Scope, CHANGESET_DIR,
     },
-    release_notes::{ReleaseNotes, TimeError},
-    semver::{Label, PackageVersions, PreReleaseNotFound, Rule, StableRule, Version},
+    release_notes::{self, ReleaseNotes},
+    semver::{Label, PackageVersions, Rule, RuleError, StableRule, Version, ZeroMajorBreaking},
     versioned_file,
     versioned_file::{cargo, Config, Format, GoVersioning, SetError, VersionedFile},
     PackageNewError::CargoLockNoDependency,
@@ -31,7 +32,13 @@ pub struct Package {
     pub versions: PackageVersions,
     versioned_files: Vec<Config>,
     pub release_notes: ReleaseNotes,
-    scopes: Option<Vec<String>>,
+    scopes: Option<Vec<Scope>>,
+    /// What to do with conventional commits that have no scope
+    pub require_scope: RequireScope,
+    /// What a breaking change does to a 0.x version
+    pub zero_major_breaking: ZeroMajorBreaking,
+    /// The tag of the latest stable release, which new release notes are compared against
+    last_stable_tag: Option<ReleaseTag>,
 }
 
 impl Package {
@@ -50,12 +57,20 @@ impl Package {
     ) -> Result<Self, Box<NewError>> {
         let (versioned_files, version_from_files) =
             validate_versioned_files(versioned_files_tracked, all_versioned_files)?;
+        let scopes = scopes
+            .map(|scopes| scopes.into_iter().map(Scope::new).collect())
+            .transpose()
+            .map_err(NewError::from)?;
 
         debug!("Looking for Git tags matching package name.");
         let mut versions = PackageVersions::from_tags(name.as_custom(), git_tags);
         if let Some(version_from_files) = version_from_files {
             versions.update_version(version_from_files);
         }
+        let last_stable_tag = git_tags.iter().find_map(|tag| {
+            let version = ReleaseTag::version(tag.as_ref(), &name)?;
+            (!version.is_prerelease()).then(|| ReleaseTag::new(&version, &name))
+        });
 
         Ok(Self {
             name,
@@ -63,9 +78,18 @@ impl Package {
             versioned_files,
             release_notes,
             scopes,
+            require_scope: RequireScope::default(),
+            zero_major_breaking: ZeroMajorBreaking::default(),
+            last_stable_tag,
         })
     }
 
+    /// The files that this package's version is written to.
+    #[must_use]
+    pub fn versioned_files(&self) -> &[Config] {
+        &self.versioned_files
+    }
+
     /// Returns the actions that must be taken to set this package to the new version, along
     /// with the version it was set to.
     ///
@@ -90,34 +114,50 @@ impl Package {
                 self.versions.update_version(version);
             }
             Bump::Rule(rule) => {
-                self.versions.bump(rule)?;
+                self.versions.bump(rule, self.zero_major_breaking)?;
             }
         };
         let version = self.versions.clone().into_latest();
         versioned_files
             .into_iter()
             .map(|file| {
-                let config = self
-                    .versioned_files
+                // The same file can be tracked more than once, like a Cargo workspace root which
+                // holds both the package version and dependencies on it.
+                let path = file.path().clone();
+                self.versioned_files
                     .iter()
-                    .find(|config| *config == file.path());
-                if let Some(config) = config {
-                    file.set_version(&version, config.dependency.as_deref(), go_versioning)
-                        .map_err(BumpError::SetError)
-                } else {
-                    Ok(file)
-                }
+                    .filter(|config| **config == path)
+                    .try_fold(file, |file, config| {
+                        file.set_version(&version, config.dependency.as_deref(), go_versioning)
+                    })
+                    .map_err(BumpError::SetError)
             })
             .collect()
     }
 
+    /// The changes from `commits` and `changeset` which apply to this package.
+    ///
+    /// Commits without a scope are skipped unless [`Self::require_scope`] is [`RequireScope::No`],
+    /// use [`validate_scope`] first to report them instead.
     #[must_use]
-    pub fn get_changes(&self, changeset: &[Release], commit_messages: &[String]) -> Vec<Change> {
-        changes_from_commit_messages(
-            commit_messages,
-            self.scopes.as_ref(),
+    pub fn get_changes(&self, changeset: &[Release], commits: &[Commit]) -> Vec<Change> {
+        let scoped_commits;
+        let commits = if self.require_scope.is_default() {
+            commits
+        } else {
+            scoped_commits = commits
+                .iter()
+                .filter(|commit| validate_scope(commit).is_ok())
+                .cloned()
+                .collect::<Vec<_>>();
+            &scoped_commits
+        };
+        changes_from_commits(
+            commits,
+            self.scopes.as_deref(),
             &self.release_notes.sections,
         )
+        .into_iter()
         .chain(Change::from_changesets(&self.name, changeset))
         .collect()
     }
@@ -152,8 +192,17 @@ impl Package {
             ChangeConfig::Calculate {
                 prerelease_label,
                 go_versioning,
+                minimum_rule,
+                forced_rule,
             } => {
-                let stable_rule = StableRule::from(changes);
+                let mut stable_rule = StableRule::from(changes);
+                if let Some(forced_rule) = forced_rule {
+                    debug!("Using overridden rule {forced_rule}");
+                    stable_rule = forced_rule;
+                } else if let Some(minimum_rule) = minimum_rule.filter(|rule| *rule > stable_rule) {
+                    debug!("Using linked rule {minimum_rule}");
+                    stable_rule = minimum_rule;
+                }
                 let rule = if let Some(pre_label) = prerelease_label {
                     Rule::Pre {
                         label: pre_label.clone(),
@@ -169,7 +218,7 @@ impl Package {
         let mut actions: Vec<Action> = changes
             .iter()
             .filter_map(|change| {
-                if let ChangeSource::ChangeFile(unique_id) = &change.original_source {
+                if let ChangeSource::ChangeFile { id: unique_id, .. } = &change.original_source {
                     if version.is_prerelease() {
                         None
                     } else {
@@ -184,13 +233,23 @@ impl Package {
             })
             .collect();
 
-        actions.extend(
-            self.release_notes
-                .create_release(version, changes, &self.name)?,
-        );
+        actions.extend(self.release_notes.create_release(
+            version,
+            changes,
+            &self.name,
+            self.last_stable_tag.as_ref(),
+        )?);
 
         Ok((updated, actions))
     }
+
+    /// Record `changes` in the "Unreleased" section of the changelog without picking a new
+    /// version. The next release replaces that section.
+    #[must_use]
+    pub fn write_unreleased(&mut self, changes: &[Change]) -> Vec<Action> {
+        self.release_notes
+            .create_unreleased(changes, self.last_stable_tag.as_ref())
+    }
 }
 
 /// Run through the provided versioned files and make sure they meet all requirements in context.
@@ -219,8 +278,8 @@ fn validate_versioned_files(
         let config = validate_dependency(config, &relevant_files)?;
         let is_dep = config.dependency.is_some();
         validated_files.push(config);
-        if is_dep {
-            // Dependencies don't have package versions
+        if is_dep || versioned_file.inherits_version() {
+            // Dependencies don't have package versions, and inherited versions come from another file
             continue;
         }
         let version = versioned_file.version().map_err(NewError::VersionedFile)?;
@@ -251,9 +310,17 @@ fn validate_dependency(
     versioned_files: &[(Config, &VersionedFile)],
 ) -> Result<Config, Box<NewError>> {
     match (&config.format, config.dependency.is_some()) {
-        (Format::Cargo, _)  // `Cargo.toml` supports either mode
-        | (Format::CargoLock, true)  // `Cargo.lock` is always a dependency 
-            => Ok(config),
+        // `Cargo.toml` and `package-lock.json` support either mode, other lockfiles always target
+        // a dependency
+        (Format::Cargo | Format::PackageLock, _)
+        | (
+            Format::CargoLock
+            | Format::PnpmLock
+            | Format::YarnLock
+            | Format::PoetryLock
+            | Format::UvLock,
+            true,
+        ) => Ok(config),
         (Format::CargoLock, false) => {
             // `Cargo.lock` needs to target a dependency. If there is a `Cargo.toml` file which is
             // _not_ a dependency, we default to that one.
@@ -269,7 +336,39 @@ fn validate_dependency(
             config.dependency = Some(cargo_package_name.to_string());
             Ok(config)
         }
-        (format, true) => Err(NewError::UnsupportedDependency(format.file_name()).into()),
+        (Format::PnpmLock | Format::YarnLock, false) => {
+            // Default to the name in the `package.json` file which is _not_ a dependency
+            let package_name = versioned_files
+                .iter()
+                .find_map(|(config, file)| match file {
+                    VersionedFile::PackageJson(file) if config.dependency.is_none() => {
+                        file.get_name()
+                    }
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    NewError::LockfileNoDependency(config.format.file_name().to_string())
+                })?;
+            config.dependency = Some(package_name.to_string());
+            Ok(config)
+        }
+        (Format::PoetryLock | Format::UvLock, false) => {
+            // Default to the name in the `pyproject.toml` file
+            let package_name = versioned_files
+                .iter()
+                .find_map(|(_, file)| match file {
+                    VersionedFile::PyProject(file) => file.get_name(),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    NewError::LockfileNoDependency(config.format.file_name().to_string())
+                })?;
+            config.dependency = Some(package_name.to_string());
+            Ok(config)
+        }
+        (format, true) => {
+            Err(NewError::UnsupportedDependency(format.file_name().to_string()).into())
+        }
         (_, false) => Ok(config),
     }
 }
@@ -279,6 +378,10 @@ pub enum ChangeConfig {
     Calculate {
         prerelease_label: Option<Label>,
         go_versioning: GoVersioning,
+        /// Use at least this rule, even if the changes imply a smaller one (for linked packages)
+        minimum_rule: Option<StableRule>,
+        /// Use exactly this rule, no matter what the changes imply
+        forced_rule: Option<StableRule>,
     },
 }
 
@@ -319,7 +422,7 @@ pub enum NewError {
             url("https://knope.tech/reference/config-file/packages#versioned_files")
         )
     )]
-    UnsupportedDependency(&'static str),
+    UnsupportedDependency(String),
     #[error("Cargo.lock must specify a dependency")]
     #[cfg_attr(
         feature = "miette",
@@ -331,11 +434,26 @@ pub enum NewError {
         )
     )]
     CargoLockNoDependency,
+    #[error("{0} must specify a dependency")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code = "knope_versioning::package::lockfile_no_dependency",
+            help = "To use this lockfile in `versioned_files`, you must either manually specify \
+            `dependency` or define a `package.json` or `pyproject.toml` with a `name` in the same \
+            array.",
+            url = "https://knope.tech/reference/config-file/packages/#versioned_files"
+        )
+    )]
+    LockfileNoDependency(String),
     #[error("Packages must have at least one versioned file")]
     NoPackages,
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     VersionedFile(#[from] versioned_file::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    InvalidScope(#[from] InvalidScope),
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -432,8 +550,9 @@ pub enum BumpError {
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     SetError(#[from] SetError),
     #[error(transparent)]
-    PreReleaseNotFound(#[from] PreReleaseNotFound),
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Rule(#[from] RuleError),
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
-    Time(#[from] TimeError),
+    ReleaseNotes(#[from] release_notes::Error),
 }
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use super::SectionName;
+use crate::changes::METADATA_PREFIX;
+
+/// A custom layout for each change in the release notes, like `- ⚠️ $summary`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChangeTemplate {
+    pub template: String,
+    /// Only use this template for changes in these sections, or in every section if empty
+    pub sections: Vec<SectionName>,
+}
+
+impl ChangeTemplate {
+    /// Lay out a change in `section`, or `None` if this template doesn't apply to the section or
+    /// uses a variable the change doesn't have.
+    ///
+    /// `$metadata.<key>` variables come from the `metadata` of a change file. Anything else after
+    /// a `$` which isn't a variable is left as-is.
+    pub(super) fn render(
+        &self,
+        section: &SectionName,
+        summary: &str,
+        details: Option<&str>,
+        commit_hash: Option<&str>,
+        metadata: &BTreeMap<String, String>,
+    ) -> Option<String> {
+        if !self.sections.is_empty() && !self.sections.contains(section) {
+            return None;
+        }
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some((before, after)) = rest.split_once('$') {
+            rendered.push_str(before);
+            let (variable, after) = split_variable(after);
+            rest = after;
+            let value = match variable {
+                "summary" => Some(summary),
+                "details" => details,
+                "commit_hash" => commit_hash,
+                _ => match variable.strip_prefix(METADATA_PREFIX) {
+                    Some(key) if !key.is_empty() => metadata.get(key).map(String::as_str),
+                    _ => {
+                        rendered.push('$');
+                        rendered.push_str(variable);
+                        continue;
+                    }
+                },
+            };
+            rendered.push_str(value?);
+        }
+        rendered.push_str(rest);
+        Some(rendered)
+    }
+}
+
+/// Split the name of a variable, like `summary` or `metadata.issue`, off the start of `text`.
+fn split_variable(text: &str) -> (&str, &str) {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let start = if text.starts_with(METADATA_PREFIX) {
+        METADATA_PREFIX.len()
+    } else {
+        0
+    };
+    let end = text
+        .char_indices()
+        .skip(start)
+        .find(|(_, c)| !is_name(*c))
+        .map_or(text.len(), |(index, _)| index);
+    text.split_at(end)
+}
+
+#[cfg(test)]
+mod test_render {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn scoped_to_section() {
+        let template = ChangeTemplate {
+            template: "- ⚠️ $summary".to_string(),
+            sections: vec![SectionName::from("Breaking Changes")],
+        };
+        assert_eq!(
+            template.render(
+                &SectionName::from("Breaking Changes"),
+                "Removed a thing",
+                None,
+                None,
+                &BTreeMap::new()
+            ),
+            Some("- ⚠️ Removed a thing".to_string())
+        );
+        assert_eq!(
+            template.render(
+                &SectionName::from("Fixes"),
+                "Fixed a thing",
+                None,
+                None,
+                &BTreeMap::new()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn missing_variable() {
+        let template = ChangeTemplate {
+            template: "- $summary ($commit_hash)".to_string(),
+            sections: Vec::new(),
+        };
+        assert_eq!(
+            template.render(
+                &SectionName::from("Fixes"),
+                "A fix",
+                None,
+                None,
+                &BTreeMap::new()
+            ),
+            None
+        );
+        assert_eq!(
+            template.render(
+                &SectionName::from("Fixes"),
+                "A fix",
+                None,
+                Some("abcdef0"),
+                &BTreeMap::new()
+            ),
+            Some("- A fix (abcdef0)".to_string())
+        );
+    }
+
+    #[test]
+    fn metadata() {
+        let template = ChangeTemplate {
+            template: "- $summary ([#$metadata.issue](https://example.com/issues/$metadata.issue))"
+                .to_string(),
+            sections: Vec::new(),
+        };
+        let metadata = BTreeMap::from([("issue".to_string(), "1234".to_string())]);
+        assert_eq!(
+            template.render(&SectionName::from("Fixes"), "A fix", None, None, &metadata),
+            Some("- A fix ([#1234](https://example.com/issues/1234))".to_string())
+        );
+        assert_eq!(
+            template.render(
+                &SectionName::from("Fixes"),
+                "A fix",
+                None,
+                None,
+                &BTreeMap::new()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_variables_are_left_alone() {
+        let template = ChangeTemplate {
+            template: "- $summary, see $PATH or $metadata. for $5".to_string(),
+            sections: Vec::new(),
+        };
+        assert_eq!(
+            template.render(
+                &SectionName::from("Fixes"),
+                "A fix",
+                None,
+                None,
+                &BTreeMap::new()
+            ),
+            Some("- A fix, see $PATH or $metadata. for $5".to_string())
+        );
+    }
+}
@@ -65,17 +65,9 @@ impl Changelog {
     /// Find a release matching `version`, if any, within the changelog.
     #[must_use]
     pub fn get_release(&self, version: &Version, package_name: &package::Name) -> Option<Release> {
-        let expected_header_start = format!(
-            "{release_header_level} {version}",
-            release_header_level = self.release_header_level
-        );
-
         let mut lines = self.content.lines();
         let (title, version) = loop {
             let line = lines.next()?;
-            if !line.starts_with(&expected_header_start) {
-                continue;
-            }
             let Ok((header_level, title_version, _)) = parse_title(line) else {
                 continue;
             };
@@ -109,15 +101,31 @@ impl Changelog {
     }
 
     /// Update `self.content` with the new release, return the diff being applied.
+    ///
+    /// Any "Unreleased" section is replaced by the new release.
     #[must_use]
     pub fn with_release(&mut self, release: &Release) -> String {
-        let mut not_written = true;
-        let new_changes = format!(
+        let new_changes = self.format_section(&release.title, &release.notes);
+        self.remove_unreleased();
+        self.insert_section(&new_changes);
+        new_changes
+    }
+
+    /// Replace the "Unreleased" section of `self.content` with `notes` (adding the section if it
+    /// doesn't exist yet), return the diff being applied.
+    #[must_use]
+    pub fn with_unreleased(&mut self, notes: &str) -> String {
+        let new_changes = self.format_section(UNRELEASED_TITLE, notes);
+        self.remove_unreleased();
+        self.insert_section(&new_changes);
+        new_changes
+    }
+
+    fn format_section(&self, title: &str, notes: &str) -> String {
+        format!(
             "{header_level} {title}\n\n{body}",
             header_level = self.release_header_level,
-            title = release.title,
-            body = release
-                .notes
+            body = notes
                 .lines()
                 .map(|line| {
                     // Release notes are at H1, we need to format them properly for this changelog
@@ -128,13 +136,18 @@ impl Changelog {
                     }
                 })
                 .join("\n")
-        );
+        )
+    }
+
+    /// Insert `new_changes` before the latest release in the changelog.
+    fn insert_section(&mut self, new_changes: &str) {
+        let mut not_written = true;
         let mut new_content = String::with_capacity(self.content.len() + new_changes.len());
 
         for line in self.content.lines() {
             if not_written && parse_title(line).is_ok() {
                 // Insert new changes before the next release in the changelog
-                new_content.push_str(&new_changes);
+                new_content.push_str(new_changes);
                 new_content.push_str("\n\n");
                 not_written = false;
             }
@@ -143,7 +156,7 @@ impl Changelog {
         }
 
         if not_written {
-            new_content.push_str(&new_changes);
+            new_content.push_str(new_changes);
         }
 
         if (self.content.ends_with('\n') || self.content.is_empty()) && !new_content.ends_with('\n')
@@ -153,10 +166,47 @@ impl Changelog {
         }
 
         self.content = new_content;
-        new_changes
+    }
+
+    /// Remove the "Unreleased" section, if any, up to the next section at the same level.
+    fn remove_unreleased(&mut self) {
+        let unreleased_header = format!(
+            "{header_level} {UNRELEASED_TITLE}",
+            header_level = self.release_header_level
+        );
+        let next_section = format!("{header_level} ", header_level = self.release_header_level);
+        let mut in_unreleased = false;
+        let mut removed = false;
+        let mut new_content = String::with_capacity(self.content.len());
+        for line in self.content.lines() {
+            if !removed && line.trim().eq_ignore_ascii_case(&unreleased_header) {
+                in_unreleased = true;
+                removed = true;
+                continue;
+            }
+            if in_unreleased {
+                if !line.starts_with(&next_section) && !line.starts_with("# ") {
+                    continue;
+                }
+                in_unreleased = false;
+            }
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+        if !removed {
+            return;
+        }
+        if !self.content.ends_with('\n') {
+            new_content.pop();
+        }
+        self.content = new_content;
     }
 }
 
+const UNRELEASED_TITLE: &str = "Unreleased";
+
+/// Parse a release title, which is a header containing a version (optionally prefixed with `v`
+/// or a package name, like `knope/v1.2.3`) and maybe a date.
 fn parse_title(title: &str) -> Result<(HeaderLevel, Version, Option<Date>), ParseError> {
     let mut parts = title.split_ascii_whitespace();
     let header_level = match parts.next() {
@@ -164,16 +214,22 @@ fn parse_title(title: &str) -> Result<(HeaderLevel, Version, Option<Date>), Pars
         Some("#") => HeaderLevel::H1,
         _ => return Err(ParseError::HeaderLevel),
     };
-    let version = parts.next().ok_or(ParseError::MissingVersion)?;
-    let version = Version::from_str(version).map_err(|_| ParseError::MissingVersion)?;
+    let mut version = None;
     let mut date = None;
     for part in parts {
-        let part = part.trim_start_matches('(').trim_end_matches(')');
-        date = Date::parse(part, format_description!("[year]-[month]-[day]")).ok();
-        if date.is_some() {
-            break;
+        let part = part.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+        if version.is_none() {
+            let part = part.rsplit('/').next().unwrap_or(part);
+            version = Version::from_str(part.strip_prefix('v').unwrap_or(part)).ok();
+            if version.is_some() {
+                continue;
+            }
+        }
+        if date.is_none() {
+            date = Date::parse(part, format_description!("[year]-[month]-[day]")).ok();
         }
     }
+    let version = version.ok_or(ParseError::MissingVersion)?;
     Ok((header_level, version, date))
 }
 
@@ -233,6 +289,56 @@ mod test_parse_title {
         assert_eq!(version, Version::new(0, 1, 2, None));
         assert_eq!(date, Some(date!(2023 - 05 - 02)));
     }
+
+    #[test]
+    fn custom_title() {
+        let title = "## knope v0.1.2-rc.0 — 2023-05-02";
+        let (header_level, version, date) = parse_title(title).unwrap();
+        assert_eq!(header_level, HeaderLevel::H2);
+        assert_eq!(version, Version::from_str("0.1.2-rc.0").unwrap());
+        assert_eq!(date, Some(date!(2023 - 05 - 02)));
+    }
+
+    #[test]
+    fn package_tag_title() {
+        let title = "## knope/v0.1.2";
+        let (_, version, _) = parse_title(title).unwrap();
+        assert_eq!(version, Version::new(0, 1, 2, None));
+    }
+}
+
+#[cfg(test)]
+mod test_unreleased {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn add_section() {
+        let mut changelog = Changelog::new(
+            RelativePathBuf::from("CHANGELOG.md"),
+            "# Changelog\n\n## 1.0.0\n\n- Existing\n".to_string(),
+        );
+        let diff = changelog.with_unreleased("## Features\n\n- New");
+        assert_eq!(diff, "## Unreleased\n\n### Features\n\n- New");
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## Unreleased\n\n### Features\n\n- New\n\n## 1.0.0\n\n- Existing\n"
+        );
+    }
+
+    #[test]
+    fn replace_last_section() {
+        let mut changelog = Changelog::new(
+            RelativePathBuf::from("CHANGELOG.md"),
+            "# Changelog\n\n## Unreleased\n\n### Features\n\n- Old\n".to_string(),
+        );
+        let _ = changelog.with_unreleased("## Features\n\n- Old\n- New");
+        assert_eq!(
+            changelog.content,
+            "# Changelog\n\n## Unreleased\n\n### Features\n\n- Old\n- New\n"
+        );
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Error)]
@@ -243,8 +349,8 @@ pub enum ParseError {
         feature = "miette",
         diagnostic(
             code = "changelog::missing_version",
-            help = "The expected changelog format is very particular, a release title must start with the
-            semantic version immediately after the header level. For example: `## 0.1.0 - 2020-12-25"
+            help = "The expected changelog format is very particular, a release title must contain the
+            semantic version, optionally prefixed with `v`. For example: `## 0.1.0 - 2020-12-25"
         )
     )]
     MissingVersion,
@@ -137,6 +137,20 @@ impl Sections {
             })
         })
     }
+
+    /// Find the configured custom change type matching a conventional commit type (like `perf`).
+    pub(crate) fn find_custom_type(&self, commit_type: &str) -> Option<&CustomChangeType> {
+        self.0.iter().find_map(|(_, sources)| {
+            sources.iter().find_map(|source| match source {
+                ChangeType::Custom(SectionSource::CustomChangeType(custom))
+                    if custom.0.eq_ignore_ascii_case(commit_type) =>
+                {
+                    Some(custom)
+                }
+                _ => None,
+            })
+        })
+    }
 }
 
 impl Default for Sections {
@@ -0,0 +1,132 @@
+/// Where the repository is hosted, so release notes can link back to it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Forge {
+    GitHub {
+        owner: String,
+        repo: String,
+    },
+    Gitea {
+        /// The base URL of the Gitea instance, like `https://codeberg.org`
+        host: String,
+        owner: String,
+        repo: String,
+    },
+}
+
+impl Forge {
+    fn repo_url(&self) -> String {
+        match self {
+            Self::GitHub { owner, repo } => format!("https://github.com/{owner}/{repo}"),
+            Self::Gitea { host, owner, repo } => {
+                format!("{host}/{owner}/{repo}", host = host.trim_end_matches('/'))
+            }
+        }
+    }
+
+    /// A link to the page showing every change between two tags.
+    pub(crate) fn compare_url(&self, from: &str, to: &str) -> String {
+        format!(
+            "{repo_url}/compare/{from}...{to}",
+            repo_url = self.repo_url()
+        )
+    }
+
+    fn pull_request_url(&self, number: &str) -> String {
+        let path = match self {
+            Self::GitHub { .. } => "pull",
+            Self::Gitea { .. } => "pulls",
+        };
+        format!("{repo_url}/{path}/{number}", repo_url = self.repo_url())
+    }
+
+    /// Turn a trailing pull request reference like `(#123)` in the summary of a commit into a
+    /// link, then add a link to the commit itself.
+    pub(crate) fn link_commit(&self, summary: &str, hash: Option<&str>) -> String {
+        let mut summary = summary
+            .strip_suffix(')')
+            .and_then(|rest| rest.rsplit_once("(#"))
+            .filter(|(_, number)| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+            .map_or_else(
+                || summary.to_string(),
+                |(start, number)| {
+                    format!(
+                        "{start}([#{number}]({url}))",
+                        url = self.pull_request_url(number)
+                    )
+                },
+            );
+        if let Some(hash) = hash {
+            let short_hash: String = hash.chars().take(7).collect();
+            summary.push_str(&format!(
+                " ([{short_hash}]({repo_url}/commit/{hash}))",
+                repo_url = self.repo_url()
+            ));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn github_compare_url() {
+        let forge = Forge::GitHub {
+            owner: "knope-dev".to_string(),
+            repo: "knope".to_string(),
+        };
+        assert_eq!(
+            forge.compare_url("v1.2.0", "v1.3.0"),
+            "https://github.com/knope-dev/knope/compare/v1.2.0...v1.3.0"
+        );
+    }
+
+    #[test]
+    fn github_link_commit() {
+        let forge = Forge::GitHub {
+            owner: "knope-dev".to_string(),
+            repo: "knope".to_string(),
+        };
+        assert_eq!(
+            forge.link_commit(
+                "Add a feature (#123)",
+                Some("0123456789abcdef0123456789abcdef01234567")
+            ),
+            "Add a feature ([#123](https://github.com/knope-dev/knope/pull/123)) \
+            ([0123456](https://github.com/knope-dev/knope/commit/0123456789abcdef0123456789abcdef01234567))"
+        );
+    }
+
+    #[test]
+    fn link_commit_without_pull_request() {
+        let forge = Forge::Gitea {
+            host: "https://codeberg.org".to_string(),
+            owner: "knope-dev".to_string(),
+            repo: "knope".to_string(),
+        };
+        assert_eq!(
+            forge.link_commit("Fix a bug (#not-a-number)", None),
+            "Fix a bug (#not-a-number)"
+        );
+        assert_eq!(
+            forge.link_commit("Fix a bug (#4)", None),
+            "Fix a bug ([#4](https://codeberg.org/knope-dev/knope/pulls/4))"
+        );
+    }
+
+    #[test]
+    fn gitea_compare_url() {
+        let forge = Forge::Gitea {
+            host: "https://codeberg.org/".to_string(),
+            owner: "knope-dev".to_string(),
+            repo: "knope".to_string(),
+        };
+        assert_eq!(
+            forge.compare_url("first/v1.2.0", "first/v1.3.0"),
+            "https://codeberg.org/knope-dev/knope/compare/first/v1.2.0...first/v1.3.0"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use relative_path::RelativePathBuf;
+use serde::Serialize;
+
+use super::{ChangeDescription, Release, Sections};
+use crate::changes::{Change, ChangeSource, CHANGESET_DIR};
+
+/// A machine-readable version of a release, for tools that don't want to parse Markdown.
+#[derive(Serialize)]
+pub(super) struct ReleaseJson<'a> {
+    package: Option<&'a str>,
+    version: String,
+    title: &'a str,
+    sections: Vec<SectionJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct SectionJson<'a> {
+    title: &'a str,
+    changes: Vec<ChangeJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ChangeJson<'a> {
+    summary: String,
+    details: Option<String>,
+    source: SourceJson<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SourceJson<'a> {
+    ConventionalCommit {
+        summary: &'a str,
+        hash: Option<&'a str>,
+    },
+    ChangeFile {
+        file: String,
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        metadata: &'a BTreeMap<String, String>,
+    },
+}
+
+impl<'a> ReleaseJson<'a> {
+    pub(super) fn new(release: &'a Release, sections: &'a Sections, changes: &'a [Change]) -> Self {
+        let sections = sections
+            .iter()
+            .filter_map(|(section_name, sources)| {
+                let changes: Vec<ChangeJson> = changes
+                    .iter()
+                    .filter(|change| sources.contains(&change.change_type))
+                    .map(ChangeJson::from)
+                    .collect();
+                (!changes.is_empty()).then_some(SectionJson {
+                    title: section_name.as_ref(),
+                    changes,
+                })
+            })
+            .collect();
+        Self {
+            package: release.package_name.as_custom(),
+            version: release.version.to_string(),
+            title: &release.title,
+            sections,
+        }
+    }
+}
+
+impl<'a> From<&'a Change> for ChangeJson<'a> {
+    fn from(change: &'a Change) -> Self {
+        let (summary, details) = match ChangeDescription::from(change) {
+            ChangeDescription::Simple(summary) => (summary, None),
+            ChangeDescription::Complex(summary, details) => (summary, Some(details)),
+        };
+        let source = match &change.original_source {
+            ChangeSource::ConventionalCommit { summary, hash } => SourceJson::ConventionalCommit {
+                summary,
+                hash: hash.as_deref(),
+            },
+            ChangeSource::ChangeFile { id, metadata } => SourceJson::ChangeFile {
+                file: RelativePathBuf::from(CHANGESET_DIR)
+                    .join(id.to_file_name())
+                    .to_string(),
+                metadata,
+            },
+        };
+        Self {
+            summary,
+            details,
+            source,
+        }
+    }
+}
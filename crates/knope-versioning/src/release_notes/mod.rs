@@ -1,77 +1,260 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::BTreeMap};
 
+pub use change_template::ChangeTemplate;
 pub use changelog::Changelog;
 pub use config::{CommitFooter, CustomChangeType, SectionName, SectionSource, Sections};
+pub use forge::Forge;
 use itertools::Itertools;
+use json::ReleaseJson;
+use relative_path::RelativePathBuf;
 pub use release::Release;
+pub use template::{Template, UnknownPlaceholder};
 use time::{macros::format_description, OffsetDateTime};
 
-use crate::{changes::Change, package, semver::Version, Action};
+use crate::{
+    action::ReleaseTag,
+    changes::{Change, ChangeSource},
+    package,
+    semver::Version,
+    Action,
+};
 
+mod change_template;
 mod changelog;
 mod config;
+mod forge;
+mod json;
 mod release;
+mod template;
 
 /// Defines how release notes are handled for a package.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReleaseNotes {
     pub sections: Sections,
-    pub changelog: Option<Changelog>,
+    /// Every changelog file that new releases are added to
+    pub changelogs: Vec<Changelog>,
+    /// Used to link to the full list of changes for each release
+    pub forge: Option<Forge>,
+    /// Where to write a JSON version of each new release, if anywhere
+    pub release_json: Option<RelativePathBuf>,
+    /// Lays out the notes of each new release instead of the default sections
+    pub template: Option<Template>,
+    /// Custom layouts for each change, the first one that applies is used
+    pub change_templates: Vec<ChangeTemplate>,
+    /// The format of each release title, like `{version} ({date})`
+    pub title: Option<String>,
 }
 
 impl ReleaseNotes {
     /// Create new release notes for use in changelogs / forges.
     ///
+    /// If there's a `forge` and a `previous_tag`, a link comparing `previous_tag` to the new
+    /// release is added to the end of the notes.
+    ///
     /// # Errors
     ///
-    /// If the current date can't be formatted
+    /// If the current date can't be formatted, the release can't be serialized to JSON, the
+    /// `template` has an unknown placeholder, or the `title` doesn't include the version
     pub fn create_release(
         &mut self,
         version: Version,
         changes: &[Change],
         package_name: &package::Name,
-    ) -> Result<Vec<Action>, TimeError> {
-        let mut notes = String::new();
-        for (section_name, sources) in self.sections.iter() {
-            let changes = changes
-                .iter()
-                .filter_map(|change| {
-                    if sources.contains(&change.change_type) {
-                        Some(ChangeDescription::from(change))
-                    } else {
-                        None
-                    }
+        previous_tag: Option<&ReleaseTag>,
+    ) -> Result<Vec<Action>, Error> {
+        let new_tag = ReleaseTag::new(&version, package_name);
+        let date = today()?;
+        let notes = if let Some(template) = &self.template {
+            let sections = self.sections(changes);
+            let changes = self.build_notes(changes, None, new_tag.as_str());
+            let compare_url = self
+                .forge
+                .as_ref()
+                .zip(previous_tag)
+                .map(|(forge, previous_tag)| {
+                    forge.compare_url(previous_tag.as_str(), new_tag.as_str())
                 })
-                .sorted()
-                .collect_vec();
-            if !changes.is_empty() {
-                notes.push_str("\n\n## ");
-                notes.push_str(section_name.as_ref());
-                notes.push_str("\n\n");
-                notes.push_str(&build_body(changes));
-            }
-        }
-
-        let notes = notes.trim().to_string();
+                .unwrap_or_default();
+            template.render(&template::Context {
+                version: &version.to_string(),
+                date: &date,
+                package: package_name.as_custom().unwrap_or_default(),
+                changes: &changes,
+                sections: &sections
+                    .iter()
+                    .map(|(name, body)| (name.as_ref(), body.clone()))
+                    .collect_vec(),
+                compare_url: &compare_url,
+            })?
+        } else {
+            self.build_notes(changes, previous_tag, new_tag.as_str())
+        };
         let release = Release {
-            title: release_title(&version)?,
+            title: self.release_title(&version, &date, package_name)?,
             version,
             notes,
             package_name: package_name.clone(),
         };
 
-        let mut pending_actions = Vec::with_capacity(2);
-        if let Some(changelog) = self.changelog.as_mut() {
+        let mut pending_actions = Vec::with_capacity(self.changelogs.len() + 2);
+        for changelog in &mut self.changelogs {
             let new_changes = changelog.with_release(&release);
             pending_actions.push(Action::WriteToFile {
                 path: changelog.path.clone(),
                 content: changelog.content.clone(),
                 diff: format!("\n{new_changes}\n"),
             });
-        };
+        }
+        if let Some(path) = &self.release_json {
+            let mut content =
+                serde_json::to_string_pretty(&ReleaseJson::new(&release, &self.sections, changes))?;
+            content.push('\n');
+            pending_actions.push(Action::WriteToFile {
+                path: path.clone(),
+                diff: format!("\n{content}"),
+                content,
+            });
+        }
         pending_actions.push(Action::CreateRelease(release));
         Ok(pending_actions)
     }
+
+    /// Write `changes` to the "Unreleased" section of each changelog, replacing whatever was there
+    /// before.
+    ///
+    /// If there's a `forge` and a `previous_tag`, a link comparing `previous_tag` to `HEAD` is
+    /// added to the end of the section.
+    pub fn create_unreleased(
+        &mut self,
+        changes: &[Change],
+        previous_tag: Option<&ReleaseTag>,
+    ) -> Vec<Action> {
+        let notes = self.build_notes(changes, previous_tag, "HEAD");
+        self.changelogs
+            .iter_mut()
+            .map(|changelog| {
+                let new_changes = changelog.with_unreleased(&notes);
+                Action::WriteToFile {
+                    path: changelog.path.clone(),
+                    content: changelog.content.clone(),
+                    diff: format!("\n{new_changes}\n"),
+                }
+            })
+            .collect()
+    }
+
+    /// Find a release matching `version` in the first changelog, if any.
+    #[must_use]
+    pub fn get_release(&self, version: &Version, package_name: &package::Name) -> Option<Release> {
+        self.changelogs
+            .first()
+            .and_then(|changelog| changelog.get_release(version, package_name))
+    }
+
+    /// The Markdown notes for `changes` at header level 1, linking to a comparison of
+    /// `previous_tag` and `new_ref` when possible.
+    fn build_notes(
+        &self,
+        changes: &[Change],
+        previous_tag: Option<&ReleaseTag>,
+        new_ref: &str,
+    ) -> String {
+        let mut notes = String::new();
+        for (section_name, body) in self.sections(changes) {
+            notes.push_str("\n\n## ");
+            notes.push_str(section_name.as_ref());
+            notes.push_str("\n\n");
+            notes.push_str(&body);
+        }
+
+        let mut notes = notes.trim().to_string();
+        if let (Some(forge), Some(previous_tag)) = (&self.forge, previous_tag) {
+            let from = previous_tag.as_str();
+            notes.push_str(&format!(
+                "\n\nFull changelog: [{from}...{new_ref}]({url})",
+                url = forge.compare_url(from, new_ref)
+            ));
+        }
+        notes
+    }
+
+    /// The title of a new release with no Markdown header level.
+    fn release_title(
+        &self,
+        version: &Version,
+        date: &str,
+        package_name: &package::Name,
+    ) -> Result<String, Error> {
+        let Some(title) = &self.title else {
+            return Ok(format!("{version} ({date})"));
+        };
+        if !title.contains("{version}") {
+            return Err(Error::TitleWithoutVersion(title.clone()));
+        }
+        Ok(title
+            .replace("{version}", &version.to_string())
+            .replace("{date}", date)
+            .replace("{package}", package_name.as_custom().unwrap_or_default())
+            .trim()
+            .to_string())
+    }
+
+    /// The body of each section which has any of `changes`, without headings.
+    fn sections(&self, changes: &[Change]) -> Vec<(&SectionName, String)> {
+        self.sections
+            .iter()
+            .filter_map(|(section_name, sources)| {
+                let changes = changes
+                    .iter()
+                    .filter(|change| sources.contains(&change.change_type))
+                    .map(|change| (ChangeDescription::new(change, self.forge.as_ref()), change))
+                    .sorted_by(|(first, _), (second, _)| first.cmp(second))
+                    .map(|(description, change)| self.layout(section_name, description, change))
+                    .collect_vec();
+                if changes.is_empty() {
+                    None
+                } else {
+                    Some((section_name, build_body(changes)))
+                }
+            })
+            .collect()
+    }
+
+    /// Lay out a single change in `section` with the first change template that applies, or
+    /// the default layout if none do.
+    fn layout(
+        &self,
+        section: &SectionName,
+        description: ChangeDescription,
+        change: &Change,
+    ) -> String {
+        let (summary, details) = match &description {
+            ChangeDescription::Simple(summary) => (summary.as_str(), None),
+            ChangeDescription::Complex(summary, details) => {
+                (summary.as_str(), Some(details.as_str()))
+            }
+        };
+        let no_metadata = BTreeMap::new();
+        let (commit_hash, metadata) = match &change.original_source {
+            ChangeSource::ConventionalCommit { hash, .. } => (
+                hash.as_ref()
+                    .map(|hash| hash.chars().take(7).collect::<String>()),
+                &no_metadata,
+            ),
+            ChangeSource::ChangeFile { metadata, .. } => (None, metadata),
+        };
+        self.change_templates
+            .iter()
+            .find_map(|template| {
+                template.render(section, summary, details, commit_hash.as_deref(), metadata)
+            })
+            .unwrap_or_else(|| match description {
+                ChangeDescription::Simple(summary) => format!("- {summary}"),
+                ChangeDescription::Complex(summary, details) => {
+                    format!("### {summary}\n\n{details}")
+                }
+            })
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -83,12 +266,57 @@ impl ReleaseNotes {
 ))]
 pub struct TimeError(#[from] time::error::Format);
 
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum Error {
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Time(#[from] TimeError),
+    #[error("Failed to serialize release notes as JSON: {0}")]
+    #[cfg_attr(feature = "miette", diagnostic(
+        code(release_notes::json),
+        help("This is probably a bug with knope, please file an issue at https://github.com/knope-dev/knope")
+    ))]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Template(#[from] UnknownPlaceholder),
+    #[error("The release title {0} doesn't include {{version}}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(release_notes::title_without_version),
+            help("Knope finds releases in changelogs by the version in their titles."),
+            url("https://knope.tech/reference/config-file/packages/#title")
+        )
+    )]
+    TitleWithoutVersion(String),
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 enum ChangeDescription {
     Simple(String),
     Complex(String, String),
 }
 
+impl ChangeDescription {
+    /// Describe `change`, linking back to the commit it came from if there's a `forge`.
+    fn new(change: &Change, forge: Option<&Forge>) -> Self {
+        let description = Self::from(change);
+        let (Some(forge), ChangeSource::ConventionalCommit { hash, .. }) =
+            (forge, &change.original_source)
+        else {
+            return description;
+        };
+        match description {
+            Self::Simple(summary) => Self::Simple(forge.link_commit(&summary, hash.as_deref())),
+            Self::Complex(summary, details) => {
+                Self::Complex(forge.link_commit(&summary, hash.as_deref()), details)
+            }
+        }
+    }
+}
+
 impl Ord for ChangeDescription {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
@@ -127,36 +355,29 @@ impl From<&Change> for ChangeDescription {
     }
 }
 
-fn build_body(changes: Vec<ChangeDescription>) -> String {
+/// Join laid out changes, with a blank line before any that span multiple lines.
+fn build_body(changes: Vec<String>) -> String {
     let mut body = String::new();
     let mut changes = changes.into_iter().peekable();
     while let Some(change) = changes.next() {
-        match change {
-            ChangeDescription::Simple(summary) => {
-                body.push_str(&format!("- {summary}"));
-            }
-            ChangeDescription::Complex(summary, details) => {
-                body.push_str(&format!("### {summary}\n\n{details}"));
-            }
-        }
+        body.push_str(&change);
         match changes.peek() {
-            Some(ChangeDescription::Simple(_)) => body.push('\n'),
-            Some(ChangeDescription::Complex(_, _)) => body.push_str("\n\n"),
+            Some(next) if next.contains('\n') => body.push_str("\n\n"),
+            Some(_) => body.push('\n'),
             None => (),
         }
     }
     body
 }
 
-/// Create the title of a new release with no Markdown header level.
+/// Today's date in UTC, as it appears in release titles.
 ///
 /// # Errors
 ///
 /// If the current date can't be formatted
-fn release_title(version: &Version) -> Result<String, TimeError> {
+fn today() -> Result<String, TimeError> {
     let format = format_description!("[year]-[month]-[day]");
-    let date_str = OffsetDateTime::now_utc().date().format(&format)?;
-    Ok(format!("{version} ({date_str})"))
+    Ok(OffsetDateTime::now_utc().date().format(&format)?)
 }
 
 #[cfg(test)]
@@ -170,7 +391,10 @@ mod test_change_description {
     fn conventional_commit() {
         let change = Change {
             change_type: ChangeType::Feature,
-            original_source: ChangeSource::ConventionalCommit(String::new()),
+            original_source: ChangeSource::ConventionalCommit {
+                summary: String::new(),
+                hash: None,
+            },
             description: "a feature".into(),
         };
         let description = ChangeDescription::from(&change);
@@ -180,11 +404,39 @@ mod test_change_description {
         );
     }
 
+    #[test]
+    fn conventional_commit_with_forge() {
+        let change = Change {
+            change_type: ChangeType::Fix,
+            original_source: ChangeSource::ConventionalCommit {
+                summary: String::new(),
+                hash: Some("abcdef0123456789".to_string()),
+            },
+            description: "a fix (#12)".into(),
+        };
+        let forge = Forge::GitHub {
+            owner: "knope-dev".to_string(),
+            repo: "knope".to_string(),
+        };
+        let description = ChangeDescription::new(&change, Some(&forge));
+        assert_eq!(
+            description,
+            ChangeDescription::Simple(
+                "a fix ([#12](https://github.com/knope-dev/knope/pull/12)) \
+                ([abcdef0](https://github.com/knope-dev/knope/commit/abcdef0123456789))"
+                    .to_string()
+            )
+        );
+    }
+
     #[test]
     fn simple_changeset() {
         let change = Change {
             change_type: ChangeType::Feature,
-            original_source: ChangeSource::ConventionalCommit(String::new()),
+            original_source: ChangeSource::ConventionalCommit {
+                summary: String::new(),
+                hash: None,
+            },
             description: "# a feature\n\n\n\n".into(),
         };
         let description = ChangeDescription::from(&change);
@@ -197,7 +449,10 @@ mod test_change_description {
     #[test]
     fn complex_changeset() {
         let change = Change {
-            original_source: ChangeSource::ConventionalCommit(String::new()),
+            original_source: ChangeSource::ConventionalCommit {
+                summary: String::new(),
+                hash: None,
+            },
             change_type: ChangeType::Feature,
             description: "# a feature\n\nwith details\n\n- first\n- second".into(),
         };
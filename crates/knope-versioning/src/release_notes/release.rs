@@ -9,3 +9,14 @@ pub struct Release {
     pub notes: String,
     pub package_name: package::Name,
 }
+
+impl Release {
+    /// The title with the package name in front, unless it already includes the package name.
+    #[must_use]
+    pub fn full_title(&self) -> String {
+        match self.package_name.as_custom() {
+            Some(name) if !self.title.contains(name) => format!("{name} {}", self.title),
+            _ => self.title.clone(),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+/// A file which lays out the notes of each release, with placeholders like `{{version}}`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Template {
+    pub contents: String,
+    /// Values for custom placeholders, by name
+    pub variables: BTreeMap<String, String>,
+}
+
+/// The values of the built-in placeholders for one release.
+pub(super) struct Context<'a> {
+    pub(super) version: &'a str,
+    pub(super) date: &'a str,
+    pub(super) package: &'a str,
+    /// Every section, with headings, the way they'd be written without a template
+    pub(super) changes: &'a str,
+    /// The body of each section that has changes, by section name
+    pub(super) sections: &'a [(&'a str, String)],
+    pub(super) compare_url: &'a str,
+}
+
+impl Template {
+    /// Replace every placeholder in the template.
+    ///
+    /// # Errors
+    ///
+    /// If there's a placeholder which isn't built-in or one of the custom `variables`
+    pub(super) fn render(&self, context: &Context) -> Result<String, UnknownPlaceholder> {
+        let mut rendered = String::with_capacity(self.contents.len());
+        let mut rest = self.contents.as_str();
+        while let Some((before, after)) = rest.split_once("{{") {
+            let Some((placeholder, after)) = after.split_once("}}") else {
+                break;
+            };
+            rendered.push_str(before);
+            rendered.push_str(self.resolve(placeholder.trim(), context)?);
+            rest = after;
+        }
+        rendered.push_str(rest);
+        Ok(rendered.trim().to_string())
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        placeholder: &str,
+        context: &'a Context,
+    ) -> Result<&'a str, UnknownPlaceholder> {
+        let value = match placeholder {
+            "version" => context.version,
+            "date" => context.date,
+            "package" => context.package,
+            "changes" => context.changes,
+            "compare_url" => context.compare_url,
+            _ => {
+                if let Some(section) = placeholder.strip_prefix("section.") {
+                    context
+                        .sections
+                        .iter()
+                        .find(|(name, _)| *name == section)
+                        .map_or("", |(_, body)| body.as_str())
+                } else {
+                    self.variables
+                        .get(placeholder)
+                        .ok_or_else(|| UnknownPlaceholder(placeholder.to_string()))?
+                }
+            }
+        };
+        Ok(value)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+#[error("Unknown placeholder {{{{{0}}}}} in the release notes template")]
+#[cfg_attr(
+    feature = "miette",
+    diagnostic(
+        code(release_notes::unknown_placeholder),
+        help("Use one of the built-in placeholders or add it to `release_notes.variables`."),
+        url("https://knope.tech/reference/config-file/packages/#release_notes")
+    )
+)]
+pub struct UnknownPlaceholder(String);
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_render {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn context() -> Context<'static> {
+        Context {
+            version: "1.2.0",
+            date: "2024-05-31",
+            package: "knope",
+            changes: "## Features\n\n- A feature",
+            sections: &[],
+            compare_url: "",
+        }
+    }
+
+    #[test]
+    fn built_in_and_custom() {
+        let template = Template {
+            contents:
+                "Version {{ version }} of {{package}} from {{date}}\n\n{{changes}}\n\n{{support}}\n"
+                    .to_string(),
+            variables: BTreeMap::from([("support".to_string(), "Get help".to_string())]),
+        };
+        assert_eq!(
+            template.render(&context()).unwrap(),
+            "Version 1.2.0 of knope from 2024-05-31\n\n## Features\n\n- A feature\n\nGet help"
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let template = Template {
+            contents: "New:\n{{section.Features}}\nFixed:\n{{section.Fixes}}".to_string(),
+            variables: BTreeMap::new(),
+        };
+        let sections = [("Features", "- A feature".to_string())];
+        let context = Context {
+            sections: &sections,
+            ..context()
+        };
+        assert_eq!(
+            template.render(&context).unwrap(),
+            "New:\n- A feature\nFixed:"
+        );
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        let template = Template {
+            contents: "{{nope}}".to_string(),
+            variables: BTreeMap::new(),
+        };
+        assert!(template.render(&context()).is_err());
+    }
+}
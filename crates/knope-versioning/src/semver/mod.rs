@@ -2,13 +2,15 @@ use std::{cmp::Ordering, fmt::Display, str::FromStr};
 
 #[cfg(feature = "miette")]
 use miette::Diagnostic;
-pub use package_versions::{PackageVersions, PreReleaseNotFound};
+pub use package_versions::{PackageVersions, PreReleaseNotFound, RuleError, ZeroMajorBreaking};
 pub use rule::{Rule, Stable as StableRule};
 use serde::{Deserialize, Serialize};
+pub use version_format::{Pep440Error, VersionFormat};
 
 mod package_versions;
 mod prerelease_map;
 mod rule;
+mod version_format;
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Version {
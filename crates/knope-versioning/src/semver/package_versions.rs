@@ -1,5 +1,9 @@
 use std::{collections::BTreeMap, fmt::Debug, str::FromStr};
 
+use itertools::Itertools;
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::{
@@ -119,17 +123,25 @@ impl PackageVersions {
     ///
     /// Versions with major component 0 have special meaning in Semantic Versioning and therefore have
     /// different behavior:
-    /// 1. [`Rule::Major`] will bump the minor component.
+    /// 1. [`Rule::Major`] will bump the minor component, unless `zero_major_breaking` is
+    ///    [`ZeroMajorBreaking::Major`].
     /// 2. [`Rule::Minor`] will bump the patch component.
     ///
     /// # Errors
     ///
-    /// Can fail if trying to run [`Rule::Release`] when there is no pre-release.
-    pub fn bump(&mut self, rule: Rule) -> Result<(), PreReleaseNotFound> {
+    /// Can fail if trying to run [`Rule::Release`] when there is no pre-release, or
+    /// [`Rule::Promote`] when there's no pre-release that can be promoted.
+    pub fn bump(
+        &mut self,
+        rule: Rule,
+        zero_major_breaking: ZeroMajorBreaking,
+    ) -> Result<(), RuleError> {
+        let stable = self.stable;
+        let bump_stable = |rule| bump_stable(stable, rule, zero_major_breaking);
         match rule {
-            Rule::Major => self.update_version(bump_stable(self.stable, Stable::Major).into()),
-            Rule::Minor => self.update_version(bump_stable(self.stable, Stable::Minor).into()),
-            Rule::Patch => self.update_version(bump_stable(self.stable, Stable::Patch).into()),
+            Rule::Major => self.update_version(bump_stable(Stable::Major).into()),
+            Rule::Minor => self.update_version(bump_stable(Stable::Minor).into()),
+            Rule::Patch => self.update_version(bump_stable(Stable::Patch).into()),
             Rule::Release => {
                 let version = self
                     .prereleases
@@ -139,7 +151,11 @@ impl PackageVersions {
                     .into();
                 self.update_version(version);
             }
-            Rule::Pre { label, stable_rule } => self.bump_pre(label, stable_rule),
+            Rule::Pre { label, stable_rule } => {
+                self.bump_pre(label, bump_stable(stable_rule));
+            }
+            Rule::Promote { labels } => self.promote(&labels)?,
+            Rule::Exact { version } => self.update_version(version),
         }
         Ok(())
     }
@@ -149,14 +165,37 @@ impl PackageVersions {
         self.stable
     }
 
-    /// Bumps the pre-release component of a [`Version`] after applying the `stable_rule`.
-    ///
-    /// # Errors
+    /// Replace the latest pre-release with the next label in `labels`.
     ///
-    /// Can fail if there's an existing pre-release component that can't be incremented.
-    fn bump_pre(&mut self, label: Label, stable_rule: Stable) {
-        debug!("Pre-release label {label} selected. Determining next stable version...");
-        let stable_component = bump_stable(self.stable, stable_rule);
+    /// If the latest stable component has pre-releases for several labels in the chain, the one
+    /// furthest along is promoted.
+    fn promote(&mut self, labels: &[Label]) -> Result<(), RuleError> {
+        let no_prerelease =
+            || RuleError::NoPrereleaseToPromote(labels.iter().map(ToString::to_string).join(", "));
+        let (stable_component, pres) = self
+            .prereleases
+            .last_key_value()
+            .ok_or_else(no_prerelease)?;
+        let stable_component = *stable_component;
+        let (current_index, current_label) = labels
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, label)| pres.get(label).is_some())
+            .ok_or_else(no_prerelease)?;
+        let next_label = labels
+            .get(current_index + 1)
+            .cloned()
+            .ok_or_else(|| RuleError::LastPrereleaseLabel(current_label.clone()))?;
+        debug!("Promoting {stable_component} from {current_label} to {next_label}");
+        self.bump_pre(next_label, stable_component);
+        Ok(())
+    }
+
+    /// Bumps the pre-release component of a [`Version`] with the already-bumped
+    /// `stable_component`.
+    fn bump_pre(&mut self, label: Label, stable_component: StableVersion) {
+        debug!("Pre-release label {label} selected, next stable version is {stable_component}");
         let pre_version = self
             .prereleases
             .get(&stable_component)
@@ -181,9 +220,36 @@ impl PackageVersions {
     }
 }
 
-fn bump_stable(version: StableVersion, rule: Stable) -> StableVersion {
+/// How to handle breaking changes (a [`Rule::Major`]) when the major component is 0.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZeroMajorBreaking {
+    /// Bump to 1.0.0
+    Major,
+    /// Bump the minor component, like 0.1.2 -> 0.2.0
+    #[default]
+    Minor,
+}
+
+impl ZeroMajorBreaking {
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn bump_stable(
+    version: StableVersion,
+    rule: Stable,
+    zero_major_breaking: ZeroMajorBreaking,
+) -> StableVersion {
     let is_0 = version.major == 0;
     match (rule, is_0) {
+        (Stable::Major, true) if zero_major_breaking == ZeroMajorBreaking::Major => {
+            let new = version.increment_major();
+            debug!("Rule is MAJOR and major component is 0, but `zero_major_breaking` is \"major\". Bumping from {version} to {new}");
+            new
+        }
         (Stable::Major, false) => {
             let new = version.increment_major();
             debug!("Using MAJOR rule to bump from {version} to {new}");
@@ -232,6 +298,33 @@ fn bump_stable(version: StableVersion, rule: Stable) -> StableVersion {
 #[error("No prerelease version found, but a Release rule was requested")]
 pub struct PreReleaseNotFound;
 
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum RuleError {
+    #[error(transparent)]
+    PreReleaseNotFound(#[from] PreReleaseNotFound),
+    #[error("No pre-release with one of the labels [{0}] was found to promote")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(semver::no_prerelease_to_promote),
+            help("The latest version must be a pre-release using one of the configured labels"),
+            url("https://knope.tech/reference/config-file/steps/bump-version/#promote")
+        )
+    )]
+    NoPrereleaseToPromote(String),
+    #[error("{0} is the last pre-release label, so it can't be promoted")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(semver::last_prerelease_label),
+            help("Use the `Release` rule to release a stable version instead"),
+            url("https://knope.tech/reference/config-file/steps/bump-version/#promote")
+        )
+    )]
+    LastPrereleaseLabel(Label),
+}
+
 impl From<StableVersion> for PackageVersions {
     fn from(version: StableVersion) -> Self {
         Self {
@@ -324,7 +417,9 @@ mod test_bump {
     #[test]
     fn major() {
         let mut versions: PackageVersions = Version::new(1, 2, 3, None).into();
-        versions.bump(Rule::Major).unwrap();
+        versions
+            .bump(Rule::Major, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(2, 0, 0, None));
     }
@@ -332,7 +427,9 @@ mod test_bump {
     #[test]
     fn major_0() {
         let mut versions = PackageVersions::from(Version::new(0, 1, 2, None));
-        versions.bump(Rule::Major).unwrap();
+        versions
+            .bump(Rule::Major, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(0, 2, 0, None));
     }
@@ -340,7 +437,9 @@ mod test_bump {
     #[test]
     fn major_unset() {
         let mut versions = PackageVersions::default();
-        versions.bump(Rule::Major).unwrap();
+        versions
+            .bump(Rule::Major, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(0, 1, 0, None));
     }
@@ -350,16 +449,40 @@ mod test_bump {
         for pre_version in ["1.2.4-rc.0", "1.3.0-rc.0", "2.0.0-rc.0"] {
             let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
             versions.update_version(Version::from_str(pre_version).unwrap());
-            versions.bump(Rule::Major).unwrap();
+            versions
+                .bump(Rule::Major, ZeroMajorBreaking::default())
+                .unwrap();
 
             assert_eq!(versions.into_latest(), Version::new(2, 0, 0, None));
         }
     }
 
+    #[test]
+    fn major_0_to_1() {
+        let mut versions = PackageVersions::from(Version::new(0, 1, 2, None));
+        versions
+            .bump(Rule::Major, ZeroMajorBreaking::Major)
+            .unwrap();
+
+        assert_eq!(versions.into_latest(), Version::new(1, 0, 0, None));
+    }
+
+    #[test]
+    fn minor_0_with_zero_major_breaking() {
+        let mut versions = PackageVersions::from(Version::new(0, 1, 2, None));
+        versions
+            .bump(Rule::Minor, ZeroMajorBreaking::Major)
+            .unwrap();
+
+        assert_eq!(versions.into_latest(), Version::new(0, 1, 3, None));
+    }
+
     #[test]
     fn minor() {
         let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
-        versions.bump(Rule::Minor).unwrap();
+        versions
+            .bump(Rule::Minor, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(1, 3, 0, None));
     }
@@ -367,7 +490,9 @@ mod test_bump {
     #[test]
     fn minor_0() {
         let mut versions = PackageVersions::from(Version::new(0, 1, 2, None));
-        versions.bump(Rule::Minor).unwrap();
+        versions
+            .bump(Rule::Minor, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(0, 1, 3, None));
     }
@@ -375,7 +500,9 @@ mod test_bump {
     #[test]
     fn minor_unset() {
         let mut versions = PackageVersions::default();
-        versions.bump(Rule::Minor).unwrap();
+        versions
+            .bump(Rule::Minor, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(0, 0, 1, None));
     }
@@ -385,7 +512,9 @@ mod test_bump {
         for pre_version in ["1.2.4-rc.0", "1.3.0-rc.0"] {
             let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
             versions.update_version(Version::from_str(pre_version).unwrap());
-            versions.bump(Rule::Minor).unwrap();
+            versions
+                .bump(Rule::Minor, ZeroMajorBreaking::default())
+                .unwrap();
 
             assert_eq!(versions.into_latest(), Version::new(1, 3, 0, None));
         }
@@ -394,7 +523,9 @@ mod test_bump {
     #[test]
     fn patch() {
         let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
-        versions.bump(Rule::Patch).unwrap();
+        versions
+            .bump(Rule::Patch, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(1, 2, 4, None));
     }
@@ -402,7 +533,9 @@ mod test_bump {
     #[test]
     fn patch_0() {
         let mut versions = PackageVersions::from(Version::new(0, 1, 0, None));
-        versions.bump(Rule::Patch).unwrap();
+        versions
+            .bump(Rule::Patch, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(0, 1, 1, None));
     }
@@ -410,7 +543,9 @@ mod test_bump {
     #[test]
     fn patch_unset() {
         let mut versions = PackageVersions::default();
-        versions.bump(Rule::Patch).unwrap();
+        versions
+            .bump(Rule::Patch, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(0, 0, 1, None));
     }
@@ -419,7 +554,9 @@ mod test_bump {
     fn patch_after_pre() {
         let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
         versions.update_version(Version::from_str("1.2.4-rc.0").unwrap());
-        versions.bump(Rule::Patch).unwrap();
+        versions
+            .bump(Rule::Patch, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(1, 2, 4, None));
     }
@@ -428,10 +565,13 @@ mod test_bump {
     fn pre() {
         let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
         versions
-            .bump(Rule::Pre {
-                label: Label::from("rc"),
-                stable_rule: Stable::Minor,
-            })
+            .bump(
+                Rule::Pre {
+                    label: Label::from("rc"),
+                    stable_rule: Stable::Minor,
+                },
+                ZeroMajorBreaking::default(),
+            )
             .unwrap();
 
         assert_eq!(
@@ -447,10 +587,13 @@ mod test_bump {
         versions.update_version(Version::from_str("1.2.4-rc.1").unwrap());
         versions.update_version(Version::from_str("2.0.0-rc.2").unwrap());
         versions
-            .bump(Rule::Pre {
-                label: Label::from("rc"),
-                stable_rule: Stable::Minor,
-            })
+            .bump(
+                Rule::Pre {
+                    label: Label::from("rc"),
+                    stable_rule: Stable::Minor,
+                },
+                ZeroMajorBreaking::default(),
+            )
             .unwrap();
 
         assert_eq!(
@@ -465,10 +608,13 @@ mod test_bump {
         versions.update_version(Version::from_str("1.2.4-beta.1").unwrap());
         versions.update_version(Version::from_str("1.2.4-rc.0").unwrap());
         versions
-            .bump(Rule::Pre {
-                label: Label::from("beta"),
-                stable_rule: Stable::Patch,
-            })
+            .bump(
+                Rule::Pre {
+                    label: Label::from("beta"),
+                    stable_rule: Stable::Patch,
+                },
+                ZeroMajorBreaking::default(),
+            )
             .unwrap();
 
         assert_eq!(
@@ -482,10 +628,29 @@ mod test_bump {
         let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
         versions.update_version(Version::from_str("1.3.0-beta.0").unwrap());
         versions
-            .bump(Rule::Pre {
-                label: Label::from("rc"),
-                stable_rule: Stable::Minor,
-            })
+            .bump(
+                Rule::Pre {
+                    label: Label::from("rc"),
+                    stable_rule: Stable::Minor,
+                },
+                ZeroMajorBreaking::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("1.3.0-rc.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn promote() {
+        let labels = vec![Label::from("alpha"), Label::from("beta"), Label::from("rc")];
+        let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
+        versions.update_version(Version::from_str("1.3.0-alpha.2").unwrap());
+        versions.update_version(Version::from_str("1.3.0-beta.1").unwrap());
+        versions
+            .bump(Rule::Promote { labels }, ZeroMajorBreaking::default())
             .unwrap();
 
         assert_eq!(
@@ -494,6 +659,58 @@ mod test_bump {
         );
     }
 
+    #[test]
+    fn promote_latest_stable_component() {
+        let labels = vec![Label::from("alpha"), Label::from("beta")];
+        let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
+        versions.update_version(Version::from_str("1.2.4-beta.0").unwrap());
+        versions.update_version(Version::from_str("2.0.0-alpha.4").unwrap());
+        versions
+            .bump(Rule::Promote { labels }, ZeroMajorBreaking::default())
+            .unwrap();
+
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("2.0.0-beta.0").unwrap()
+        );
+    }
+
+    #[test]
+    fn promote_last_label() {
+        let labels = vec![Label::from("beta"), Label::from("rc")];
+        let mut versions = PackageVersions::from(Version::from_str("1.0.0-rc.0").unwrap());
+        let result = versions.bump(Rule::Promote { labels }, ZeroMajorBreaking::default());
+
+        assert!(matches!(result, Err(RuleError::LastPrereleaseLabel(_))));
+    }
+
+    #[test]
+    fn promote_without_prerelease() {
+        let labels = vec![Label::from("beta"), Label::from("rc")];
+        let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
+        let result = versions.bump(Rule::Promote { labels }, ZeroMajorBreaking::default());
+
+        assert!(matches!(result, Err(RuleError::NoPrereleaseToPromote(_))));
+    }
+
+    #[test]
+    fn exact() {
+        let mut versions = PackageVersions::from(Version::new(1, 2, 3, None));
+        versions
+            .bump(
+                Rule::Exact {
+                    version: Version::from_str("2.0.0-rc.0").unwrap(),
+                },
+                ZeroMajorBreaking::default(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            versions.into_latest(),
+            Version::from_str("2.0.0-rc.0").unwrap()
+        );
+    }
+
     #[test]
     fn release() {
         let mut versions = PackageVersions::default();
@@ -501,7 +718,9 @@ mod test_bump {
         versions.update_version(Version::from_str("1.2.4-rc.1").unwrap());
         versions.update_version(Version::from_str("2.0.0-rc.2").unwrap());
 
-        versions.bump(Rule::Release).unwrap();
+        versions
+            .bump(Rule::Release, ZeroMajorBreaking::default())
+            .unwrap();
 
         assert_eq!(versions.into_latest(), Version::new(2, 0, 0, None));
     }
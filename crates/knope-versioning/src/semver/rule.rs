@@ -3,7 +3,7 @@ use std::fmt::Display;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
-use super::Label;
+use super::{Label, Version};
 use crate::changes::{Change, ChangeType};
 
 /// The various rules that can be used when bumping semantic versions.
@@ -19,6 +19,16 @@ pub enum Rule {
         stable_rule: Stable,
     },
     Release,
+    /// Move the latest pre-release to the next label in `labels`, keeping the stable component.
+    ///
+    /// For `["alpha", "beta", "rc"]`, `1.2.0-beta.3` becomes `1.2.0-rc.0`.
+    Promote {
+        labels: Vec<Label>,
+    },
+    /// Set the version to exactly `version`, like `--override-version` does.
+    Exact {
+        version: Version,
+    },
 }
 
 impl From<Stable> for Rule {
@@ -0,0 +1,151 @@
+use std::str::FromStr;
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+
+use super::{Label, PreVersion, Prerelease, Version};
+
+/// How a version is written to a file. Tags always use [`VersionFormat::SemVer`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionFormat {
+    /// Like `1.2.0-rc.1`
+    #[default]
+    SemVer,
+    /// [PEP 440](https://peps.python.org/pep-0440/), like `1.2.0rc1`
+    Pep440,
+}
+
+/// The PEP 440 pre-release segment which corresponds to a Semantic Versioning label.
+fn pep440_segment(label: &Label) -> Option<&'static str> {
+    match label.0.as_str() {
+        "alpha" | "a" => Some("a"),
+        "beta" | "b" => Some("b"),
+        "rc" | "c" | "pre" | "preview" => Some("rc"),
+        "dev" => Some(".dev"),
+        _ => None,
+    }
+}
+
+impl Version {
+    /// Format the version for `format`, which may not be possible for every pre-release label.
+    ///
+    /// # Errors
+    ///
+    /// If the format is [`VersionFormat::Pep440`] and the pre-release label has no PEP 440
+    /// equivalent.
+    pub fn to_formatted_string(&self, format: VersionFormat) -> Result<String, Pep440Error> {
+        match (format, self) {
+            (VersionFormat::SemVer, _) | (VersionFormat::Pep440, Self::Stable(_)) => {
+                Ok(self.to_string())
+            }
+            (
+                VersionFormat::Pep440,
+                Self::Pre(PreVersion {
+                    stable_component,
+                    pre_component,
+                }),
+            ) => {
+                let segment = pep440_segment(&pre_component.label)
+                    .ok_or_else(|| Pep440Error(pre_component.label.clone()))?;
+                Ok(format!(
+                    "{stable_component}{segment}{}",
+                    pre_component.version
+                ))
+            }
+        }
+    }
+
+    /// Parse a PEP 440 version like `1.2.0rc1`, which only supports the subset of PEP 440 that
+    /// can be represented as a Semantic Version.
+    pub(crate) fn from_pep440(s: &str) -> Option<Self> {
+        let pre_start = s.find(|c: char| !c.is_ascii_digit() && c != '.');
+        let (stable, pre) = match pre_start {
+            Some(index) => s.split_at(index),
+            None => (s, ""),
+        };
+        let (stable, label) = if pre.is_empty() {
+            (stable, None)
+        } else if let (Some(stable), Some(number)) =
+            (stable.strip_suffix('.'), pre.strip_prefix("dev"))
+        {
+            (stable, Some(("dev", number)))
+        } else {
+            let digits_start = pre.find(|c: char| c.is_ascii_digit())?;
+            let (segment, number) = pre.split_at(digits_start);
+            let label = match segment {
+                "a" => "alpha",
+                "b" => "beta",
+                "rc" | "c" => "rc",
+                _ => return None,
+            };
+            (stable, Some((label, number)))
+        };
+        let Ok(Self::Stable(stable)) = Self::from_str(stable) else {
+            return None;
+        };
+        match label {
+            None => Some(Self::Stable(stable)),
+            Some((label, number)) => Some(Self::Pre(PreVersion {
+                stable_component: stable,
+                pre_component: Prerelease::new(Label::from(label), number.parse().ok()?),
+            })),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+#[error("The pre-release label {0} can't be written as a PEP 440 version")]
+#[cfg_attr(
+    feature = "miette",
+    diagnostic(
+        code(semver::pep440_label),
+        help("PEP 440 only supports the labels `alpha`, `beta`, `rc`, and `dev`"),
+        url("https://knope.tech/reference/config-file/packages/#pyprojecttoml")
+    )
+)]
+pub struct Pep440Error(pub Label);
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn format_pre_releases() {
+        for (semver, pep440) in [
+            ("1.2.0", "1.2.0"),
+            ("1.2.0-rc.1", "1.2.0rc1"),
+            ("1.2.0-beta.0", "1.2.0b0"),
+            ("1.2.0-alpha.3", "1.2.0a3"),
+            ("1.2.0-dev.2", "1.2.0.dev2"),
+        ] {
+            let version = Version::from_str(semver).unwrap();
+            assert_eq!(
+                version.to_formatted_string(VersionFormat::Pep440).unwrap(),
+                pep440
+            );
+            assert_eq!(
+                version.to_formatted_string(VersionFormat::SemVer).unwrap(),
+                semver
+            );
+            assert_eq!(Version::from_pep440(pep440), Some(version));
+        }
+    }
+
+    #[test]
+    fn unsupported_label() {
+        let version = Version::from_str("1.2.0-nightly.1").unwrap();
+        assert!(version.to_formatted_string(VersionFormat::Pep440).is_err());
+    }
+
+    #[test]
+    fn invalid_pep440() {
+        assert_eq!(Version::from_pep440("1.2.0.post1"), None);
+        assert_eq!(Version::from_pep440("1.2"), None);
+    }
+}
@@ -31,10 +31,13 @@ impl Cargo {
         })
     }
 
+    /// Get the version of the package, either from `package.version` or, in a workspace root,
+    /// from `workspace.package.version`.
     pub(super) fn get_version(&self) -> Result<Version, Error> {
         self.document
             .get("package")
             .and_then(|package| package.get("version")?.as_str())
+            .or_else(|| workspace_package_version(&self.document)?.as_str())
             .ok_or_else(|| Error::MissingRequiredProperties {
                 property: "package.version",
                 path: self.path.clone(),
@@ -69,19 +72,100 @@ impl Cargo {
             }
             format!("{dependency}.version = {new_version}")
         } else {
-            let version = self
+            let package_version = self
                 .document
                 .get_mut("package")
-                .and_then(|package| package.get_mut("version"));
-            if let Some(version) = version {
+                .and_then(|package| package.get_mut("version"))
+                .filter(|version| version.is_str());
+            if let Some(version) = package_version {
                 *version = value(new_version.to_string());
+                format!("version = {new_version}")
+            } else if let Some(version) = self
+                .document
+                .get_mut("workspace")
+                .and_then(|workspace| workspace.get_mut("package")?.get_mut("version"))
+            {
+                *version = value(new_version.to_string());
+                format!("workspace.package.version = {new_version}")
+            } else {
+                // The version is inherited from the workspace root, which is a separate file
+                return self;
             }
-            format!("version = {new_version}")
         };
         self.diff.push(diff);
         self
     }
 
+    /// Whether this is a workspace member with `version.workspace = true`, meaning the version
+    /// is stored in the workspace root's `Cargo.toml` instead of this file.
+    #[must_use]
+    pub fn inherits_version(&self) -> bool {
+        let inherits = self
+            .document
+            .get("package")
+            .and_then(|package| package.get("version")?.get("workspace")?.as_bool())
+            .unwrap_or(false);
+        inherits && workspace_package_version(&self.document).is_none()
+    }
+
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        name_from_document(&self.document)
+    }
+
+    /// The paths listed in `workspace.members`, if this is a workspace root.
+    #[must_use]
+    pub fn workspace_members(&self) -> Vec<&str> {
+        self.document
+            .get("workspace")
+            .and_then(|workspace| workspace.get("members")?.as_array())
+            .map(|members| {
+                members
+                    .iter()
+                    .filter_map(|member| member.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Whether this file depends on `dependency` by `path` with a `version` requirement, which
+    /// should be kept up to date with the dependency's version.
+    #[must_use]
+    pub fn contains_path_dependency(&self, dependency: &str) -> bool {
+        let is_versioned_path = |dep: &toml_edit::Item| {
+            dep.get("path").is_some() && dep.get("version").is_some_and(toml_edit::Item::is_str)
+        };
+        ["dependencies", "dev-dependencies"]
+            .into_iter()
+            .filter_map(|table| self.document.get(table)?.get(dependency))
+            .chain(
+                self.document
+                    .get("workspace")
+                    .and_then(|workspace| workspace.get("dependencies")?.get(dependency)),
+            )
+            .any(is_versioned_path)
+    }
+
+    /// Whether this package needs `dependency` to build or test, so `dependency` must be published
+    /// first.
+    #[must_use]
+    pub fn depends_on(&self, dependency: &str) -> bool {
+        ["dependencies", "build-dependencies", "dev-dependencies"]
+            .into_iter()
+            .any(|table| {
+                self.document
+                    .get(table)
+                    .and_then(|deps| deps.get(dependency))
+                    .is_some()
+            })
+    }
+
+    /// Whether this file is the root of a Cargo workspace.
+    #[must_use]
+    pub fn is_workspace_root(&self) -> bool {
+        self.document.get("workspace").is_some()
+    }
+
     pub(super) fn write(self) -> Option<Action> {
         if self.diff.is_empty() {
             return None;
@@ -94,6 +178,12 @@ impl Cargo {
     }
 }
 
+fn workspace_package_version(document: &DocumentMut) -> Option<&toml_edit::Item> {
+    document
+        .get("workspace")
+        .and_then(|workspace| workspace.get("package")?.get("version"))
+}
+
 #[must_use]
 pub fn name_from_document(document: &DocumentMut) -> Option<&str> {
     document
@@ -101,16 +191,20 @@ pub fn name_from_document(document: &DocumentMut) -> Option<&str> {
         .and_then(|package| package.get("name")?.as_str())
 }
 
+/// Whether the document has a dependency on `dependency` with a version which can be updated.
+///
+/// Dependencies declared with `workspace = true` get their version from the workspace root, so they
+/// don't count.
 #[must_use]
 pub fn contains_dependency(document: &DocumentMut, dependency: &str) -> bool {
     document
         .get("dependencies")
         .and_then(|deps| deps.get(dependency))
-        .is_some()
+        .is_some_and(|dep| !is_inherited(dep))
         || document
             .get("dev-dependencies")
             .and_then(|deps| deps.get(dependency))
-            .is_some()
+            .is_some_and(|dep| !is_inherited(dep))
         || document
             .get("workspace")
             .and_then(|workspace| workspace.get("dependencies")?.get(dependency))
@@ -181,6 +275,21 @@ mod test_contains_dependency {
         assert!(contains_dependency(&document, "knope-versioning"));
     }
 
+    #[test]
+    fn inherited_dependency() {
+        let content = r#"
+        [package]
+        name = "tester"
+        version = "1.2.3-rc.0"
+        
+        [dependencies]
+        knope-versioning = { workspace = true }
+        "#;
+
+        let document: DocumentMut = content.parse().expect("valid toml");
+        assert!(!contains_dependency(&document, "knope-versioning"));
+    }
+
     #[test]
     fn workspace_dependency() {
         let content = r#"
@@ -197,7 +306,16 @@ mod test_contains_dependency {
     }
 }
 
+fn is_inherited(dep: &toml_edit::Item) -> bool {
+    dep.get("workspace")
+        .and_then(toml_edit::Item::as_bool)
+        .unwrap_or(false)
+}
+
 fn write_version_to_dep(dep: &mut toml_edit::Item, version: &Version) {
+    if is_inherited(dep) {
+        return;
+    }
     if let Some(table) = dep.as_table_mut() {
         table.insert("version", value(version.to_string()));
     } else if let Some(table) = dep.as_inline_table_mut() {
@@ -283,6 +401,92 @@ mod tests {
         assert_eq!(new.document.to_string(), expected);
     }
 
+    #[test]
+    fn depends_on() {
+        let content = r#"
+        [package]
+        name = "tester"
+        version = "0.1.0"
+
+        [dependencies]
+        first = { path = "../first", version = "0.1.0" }
+
+        [build-dependencies]
+        second = { workspace = true }
+        "#;
+
+        let cargo = Cargo::new(RelativePathBuf::from("tester/Cargo.toml"), content).unwrap();
+        assert!(cargo.depends_on("first"));
+        assert!(cargo.depends_on("second"));
+        assert!(!cargo.depends_on("tester"));
+    }
+
+    #[test]
+    fn set_workspace_package_version() {
+        let content = r#"
+        [workspace]
+        members = ["first"]
+
+        [workspace.package]
+        version = "0.1.0"
+        "#;
+
+        let new = Cargo::new(RelativePathBuf::from("Cargo.toml"), content).unwrap();
+        assert_eq!(
+            new.get_version().unwrap(),
+            Version::from_str("0.1.0").unwrap()
+        );
+        assert!(!new.inherits_version());
+
+        let action = new
+            .set_version(&Version::from_str("0.2.0").unwrap(), None)
+            .write()
+            .expect("diff to write");
+        let expected = Action::WriteToFile {
+            path: RelativePathBuf::from("Cargo.toml"),
+            content: content.replace("0.1.0", "0.2.0"),
+            diff: "workspace.package.version = 0.2.0".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
+
+    #[test]
+    fn inherited_version() {
+        let content = r#"
+        [package]
+        name = "first"
+        version.workspace = true
+        "#;
+
+        let new = Cargo::new(RelativePathBuf::from("first/Cargo.toml"), content).unwrap();
+        assert!(new.inherits_version());
+        assert!(new
+            .set_version(&Version::from_str("0.2.0").unwrap(), None)
+            .write()
+            .is_none());
+    }
+
+    #[test]
+    fn path_dependencies() {
+        let content = r#"
+        [package]
+        name = "tester"
+        version = "1.0.0"
+
+        [dependencies]
+        registry-only = "0.1.0"
+        path-only = { path = "../path-only" }
+
+        [dev-dependencies]
+        path-and-version = { path = "../path-and-version", version = "0.1.0" }
+        "#;
+
+        let cargo = Cargo::new(RelativePathBuf::from("Cargo.toml"), content).unwrap();
+        assert!(cargo.contains_path_dependency("path-and-version"));
+        assert!(!cargo.contains_path_dependency("path-only"));
+        assert!(!cargo.contains_path_dependency("registry-only"));
+    }
+
     #[test]
     fn dependencies() {
         let content = r#"
@@ -57,7 +57,14 @@ impl CargoLock {
             if name != dependency {
                 continue;
             }
-            self.diff.push(format!("{name} = {new_version}"));
+            // Several packages can share one lockfile, only keep the latest change for each
+            let prefix = format!("{name} = ");
+            let diff = format!("{prefix}{new_version}");
+            if let Some(existing) = self.diff.iter_mut().find(|it| it.starts_with(&prefix)) {
+                *existing = diff;
+            } else {
+                self.diff.push(diff);
+            }
             match package.get_mut("version") {
                 Some(version) => {
                     *version = value(new_version.to_string());
@@ -125,3 +132,45 @@ pub enum SetError {
     )]
     MalformedPackage(RelativePathBuf),
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CONTENT: &str = r#"version = 4
+
+[[package]]
+name = "first"
+version = "1.0.0"
+
+[[package]]
+name = "second"
+version = "0.1.0"
+dependencies = ["first"]
+"#;
+
+    #[test]
+    fn set_multiple_members() {
+        let action = CargoLock::new(RelativePathBuf::from("Cargo.lock"), CONTENT)
+            .unwrap()
+            .set_version(&Version::from_str("1.1.0").unwrap(), Some("first"))
+            .unwrap()
+            .set_version(&Version::from_str("0.2.0").unwrap(), Some("second"))
+            .unwrap()
+            .set_version(&Version::from_str("1.2.0").unwrap(), Some("first"))
+            .unwrap()
+            .write();
+
+        let expected = Action::WriteToFile {
+            path: RelativePathBuf::from("Cargo.lock"),
+            content: CONTENT.replace("1.0.0", "1.2.0").replace("0.1.0", "0.2.0"),
+            diff: "first = 1.2.0, second = 0.2.0".to_string(),
+        };
+        assert_eq!(action, Some(expected));
+    }
+}
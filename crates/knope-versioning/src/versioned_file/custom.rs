@@ -0,0 +1,400 @@
+use std::{fmt, fmt::Display, str::FromStr};
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use thiserror::Error;
+use toml_edit::{DocumentMut, Item, TomlError, Value};
+
+use crate::{action::Action, semver, semver::Version};
+
+/// The structured data formats which can be versioned with a key path, for files Knope doesn't
+/// natively understand.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileType {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Toml => write!(f, "toml"),
+            Self::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+/// A JSON, TOML, or YAML file which contains a version at a specific key path, like
+/// `tool.mycompany.release.version`.
+#[derive(Clone, Debug)]
+pub struct Custom {
+    path: RelativePathBuf,
+    raw: String,
+    file_type: FileType,
+    key: String,
+    version: Version,
+    diff: Option<String>,
+}
+
+impl Custom {
+    pub(crate) fn new(
+        path: RelativePathBuf,
+        content: String,
+        file_type: FileType,
+        key: &str,
+    ) -> Result<Self, Error> {
+        let raw_version = match file_type {
+            FileType::Json => {
+                let json: JsonValue =
+                    serde_json::from_str(&content).map_err(|source| Error::Json {
+                        path: path.clone(),
+                        source,
+                    })?;
+                json_pointer(key)
+                    .and_then(|pointer| json.pointer(&pointer)?.as_str().map(String::from))
+            }
+            FileType::Toml => {
+                let document: DocumentMut = content.parse().map_err(|source| Error::Toml {
+                    path: path.clone(),
+                    source,
+                })?;
+                key_parts(key)
+                    .try_fold(document.as_item(), |item, part| item.get(part))
+                    .and_then(|item| item.as_str().map(String::from))
+            }
+            FileType::Yaml => {
+                let yaml: YamlValue =
+                    serde_yaml::from_str(&content).map_err(|source| Error::Yaml {
+                        path: path.clone(),
+                        source,
+                    })?;
+                key_parts(key)
+                    .try_fold(&yaml, |value, part| value.get(part))
+                    .and_then(|value| value.as_str().map(String::from))
+            }
+        }
+        .ok_or_else(|| Error::MissingKey {
+            path: path.clone(),
+            key: key.to_string(),
+        })?;
+
+        Ok(Self {
+            version: Version::from_str(&raw_version)?,
+            path,
+            raw: content,
+            file_type,
+            key: key.to_string(),
+            diff: None,
+        })
+    }
+
+    pub(crate) fn get_version(&self) -> &Version {
+        &self.version
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    pub(crate) fn set_version(mut self, new_version: &Version) -> Result<Self, SetError> {
+        let new_version_str = new_version.to_string();
+        self.raw = match self.file_type {
+            FileType::Json => {
+                let mut json: JsonValue = serde_json::from_str(&self.raw)?;
+                if let Some(version) =
+                    json_pointer(&self.key).and_then(|pointer| json.pointer_mut(&pointer))
+                {
+                    *version = JsonValue::String(new_version_str.clone());
+                }
+                let mut content = serde_json::to_string_pretty(&json)?;
+                if self.raw.ends_with('\n') {
+                    content.push('\n');
+                }
+                content
+            }
+            FileType::Toml => {
+                let mut document: DocumentMut = self.raw.parse()?;
+                if let Some(version) = key_parts(&self.key)
+                    .try_fold(document.as_item_mut(), |item, part| item.get_mut(part))
+                    .and_then(Item::as_value_mut)
+                {
+                    // Keep any comments or whitespace around the old value
+                    let decor = version.decor().clone();
+                    *version = Value::from(new_version_str.as_str());
+                    *version.decor_mut() = decor;
+                }
+                document.to_string()
+            }
+            FileType::Yaml => set_yaml_version(
+                &self.raw,
+                &self.key,
+                &self.version.to_string(),
+                &new_version_str,
+            )?,
+        };
+        self.version = new_version.clone();
+        self.diff = Some(format!("{key} = {new_version_str}", key = self.key));
+        Ok(self)
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        self.diff.map(|diff| Action::WriteToFile {
+            content: self.raw,
+            path: self.path,
+            diff,
+        })
+    }
+}
+
+fn key_parts(key: &str) -> impl Iterator<Item = &str> {
+    key.split('.')
+}
+
+/// Convert a dotted key path into a [JSON pointer](https://datatracker.ietf.org/doc/html/rfc6901)
+fn json_pointer(key: &str) -> Option<String> {
+    if key.is_empty() {
+        return None;
+    }
+    let mut pointer = String::with_capacity(key.len() + 1);
+    for part in key_parts(key) {
+        pointer.push('/');
+        pointer.push_str(&part.replace('~', "~0").replace('/', "~1"));
+    }
+    Some(pointer)
+}
+
+/// `serde_yaml` doesn't preserve comments or formatting, so try to replace only the line
+/// containing the version. If that line can't be found, re-serialize the whole document.
+fn set_yaml_version(
+    raw: &str,
+    key: &str,
+    old_version: &str,
+    new_version: &str,
+) -> Result<String, serde_yaml::Error> {
+    let target = key_parts(key).collect::<Vec<_>>();
+    let mut stack: Vec<(usize, &str)> = Vec::new();
+    for (index, line) in raw.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((line_key, rest)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let indent = line.len() - trimmed.len();
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+        stack.push((indent, line_key.trim().trim_matches(['"', '\''])));
+        if stack
+            .iter()
+            .map(|(_, part)| *part)
+            .eq(target.iter().copied())
+            && rest.contains(old_version)
+        {
+            let indentation = line.strip_suffix(trimmed).unwrap_or_default();
+            let new_line = format!(
+                "{indentation}{line_key}:{}",
+                rest.replacen(old_version, new_version, 1)
+            );
+            // Other lines could be identical, so only replace this one
+            return Ok(raw
+                .split_inclusive('\n')
+                .enumerate()
+                .map(|(other, line)| {
+                    if other == index {
+                        new_line.as_str()
+                    } else {
+                        line
+                    }
+                })
+                .collect());
+        }
+    }
+
+    let mut yaml: YamlValue = serde_yaml::from_str(raw)?;
+    if let Some(version) = target
+        .iter()
+        .try_fold(&mut yaml, |value, part| value.get_mut(part))
+    {
+        *version = YamlValue::String(new_version.to_string());
+    }
+    serde_yaml::to_string(&yaml)
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error("Error deserializing {path} as JSON: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::json),
+            url(
+                "https://knope.tech/reference/config-file/packages/#other-json-toml-or-yaml-files"
+            )
+        )
+    )]
+    Json {
+        path: RelativePathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Error deserializing {path} as TOML: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::toml),
+            url(
+                "https://knope.tech/reference/config-file/packages/#other-json-toml-or-yaml-files"
+            )
+        )
+    )]
+    Toml {
+        path: RelativePathBuf,
+        #[source]
+        source: TomlError,
+    },
+    #[error("Error deserializing {path} as YAML: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::yaml),
+            url(
+                "https://knope.tech/reference/config-file/packages/#other-json-toml-or-yaml-files"
+            )
+        )
+    )]
+    Yaml {
+        path: RelativePathBuf,
+        #[source]
+        source: serde_yaml::Error,
+    },
+    #[error("Could not find a string at {key} in {path}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::missing_key),
+            help("The `key` must be a dot-separated path to a string containing the version"),
+            url(
+                "https://knope.tech/reference/config-file/packages/#other-json-toml-or-yaml-files"
+            )
+        )
+    )]
+    MissingKey { path: RelativePathBuf, key: String },
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Semver(#[from] semver::Error),
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum SetError {
+    #[error("Error serializing JSON, this is a bug: {0}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::json_serialize),
+            help("This is a bug in knope, please report it."),
+            url("https://github.com/knope-dev/knope/issues")
+        )
+    )]
+    Json(#[from] serde_json::Error),
+    #[error("Error parsing TOML, this is a bug: {0}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::toml_serialize),
+            help("This is a bug in knope, please report it."),
+            url("https://github.com/knope-dev/knope/issues")
+        )
+    )]
+    Toml(#[from] TomlError),
+    #[error("Error serializing YAML, this is a bug: {0}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::custom::yaml_serialize),
+            help("This is a bug in knope, please report it."),
+            url("https://github.com/knope-dev/knope/issues")
+        )
+    )]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn set(content: &str, file_type: FileType, key: &str) -> String {
+        let file =
+            Custom::new(RelativePathBuf::new(), content.to_string(), file_type, key).unwrap();
+        assert_eq!(file.get_version(), &Version::from_str("1.2.3").unwrap());
+        let Some(Action::WriteToFile { content, .. }) = file
+            .set_version(&Version::from_str("2.0.0-rc.0").unwrap())
+            .unwrap()
+            .write()
+        else {
+            panic!("Expected a file to write");
+        };
+        content
+    }
+
+    #[test]
+    fn json() {
+        let content =
+            "{\n  \"release\": {\n    \"version\": \"1.2.3\"\n  },\n  \"name\": \"x\"\n}\n";
+        assert_eq!(
+            set(content, FileType::Json, "release.version"),
+            content.replace("1.2.3", "2.0.0-rc.0")
+        );
+    }
+
+    #[test]
+    fn toml() {
+        let content = "[tool.mycompany.release]\nversion = \"1.2.3\" # keep me\nother = 1\n";
+        assert_eq!(
+            set(content, FileType::Toml, "tool.mycompany.release.version"),
+            "[tool.mycompany.release]\nversion = \"2.0.0-rc.0\" # keep me\nother = 1\n"
+        );
+    }
+
+    #[test]
+    fn yaml() {
+        let content = "# A comment\napp:\n  name: thing\n  meta:\n    version: \"1.2.3\" # here\nversion: 1.2.3\n";
+        assert_eq!(
+            set(content, FileType::Yaml, "app.meta.version"),
+            "# A comment\napp:\n  name: thing\n  meta:\n    version: \"2.0.0-rc.0\" # here\nversion: 1.2.3\n"
+        );
+    }
+
+    #[test]
+    fn yaml_identical_lines() {
+        let content = "other:\n  version: 1.2.3\napp:\n  version: 1.2.3\n";
+        assert_eq!(
+            set(content, FileType::Yaml, "app.version"),
+            "other:\n  version: 1.2.3\napp:\n  version: 2.0.0-rc.0\n"
+        );
+    }
+
+    #[test]
+    fn missing_key() {
+        let result = Custom::new(
+            RelativePathBuf::new(),
+            "{\"version\": 1}".to_string(),
+            FileType::Json,
+            "version",
+        );
+        assert!(matches!(result, Err(Error::MissingKey { .. })));
+    }
+}
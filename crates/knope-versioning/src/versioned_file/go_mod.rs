@@ -241,8 +241,8 @@ pub enum GoVersioning {
 ///
 /// More details from [the go docs](https://go.dev/doc/modules/gomod-ref#module)
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct ModuleLine {
-    module: String,
+pub(super) struct ModuleLine {
+    pub(super) module: String,
     major_version: Option<u64>,
     version: Option<Version>,
 }
@@ -0,0 +1,98 @@
+//! Helpers for [Go workspaces](https://go.dev/ref/mod#workspaces), where a `go.work` file lists
+//! several modules which are developed together.
+
+use std::str::FromStr;
+
+use relative_path::RelativePathBuf;
+
+use super::go_mod::ModuleLine;
+
+/// The directories of the modules in the `use` directives of a `go.work` file, relative to the
+/// directory containing `go.work`. The root module (`use .`) is an empty path.
+#[must_use]
+pub fn members(go_work: &str) -> Vec<RelativePathBuf> {
+    let mut members = Vec::new();
+    let mut in_use_block = false;
+    for line in go_work.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let path = if in_use_block {
+            if line == ")" {
+                in_use_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("use") {
+            let rest = rest.trim_start();
+            if rest == "(" {
+                in_use_block = true;
+                continue;
+            }
+            if rest.len() == line.len() - "use".len() {
+                // Something like `user`, not a `use` directive
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let path = path.trim_matches(['"', '`']);
+        if !path.is_empty() {
+            members.push(RelativePathBuf::from(path).normalize());
+        }
+    }
+    members
+}
+
+/// The last component of the module path in a `go.mod` file, ignoring any major version suffix.
+///
+/// For `module github.com/owner/repo/v2`, this is `repo`.
+#[must_use]
+pub fn module_name(go_mod: &str) -> Option<String> {
+    let line = go_mod.lines().find(|line| line.starts_with("module "))?;
+    let module_line = ModuleLine::from_str(line).ok()?;
+    module_line
+        .module
+        .rsplit('/')
+        .next()
+        .map(|name| name.trim_matches('"').to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn use_directives() {
+        let content = r#"go 1.22
+
+use . // The root module
+use ./tools/cli
+
+use (
+    ./api
+    "./nested/module"
+    // ./disabled
+)
+"#;
+        assert_eq!(
+            members(content),
+            vec![
+                RelativePathBuf::new(),
+                RelativePathBuf::from("tools/cli"),
+                RelativePathBuf::from("api"),
+                RelativePathBuf::from("nested/module"),
+            ]
+        );
+    }
+
+    #[test]
+    fn module_name_ignores_major_version() {
+        assert_eq!(
+            module_name("module github.com/owner/repo/v2 // v2.1.4\n\ngo 1.22\n"),
+            Some("repo".to_string())
+        );
+    }
+}
@@ -1,26 +1,44 @@
 use std::{fmt::Debug, path::PathBuf};
 
 use cargo::Cargo;
+use custom::Custom;
+pub use custom::FileType;
 pub use go_mod::{GoMod, GoVersioning};
 use package_json::PackageJson;
+use package_lock::PackageLock;
+use pkgbuild::Pkgbuild;
+use pnpm_lock::PnpmLock;
 use pubspec::PubSpec;
 use pyproject::PyProject;
+use python_lock::PythonLock;
+use regex_file::RegexFile;
 use relative_path::RelativePathBuf;
 use serde::{Serialize, Serializer};
+use snapcraft::Snapcraft;
+use yarn_lock::YarnLock;
 
 use crate::{
     action::ActionSet::{Single, Two},
-    semver::Version,
+    semver::{Pep440Error, Version, VersionFormat},
     versioned_file::cargo_lock::CargoLock,
     Action,
 };
 
 pub mod cargo;
 mod cargo_lock;
+mod custom;
 mod go_mod;
+pub mod go_work;
 mod package_json;
+mod package_lock;
+mod pkgbuild;
+mod pnpm_lock;
 mod pubspec;
 mod pyproject;
+mod python_lock;
+mod regex_file;
+mod snapcraft;
+mod yarn_lock;
 
 #[derive(Clone, Debug)]
 pub enum VersionedFile {
@@ -29,7 +47,15 @@ pub enum VersionedFile {
     PubSpec(PubSpec),
     GoMod(GoMod),
     PackageJson(PackageJson),
+    PackageLock(PackageLock),
+    PnpmLock(PnpmLock),
+    YarnLock(YarnLock),
     PyProject(PyProject),
+    PythonLock(PythonLock),
+    Snapcraft(Snapcraft),
+    Pkgbuild(Pkgbuild),
+    Custom(Custom),
+    Regex(RegexFile),
 }
 
 impl VersionedFile {
@@ -44,16 +70,19 @@ impl VersionedFile {
         content: String,
         git_tags: &[S],
     ) -> Result<Self, Error> {
-        match config.format {
+        match &config.format {
             Format::Cargo => Cargo::new(config.as_path(), &content)
                 .map(VersionedFile::Cargo)
                 .map_err(Error::Cargo),
             Format::CargoLock => CargoLock::new(config.as_path(), &content)
                 .map(VersionedFile::CargoLock)
                 .map_err(Error::CargoLock),
-            Format::PyProject => PyProject::new(config.as_path(), content)
+            Format::PyProject => PyProject::new(config.as_path(), content, config.version_format)
                 .map(VersionedFile::PyProject)
                 .map_err(Error::PyProject),
+            Format::PoetryLock | Format::UvLock => PythonLock::new(config.as_path(), &content)
+                .map(VersionedFile::PythonLock)
+                .map_err(Error::PythonLock),
             Format::PubSpec => PubSpec::new(config.as_path(), content)
                 .map(VersionedFile::PubSpec)
                 .map_err(Error::PubSpec),
@@ -63,6 +92,30 @@ impl VersionedFile {
             Format::PackageJson => PackageJson::new(config.as_path(), content)
                 .map(VersionedFile::PackageJson)
                 .map_err(Error::PackageJson),
+            Format::PackageLock => PackageLock::new(config.as_path(), &content)
+                .map(VersionedFile::PackageLock)
+                .map_err(Error::PackageLock),
+            Format::PnpmLock => PnpmLock::new(config.as_path(), content)
+                .map(VersionedFile::PnpmLock)
+                .map_err(Error::PnpmLock),
+            Format::YarnLock => Ok(VersionedFile::YarnLock(YarnLock::new(
+                config.as_path(),
+                content,
+            ))),
+            Format::Snapcraft => Snapcraft::new(config.as_path(), content)
+                .map(VersionedFile::Snapcraft)
+                .map_err(Error::Snapcraft),
+            Format::Pkgbuild => Pkgbuild::new(config.as_path(), content)
+                .map(VersionedFile::Pkgbuild)
+                .map_err(Error::Pkgbuild),
+            Format::Custom { file_type, key, .. } => {
+                Custom::new(config.as_path(), content, *file_type, key)
+                    .map(VersionedFile::Custom)
+                    .map_err(Error::Custom)
+            }
+            Format::Regex { search, .. } => RegexFile::new(config.as_path(), content, search)
+                .map(VersionedFile::Regex)
+                .map_err(Error::Regex),
         }
     }
 
@@ -72,9 +125,17 @@ impl VersionedFile {
             VersionedFile::Cargo(cargo) => &cargo.path,
             VersionedFile::CargoLock(cargo_lock) => &cargo_lock.path,
             VersionedFile::PyProject(pyproject) => &pyproject.path,
+            VersionedFile::PythonLock(python_lock) => python_lock.get_path(),
             VersionedFile::PubSpec(pubspec) => pubspec.get_path(),
             VersionedFile::GoMod(gomod) => gomod.get_path(),
             VersionedFile::PackageJson(package_json) => package_json.get_path(),
+            VersionedFile::PackageLock(package_lock) => package_lock.get_path(),
+            VersionedFile::PnpmLock(pnpm_lock) => pnpm_lock.get_path(),
+            VersionedFile::YarnLock(yarn_lock) => yarn_lock.get_path(),
+            VersionedFile::Snapcraft(snapcraft) => snapcraft.get_path(),
+            VersionedFile::Pkgbuild(pkgbuild) => pkgbuild.get_path(),
+            VersionedFile::Custom(custom) => custom.get_path(),
+            VersionedFile::Regex(regex) => regex.get_path(),
         }
     }
 
@@ -86,11 +147,31 @@ impl VersionedFile {
     pub fn version(&self) -> Result<Version, Error> {
         match self {
             VersionedFile::Cargo(cargo) => cargo.get_version().map_err(Error::Cargo),
-            VersionedFile::CargoLock(_) => Err(Error::NoVersion),
+            VersionedFile::CargoLock(_)
+            | VersionedFile::PnpmLock(_)
+            | VersionedFile::YarnLock(_)
+            | VersionedFile::PythonLock(_) => Err(Error::NoVersion),
             VersionedFile::PyProject(pyproject) => Ok(pyproject.version.clone()),
             VersionedFile::PubSpec(pubspec) => Ok(pubspec.get_version().clone()),
             VersionedFile::GoMod(gomod) => Ok(gomod.get_version().clone()),
             VersionedFile::PackageJson(package_json) => Ok(package_json.get_version().clone()),
+            VersionedFile::PackageLock(package_lock) => {
+                package_lock.get_version().map_err(Error::PackageLock)
+            }
+            VersionedFile::Snapcraft(snapcraft) => Ok(snapcraft.get_version().clone()),
+            VersionedFile::Pkgbuild(pkgbuild) => Ok(pkgbuild.get_version().clone()),
+            VersionedFile::Custom(custom) => Ok(custom.get_version().clone()),
+            VersionedFile::Regex(regex) => Ok(regex.get_version().clone()),
+        }
+    }
+
+    /// Whether this file gets its version from another file, like a Cargo workspace member
+    /// with `version.workspace = true`.
+    #[must_use]
+    pub fn inherits_version(&self) -> bool {
+        match self {
+            VersionedFile::Cargo(cargo) => cargo.inherits_version(),
+            _ => false,
         }
     }
 
@@ -111,7 +192,14 @@ impl VersionedFile {
                 .set_version(new_version, dependency)
                 .map(Self::CargoLock)
                 .map_err(SetError::CargoLock),
-            Self::PyProject(pyproject) => Ok(Self::PyProject(pyproject.set_version(new_version))),
+            Self::PyProject(pyproject) => pyproject
+                .set_version(new_version)
+                .map_err(SetError::Pep440)
+                .map(Self::PyProject),
+            Self::PythonLock(python_lock) => Ok(Self::PythonLock(match dependency {
+                Some(dependency) => python_lock.set_version(new_version, dependency),
+                None => python_lock,
+            })),
             Self::PubSpec(pubspec) => pubspec
                 .set_version(new_version)
                 .map_err(SetError::Yaml)
@@ -124,6 +212,27 @@ impl VersionedFile {
                 .set_version(new_version)
                 .map_err(SetError::Json)
                 .map(Self::PackageJson),
+            Self::PackageLock(package_lock) => Ok(Self::PackageLock(
+                package_lock.set_version(new_version, dependency),
+            )),
+            Self::PnpmLock(pnpm_lock) => Ok(Self::PnpmLock(match dependency {
+                Some(dependency) => pnpm_lock.set_version(new_version, dependency),
+                None => pnpm_lock,
+            })),
+            Self::YarnLock(yarn_lock) => Ok(Self::YarnLock(match dependency {
+                Some(dependency) => yarn_lock.set_version(new_version, dependency),
+                None => yarn_lock,
+            })),
+            Self::Snapcraft(snapcraft) => snapcraft
+                .set_version(new_version)
+                .map_err(SetError::Snapcraft)
+                .map(Self::Snapcraft),
+            Self::Pkgbuild(pkgbuild) => Ok(Self::Pkgbuild(pkgbuild.set_version(new_version))),
+            Self::Custom(custom) => custom
+                .set_version(new_version)
+                .map_err(SetError::Custom)
+                .map(Self::Custom),
+            Self::Regex(regex) => Ok(Self::Regex(regex.set_version(new_version))),
         }
     }
 
@@ -132,9 +241,17 @@ impl VersionedFile {
             Self::Cargo(cargo) => cargo.write().map(Single),
             Self::CargoLock(cargo_lock) => cargo_lock.write().map(Single),
             Self::PyProject(pyproject) => pyproject.write().map(Single),
+            Self::PythonLock(python_lock) => python_lock.write().map(Single),
             Self::PubSpec(pubspec) => pubspec.write().map(Single),
             Self::GoMod(gomod) => gomod.write().map(Two),
             Self::PackageJson(package_json) => package_json.write().map(Single),
+            Self::PackageLock(package_lock) => package_lock.write().map(Single),
+            Self::PnpmLock(pnpm_lock) => pnpm_lock.write().map(Single),
+            Self::YarnLock(yarn_lock) => yarn_lock.write().map(Single),
+            Self::Snapcraft(snapcraft) => snapcraft.write().map(Single),
+            Self::Pkgbuild(pkgbuild) => pkgbuild.write().map(Single),
+            Self::Custom(custom) => custom.write().map(Single),
+            Self::Regex(regex) => regex.write().map(Single),
         }
     }
 }
@@ -168,6 +285,15 @@ pub enum SetError {
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     CargoLock(#[from] cargo_lock::SetError),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Snapcraft(#[from] snapcraft::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Custom(#[from] custom::SetError),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Pep440(#[from] Pep440Error),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -194,6 +320,9 @@ pub enum Error {
     PyProject(#[from] pyproject::Error),
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    PythonLock(#[from] python_lock::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
     PubSpec(#[from] pubspec::Error),
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
@@ -201,27 +330,71 @@ pub enum Error {
     #[error(transparent)]
     #[cfg_attr(feature = "miette", diagnostic(transparent))]
     PackageJson(#[from] package_json::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    PackageLock(#[from] package_lock::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    PnpmLock(#[from] pnpm_lock::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Snapcraft(#[from] snapcraft::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Pkgbuild(#[from] pkgbuild::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Custom(#[from] custom::Error),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Regex(#[from] regex_file::Error),
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) enum Format {
     Cargo,
     CargoLock,
     PyProject,
+    PoetryLock,
+    UvLock,
     PubSpec,
     GoMod,
     PackageJson,
+    PackageLock,
+    PnpmLock,
+    YarnLock,
+    Snapcraft,
+    Pkgbuild,
+    /// Any JSON, TOML, or YAML file with a version at a user-provided key path
+    Custom {
+        file_name: String,
+        file_type: FileType,
+        key: String,
+    },
+    /// Any file where the version is found by a user-provided `search` pattern
+    Regex {
+        file_name: String,
+        search: String,
+    },
 }
 
 impl Format {
-    pub(crate) const fn file_name(self) -> &'static str {
+    pub(crate) fn file_name(&self) -> &str {
         match self {
             Format::Cargo => "Cargo.toml",
             Format::CargoLock => "Cargo.lock",
             Format::PyProject => "pyproject.toml",
+            Format::PoetryLock => "poetry.lock",
+            Format::UvLock => "uv.lock",
             Format::PubSpec => "pubspec.yaml",
             Format::GoMod => "go.mod",
             Format::PackageJson => "package.json",
+            Format::PackageLock => "package-lock.json",
+            Format::PnpmLock => "pnpm-lock.yaml",
+            Format::YarnLock => "yarn.lock",
+            Format::Snapcraft => "snapcraft.yaml",
+            Format::Pkgbuild => "PKGBUILD",
+            Format::Custom { file_name, .. } | Format::Regex { file_name, .. } => file_name,
         }
     }
 
@@ -230,9 +403,16 @@ impl Format {
             "Cargo.toml" => Some(Format::Cargo),
             "Cargo.lock" => Some(Format::CargoLock),
             "pyproject.toml" => Some(Format::PyProject),
+            "poetry.lock" => Some(Format::PoetryLock),
+            "uv.lock" => Some(Format::UvLock),
             "pubspec.yaml" => Some(Format::PubSpec),
             "go.mod" => Some(Format::GoMod),
             "package.json" => Some(Format::PackageJson),
+            "package-lock.json" => Some(Format::PackageLock),
+            "pnpm-lock.yaml" => Some(Format::PnpmLock),
+            "yarn.lock" => Some(Format::YarnLock),
+            "snapcraft.yaml" => Some(Format::Snapcraft),
+            "PKGBUILD" => Some(Format::Pkgbuild),
             _ => None,
         }
     }
@@ -262,6 +442,8 @@ pub struct Config {
     pub(crate) format: Format,
     /// If, within the file, we're versioning a dependency (not the entire package)
     pub dependency: Option<String>,
+    /// How the version is written to the file
+    version_format: VersionFormat,
 }
 
 impl Config {
@@ -280,9 +462,96 @@ impl Config {
             parent,
             format,
             dependency,
+            version_format: VersionFormat::SemVer,
         })
     }
 
+    /// Create a `Config` for a file which Knope doesn't natively support, where the version
+    /// is stored at `key` (a dot-separated path like `tool.mycompany.version`).
+    ///
+    /// # Errors
+    ///
+    /// If the path doesn't have a file name
+    pub fn custom(
+        path: RelativePathBuf,
+        file_type: FileType,
+        key: String,
+    ) -> Result<Self, UnknownFile> {
+        let Some(file_name) = path.file_name() else {
+            return Err(UnknownFile { path });
+        };
+        let format = Format::Custom {
+            file_name: file_name.to_string(),
+            file_type,
+            key,
+        };
+        Ok(Config {
+            parent: path.parent().map(RelativePathBuf::from),
+            format,
+            dependency: None,
+            version_format: VersionFormat::SemVer,
+        })
+    }
+
+    /// Create a `Config` for any file where the version can be found with `search`, a regex
+    /// containing a `{version}` placeholder.
+    ///
+    /// # Errors
+    ///
+    /// If the path doesn't have a file name
+    pub fn regex(path: RelativePathBuf, search: String) -> Result<Self, UnknownFile> {
+        let Some(file_name) = path.file_name() else {
+            return Err(UnknownFile { path });
+        };
+        let format = Format::Regex {
+            file_name: file_name.to_string(),
+            search,
+        };
+        Ok(Config {
+            parent: path.parent().map(RelativePathBuf::from),
+            format,
+            dependency: None,
+            version_format: VersionFormat::SemVer,
+        })
+    }
+
+    /// The `search` pattern, if this file is found with a regex.
+    #[must_use]
+    pub fn search(&self) -> Option<&str> {
+        match &self.format {
+            Format::Regex { search, .. } => Some(search),
+            _ => None,
+        }
+    }
+
+    /// The file type and key path, if this is a custom file.
+    #[must_use]
+    pub fn custom_key(&self) -> Option<(FileType, &str)> {
+        match &self.format {
+            Format::Custom { file_type, key, .. } => Some((*file_type, key)),
+            _ => None,
+        }
+    }
+
+    /// Write the version to this file using `version_format`, only used by `pyproject.toml`.
+    #[must_use]
+    pub fn with_version_format(mut self, version_format: VersionFormat) -> Self {
+        self.version_format = version_format;
+        self
+    }
+
+    #[must_use]
+    pub fn version_format(&self) -> VersionFormat {
+        self.version_format
+    }
+
+    /// Whether `other` describes the same file in the same format, regardless of which
+    /// dependency within it is being versioned.
+    #[must_use]
+    pub fn is_same_file(&self, other: &Self) -> bool {
+        self.format == other.format && self.as_path() == other.as_path()
+    }
+
     #[must_use]
     pub fn as_path(&self) -> RelativePathBuf {
         self.parent.as_ref().map_or_else(
@@ -303,26 +572,31 @@ impl Config {
                 format: Format::Cargo,
                 parent: None,
                 dependency: None,
+                version_format: VersionFormat::SemVer,
             },
             Config {
                 parent: None,
                 format: Format::GoMod,
                 dependency: None,
+                version_format: VersionFormat::SemVer,
             },
             Config {
                 parent: None,
                 format: Format::PackageJson,
                 dependency: None,
+                version_format: VersionFormat::SemVer,
             },
             Config {
                 parent: None,
                 format: Format::PubSpec,
                 dependency: None,
+                version_format: VersionFormat::SemVer,
             },
             Config {
                 parent: None,
                 format: Format::PyProject,
                 dependency: None,
+                version_format: VersionFormat::SemVer,
             },
         ]
     }
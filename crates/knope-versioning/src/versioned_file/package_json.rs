@@ -32,6 +32,10 @@ impl PackageJson {
         &self.parsed.version
     }
 
+    pub(crate) fn get_name(&self) -> Option<&str> {
+        self.parsed.name.as_deref()
+    }
+
     pub(crate) fn get_path(&self) -> &RelativePathBuf {
         &self.path
     }
@@ -74,10 +78,13 @@ pub enum Error {
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 struct Json {
+    #[serde(default)]
+    name: Option<String>,
     version: Version,
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use std::str::FromStr;
 
@@ -0,0 +1,229 @@
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{action::Action, semver::Version};
+
+/// A `package-lock.json` file, including the entries for any npm workspaces.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackageLock {
+    path: RelativePathBuf,
+    json: Value,
+    trailing_newline: bool,
+    diff: Vec<String>,
+}
+
+impl PackageLock {
+    pub(crate) fn new(path: RelativePathBuf, content: &str) -> Result<Self, Error> {
+        let json = serde_json::from_str(content).map_err(|source| Error::Deserialize {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(Self {
+            path,
+            json,
+            trailing_newline: content.ends_with('\n'),
+            diff: Vec::new(),
+        })
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    /// The version of the root package
+    pub(crate) fn get_version(&self) -> Result<Version, Error> {
+        self.json
+            .get("version")
+            .or_else(|| self.json.get("packages")?.get("")?.get("version"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::MissingVersion(self.path.clone()))?
+            .parse()
+            .map_err(Error::Semver)
+    }
+
+    /// Set the version of the root package or, if `dependency` is set, of the package with that
+    /// name (usually a workspace).
+    #[must_use]
+    pub(crate) fn set_version(mut self, new_version: &Version, dependency: Option<&str>) -> Self {
+        let new_version_str = new_version.to_string();
+        let Some(dependency) = dependency else {
+            if let Some(version) = self.json.get_mut("version") {
+                *version = Value::String(new_version_str.clone());
+            }
+            if let Some(version) = self
+                .json
+                .get_mut("packages")
+                .and_then(|packages| packages.get_mut("")?.get_mut("version"))
+            {
+                *version = Value::String(new_version_str.clone());
+            }
+            self.diff.push(format!("version = {new_version_str}"));
+            return self;
+        };
+
+        let mut changed = false;
+        let installed_path = format!("node_modules/{dependency}");
+        if let Some(packages) = self.json.get_mut("packages").and_then(Value::as_object_mut) {
+            for (path, package) in packages.iter_mut() {
+                // Workspaces have an entry at their own path containing their name, plus a link
+                // from `node_modules` which has no version.
+                let is_workspace = package.get("name").and_then(Value::as_str) == Some(dependency);
+                let is_installed = (*path == installed_path
+                    || path.ends_with(&format!("/{installed_path}")))
+                    && package.get("link").is_none();
+                if !is_workspace && !is_installed {
+                    continue;
+                }
+                if let Some(version) = package.get_mut("version") {
+                    *version = Value::String(new_version_str.clone());
+                    changed = true;
+                }
+            }
+        }
+        // `lockfileVersion` 1 (and 2, for backwards compatibility) has a flat `dependencies` object
+        if let Some(version) = self
+            .json
+            .get_mut("dependencies")
+            .and_then(|dependencies| dependencies.get_mut(dependency)?.get_mut("version"))
+            .filter(|version| version.as_str().is_some_and(|it| !it.starts_with("file:")))
+        {
+            *version = Value::String(new_version_str.clone());
+            changed = true;
+        }
+        if changed {
+            self.diff.push(format!("{dependency} = {new_version_str}"));
+        }
+        self
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        if self.diff.is_empty() {
+            return None;
+        }
+        // This can only fail for maps with non-string keys, which JSON can't have
+        let mut content = serde_json::to_string_pretty(&self.json).ok()?;
+        if self.trailing_newline {
+            content.push('\n');
+        }
+        Some(Action::WriteToFile {
+            path: self.path,
+            content,
+            diff: self.diff.join(", "),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error("Error deserializing {path}: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::package_lock::deserialize),
+            url("https://knope.tech/reference/config-file/packages/#package-lockjson")
+        )
+    )]
+    Deserialize {
+        path: RelativePathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("{0} doesn't contain a version for the root package")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::package_lock::missing_version),
+            help("To version a workspace instead of the root package, specify a `dependency`"),
+            url("https://knope.tech/reference/config-file/packages/#package-lockjson")
+        )
+    )]
+    MissingVersion(RelativePathBuf),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Semver(#[from] crate::semver::Error),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CONTENT: &str = r#"{
+  "name": "monorepo",
+  "version": "1.0.0",
+  "lockfileVersion": 3,
+  "requires": true,
+  "packages": {
+    "": {
+      "name": "monorepo",
+      "version": "1.0.0",
+      "workspaces": [
+        "packages/*"
+      ]
+    },
+    "node_modules/first": {
+      "resolved": "packages/first",
+      "link": true
+    },
+    "node_modules/left-pad": {
+      "version": "1.3.0"
+    },
+    "packages/first": {
+      "name": "first",
+      "version": "0.1.0"
+    },
+    "packages/second/node_modules/first": {
+      "version": "0.1.0"
+    }
+  }
+}
+"#;
+
+    #[test]
+    fn root_version() {
+        let lock = PackageLock::new(RelativePathBuf::from("package-lock.json"), CONTENT).unwrap();
+        assert_eq!(
+            lock.get_version().unwrap(),
+            Version::from_str("1.0.0").unwrap()
+        );
+
+        let Some(Action::WriteToFile { content, diff, .. }) = lock
+            .set_version(&Version::from_str("1.1.0").unwrap(), None)
+            .write()
+        else {
+            panic!("Expected a file to write");
+        };
+        assert_eq!(content, CONTENT.replace("1.0.0", "1.1.0"));
+        assert_eq!(diff, "version = 1.1.0");
+    }
+
+    #[test]
+    fn workspace() {
+        let Some(Action::WriteToFile { content, diff, .. }) =
+            PackageLock::new(RelativePathBuf::from("package-lock.json"), CONTENT)
+                .unwrap()
+                .set_version(&Version::from_str("0.2.0").unwrap(), Some("first"))
+                .write()
+        else {
+            panic!("Expected a file to write");
+        };
+        assert_eq!(content, CONTENT.replace("0.1.0", "0.2.0"));
+        assert_eq!(diff, "first = 0.2.0");
+    }
+
+    #[test]
+    fn unknown_dependency() {
+        let lock = PackageLock::new(RelativePathBuf::from("package-lock.json"), CONTENT)
+            .unwrap()
+            .set_version(&Version::from_str("0.2.0").unwrap(), Some("other"));
+        assert!(lock.write().is_none());
+    }
+}
@@ -0,0 +1,166 @@
+use std::str::FromStr;
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use thiserror::Error;
+
+use crate::{
+    action::Action,
+    semver::{self, Version},
+};
+
+/// An Arch Linux `PKGBUILD` file.
+///
+/// `pkgver` can't contain a `-`, so pre-release versions are written with a `_` instead
+/// (`1.2.3-rc.0` becomes `1.2.3_rc.0`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pkgbuild {
+    path: RelativePathBuf,
+    raw: String,
+    version: Version,
+    diff: Option<String>,
+}
+
+impl Pkgbuild {
+    pub(crate) fn new(path: RelativePathBuf, content: String) -> Result<Self, Error> {
+        let raw_version = content
+            .lines()
+            .find_map(|line| line.strip_prefix("pkgver="))
+            .ok_or_else(|| Error::MissingPkgver(path.clone()))?;
+        let version = Version::from_str(&unquote(raw_version).replace('_', "-"))?;
+        Ok(Self {
+            path,
+            raw: content,
+            version,
+            diff: None,
+        })
+    }
+
+    pub(crate) fn get_version(&self) -> &Version {
+        &self.version
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    /// Set `pkgver` to the new version and reset `pkgrel` to 1, since this is a new upstream
+    /// release.
+    pub(crate) fn set_version(mut self, new_version: &Version) -> Self {
+        let pkgver = new_version.to_string().replace('-', "_");
+        let mut content = String::with_capacity(self.raw.len());
+        for line in self.raw.lines() {
+            if line.starts_with("pkgver=") {
+                content.push_str("pkgver=");
+                content.push_str(&pkgver);
+            } else if line.starts_with("pkgrel=") {
+                content.push_str("pkgrel=1");
+            } else {
+                content.push_str(line);
+            }
+            content.push('\n');
+        }
+        if !self.raw.ends_with('\n') {
+            content.pop();
+        }
+        self.raw = content;
+        self.version = new_version.clone();
+        self.diff = Some(format!("pkgver={pkgver}, pkgrel=1"));
+        self
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        self.diff.map(|diff| Action::WriteToFile {
+            content: self.raw,
+            path: self.path,
+            diff,
+        })
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|it| it.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|it| it.strip_suffix('\''))
+        })
+        .unwrap_or(value)
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error("Could not find a `pkgver=` line in {0}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(pkgbuild::missing_pkgver),
+            help("knope expects the PKGBUILD to set `pkgver` at the start of a line"),
+            url("https://knope.tech/reference/config-file/packages/#pkgbuild")
+        )
+    )]
+    MissingPkgver(RelativePathBuf),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Version(#[from] semver::Error),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CONTENT: &str = "\
+# Maintainer: Someone <someone@example.com>
+pkgname=knope
+pkgver=0.18.2
+pkgrel=3
+arch=('x86_64')
+";
+
+    #[test]
+    fn get_version() {
+        assert_eq!(
+            Pkgbuild::new(RelativePathBuf::new(), CONTENT.to_string())
+                .unwrap()
+                .get_version(),
+            &Version::from_str("0.18.2").unwrap()
+        );
+    }
+
+    #[test]
+    fn get_pre_version() {
+        let content = CONTENT.replace("0.18.2", "\"1.0.0_rc.1\"");
+        assert_eq!(
+            Pkgbuild::new(RelativePathBuf::new(), content)
+                .unwrap()
+                .get_version(),
+            &Version::from_str("1.0.0-rc.1").unwrap()
+        );
+    }
+
+    #[test]
+    fn set_version_resets_pkgrel() {
+        let action = Pkgbuild::new(RelativePathBuf::from("PKGBUILD"), CONTENT.to_string())
+            .unwrap()
+            .set_version(&Version::from_str("1.0.0-rc.0").unwrap())
+            .write()
+            .expect("diff to write");
+
+        let expected = Action::WriteToFile {
+            path: RelativePathBuf::from("PKGBUILD"),
+            content: CONTENT
+                .replace("0.18.2", "1.0.0_rc.0")
+                .replace("pkgrel=3", "pkgrel=1"),
+            diff: "pkgver=1.0.0_rc.0, pkgrel=1".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
+}
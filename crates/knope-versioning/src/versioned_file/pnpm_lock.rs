@@ -0,0 +1,186 @@
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use regex::Regex;
+use relative_path::RelativePathBuf;
+use thiserror::Error;
+
+use crate::{action::Action, semver::Version};
+
+/// A `pnpm-lock.yaml` file.
+///
+/// Only the `workspace:` dependencies of each importer are updated, since the rest of the file
+/// is about packages from a registry.
+#[derive(Clone, Debug)]
+pub struct PnpmLock {
+    path: RelativePathBuf,
+    raw: String,
+    diff: Vec<String>,
+}
+
+impl PnpmLock {
+    pub(crate) fn new(path: RelativePathBuf, content: String) -> Result<Self, Error> {
+        if let Err(source) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+            return Err(Error::Deserialize { path, source });
+        }
+        Ok(Self {
+            path,
+            raw: content,
+            diff: Vec::new(),
+        })
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    #[must_use]
+    pub(crate) fn set_version(mut self, new_version: &Version, dependency: &str) -> Self {
+        let new_version = new_version.to_string();
+        let mut stack: Vec<(usize, String)> = Vec::new();
+        let mut is_workspace_dependency = false;
+        let mut changed = false;
+        let mut content = String::with_capacity(self.raw.len());
+        for line in self.raw.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            let indent = line.len() - trimmed.len();
+            let Some((key, value)) = trimmed
+                .split_once(':')
+                .filter(|_| !trimmed.starts_with('#'))
+            else {
+                content.push_str(line);
+                continue;
+            };
+            while stack.last().is_some_and(|(level, _)| *level >= indent) {
+                stack.pop();
+            }
+            stack.push((indent, unquote(key).to_string()));
+
+            // importers.<path>.<dependency kind>.<name>.<specifier | version>
+            let keys: Vec<&str> = stack.iter().map(|(_, key)| key.as_str()).collect();
+            let field = match keys.as_slice() {
+                ["importers", _, kind, name, field]
+                    if *name == dependency && kind.ends_with("ependencies") =>
+                {
+                    Some(*field)
+                }
+                _ => None,
+            };
+            let value = value.trim();
+            let new_value = match field {
+                Some("specifier") => {
+                    is_workspace_dependency = unquote(value).starts_with("workspace:");
+                    is_workspace_dependency.then(|| replace_version(value, &new_version))
+                }
+                Some("version") if is_workspace_dependency && !value.starts_with("link:") => {
+                    Some(replace_version(value, &new_version))
+                }
+                _ => None,
+            };
+            match new_value {
+                Some(new_value) if new_value != value => {
+                    changed = true;
+                    content.push_str(&line.replacen(value, &new_value, 1));
+                }
+                _ => content.push_str(line),
+            }
+        }
+        if changed {
+            self.raw = content;
+            self.diff.push(format!("{dependency} = {new_version}"));
+        }
+        self
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        if self.diff.is_empty() {
+            return None;
+        }
+        Some(Action::WriteToFile {
+            path: self.path,
+            content: self.raw,
+            diff: self.diff.join(", "),
+        })
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(['"', '\''])
+}
+
+/// Replace the first semantic version in `value`, keeping any range operator or protocol
+fn replace_version(value: &str, new_version: &str) -> String {
+    Regex::new(r"\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?").map_or_else(
+        |_| value.to_string(),
+        |regex| regex.replace(value, new_version).into_owned(),
+    )
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error("Error deserializing {path}: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::pnpm_lock::deserialize),
+            url("https://knope.tech/reference/config-file/packages/#pnpm-lockyaml")
+        )
+    )]
+    Deserialize {
+        path: RelativePathBuf,
+        #[source]
+        source: serde_yaml::Error,
+    },
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn workspace_dependencies() {
+        let content = "\
+lockfileVersion: '9.0'
+
+importers:
+
+  .: {}
+
+  packages/first: {}
+
+  packages/second:
+    dependencies:
+      first:
+        specifier: workspace:^0.1.0
+        version: 0.1.0
+      left-pad:
+        specifier: ^0.1.0
+        version: 0.1.0
+    devDependencies:
+      first:
+        specifier: workspace:*
+        version: link:../first
+";
+        let Some(Action::WriteToFile {
+            content: new, diff, ..
+        }) = PnpmLock::new(RelativePathBuf::from("pnpm-lock.yaml"), content.to_string())
+            .unwrap()
+            .set_version(&Version::from_str("0.2.0").unwrap(), "first")
+            .write()
+        else {
+            panic!("Expected a file to write");
+        };
+        assert_eq!(
+            new,
+            content
+                .replace("workspace:^0.1.0", "workspace:^0.2.0")
+                .replacen("version: 0.1.0", "version: 0.2.0", 1)
+        );
+        assert_eq!(diff, "first = 0.2.0");
+    }
+}
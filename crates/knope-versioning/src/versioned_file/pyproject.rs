@@ -7,7 +7,10 @@ use serde::Deserialize;
 use thiserror::Error;
 use toml::Spanned;
 
-use crate::{action::Action, semver::Version};
+use crate::{
+    action::Action,
+    semver::{Pep440Error, Version, VersionFormat},
+};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PyProject {
@@ -15,28 +18,57 @@ pub struct PyProject {
     raw_toml: String,
     parsed: Toml,
     pub(super) version: Version,
+    version_format: VersionFormat,
     diff: Option<String>,
 }
 
 impl PyProject {
-    pub(crate) fn new(path: RelativePathBuf, raw_toml: String) -> Result<Self, Error> {
+    pub(crate) fn new(
+        path: RelativePathBuf,
+        raw_toml: String,
+        version_format: VersionFormat,
+    ) -> Result<Self, Error> {
         match toml::from_str::<Toml>(&raw_toml) {
             Ok(parsed) => parsed
                 .version(&path)
-                .and_then(|version| Version::from_str(version).map_err(Error::from))
+                .and_then(|version| {
+                    Version::from_str(version)
+                        .or_else(|err| {
+                            (version_format == VersionFormat::Pep440)
+                                .then(|| Version::from_pep440(version))
+                                .flatten()
+                                .ok_or(err)
+                        })
+                        .map_err(Error::from)
+                })
                 .map(|version| PyProject {
                     path,
                     raw_toml,
                     parsed,
                     version,
+                    version_format,
                     diff: None,
                 }),
             Err(err) => Err(Error::Deserialization(path, err)),
         }
     }
 
-    pub(crate) fn set_version(mut self, new_version: &Version) -> Self {
-        let version_str = new_version.to_string();
+    /// The name of the project, from `project.name` or `tool.poetry.name`
+    pub(crate) fn get_name(&self) -> Option<&str> {
+        self.parsed
+            .project
+            .as_ref()
+            .and_then(|project| project.name.as_deref())
+            .or_else(|| {
+                self.parsed
+                    .tool
+                    .as_ref()
+                    .and_then(|tool| tool.poetry.as_ref()?.name.as_deref())
+            })
+    }
+
+    pub(crate) fn set_version(mut self, new_version: &Version) -> Result<Self, Pep440Error> {
+        let version_str = new_version.to_formatted_string(self.version_format)?;
         let (poetry_version, project_version) = self.parsed.versions();
 
         for version in [poetry_version, project_version].into_iter().flatten() {
@@ -46,7 +78,7 @@ impl PyProject {
             self.raw_toml.replace_range(start..end, &version_str);
         }
         self.diff = Some(version_str);
-        self
+        Ok(self)
     }
 
     pub(crate) fn write(self) -> Option<Action> {
@@ -151,10 +183,12 @@ struct Tool {
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 struct Metadata {
+    name: Option<String>,
     version: Option<Spanned<String>>,
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use pretty_assertions::assert_eq;
 
@@ -169,9 +203,13 @@ mod tests {
         "#;
 
         assert_eq!(
-            PyProject::new(RelativePathBuf::new(), content.to_string())
-                .unwrap()
-                .version,
+            PyProject::new(
+                RelativePathBuf::new(),
+                content.to_string(),
+                VersionFormat::SemVer
+            )
+            .unwrap()
+            .version,
             Version::from_str("0.1.0-rc.0").unwrap()
         );
     }
@@ -185,9 +223,13 @@ mod tests {
         "#;
 
         assert_eq!(
-            PyProject::new(RelativePathBuf::new(), content.to_string())
-                .unwrap()
-                .version,
+            PyProject::new(
+                RelativePathBuf::new(),
+                content.to_string(),
+                VersionFormat::SemVer
+            )
+            .unwrap()
+            .version,
             Version::from_str("0.1.0-rc.0").unwrap()
         );
     }
@@ -205,9 +247,13 @@ mod tests {
         "#;
 
         assert_eq!(
-            PyProject::new(RelativePathBuf::new(), content.to_string())
-                .unwrap()
-                .version,
+            PyProject::new(
+                RelativePathBuf::new(),
+                content.to_string(),
+                VersionFormat::SemVer
+            )
+            .unwrap()
+            .version,
             Version::from_str("0.1.0-rc.0").unwrap()
         );
     }
@@ -224,7 +270,11 @@ mod tests {
         version = "2.3.4"
         "#;
 
-        match PyProject::new(RelativePathBuf::new(), content.to_string()) {
+        match PyProject::new(
+            RelativePathBuf::new(),
+            content.to_string(),
+            VersionFormat::SemVer,
+        ) {
             Err(Error::InconsistentVersions {
                 poetry, project, ..
             }) => {
@@ -247,10 +297,15 @@ mod tests {
         version = "0.1.0-rc.0"
         "#;
 
-        let pyproject =
-            PyProject::new(RelativePathBuf::from("beep/boop"), String::from(content)).unwrap();
+        let pyproject = PyProject::new(
+            RelativePathBuf::from("beep/boop"),
+            String::from(content),
+            VersionFormat::SemVer,
+        )
+        .unwrap();
         let action = pyproject
             .set_version(&Version::from_str("1.2.3-rc.4").unwrap())
+            .unwrap()
             .write()
             .expect("Diff to write");
 
@@ -270,4 +325,33 @@ mod tests {
         };
         assert_eq!(action, expected);
     }
+
+    #[test]
+    fn test_set_version_pep440() {
+        let content = r#"
+        [project]
+        name = "tester"
+        version = "1.2.3rc0"
+        "#;
+
+        let pyproject = PyProject::new(
+            RelativePathBuf::from("pyproject.toml"),
+            String::from(content),
+            VersionFormat::Pep440,
+        )
+        .unwrap();
+        assert_eq!(pyproject.version, Version::from_str("1.2.3-rc.0").unwrap());
+        let action = pyproject
+            .set_version(&Version::from_str("1.2.3-rc.1").unwrap())
+            .unwrap()
+            .write()
+            .expect("Diff to write");
+
+        let expected = Action::WriteToFile {
+            content: content.replace("1.2.3rc0", "1.2.3rc1"),
+            path: RelativePathBuf::from("pyproject.toml"),
+            diff: "1.2.3rc1".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
 }
@@ -0,0 +1,157 @@
+use relative_path::RelativePathBuf;
+use toml_edit::{value, DocumentMut, TomlError};
+
+use crate::{semver::Version, Action};
+
+/// A `poetry.lock` or `uv.lock` file, both of which have an array of `[[package]]` tables.
+#[derive(Clone, Debug)]
+pub struct PythonLock {
+    path: RelativePathBuf,
+    document: DocumentMut,
+    diff: Vec<String>,
+}
+
+impl PythonLock {
+    pub(crate) fn new(path: RelativePathBuf, toml: &str) -> Result<Self, Error> {
+        let document: DocumentMut = toml.parse().map_err(|source| Error::Toml {
+            source,
+            path: path.clone(),
+        })?;
+        Ok(Self {
+            path,
+            document,
+            diff: Vec::new(),
+        })
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    /// Update the version of every package named `dependency`.
+    ///
+    /// `uv.lock` contains an entry for the project itself, `poetry.lock` only for path
+    /// dependencies, so it's not an error if there isn't a matching package.
+    #[must_use]
+    pub(crate) fn set_version(mut self, new_version: &Version, dependency: &str) -> Self {
+        let dependency_name = normalize(dependency);
+        let Some(packages) = self
+            .document
+            .get_mut("package")
+            .and_then(|package| package.as_array_of_tables_mut())
+        else {
+            return self;
+        };
+        for package in packages.iter_mut() {
+            let matches = package
+                .get("name")
+                .and_then(|name| name.as_str())
+                .is_some_and(|name| normalize(name) == dependency_name);
+            if !matches {
+                continue;
+            }
+            if let Some(version) = package.get_mut("version") {
+                *version = value(new_version.to_string());
+                self.diff.push(format!("{dependency} = {new_version}"));
+            }
+        }
+        self
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        if self.diff.is_empty() {
+            return None;
+        }
+        Some(Action::WriteToFile {
+            content: self.document.to_string(),
+            path: self.path,
+            diff: self.diff.join(", "),
+        })
+    }
+}
+
+/// Normalize names [like Python packaging does](https://packaging.python.org/en/latest/specifications/name-normalization/)
+fn normalize(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+#[derive(Debug, thiserror::Error)]
+#[cfg_attr(feature = "miette", derive(miette::Diagnostic))]
+pub enum Error {
+    #[error("Invalid TOML in {path}: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(code(knope_versioning::python_lock::toml))
+    )]
+    Toml {
+        path: RelativePathBuf,
+        #[source]
+        source: TomlError,
+    },
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn uv_lock() {
+        let content = r#"version = 1
+requires-python = ">=3.12"
+
+[[package]]
+name = "my-package"
+version = "0.1.0"
+source = { editable = "." }
+dependencies = [
+    { name = "requests" },
+]
+
+[[package]]
+name = "requests"
+version = "0.1.0"
+source = { registry = "https://pypi.org/simple" }
+"#;
+        let Some(Action::WriteToFile {
+            content: new, diff, ..
+        }) = PythonLock::new(RelativePathBuf::from("uv.lock"), content)
+            .unwrap()
+            .set_version(&Version::from_str("0.2.0").unwrap(), "My_Package")
+            .write()
+        else {
+            panic!("Expected a file to write");
+        };
+        assert_eq!(new, content.replacen("0.1.0", "0.2.0", 1));
+        assert_eq!(diff, "My_Package = 0.2.0");
+    }
+
+    #[test]
+    fn poetry_lock_without_project() {
+        let content = r#"[[package]]
+name = "requests"
+version = "2.32.3"
+
+[metadata]
+lock-version = "2.0"
+"#;
+        let lock = PythonLock::new(RelativePathBuf::from("poetry.lock"), content)
+            .unwrap()
+            .set_version(&Version::from_str("0.2.0").unwrap(), "my-package");
+        assert!(lock.write().is_none());
+    }
+}
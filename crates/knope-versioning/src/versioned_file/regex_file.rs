@@ -0,0 +1,232 @@
+use std::str::FromStr;
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use regex::Regex;
+use relative_path::RelativePathBuf;
+use thiserror::Error;
+
+use crate::{
+    action::Action,
+    semver::{self, Version},
+};
+
+/// The placeholder in a `search` pattern which is replaced with the capture group for the version
+const PLACEHOLDER: &str = "{version}";
+
+/// Roughly a semantic version, which is all Knope can write anyway
+const VERSION_PATTERN: &str = r"(?<version>\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?(?:\+[0-9A-Za-z.-]+)?)";
+
+/// Any file, where the version is found using a user-provided regex, like
+/// `#define VERSION "{version}"`.
+///
+/// The pattern must match exactly once in the file.
+#[derive(Clone, Debug)]
+pub struct RegexFile {
+    path: RelativePathBuf,
+    raw: String,
+    /// The byte range of the version within `raw`
+    start: usize,
+    end: usize,
+    version: Version,
+    diff: Option<String>,
+}
+
+impl RegexFile {
+    pub(crate) fn new(path: RelativePathBuf, content: String, search: &str) -> Result<Self, Error> {
+        let regex = build_regex(search)?;
+        let mut matches = regex
+            .captures_iter(&content)
+            .filter_map(|captures| captures.name("version"));
+        let Some(version_match) = matches.next() else {
+            return Err(Error::NoMatch {
+                path,
+                search: search.to_string(),
+            });
+        };
+        let count = matches.count() + 1;
+        if count > 1 {
+            return Err(Error::MultipleMatches {
+                path,
+                search: search.to_string(),
+                count,
+            });
+        }
+        let (start, end) = (version_match.start(), version_match.end());
+        let version = Version::from_str(version_match.as_str())?;
+        Ok(Self {
+            path,
+            raw: content,
+            start,
+            end,
+            version,
+            diff: None,
+        })
+    }
+
+    pub(crate) fn get_version(&self) -> &Version {
+        &self.version
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    pub(crate) fn set_version(mut self, new_version: &Version) -> Self {
+        let new_version_str = new_version.to_string();
+        self.raw
+            .replace_range(self.start..self.end, &new_version_str);
+        self.end = self.start + new_version_str.len();
+        self.version = new_version.clone();
+        self.diff = Some(new_version_str);
+        self
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        self.diff.map(|diff| Action::WriteToFile {
+            content: self.raw,
+            path: self.path,
+            diff,
+        })
+    }
+}
+
+fn build_regex(search: &str) -> Result<Regex, Error> {
+    let placeholders = search.matches(PLACEHOLDER).count();
+    if placeholders != 1 {
+        return Err(Error::Placeholder {
+            search: search.to_string(),
+            count: placeholders,
+        });
+    }
+    Regex::new(&search.replace(PLACEHOLDER, VERSION_PATTERN)).map_err(|source| Error::Regex {
+        search: search.to_string(),
+        source,
+    })
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error(
+        "The search pattern {search:?} must contain `{{version}}` exactly once, found {count}"
+    )]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::regex::placeholder),
+            help("Put `{{version}}` where the version is, like `#define VERSION \"{{version}}\"`"),
+            url("https://knope.tech/reference/config-file/packages/#any-other-file")
+        )
+    )]
+    Placeholder { search: String, count: usize },
+    #[error("The search pattern {search:?} is not a valid regex: {source}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::regex::invalid),
+            url("https://knope.tech/reference/config-file/packages/#any-other-file")
+        )
+    )]
+    Regex {
+        search: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("The search pattern {search:?} didn't match anything in {path}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::regex::no_match),
+            help("The pattern must match exactly once, including a valid semantic version"),
+            url("https://knope.tech/reference/config-file/packages/#any-other-file")
+        )
+    )]
+    NoMatch {
+        path: RelativePathBuf,
+        search: String,
+    },
+    #[error("The search pattern {search:?} matched {count} times in {path}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(knope_versioning::regex::multiple_matches),
+            help("The pattern must match exactly once, try making it more specific"),
+            url("https://knope.tech/reference/config-file/packages/#any-other-file")
+        )
+    )]
+    MultipleMatches {
+        path: RelativePathBuf,
+        search: String,
+        count: usize,
+    },
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Version(#[from] semver::Error),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const HEADER: &str = "#pragma once\n#define VERSION \"1.2.3\"\n#define OTHER \"4.5.6\"\n";
+    const SEARCH: &str = r#"#define VERSION "{version}""#;
+
+    #[test]
+    fn get_version() {
+        let file = RegexFile::new(RelativePathBuf::new(), HEADER.to_string(), SEARCH).unwrap();
+        assert_eq!(file.get_version(), &Version::from_str("1.2.3").unwrap());
+    }
+
+    #[test]
+    fn set_version() {
+        let action = RegexFile::new(
+            RelativePathBuf::from("version.h"),
+            HEADER.to_string(),
+            SEARCH,
+        )
+        .unwrap()
+        .set_version(&Version::from_str("1.3.0-rc.0").unwrap())
+        .write()
+        .expect("diff to write");
+
+        let expected = Action::WriteToFile {
+            path: RelativePathBuf::from("version.h"),
+            content: HEADER.replace("1.2.3", "1.3.0-rc.0"),
+            diff: "1.3.0-rc.0".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
+
+    #[test]
+    fn multiple_matches() {
+        let result = RegexFile::new(
+            RelativePathBuf::new(),
+            HEADER.to_string(),
+            r#"#define \w+ "{version}""#,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::MultipleMatches { count: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn no_match() {
+        let result = RegexFile::new(
+            RelativePathBuf::new(),
+            HEADER.to_string(),
+            "VERSION={version}",
+        );
+        assert!(matches!(result, Err(Error::NoMatch { .. })));
+    }
+
+    #[test]
+    fn missing_placeholder() {
+        let result = RegexFile::new(RelativePathBuf::new(), HEADER.to_string(), "VERSION");
+        assert!(matches!(result, Err(Error::Placeholder { count: 0, .. })));
+    }
+}
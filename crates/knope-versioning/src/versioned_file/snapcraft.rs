@@ -0,0 +1,174 @@
+use std::str::FromStr;
+
+#[cfg(feature = "miette")]
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use serde::Deserialize;
+use serde_yaml::from_str;
+use thiserror::Error;
+
+use crate::{
+    action::Action,
+    semver::{self, Version},
+};
+
+/// A `snapcraft.yaml` file, used to build Snap packages.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Snapcraft {
+    path: RelativePathBuf,
+    raw: String,
+    /// The version exactly as it's written in the file, so we can replace it without losing quotes
+    raw_version: String,
+    version: Version,
+    diff: Option<String>,
+}
+
+impl Snapcraft {
+    pub(crate) fn new(path: RelativePathBuf, content: String) -> Result<Self, Error> {
+        let parsed: Yaml = match from_str(&content) {
+            Ok(parsed) => parsed,
+            Err(source) => return Err(Error::Deserialize { path, source }),
+        };
+        let version = Version::from_str(&parsed.version)?;
+        Ok(Snapcraft {
+            path,
+            raw: content,
+            raw_version: parsed.version,
+            version,
+            diff: None,
+        })
+    }
+
+    pub(crate) fn get_version(&self) -> &Version {
+        &self.version
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    pub(crate) fn set_version(mut self, new_version: &Version) -> Result<Self, Error> {
+        let new_version_str = new_version.to_string();
+        let mut replaced = false;
+        self.raw = self
+            .raw
+            .split_inclusive('\n')
+            .map(|line| {
+                if replaced || !line.starts_with("version:") {
+                    return line.to_string();
+                }
+                replaced = true;
+                // Replace only the version itself to preserve quotes & comments
+                line.replacen(&self.raw_version, &new_version_str, 1)
+            })
+            .collect();
+        if !replaced {
+            return Err(Error::NoVersionLine(self.path));
+        }
+        self.raw_version.clone_from(&new_version_str);
+        self.version = new_version.clone();
+        self.diff = Some(new_version_str);
+        Ok(self)
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        self.diff.map(|diff| Action::WriteToFile {
+            content: self.raw,
+            path: self.path,
+            diff,
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+#[cfg_attr(feature = "miette", derive(Diagnostic))]
+pub enum Error {
+    #[error("Error deserializing {path}: {source}")]
+    #[cfg_attr(feature = "miette", diagnostic(
+        code(snapcraft_yaml::deserialize),
+        help("knope expects the snapcraft.yaml file to be an object with a top level `version` property"),
+        url("https://knope.tech/reference/config-file/packages/#snapcraftyaml")
+    ))]
+    Deserialize {
+        path: RelativePathBuf,
+        #[source]
+        source: serde_yaml::Error,
+    },
+    #[error("Could not find a top-level `version:` line in {0}")]
+    #[cfg_attr(
+        feature = "miette",
+        diagnostic(
+            code(snapcraft_yaml::no_version_line),
+            help("The `version` key must be at the top level of the file, not in a nested or flow mapping."),
+            url("https://knope.tech/reference/config-file/packages/#snapcraftyaml")
+        )
+    )]
+    NoVersionLine(RelativePathBuf),
+    #[error(transparent)]
+    #[cfg_attr(feature = "miette", diagnostic(transparent))]
+    Version(#[from] semver::Error),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Yaml {
+    version: String,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn get_version() {
+        let content = "name: my-snap\nversion: '1.2.3'\nsummary: A snap\n";
+
+        assert_eq!(
+            Snapcraft::new(RelativePathBuf::new(), content.to_string())
+                .unwrap()
+                .get_version(),
+            &Version::from_str("1.2.3").unwrap()
+        );
+    }
+
+    #[test]
+    fn set_version_preserves_quotes() {
+        let content = "name: my-snap\nversion: '1.2.3' # the version\nsummary: A snap\n";
+
+        let action = Snapcraft::new(RelativePathBuf::from("snap/snapcraft.yaml"), content.into())
+            .unwrap()
+            .set_version(&Version::from_str("2.0.0-rc.0").unwrap())
+            .unwrap()
+            .write()
+            .expect("diff to write");
+
+        let expected = Action::WriteToFile {
+            path: RelativePathBuf::from("snap/snapcraft.yaml"),
+            content: "name: my-snap\nversion: '2.0.0-rc.0' # the version\nsummary: A snap\n"
+                .to_string(),
+            diff: "2.0.0-rc.0".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
+
+    #[test]
+    fn set_version_only_changes_top_level_line() {
+        let content = "description: |\n  version: '1.2.3'\nversion: '1.2.3'\n";
+
+        let action = Snapcraft::new(RelativePathBuf::from("snapcraft.yaml"), content.into())
+            .unwrap()
+            .set_version(&Version::from_str("1.3.0").unwrap())
+            .unwrap()
+            .write()
+            .expect("diff to write");
+
+        let expected = Action::WriteToFile {
+            path: RelativePathBuf::from("snapcraft.yaml"),
+            content: "description: |\n  version: '1.2.3'\nversion: '1.3.0'\n".to_string(),
+            diff: "1.3.0".to_string(),
+        };
+        assert_eq!(action, expected);
+    }
+}
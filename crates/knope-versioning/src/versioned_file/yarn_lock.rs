@@ -0,0 +1,138 @@
+use relative_path::RelativePathBuf;
+
+use crate::{action::Action, semver::Version};
+
+/// The placeholder Yarn uses as the version of workspaces that don't need a real one
+const LOCAL_VERSION: &str = "0.0.0-use.local";
+
+/// A `yarn.lock` file.
+///
+/// Only the entries for workspaces (`<name>@workspace:<path>`) are updated, since the rest of the
+/// file is about packages from a registry.
+#[derive(Clone, Debug)]
+pub struct YarnLock {
+    path: RelativePathBuf,
+    raw: String,
+    diff: Vec<String>,
+}
+
+impl YarnLock {
+    pub(crate) fn new(path: RelativePathBuf, content: String) -> Self {
+        Self {
+            path,
+            raw: content,
+            diff: Vec::new(),
+        }
+    }
+
+    pub(crate) fn get_path(&self) -> &RelativePathBuf {
+        &self.path
+    }
+
+    #[must_use]
+    pub(crate) fn set_version(mut self, new_version: &Version, dependency: &str) -> Self {
+        let new_version = new_version.to_string();
+        let workspace_descriptor = format!("{dependency}@workspace:");
+        let mut in_workspace_entry = false;
+        let mut changed = false;
+        let mut content = String::with_capacity(self.raw.len());
+        for line in self.raw.split_inclusive('\n') {
+            if !line.starts_with([' ', '\t', '#']) && line.trim_end().ends_with(':') {
+                // A new entry, which can have several comma-separated descriptors
+                in_workspace_entry =
+                    line.trim_end()
+                        .trim_end_matches(':')
+                        .split(',')
+                        .any(|descriptor| {
+                            descriptor
+                                .trim()
+                                .trim_matches('"')
+                                .starts_with(&workspace_descriptor)
+                        });
+                content.push_str(line);
+                continue;
+            }
+            let version = line
+                .trim_start()
+                .strip_prefix("version")
+                .and_then(|rest| rest.strip_prefix(':').or(Some(rest)))
+                .map(|value| value.trim().trim_matches('"'))
+                .filter(|value| !value.is_empty());
+            match version {
+                Some(version) if in_workspace_entry && version != LOCAL_VERSION => {
+                    changed = true;
+                    content.push_str(&line.replacen(version, &new_version, 1));
+                }
+                _ => content.push_str(line),
+            }
+        }
+        if changed {
+            self.raw = content;
+            self.diff.push(format!("{dependency} = {new_version}"));
+        }
+        self
+    }
+
+    pub(crate) fn write(self) -> Option<Action> {
+        if self.diff.is_empty() {
+            return None;
+        }
+        Some(Action::WriteToFile {
+            path: self.path,
+            content: self.raw,
+            diff: self.diff.join(", "),
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const CONTENT: &str = r#"__metadata:
+  version: 8
+  cacheKey: 10c0
+
+"first@workspace:^, first@workspace:packages/first":
+  version: 0.1.0
+  resolution: "first@workspace:packages/first"
+  languageName: unknown
+  linkType: soft
+
+"left-pad@npm:^0.1.0":
+  version: 0.1.0
+  resolution: "left-pad@npm:0.1.0"
+
+"second@workspace:packages/second":
+  version: 0.0.0-use.local
+  resolution: "second@workspace:packages/second"
+"#;
+
+    #[test]
+    fn workspace_entry() {
+        let Some(Action::WriteToFile { content, diff, .. }) =
+            YarnLock::new(RelativePathBuf::from("yarn.lock"), CONTENT.to_string())
+                .set_version(&Version::from_str("0.2.0").unwrap(), "first")
+                .write()
+        else {
+            panic!("Expected a file to write");
+        };
+        assert_eq!(
+            content,
+            CONTENT.replacen("version: 0.1.0", "version: 0.2.0", 1)
+        );
+        assert_eq!(diff, "first = 0.2.0");
+    }
+
+    #[test]
+    fn local_version_is_kept() {
+        let lock = YarnLock::new(RelativePathBuf::from("yarn.lock"), CONTENT.to_string())
+            .set_version(&Version::from_str("0.2.0").unwrap(), "second");
+        assert!(lock.write().is_none());
+    }
+}
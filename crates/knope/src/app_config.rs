@@ -8,11 +8,14 @@ use crate::{prompt, prompt::get_input};
 /// For managing configuration of knope globally
 
 pub(crate) fn get_or_prompt_for_email() -> Result<String, Error> {
-    load_value_or_prompt("email", "Input your email address")
+    std::env::var("JIRA_EMAIL")
+        .or_else(|_| load_value_or_prompt("email", "Input your email address"))
 }
 
 pub(crate) fn get_or_prompt_for_jira_token() -> Result<String, Error> {
-    load_value_or_prompt("jira_token", "No Jira token found, generate one from https://id.atlassian.com/manage-profile/security/api-tokens and input here")
+    std::env::var("JIRA_API_TOKEN").or_else(|_| {
+        load_value_or_prompt("jira_token", "No Jira token found, generate one from https://id.atlassian.com/manage-profile/security/api-tokens and input here")
+    })
 }
 
 pub(crate) fn get_or_prompt_for_github_token() -> Result<String, Error> {
@@ -37,6 +40,37 @@ pub(crate) fn get_or_prompt_for_gitea_token(host: &str) -> Result<String, Error>
     })
 }
 
+/// The GitHub token from the environment or a previous prompt, without prompting for one.
+pub(crate) fn github_token() -> Option<String> {
+    std::env::var("GITHUB_TOKEN")
+        .ok()
+        .or_else(|| load_value("github_token"))
+}
+
+/// The Gitea token from the environment or a previous prompt, without prompting for one.
+pub(crate) fn gitea_token() -> Option<String> {
+    std::env::var("GITEA_TOKEN")
+        .ok()
+        .or_else(|| load_value("gitea_token"))
+}
+
+/// The Jira email and API token from the environment or previous prompts, without prompting for
+/// either.
+pub(crate) fn jira_credentials() -> Option<(String, String)> {
+    let email = std::env::var("JIRA_EMAIL")
+        .ok()
+        .or_else(|| load_value("email"))?;
+    let token = std::env::var("JIRA_API_TOKEN")
+        .ok()
+        .or_else(|| load_value("jira_token"))?;
+    Some((email, token))
+}
+
+fn load_value(key: &str) -> Option<String> {
+    let app_dirs = AppDirs::new(Some("knope"), true)?;
+    std::fs::read_to_string(app_dirs.config_dir.join(key)).ok()
+}
+
 pub(crate) fn load_value_or_prompt(key: &str, prompt: &str) -> Result<String, Error> {
     let app_dirs = AppDirs::new(Some("knope"), true).ok_or(Error::CouldNotOpenConfigPath)?;
     let config_path = app_dirs.config_dir.join(key);
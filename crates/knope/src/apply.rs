@@ -0,0 +1,208 @@
+//! `knope apply`, which carries out a plan saved by `--plan` exactly as it was written.
+
+use std::path::{Path, PathBuf};
+
+use clap::{value_parser, Arg};
+use knope_versioning::{package::Name, release_notes::Release, ReleaseTag};
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use tracing::info;
+
+use crate::{
+    fs,
+    fs::WriteType,
+    integrations::git,
+    plan::{Action, Saved},
+    state::{RunType, State},
+    step::{command, push, releases},
+};
+
+/// The name of the built-in subcommand, which is skipped if a workflow has the same name.
+pub(crate) const NAME: &str = "apply";
+const PLAN: &str = "plan";
+
+pub(crate) fn subcommand() -> clap::Command {
+    clap::Command::new(NAME)
+        .about("Carry out a plan saved by running a workflow with `--plan`")
+        .arg(
+            Arg::new(PLAN)
+                .help("The file the plan was saved to")
+                .value_parser(value_parser!(PathBuf))
+                .required(true),
+        )
+}
+
+/// The path of the plan from the arguments of [`subcommand`].
+pub(crate) fn plan_path(matches: Option<&clap::ArgMatches>) -> Option<PathBuf> {
+    matches?.get_one::<PathBuf>(PLAN).cloned()
+}
+
+/// Do everything in the plan at `path`, in order.
+///
+/// The plan can only be applied to the commit it was made from, so that nothing it was based on
+/// has changed in between.
+pub(crate) fn run(mut state: State, path: &Path) -> Result<(), Error> {
+    let source = fs::read_to_string(path)?;
+    let plan: Saved = serde_json::from_str(&source).map_err(|source| Error::Parse {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if let Some(planned) = plan.head {
+        let current = git::head_commit_id().ok();
+        if current.as_ref() != Some(&planned) {
+            return Err(Error::Stale {
+                workflow: plan.workflow,
+                planned,
+                current: current.unwrap_or_default(),
+            });
+        }
+    }
+    info!("Applying the plan for {}", plan.workflow);
+    for action in plan.actions {
+        state = apply(action, state)?;
+    }
+    Ok(())
+}
+
+fn apply(action: Action, mut state: State) -> Result<State, Error> {
+    match action {
+        Action::WriteFile { path, content } => {
+            fs::write(
+                WriteType::Real::<String, String>(content),
+                Path::new(&path),
+                &mut None,
+            )?;
+        }
+        Action::DeleteFile { path } => {
+            // Changesets are deleted once per package, so the file may already be gone
+            fs::remove_file(RunType::Real(Path::new(&path)), &mut None).ok();
+        }
+        Action::StageFiles { paths } => git::add_files(&relative(paths))?,
+        Action::Commit {
+            message,
+            files,
+            signed,
+        } => git::commit(
+            RunType::Real(&message),
+            &relative(files),
+            state.signing.as_ref().filter(|_| signed),
+            &mut None,
+        )?,
+        Action::Tag {
+            name,
+            message,
+            signed,
+        } => git::create_tag(
+            RunType::Real(&name),
+            &message,
+            state.signing.as_ref().filter(|_| signed),
+            &mut None,
+        )?,
+        Action::Release {
+            package,
+            version,
+            title,
+            notes,
+        } => {
+            let release = Release {
+                title,
+                version,
+                notes,
+                package_name: package.map_or(Name::Default, Name::Custom),
+            };
+            state = release_to_forges(&release, state)?;
+        }
+        Action::Command {
+            command,
+            shell,
+            env,
+        } => {
+            let (_, new_state) =
+                command::run_command(RunType::Real(state), command, shell, None, None, 0, env)?
+                    .take();
+            state = new_state;
+        }
+        Action::Push => {
+            let (_, new_state) = push::run(RunType::Real(state))?.take();
+            state = new_state;
+        }
+    }
+    Ok(state)
+}
+
+fn release_to_forges(release: &Release, mut state: State) -> Result<State, Error> {
+    let tag = ReleaseTag::new(&release.version, &release.package_name);
+    if let Some(github_config) = &state.github_config {
+        let uploads = state
+            .packages
+            .iter()
+            .find(|package| package.name() == &release.package_name)
+            .and_then(|package| package.uploads(&release.version, state.signing.as_ref()));
+        state.github = releases::github::release(
+            release,
+            RunType::Real(state.github),
+            github_config,
+            uploads.as_ref(),
+            &tag,
+            &mut None,
+        )
+        .map_err(releases::Error::from)?;
+    }
+    if let Some(gitea_config) = &state.gitea_config {
+        state.gitea = releases::gitea::release(
+            release,
+            RunType::Real(state.gitea),
+            gitea_config,
+            &tag,
+            &mut None,
+        )
+        .map_err(releases::Error::from)?;
+    }
+    Ok(state)
+}
+
+fn relative(paths: Vec<String>) -> Vec<RelativePathBuf> {
+    paths.into_iter().map(RelativePathBuf::from).collect()
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not parse the plan in {}: {source}", path.display())]
+    #[diagnostic(
+        code(apply::parse),
+        help("Plans are created by running a workflow with `--plan`, and shouldn't be edited."),
+        url("https://knope.tech/reference/command-line-arguments/#--plan")
+    )]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error(
+        "The plan for {workflow} was made at commit {planned}, but the current commit is {current}"
+    )]
+    #[diagnostic(
+        code(apply::stale),
+        help("Check out the commit the plan was made at, or make a new plan with `--plan`."),
+        url("https://knope.tech/reference/command-line-arguments/#apply")
+    )]
+    Stale {
+        workflow: String,
+        planned: String,
+        current: String,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Release(#[from] releases::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Command(#[from] command::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Push(#[from] push::Error),
+}
@@ -0,0 +1,220 @@
+//! The `--check-remote` option of `--validate`, which makes sure that every configured forge (and
+//! Jira) can be reached with the available credentials before a workflow needs them.
+
+use miette::Diagnostic;
+use serde::Deserialize;
+
+use crate::{
+    app_config::{gitea_token, github_token, jira_credentials},
+    config,
+    step::issues::jira,
+    workflow::Workflow,
+};
+
+/// Every problem found with the configured services.
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Could not use the configured services")]
+pub(crate) struct Errors {
+    #[related]
+    errors: Vec<Error>,
+}
+
+/// Check the credentials for each of the configured services, along with the repos or projects
+/// they point to.
+///
+/// Write access is only required if a step in `workflows` writes to a forge.
+pub(crate) fn run(
+    github: Option<&config::GitHub>,
+    gitea: Option<&config::Gitea>,
+    jira: Option<&config::Jira>,
+    workflows: &[Workflow],
+) -> Result<(), Errors> {
+    let needs_write = workflows
+        .iter()
+        .flat_map(|workflow| workflow.steps.iter().chain(&workflow.on_error))
+        .any(|workflow_step| workflow_step.step.writes_to_forge());
+    let mut errors = Vec::new();
+    if let Some(github) = github {
+        errors.extend(check_github(github, needs_write));
+    }
+    if let Some(gitea) = gitea {
+        errors.extend(check_gitea(gitea, needs_write));
+    }
+    if let Some(jira) = jira {
+        if jira_credentials().is_none() {
+            errors.push(Error::MissingJiraCredentials);
+        } else if let Err(err) = jira::validate_project(jira) {
+            errors.push(err.into());
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Errors { errors })
+    }
+}
+
+/// Just enough of a repository from the GitHub or Gitea API to know what the token can do.
+#[derive(Deserialize)]
+struct Repository {
+    #[serde(default)]
+    permissions: Option<Permissions>,
+}
+
+#[derive(Deserialize)]
+struct Permissions {
+    push: bool,
+}
+
+impl Repository {
+    fn can_push(&self) -> bool {
+        self.permissions
+            .as_ref()
+            .is_some_and(|permissions| permissions.push)
+    }
+}
+
+fn check_github(config: &config::GitHub, needs_write: bool) -> Vec<Error> {
+    let Some(token) = github_token().filter(|token| !token.is_empty()) else {
+        return vec![Error::MissingToken { forge: "GitHub" }];
+    };
+    let full_name = format!("{}/{}", config.owner, config.repo);
+    let response = match ureq::get(&format!("https://api.github.com/repos/{full_name}"))
+        .set("Authorization", &format!("token {token}"))
+        .call()
+    {
+        Ok(response) => response,
+        Err(err) => return vec![status_error("GitHub", full_name, err)],
+    };
+    let mut errors = Vec::new();
+    // Only classic tokens have scopes, fine-grained tokens are limited by `permissions` instead
+    if let Some(scopes) = response.header("X-OAuth-Scopes") {
+        let scopes = scopes.split(',').map(str::trim).collect::<Vec<_>>();
+        if needs_write && !scopes.contains(&"repo") && !scopes.contains(&"public_repo") {
+            errors.push(Error::MissingScope {
+                scopes: scopes.join(", "),
+            });
+        }
+    }
+    match response.into_json::<Repository>() {
+        Ok(repository) if needs_write && !repository.can_push() => {
+            errors.push(Error::ReadOnly {
+                forge: "GitHub",
+                repo: full_name,
+            });
+        }
+        Ok(_) => {}
+        Err(source) => errors.push(Error::ApiResponse {
+            forge: "GitHub",
+            source,
+        }),
+    }
+    errors
+}
+
+fn check_gitea(config: &config::Gitea, needs_write: bool) -> Vec<Error> {
+    let Some(token) = gitea_token().filter(|token| !token.is_empty()) else {
+        return vec![Error::MissingToken { forge: "Gitea" }];
+    };
+    let full_name = format!("{}/{}", config.owner, config.repo);
+    let repository = ureq::get(&config.get_repo_url())
+        .set("Accept", "application/json")
+        .query("access_token", &token)
+        .call()
+        .map_err(|err| status_error("Gitea", full_name.clone(), err))
+        .and_then(|response| {
+            response
+                .into_json::<Repository>()
+                .map_err(|source| Error::ApiResponse {
+                    forge: "Gitea",
+                    source,
+                })
+        });
+    match repository {
+        Ok(repository) if needs_write && !repository.can_push() => vec![Error::ReadOnly {
+            forge: "Gitea",
+            repo: full_name,
+        }],
+        Ok(_) => Vec::new(),
+        Err(err) => vec![err],
+    }
+}
+
+fn status_error(forge: &'static str, repo: String, err: ureq::Error) -> Error {
+    match err {
+        ureq::Error::Status(401, _) => Error::Unauthorized { forge },
+        ureq::Error::Status(404, _) => Error::RepoNotFound { forge, repo },
+        err => Error::ApiRequest {
+            forge,
+            err: Box::new(err),
+        },
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("No {forge} token found")]
+    #[diagnostic(
+        code(check_remote::missing_token),
+        help("Set the GITHUB_TOKEN or GITEA_TOKEN environment variable, or run a workflow which uses the forge to be prompted for one."),
+        url("https://knope.tech/reference/command-line-arguments/#--check-remote")
+    )]
+    MissingToken { forge: &'static str },
+    #[error("{forge} rejected the token")]
+    #[diagnostic(
+        code(check_remote::unauthorized),
+        help("The token may have expired or been revoked, generate a new one."),
+        url("https://knope.tech/reference/command-line-arguments/#--check-remote")
+    )]
+    Unauthorized { forge: &'static str },
+    #[error("Could not find the repository {repo} on {forge}")]
+    #[diagnostic(
+        code(check_remote::repo_not_found),
+        help("Check the `owner` and `repo` in the config file, and that the token can access private repositories."),
+        url("https://knope.tech/reference/command-line-arguments/#--check-remote")
+    )]
+    RepoNotFound { forge: &'static str, repo: String },
+    #[error("The GitHub token is missing the `repo` scope, it has: {scopes}")]
+    #[diagnostic(
+        code(check_remote::missing_scope),
+        help("Steps like `Release` and `CreatePullRequest` need the `repo` (or `public_repo`) scope."),
+        url("https://knope.tech/reference/command-line-arguments/#--check-remote")
+    )]
+    MissingScope { scopes: String },
+    #[error("The {forge} token can't write to {repo}")]
+    #[diagnostic(
+        code(check_remote::read_only),
+        help("Steps like `Release` and `CreatePullRequest` need a token which can push to the repository."),
+        url("https://knope.tech/reference/command-line-arguments/#--check-remote")
+    )]
+    ReadOnly { forge: &'static str, repo: String },
+    #[error("Trouble communicating with {forge}: {err}")]
+    #[diagnostic(
+        code(check_remote::api_request_error),
+        help("This may be a network issue, or the forge may be down.")
+    )]
+    ApiRequest {
+        forge: &'static str,
+        #[source]
+        err: Box<ureq::Error>,
+    },
+    #[error("Trouble decoding the response from {forge}: {source}")]
+    #[diagnostic(
+        code(check_remote::api_response_error),
+        help("Failure to decode a response is probably a bug. Please report it at https://github.com/knope-dev/knope")
+    )]
+    ApiResponse {
+        forge: &'static str,
+        source: std::io::Error,
+    },
+    #[error("No Jira credentials found")]
+    #[diagnostic(
+        code(check_remote::missing_jira_credentials),
+        help("Set the JIRA_EMAIL and JIRA_API_TOKEN environment variables, or run a Jira step to be prompted for them."),
+        url("https://knope.tech/reference/config-file/jira/")
+    )]
+    MissingJiraCredentials,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Jira(#[from] jira::Error),
+}
@@ -0,0 +1,249 @@
+//! Config embedded in a project's own manifest, like `[package.metadata.knope]` in `Cargo.toml` or
+//! the `"knope"` key of `package.json`, so single-package projects don't need another file.
+
+use std::path::{Path, PathBuf};
+
+use miette::{NamedSource, SourceSpan};
+use serde::Serialize;
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::{file_format::without_nulls, Error, FileFormat};
+use crate::fs;
+
+/// A manifest which can hold Knope's config.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Embedded {
+    CargoToml,
+    PackageJson,
+}
+
+impl Embedded {
+    const ALL: [Self; 2] = [Self::CargoToml, Self::PackageJson];
+
+    /// Which manifest `path` is, if any, by its file name.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        Self::ALL
+            .into_iter()
+            .find(|embedded| embedded.file_name() == file_name)
+    }
+
+    /// The first manifest in the current directory which contains Knope config.
+    pub(crate) fn find() -> Option<PathBuf> {
+        Self::ALL.into_iter().find_map(|embedded| {
+            let path = PathBuf::from(embedded.file_name());
+            let source_code = fs::read_to_string(&path).ok()?;
+            matches!(embedded.extract(&source_code), Ok(Some(_))).then_some(path)
+        })
+    }
+
+    const fn file_name(self) -> &'static str {
+        match self {
+            Self::CargoToml => "Cargo.toml",
+            Self::PackageJson => "package.json",
+        }
+    }
+
+    /// Where in the manifest the config lives, for error messages.
+    const fn key(self) -> &'static str {
+        match self {
+            Self::CargoToml => "package.metadata.knope",
+            Self::PackageJson => "knope",
+        }
+    }
+
+    const fn format(self) -> FileFormat {
+        match self {
+            Self::CargoToml => FileFormat::Toml,
+            Self::PackageJson => FileFormat::Json,
+        }
+    }
+
+    /// Get the TOML for the config embedded in `source_code` (from the manifest at `path`), along
+    /// with the name to show in errors.
+    pub(crate) fn to_toml(
+        self,
+        path: &Path,
+        source_code: String,
+    ) -> Result<(String, String), Error> {
+        let config = match self.extract(&source_code) {
+            Ok(Some(config)) => config,
+            Ok(None) => {
+                return Err(Error::MissingEmbeddedConfig {
+                    path: path.to_path_buf(),
+                    key: self.key(),
+                })
+            }
+            Err((message, span)) => {
+                return Err(Error::Format {
+                    format: self.format(),
+                    message,
+                    span,
+                    source_code: NamedSource::new(path.display().to_string(), source_code),
+                })
+            }
+        };
+        let toml = ::toml::to_string(&config).map_err(|err| Error::Format {
+            format: self.format(),
+            message: err.to_string(),
+            span: None,
+            source_code: NamedSource::new(path.display().to_string(), source_code),
+        })?;
+        Ok((format!("{} ({})", path.display(), self.key()), toml))
+    }
+
+    /// The config in `source_code`, if there is any.
+    fn extract(self, source_code: &str) -> Result<Option<Value>, (String, Option<SourceSpan>)> {
+        let config = match self {
+            Self::CargoToml => {
+                let manifest: ::toml::Table = ::toml::from_str(source_code)
+                    .map_err(|err| (err.message().to_string(), err.span().map(SourceSpan::from)))?;
+                manifest
+                    .get("package")
+                    .and_then(|package| package.get("metadata"))
+                    .and_then(|metadata| metadata.get("knope"))
+                    .map(|config| {
+                        serde_json::to_value(config).map_err(|err| (err.to_string(), None))
+                    })
+                    .transpose()?
+            }
+            Self::PackageJson => {
+                let mut manifest: Value =
+                    serde_json::from_str(source_code).map_err(|err| (err.to_string(), None))?;
+                manifest.get_mut("knope").map(Value::take)
+            }
+        };
+        Ok(config.map(without_nulls))
+    }
+
+    /// Replace the config in the manifest `source_code` with `config`, leaving the rest alone.
+    pub(crate) fn replace<T: Serialize>(
+        self,
+        source_code: &str,
+        config: &T,
+    ) -> Result<String, String> {
+        match self {
+            Self::CargoToml => {
+                let mut manifest: DocumentMut = source_code
+                    .parse()
+                    .map_err(|err: toml_edit::TomlError| err.to_string())?;
+                let knope: DocumentMut = ::toml::to_string(config)
+                    .map_err(|err| err.to_string())?
+                    .parse()
+                    .map_err(|err: toml_edit::TomlError| err.to_string())?;
+                let mut knope = knope.as_table().clone();
+                // Keep the new tables right after `[package]` instead of mixed in with the others
+                if let Some(position) = manifest
+                    .get("package")
+                    .and_then(Item::as_table)
+                    .and_then(Table::position)
+                {
+                    set_position(&mut knope, position);
+                }
+                knope.set_implicit(false);
+                let metadata = manifest
+                    .get_mut("package")
+                    .and_then(Item::as_table_mut)
+                    .ok_or_else(|| String::from("Cargo.toml must contain a [package] table"))?
+                    .entry("metadata")
+                    .or_insert_with(|| Item::Table(Table::new()))
+                    .as_table_mut()
+                    .ok_or_else(|| String::from("package.metadata must be a table"))?;
+                metadata.insert("knope", Item::Table(knope));
+                if metadata.len() == 1 {
+                    metadata.set_implicit(true);
+                }
+                Ok(manifest.to_string())
+            }
+            Self::PackageJson => {
+                let mut manifest: Value =
+                    serde_json::from_str(source_code).map_err(|err| err.to_string())?;
+                let config = serde_json::to_value(config).map_err(|err| err.to_string())?;
+                manifest
+                    .as_object_mut()
+                    .ok_or_else(|| String::from("package.json must contain an object"))?
+                    .insert(String::from("knope"), config);
+                serde_json::to_string_pretty(&manifest)
+                    .map(|json| json + "\n")
+                    .map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
+fn set_position(table: &mut Table, position: usize) {
+    table.set_position(position);
+    for (_, item) in table.iter_mut() {
+        match item {
+            Item::Table(table) => set_position(table, position),
+            Item::ArrayOfTables(tables) => {
+                for table in tables.iter_mut() {
+                    set_position(table, position);
+                }
+            }
+            Item::None | Item::Value(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_from_cargo_toml() {
+        let cargo_toml = r#"
+[package]
+name = "knope"
+version = "1.0.0"
+
+[package.metadata.knope.package]
+versioned_files = ["Cargo.toml"]
+
+[[package.metadata.knope.workflows]]
+name = "release"
+
+[[package.metadata.knope.workflows.steps]]
+type = "PrepareRelease"
+"#;
+        let (name, toml) = Embedded::CargoToml
+            .to_toml(Path::new("Cargo.toml"), cargo_toml.to_string())
+            .unwrap();
+        assert_eq!(name, "Cargo.toml (package.metadata.knope)");
+        let value: ::toml::Value = ::toml::from_str(&toml).unwrap();
+        let expected: ::toml::Value = ::toml::from_str(
+            r#"
+            [package]
+            versioned_files = ["Cargo.toml"]
+
+            [[workflows]]
+            name = "release"
+
+            [[workflows.steps]]
+            type = "PrepareRelease"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn missing_from_package_json() {
+        let result = Embedded::PackageJson.to_toml(
+            Path::new("package.json"),
+            String::from(r#"{"name": "knope", "version": "1.0.0"}"#),
+        );
+        assert!(matches!(result, Err(Error::MissingEmbeddedConfig { .. })));
+    }
+
+    #[test]
+    fn replace_without_package() {
+        let result = Embedded::CargoToml.replace("[workspace]\n", &serde_json::json!({}));
+        assert_eq!(
+            result,
+            Err(String::from("Cargo.toml must contain a [package] table"))
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Config files can be written in YAML or JSON instead of TOML, which are converted to TOML before
+//! parsing so that every format is validated the same way.
+
+use std::path::Path;
+
+use miette::{NamedSource, SourceSpan};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::Error;
+
+/// The languages a config file can be written in, detected from its extension.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum FileFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl FileFormat {
+    /// Anything without a `.yaml`, `.yml`, or `.json` extension is TOML.
+    pub(crate) fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// Get the TOML equivalent of `source_code` (from the file at `path`), along with the name
+    /// to show in errors.
+    ///
+    /// Since spans in the converted TOML don't line up with the original file, errors name the
+    /// converted TOML instead of the original file.
+    pub(crate) fn to_toml(
+        self,
+        path: &Path,
+        source_code: String,
+    ) -> Result<(String, String), Error> {
+        let value = match self {
+            Self::Toml => return Ok((path.display().to_string(), source_code)),
+            Self::Yaml => serde_yaml::from_str::<Value>(&source_code).map_err(|err| {
+                let span = err
+                    .location()
+                    .map(|location| SourceSpan::from(location.index()));
+                (err.to_string(), span)
+            }),
+            Self::Json => serde_json::from_str::<Value>(&source_code).map_err(|err| {
+                let span = offset(&source_code, err.line(), err.column()).map(SourceSpan::from);
+                (err.to_string(), span)
+            }),
+        }
+        .map_err(|(message, span)| Error::Format {
+            format: self,
+            message,
+            span,
+            source_code: NamedSource::new(path.display().to_string(), source_code.clone()),
+        })?;
+
+        let toml = ::toml::to_string(&without_nulls(value)).map_err(|err| Error::Format {
+            format: self,
+            message: err.to_string(),
+            span: None,
+            source_code: NamedSource::new(path.display().to_string(), source_code),
+        })?;
+        Ok((format!("{} (as TOML)", path.display()), toml))
+    }
+
+    /// Write `config` in this format.
+    pub(crate) fn serialize<T: Serialize>(self, config: &T) -> Result<String, String> {
+        match self {
+            Self::Toml => ::toml::to_string(config).map_err(|err| err.to_string()),
+            Self::Yaml => serde_yaml::to_string(config).map_err(|err| err.to_string()),
+            Self::Json => serde_json::to_string_pretty(config)
+                .map(|json| json + "\n")
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Toml => write!(f, "TOML"),
+            Self::Yaml => write!(f, "YAML"),
+            Self::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+/// TOML has no `null`, so keys set to `null` (or left empty in YAML) are treated as missing.
+pub(super) fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+/// Convert a 1-indexed line and column into a byte offset in `source`.
+fn offset(source: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(line.checked_sub(1)?)
+        .map(str::len)
+        .sum();
+    Some(line_start + column.saturating_sub(1))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_to_toml() {
+        let yaml = "package:\n  versioned_files:\n    - Cargo.toml\n  changelog:\nworkflows:\n  - name: release\n    steps:\n      - type: PrepareRelease\n";
+        let (_, toml) = FileFormat::Yaml
+            .to_toml(Path::new("knope.yaml"), yaml.to_string())
+            .unwrap();
+        let value: ::toml::Value = ::toml::from_str(&toml).unwrap();
+        let expected: ::toml::Value = ::toml::from_str(
+            r#"
+            [package]
+            versioned_files = ["Cargo.toml"]
+
+            [[workflows]]
+            name = "release"
+
+            [[workflows.steps]]
+            type = "PrepareRelease"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+    }
+}
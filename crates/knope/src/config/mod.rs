@@ -1,27 +1,37 @@
-use std::path::Path;
+use std::{
+    mem,
+    path::{Path, PathBuf},
+};
 
-use ::toml::{from_str, to_string, Spanned};
+use ::toml::{from_str, Spanned};
 use indexmap::IndexMap;
 use itertools::Itertools;
-use knope_versioning::package::Name;
-use miette::{Diagnostic, IntoDiagnostic, Result, SourceSpan};
+use knope_versioning::{package::Name, semver::StableRule};
+use miette::{miette, Diagnostic, IntoDiagnostic, NamedSource, Result, SourceSpan};
 pub(crate) use package::Package;
 use serde::Serialize;
 use thiserror::Error;
 use toml::ConfigLoader;
 
 use crate::{
+    deprecation::Deprecation,
     fs,
     integrations::git,
-    step::{PrepareRelease, Step},
+    step::{get_version::Format, PrepareRelease, Step},
     variables::Variable,
-    workflow::Workflow,
+    workflow::{Workflow, WorkflowStep},
 };
 
+mod embedded;
+mod file_format;
 mod package;
 mod toml;
+mod upgrade;
 
-pub(crate) use toml::{GitHub, Gitea, Jira};
+use embedded::Embedded;
+use file_format::FileFormat;
+pub(crate) use toml::{Git, GitHub, Gitea, Jira, Signing, SigningFormat};
+pub(crate) use upgrade::{upgrade_file, Migration};
 
 use crate::fs::WriteType;
 
@@ -37,24 +47,163 @@ pub(crate) struct Config {
     pub(crate) github: Option<GitHub>,
     /// Optional configuration to communicate with a Gitea instance
     pub(crate) gitea: Option<Gitea>,
+    /// Optional configuration to sign tags and commits
+    pub(crate) signing: Option<Signing>,
+    /// Optional configuration for how Knope uses the Git repository
+    pub(crate) git: Option<Git>,
+    /// Deprecated syntax that was used, to warn about
+    pub(crate) deprecations: Vec<Deprecation>,
 }
 
 impl Config {
-    const CONFIG_PATH: &'static str = "knope.toml";
+    /// Where the config file can be, in order of preference, unless `--config` or `KNOPE_CONFIG`
+    /// says otherwise.
+    pub(crate) const DEFAULT_PATHS: [&'static str; 3] = ["knope.toml", "knope.yaml", "knope.json"];
+
+    /// The first of [`Self::DEFAULT_PATHS`] that exists, then a `Cargo.toml` or `package.json`
+    /// with Knope config in it, or `knope.toml` if there's no config anywhere.
+    pub(crate) fn default_path() -> PathBuf {
+        Self::DEFAULT_PATHS
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .or_else(Embedded::find)
+            .unwrap_or_else(|| PathBuf::from(Self::DEFAULT_PATHS[0]))
+    }
 
-    /// Create a `Config` from a TOML file or load the default config via `generate`
+    /// Create a `Config` from the TOML, YAML, or JSON file at `path` or load the default config via `generate`
     ///
     /// ## Errors
     /// 1. Can't parse file contents into a Config
-    pub(crate) fn load() -> Result<ConfigSource, Error> {
-        let Ok(source_code) = fs::read_to_string(Self::CONFIG_PATH) else {
-            tracing::debug!("No `knope.toml` found, using default config");
+    pub(crate) fn load(path: &Path) -> Result<ConfigSource, Error> {
+        let Ok(source_code) = fs::read_to_string(path) else {
+            tracing::debug!("No `{}` found, using default config", path.display());
             return Ok(ConfigSource::Default(generate()?));
         };
 
-        let config_loader: ConfigLoader = from_str(&source_code)?;
-        let config_source = Self::try_from((config_loader, source_code)).map(ConfigSource::File)?;
-        config_source.fill_in_gaps()
+        let config = Self::load_file(path, source_code, &mut Vec::new())?;
+        // Each file is checked on its own, but `gitea` and `assets` could come from different files
+        if config.gitea.is_some()
+            && config
+                .packages
+                .iter()
+                .any(|package| package.assets.is_some())
+        {
+            return Err(Error::GiteaAssetUploads);
+        }
+        ConfigSource::File(config).fill_in_gaps()
+    }
+
+    /// Parse the config file at `path`, on top of any config files that it `extends` or
+    /// `include`s (relative to `path`).
+    ///
+    /// `loading` is the chain of files which included this one, to catch cycles.
+    fn load_file(
+        path: &Path,
+        source_code: String,
+        loading: &mut Vec<PathBuf>,
+    ) -> Result<Self, Error> {
+        let (name, source_code) = if let Some(embedded) = Embedded::from_path(path) {
+            embedded.to_toml(path, source_code)?
+        } else {
+            FileFormat::from_path(path).to_toml(path, source_code)?
+        };
+        let (source_code, migrations) = upgrade::in_memory(source_code)?;
+        let deprecations = migrations
+            .into_iter()
+            .map(|migration| Deprecation::from_migration(path, migration))
+            .collect_vec();
+        let name = if deprecations.is_empty() {
+            name
+        } else {
+            format!("{name} (upgraded)")
+        };
+        let mut config_loader: ConfigLoader =
+            from_str(&source_code).map_err(|source| Error::Toml {
+                message: source.message().to_string(),
+                span: source.span().map(SourceSpan::from),
+                source_code: NamedSource::new(name.clone(), source_code.clone()),
+            })?;
+        let source_code = NamedSource::new(name, source_code);
+
+        let include_paths = config_loader
+            .extends
+            .take()
+            .into_iter()
+            .chain(mem::take(&mut config_loader.include));
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        loading.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        let mut base: Option<Self> = None;
+        for include in include_paths {
+            let include_path = directory.join(include.get_ref());
+            let canonical = include_path
+                .canonicalize()
+                .unwrap_or_else(|_| include_path.clone());
+            if loading.contains(&canonical) {
+                return Err(Error::IncludeCycle {
+                    path: include_path,
+                    source_code,
+                    span: include.span().into(),
+                });
+            }
+            let Ok(include_source) = fs::read_to_string(&include_path) else {
+                return Err(Error::MissingInclude {
+                    path: include_path,
+                    source_code,
+                    span: include.span().into(),
+                });
+            };
+            let include_config = Self::load_file(&include_path, include_source, loading)?;
+            base = Some(match base {
+                Some(base) => include_config.on_top_of(base),
+                None => include_config,
+            });
+        }
+        loading.pop();
+
+        let mut config = Self::try_from((config_loader, source_code))?;
+        config.deprecations = deprecations;
+        Ok(match base {
+            Some(base) => config.on_top_of(base),
+            None => config,
+        })
+    }
+
+    /// Fill in anything missing from `self` with `base`.
+    ///
+    /// Packages are only taken from `base` if `self` doesn't define any. Workflows from `self`
+    /// replace those with the same name in `base`, and the rest are added after.
+    fn on_top_of(self, base: Self) -> Self {
+        let packages = if self.packages.is_empty() {
+            base.packages
+        } else {
+            self.packages
+        };
+        let mut workflows = base.workflows;
+        for workflow in self.workflows {
+            if let Some(existing) = workflows
+                .iter_mut()
+                .find(|existing| existing.name == workflow.name)
+            {
+                *existing = workflow;
+            } else {
+                workflows.push(workflow);
+            }
+        }
+        Self {
+            packages,
+            workflows,
+            jira: self.jira.or(base.jira),
+            github: self.github.or(base.github),
+            gitea: self.gitea.or(base.gitea),
+            signing: self.signing.or(base.signing),
+            git: self.git.or(base.git),
+            deprecations: base
+                .deprecations
+                .into_iter()
+                .chain(self.deprecations)
+                .collect(),
+        }
     }
 
     /// Set the prerelease label for all `PrepareRelease` steps in all workflows in `self`.
@@ -64,8 +213,15 @@ impl Config {
         }
     }
 
-    /// Write out the Config to `knope.toml`.
-    pub(crate) fn write_out(mut self) -> Result<()> {
+    /// Force the rule of all `PrepareRelease` steps in all workflows in `self`.
+    pub(crate) fn set_rule(&mut self, rule: StableRule) {
+        for workflow in &mut self.workflows {
+            workflow.set_rule(rule);
+        }
+    }
+
+    /// Write out the Config to `path`.
+    pub(crate) fn write_out(mut self, path: &Path) -> Result<()> {
         #[derive(Serialize)]
         struct SimpleConfig {
             #[serde(skip_serializing_if = "Option::is_none")]
@@ -73,8 +229,14 @@ impl Config {
             #[serde(skip_serializing_if = "IndexMap::is_empty")]
             packages: IndexMap<String, knope_config::Package>,
             workflows: Vec<Workflow>,
+            #[serde(skip_serializing_if = "Option::is_none")]
             github: Option<GitHub>,
+            #[serde(skip_serializing_if = "Option::is_none")]
             gitea: Option<Gitea>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            signing: Option<Signing>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            git: Option<Git>,
         }
 
         let (package, packages) = if self.packages.len() < 2 {
@@ -98,47 +260,75 @@ impl Config {
             workflows: self.workflows,
             github: self.github,
             gitea: self.gitea,
+            signing: self.signing,
+            git: self.git,
         };
         #[allow(clippy::unwrap_used)] // because serde is annoying... I know it will serialize
-        let serialized = to_string(&config).unwrap();
+        let serialized = if let Some(embedded) = Embedded::from_path(path) {
+            let manifest = fs::read_to_string(path)?;
+            embedded
+                .replace(&manifest, &config)
+                .map_err(|message| miette!("Could not update {}: {message}", path.display()))?
+        } else {
+            FileFormat::from_path(path).serialize(&config).unwrap()
+        };
 
         fs::write(
             WriteType::Real::<String, String>(serialized),
-            Path::new(Config::CONFIG_PATH),
+            path,
+            &mut None,
         )
         .into_diagnostic()
     }
 }
 
-impl TryFrom<(ConfigLoader, String)> for Config {
+impl TryFrom<(ConfigLoader, NamedSource<String>)> for Config {
     type Error = Error;
 
     fn try_from(
-        (config, source_code): (ConfigLoader, String),
+        (config, source_code): (ConfigLoader, NamedSource<String>),
     ) -> std::result::Result<Self, Self::Error> {
         let packages = match (config.package, config.packages) {
             (Some(package), Some(packages)) => {
-                return if let Some(first_packages) = packages.first() {
+                let first_packages = packages
+                    .defined
+                    .first()
+                    .map(|(_, package)| package.span())
+                    .or_else(|| packages.discovery.as_ref().map(Spanned::span));
+                return if let Some(first_packages) = first_packages {
                     Err(Error::ConflictingPackages {
                         source_code,
                         package_definition: package.span().into(),
-                        packages_definition: first_packages.1.span().into(),
+                        packages_definition: first_packages.into(),
                     })
                 } else {
                     Err(Error::EmptyPackages)
-                }
+                };
             }
             (Some(package), None) => vec![Package::from_toml(
                 Name::Default,
                 package.into_inner(),
                 &source_code,
             )?],
-            (None, Some(packages)) => packages
-                .into_iter()
-                .map(|(name, spanned)| {
-                    Package::from_toml(Name::Custom(name), spanned.into_inner(), &source_code)
-                })
-                .try_collect()?,
+            (None, Some(packages)) => {
+                let mut defined: Vec<Package> = packages
+                    .defined
+                    .into_iter()
+                    .map(|(name, spanned)| {
+                        Package::from_toml(Name::Custom(name), spanned.into_inner(), &source_code)
+                    })
+                    .try_collect()?;
+                if let Some(discovery) = packages.discovery {
+                    let discovered = Package::discover(&discovery.into_inner().globs)
+                        .map_err(package::Error::from)?;
+                    for package in discovered {
+                        if defined.iter().all(|existing| existing.name != package.name) {
+                            defined.push(package);
+                        }
+                    }
+                }
+                defined
+            }
             (None, None) => Vec::new(),
         };
 
@@ -163,6 +353,9 @@ impl TryFrom<(ConfigLoader, String)> for Config {
             jira: config.jira.map(Spanned::into_inner),
             github: config.github.map(Spanned::into_inner),
             gitea: config.gitea.map(Spanned::into_inner),
+            signing: config.signing.map(Spanned::into_inner),
+            git: config.git.map(Spanned::into_inner),
+            deprecations: Vec::new(),
         })
     }
 }
@@ -205,13 +398,40 @@ impl ConfigSource {
 
 #[derive(Debug, Diagnostic, Error)]
 pub(crate) enum Error {
-    #[error(transparent)]
+    #[error("Invalid TOML: {message}")]
     #[diagnostic(
         code(config::toml),
         help("Check the TOML is valid."),
         url("https://knope.tech/reference/config-file/packages/")
     )]
-    Toml(#[from] ::toml::de::Error),
+    Toml {
+        message: String,
+        #[source_code]
+        source_code: NamedSource<String>,
+        #[label("Here")]
+        span: Option<SourceSpan>,
+    },
+    #[error("Invalid {format}: {message}")]
+    #[diagnostic(
+        code(config::format),
+        help("Check the {format} is valid, it must have the same structure as `knope.toml`."),
+        url("https://knope.tech/reference/config-file/packages/")
+    )]
+    Format {
+        format: FileFormat,
+        message: String,
+        #[source_code]
+        source_code: NamedSource<String>,
+        #[label("Here")]
+        span: Option<SourceSpan>,
+    },
+    #[error("No Knope config found at `{key}` in {}", path.display())]
+    #[diagnostic(
+        code(config::missing_embedded_config),
+        help("Add Knope's config to the manifest, or use a separate `knope.toml` instead."),
+        url("https://knope.tech/reference/config-file/formats/#embedded-config")
+    )]
+    MissingEmbeddedConfig { path: PathBuf, key: &'static str },
     #[error("You cannot define both `packages` and `package`")]
     #[diagnostic(
         code(config::conflicting_packages),
@@ -220,7 +440,7 @@ pub(crate) enum Error {
     )]
     ConflictingPackages {
         #[source_code]
-        source_code: String,
+        source_code: NamedSource<String>,
         #[label("`package` defined here")]
         package_definition: SourceSpan,
         #[label("`packages` defined here")]
@@ -240,11 +460,40 @@ pub(crate) enum Error {
         url("https://github.com/knope-dev/knope/issues/779")
     )]
     GiteaAssetUploads,
+    #[error("Could not read the included config file {}", path.display())]
+    #[diagnostic(
+        code(config::missing_include),
+        help("Paths in `extends` and `include` are relative to the config file they're in."),
+        url("https://knope.tech/reference/config-file/include/")
+    )]
+    MissingInclude {
+        path: PathBuf,
+        #[source_code]
+        source_code: NamedSource<String>,
+        #[label("Included here")]
+        span: SourceSpan,
+    },
+    #[error("Config files include each other forever: {}", path.display())]
+    #[diagnostic(
+        code(config::include_cycle),
+        help("Remove one of the `extends` or `include` entries to break the cycle."),
+        url("https://knope.tech/reference/config-file/include/")
+    )]
+    IncludeCycle {
+        path: PathBuf,
+        #[source_code]
+        source_code: NamedSource<String>,
+        #[label("Included here")]
+        span: SourceSpan,
+    },
     #[error(transparent)]
     #[diagnostic(transparent)]
     Package(#[from] package::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
+    Upgrade(#[from] upgrade::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
     VersionedFile(#[from] package::VersionedFileError),
 }
 
@@ -252,10 +501,18 @@ pub(crate) enum Error {
 pub(crate) fn generate() -> Result<Config, package::Error> {
     let packages = Package::find_in_working_dir()?;
 
-    let first_remote = git::get_first_remote();
-    let github = match first_remote {
-        Some(ref remote) if remote.contains("github.com") => {
-            let parts = remote.split('/').collect::<Vec<_>>();
+    // In a fork, `upstream` is usually the repository that releases belong to
+    let remote_names = git::get_remote_names();
+    let remote_name = remote_names
+        .iter()
+        .find(|name| *name == "upstream")
+        .or_else(|| remote_names.first());
+    let remote_url = remote_name.and_then(|name| git::get_remote_url(name));
+    // Only record the remote when there's a choice to make
+    let remote = remote_name.filter(|_| remote_names.len() > 1).cloned();
+    let github = match remote_url {
+        Some(ref remote_url) if remote_url.contains("github.com") => {
+            let parts = remote_url.split('/').collect::<Vec<_>>();
             let owner = parts.get(parts.len() - 2).map(|owner| {
                 owner
                     .strip_prefix("git@github.com:")
@@ -269,17 +526,27 @@ pub(crate) fn generate() -> Result<Config, package::Error> {
 
             owner
                 .and_then(|owner| repo.map(|repo| (owner, repo)))
-                .map(|(owner, repo)| GitHub { owner, repo })
+                .map(|(owner, repo)| GitHub {
+                    owner,
+                    repo,
+                    retries: None,
+                    remote: remote.clone(),
+                    draft: false,
+                    make_latest: None,
+                    discussion_category: None,
+                    append_generated_notes: false,
+                    truncated_notes_url: None,
+                })
         }
         _ => None,
     };
 
-    let gitea = first_remote.as_ref().and_then(|remote| {
+    let gitea = remote_url.as_ref().and_then(|remote_url| {
         if Gitea::KNOWN_PUBLIC_GITEA_HOSTS
             .iter()
-            .any(|known_host| remote.contains(known_host))
+            .any(|known_host| remote_url.contains(known_host))
         {
-            Gitea::try_from_remote(remote)
+            Gitea::try_from_remote(remote_url).map(|gitea| Gitea { remote, ..gitea })
         } else {
             None
         }
@@ -290,7 +557,10 @@ pub(crate) fn generate() -> Result<Config, package::Error> {
         jira: None,
         github,
         gitea,
+        signing: None,
+        git: None,
         packages,
+        deprecations: Vec::new(),
     })
 }
 
@@ -305,36 +575,29 @@ fn generate_workflows(has_forge: bool, packages: &[Package]) -> Vec<Workflow> {
 
     let mut release_steps = if has_forge {
         vec![
-            Step::Command {
-                command: format!("git commit -m \"{commit_message}\"",),
+            Step::Commit {
+                message: String::from(commit_message),
                 variables,
-                shell: None,
             },
-            Step::Command {
-                command: String::from("git push"),
-                variables: None,
-                shell: None,
+            Step::Push,
+            Step::Release {
+                parallel: false,
+                annotate_tags: false,
+                close_fixed_issues: false,
             },
-            Step::Release,
         ]
     } else {
         vec![
-            Step::Command {
-                command: format!("git commit -m \"{commit_message}\""),
+            Step::Commit {
+                message: String::from(commit_message),
                 variables,
-                shell: None,
-            },
-            Step::Release,
-            Step::Command {
-                command: String::from("git push"),
-                variables: None,
-                shell: None,
             },
-            Step::Command {
-                command: String::from("git push --tags"),
-                variables: None,
-                shell: None,
+            Step::Release {
+                parallel: false,
+                annotate_tags: false,
+                close_fixed_issues: false,
             },
+            Step::Push,
         ]
     };
     release_steps.insert(0, Step::PrepareRelease(PrepareRelease::default()));
@@ -343,29 +606,36 @@ fn generate_workflows(has_forge: bool, packages: &[Package]) -> Vec<Workflow> {
         Workflow {
             name: String::from("release"),
             help_text: None,
-            steps: release_steps,
+            steps: release_steps.into_iter().map(WorkflowStep::from).collect(),
+            on_error: Vec::new(),
+            env: IndexMap::new(),
+            packages: None,
+            lock: false,
         },
         Workflow {
             name: String::from("document-change"),
             help_text: None,
-            steps: vec![Step::CreateChangeFile],
+            steps: vec![Step::CreateChangeFile.into()],
+            on_error: Vec::new(),
+            env: IndexMap::new(),
+            packages: None,
+            lock: false,
         },
     ];
 
-    if packages.len() == 1 {
-        let mut get_version_variables = IndexMap::new();
-        get_version_variables.insert(String::from("$version"), Variable::Version);
-
-        let get_version_steps = vec![Step::Command {
-            command: String::from("echo \"$version\""),
-            variables: Some(get_version_variables),
-            shell: None,
-        }];
-
+    if !packages.is_empty() {
         workflows.push(Workflow {
             name: String::from("get-version"),
             help_text: Some(String::from("Get the current version of the project")),
-            steps: get_version_steps,
+            steps: vec![Step::GetVersion {
+                format: Format::default(),
+                file: None,
+            }
+            .into()],
+            on_error: Vec::new(),
+            env: IndexMap::new(),
+            packages: None,
+            lock: false,
         });
     }
     workflows
@@ -375,6 +645,8 @@ fn generate_workflows(has_forge: bool, packages: &[Package]) -> Vec<Workflow> {
 #[allow(clippy::unwrap_used)]
 mod test_errors {
 
+    use miette::NamedSource;
+
     use super::Config;
 
     #[test]
@@ -390,7 +662,7 @@ mod test_errors {
         "#
         .to_string();
         let config: super::toml::ConfigLoader = toml::from_str(&toml_string).unwrap();
-        let config = Config::try_from((config, toml_string));
+        let config = Config::try_from((config, NamedSource::new("knope.toml", toml_string)));
         assert!(config.is_err(), "Expected an error, got {config:?}");
     }
 
@@ -413,7 +685,7 @@ mod test_errors {
         "#
         .to_string();
         let config: super::toml::ConfigLoader = toml::from_str(&toml_string).unwrap();
-        let config = Config::try_from((config, toml_string));
+        let config = Config::try_from((config, NamedSource::new("knope.toml", toml_string)));
         assert!(config.is_err(), "Expected an error, got {config:?}");
     }
 }
@@ -2,9 +2,15 @@ use std::{ops::Range, path::PathBuf, str::FromStr};
 
 use ::toml::Spanned;
 use itertools::Itertools;
-use knope_config::{Assets, ChangelogSection};
-use knope_versioning::{package, versioned_file::cargo, UnknownFile, VersionedFileConfig};
-use miette::Diagnostic;
+use knope_config::{Assets, ChangelogSection, Changelogs, Checksums, ReleaseNotes};
+use knope_versioning::{
+    changes::RequireScope,
+    package,
+    semver::ZeroMajorBreaking,
+    versioned_file::{cargo, go_work},
+    UnknownFile, VersionedFileConfig,
+};
+use miette::{Diagnostic, NamedSource};
 use relative_path::{RelativePath, RelativePathBuf};
 use thiserror::Error;
 use toml_edit::{DocumentMut, TomlError};
@@ -17,15 +23,31 @@ pub struct Package {
     pub(crate) name: package::Name,
     /// The files which define the current version of the package.
     pub(crate) versioned_files: Vec<VersionedFileConfig>,
-    /// The path to the `CHANGELOG.md` file (if any) to be updated when running [`Step::PrepareRelease`].
-    pub(crate) changelog: Option<RelativePathBuf>,
+    /// The paths to any `CHANGELOG.md` files to be updated when running [`Step::PrepareRelease`].
+    pub(crate) changelogs: Vec<RelativePathBuf>,
+    /// Where to write the details of each new release as JSON, if anywhere.
+    pub(crate) release_json: Option<RelativePathBuf>,
+    /// A template for the notes of each release, and any custom variables for it.
+    pub(crate) release_notes: Option<ReleaseNotes>,
     /// Optional scopes that can be used to filter commits when running [`Step::PrepareRelease`].
     pub(crate) scopes: Option<Vec<String>>,
+    /// What to do with conventional commits that have no scope.
+    pub(crate) require_scope: RequireScope,
     /// Extra sections that should be added to the changelog from custom footers in commit messages
     /// or change set types.
     pub(crate) extra_changelog_sections: Vec<ChangelogSection>,
     pub(crate) assets: Option<Assets>,
+    pub(crate) checksums: Option<Checksums>,
+    pub(crate) sigstore: bool,
     pub(crate) ignore_go_major_versioning: bool,
+    pub(crate) zero_major_breaking: ZeroMajorBreaking,
+    /// Only use commits which change files matching `paths` when running [`Step::PrepareRelease`].
+    pub(crate) filter_commits_by_path: bool,
+    /// Glob patterns for the files in this package, defaults to the directory of the first
+    /// versioned file.
+    pub(crate) paths: Vec<String>,
+    /// Packages in the same version group are always released with the same new version.
+    pub(crate) version_group: Option<String>,
 }
 
 impl Package {
@@ -36,13 +58,20 @@ impl Package {
             return Ok(packages);
         }
 
+        let packages = Self::go_workspace_members()?;
+        if !packages.is_empty() {
+            return Ok(packages);
+        }
+
         let default_changelog_path = RelativePathBuf::from("CHANGELOG.md");
-        let changelog = default_changelog_path
+        let changelogs = default_changelog_path
             .to_path("")
             .exists()
-            .then_some(default_changelog_path);
+            .then_some(default_changelog_path)
+            .into_iter()
+            .collect();
 
-        let versioned_files = VersionedFileConfig::defaults()
+        let mut versioned_files = VersionedFileConfig::defaults()
             .into_iter()
             .filter_map(|file_name| {
                 let path = file_name.as_path();
@@ -53,12 +82,25 @@ impl Package {
                 }
             })
             .collect_vec();
+        if versioned_files
+            .iter()
+            .any(|file| file.as_path() == "pyproject.toml")
+        {
+            // Keep the project's own entry in the lockfile up to date
+            versioned_files.extend(
+                ["poetry.lock", "uv.lock"]
+                    .into_iter()
+                    .map(RelativePathBuf::from)
+                    .filter(|path| path.to_path("").exists())
+                    .filter_map(|path| VersionedFileConfig::new(path, None).ok()),
+            );
+        }
         if versioned_files.is_empty() {
             Ok(vec![])
         } else {
             Ok(vec![Self {
                 versioned_files,
-                changelog,
+                changelogs,
                 ..Self::default()
             }])
         }
@@ -74,12 +116,13 @@ impl Package {
         let workspace_path = cargo_toml_path
             .parent()
             .ok_or_else(|| CargoWorkspaceError::Parent(cargo_toml_path.into()))?;
-        let Some(members) = cargo_toml
+        let Some(workspace) = cargo_toml
             .get("workspace")
-            .and_then(|workspace| workspace.as_table()?.get("members")?.as_array())
+            .and_then(|workspace| workspace.as_table())
         else {
             return Ok(Vec::new());
         };
+        let members = workspace_member_dirs(workspace, workspace_path)?;
 
         let cargo_lock_path = workspace_path.join("Cargo.lock");
         let cargo_lock = if cargo_lock_path.to_path("").exists() {
@@ -89,17 +132,17 @@ impl Package {
         };
 
         let members: Vec<WorkspaceMember> = members
-            .iter()
-            .map(|member_val| {
-                let member = member_val.as_str().ok_or(CargoWorkspaceError::Members)?;
-                let member_config =
-                    VersionedFileConfig::new(workspace_path.join(member).join("Cargo.toml"), None)?;
+            .into_iter()
+            .map(|member| {
+                let member_config = VersionedFileConfig::new(member.join("Cargo.toml"), None)?;
                 let member_contents = read_to_string(member_config.as_path().to_path("."))?;
                 let document = DocumentMut::from_str(&member_contents)
                     .map_err(|err| CargoWorkspaceError::Toml(err, member_config.as_path()))?;
                 let name = cargo::name_from_document(&document)
                     .ok_or_else(|| CargoWorkspaceError::NoPackageName(member_config.as_path()))?;
                 Ok(WorkspaceMember {
+                    changelog: Some(member.join("CHANGELOG.md"))
+                        .filter(|changelog| changelog.to_path("").exists()),
                     path: member_config,
                     name: name.to_string(),
                     document,
@@ -139,27 +182,119 @@ impl Package {
                     name: package::Name::Custom(member.name.clone()),
                     versioned_files,
                     scopes: Some(vec![member.name.clone()]),
-                    changelog: None,
+                    require_scope: RequireScope::default(),
+                    changelogs: member.changelog.clone().into_iter().collect(),
+                    release_json: None,
+                    release_notes: None,
                     extra_changelog_sections: vec![],
                     assets: None,
+                    checksums: None,
+                    sigstore: false,
                     ignore_go_major_versioning: false,
+                    zero_major_breaking: ZeroMajorBreaking::default(),
+                    filter_commits_by_path: false,
+                    paths: Vec::new(),
+                    version_group: None,
                 }
             })
             .collect())
     }
 
+    /// Every module in a `go.work` file becomes a package named after its directory, so that
+    /// package tags match the tags Go expects for the module.
+    fn go_workspace_members() -> Result<Vec<Self>, GoWorkspaceError> {
+        let Ok(contents) = read_to_string("go.work") else {
+            return Ok(Vec::new());
+        };
+        let members = go_work::members(&contents);
+        let single_member = members.len() == 1;
+        members
+            .into_iter()
+            .map(|member| {
+                let go_mod = VersionedFileConfig::new(member.join("go.mod"), None)?;
+                let name = if single_member {
+                    package::Name::Default
+                } else if member.as_str().is_empty() {
+                    let contents = read_to_string(go_mod.to_pathbuf())?;
+                    go_work::module_name(&contents)
+                        .map(package::Name::Custom)
+                        .ok_or_else(|| GoWorkspaceError::NoModuleName(go_mod.as_path()))?
+                } else {
+                    package::Name::Custom(member.to_string())
+                };
+                let changelog = member.join("CHANGELOG.md");
+                Ok(Self {
+                    scopes: name.as_custom().map(|name| vec![name.to_string()]),
+                    name,
+                    versioned_files: vec![go_mod],
+                    changelogs: changelog
+                        .to_path("")
+                        .exists()
+                        .then_some(changelog)
+                        .into_iter()
+                        .collect(),
+                    ..Self::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Every versioned file matching one of `globs` becomes a package named after its directory.
+    pub(crate) fn discover(globs: &[String]) -> Result<Vec<Self>, DiscoveryError> {
+        let mut packages: Vec<Self> = Vec::new();
+        for pattern in globs {
+            for path in glob::glob(pattern)? {
+                let path = RelativePathBuf::from_path(path?)
+                    .map_err(|_| DiscoveryError::NoDirectory(pattern.clone()))?;
+                let directory = path.parent().unwrap_or(RelativePath::new(""));
+                let name = directory
+                    .file_name()
+                    .ok_or_else(|| DiscoveryError::NoDirectory(path.to_string()))?
+                    .to_string();
+                if packages
+                    .iter()
+                    .any(|package| package.name.as_custom() == Some(name.as_str()))
+                {
+                    return Err(DiscoveryError::DuplicateName(name));
+                }
+                let changelog = directory.join("CHANGELOG.md");
+                packages.push(Self {
+                    name: package::Name::Custom(name),
+                    versioned_files: vec![VersionedFileConfig::new(path, None)?],
+                    changelogs: changelog
+                        .to_path("")
+                        .exists()
+                        .then_some(changelog)
+                        .into_iter()
+                        .collect(),
+                    ..Self::default()
+                });
+            }
+        }
+        Ok(packages)
+    }
+
     pub(crate) fn from_toml(
         name: package::Name,
         package: knope_config::Package,
-        source_code: &str,
+        source_code: &NamedSource<String>,
     ) -> Result<Self, VersionedFileError> {
         let knope_config::Package {
             versioned_files,
             changelog,
+            release_json,
+            release_notes,
             scopes,
+            require_scope,
             extra_changelog_sections,
             assets,
+            checksums,
+            sigstore,
             ignore_go_major_versioning,
+            zero_major_breaking,
+            filter_commits_by_path,
+            paths,
+            version_group,
         } = package;
         let versioned_files = versioned_files
             .into_iter()
@@ -169,7 +304,7 @@ impl Package {
                     .map_err(|source| VersionedFileError::UnknownFile {
                         source,
                         span: span.clone(),
-                        source_code: source_code.to_string(),
+                        source_code: source_code.clone(),
                     })
                     .and_then(|path| {
                         let pathbuf = path.to_pathbuf();
@@ -179,7 +314,7 @@ impl Package {
                             Err(VersionedFileError::Missing {
                                 path: pathbuf,
                                 span,
-                                source_code: source_code.to_string(),
+                                source_code: source_code.clone(),
                             })
                         }
                     })
@@ -188,11 +323,20 @@ impl Package {
         Ok(Self {
             name,
             versioned_files,
-            changelog,
+            changelogs: changelog.map(Vec::from).unwrap_or_default(),
+            release_json,
+            release_notes,
             scopes,
+            require_scope,
             extra_changelog_sections,
             assets,
+            checksums,
+            sigstore,
             ignore_go_major_versioning,
+            zero_major_breaking,
+            filter_commits_by_path,
+            paths,
+            version_group,
         })
     }
 }
@@ -205,18 +349,69 @@ impl From<Package> for knope_config::Package {
                 .into_iter()
                 .map(|it| Spanned::new(0..0, knope_config::VersionedFile::from(it)))
                 .collect(),
-            changelog: package.changelog,
+            changelog: Changelogs::from_paths(package.changelogs),
+            release_json: package.release_json,
+            release_notes: package.release_notes,
             scopes: package.scopes,
+            require_scope: package.require_scope,
             extra_changelog_sections: package.extra_changelog_sections,
             assets: package.assets,
+            checksums: package.checksums,
+            sigstore: package.sigstore,
             ignore_go_major_versioning: package.ignore_go_major_versioning,
+            zero_major_breaking: package.zero_major_breaking,
+            filter_commits_by_path: package.filter_commits_by_path,
+            paths: package.paths,
+            version_group: package.version_group,
         }
     }
 }
 
+/// The directories of every member of a Cargo `workspace`, expanding glob patterns and skipping
+/// anything in `exclude`.
+fn workspace_member_dirs(
+    workspace: &toml_edit::Table,
+    workspace_path: &RelativePath,
+) -> Result<Vec<RelativePathBuf>, CargoWorkspaceError> {
+    let strings = |key: &str| -> Result<Vec<&str>, CargoWorkspaceError> {
+        workspace
+            .get(key)
+            .and_then(toml_edit::Item::as_array)
+            .map_or_else(Vec::new, |array| array.iter().collect())
+            .into_iter()
+            .map(|value| value.as_str().ok_or(CargoWorkspaceError::Members))
+            .collect()
+    };
+    let exclude = strings("exclude")?
+        .into_iter()
+        .map(|path| workspace_path.join(path).normalize())
+        .collect_vec();
+    let mut members = Vec::new();
+    for member in strings("members")? {
+        let member = workspace_path.join(member);
+        if member.as_str().contains(['*', '?', '[']) {
+            for path in glob::glob(member.as_str())? {
+                let path = path?;
+                if path.join("Cargo.toml").exists() {
+                    members.extend(RelativePathBuf::from_path(path).ok());
+                }
+            }
+        } else {
+            members.push(member);
+        }
+    }
+    Ok(members
+        .into_iter()
+        .map(|member| member.normalize())
+        .filter(|member| !exclude.contains(member))
+        .unique()
+        .collect())
+}
+
 #[derive(Debug)]
 struct WorkspaceMember {
     path: VersionedFileConfig,
+    changelog: Option<RelativePathBuf>,
     name: String,
     document: DocumentMut,
 }
@@ -229,7 +424,7 @@ pub enum VersionedFileError {
         #[diagnostic_source]
         source: UnknownFile,
         #[source_code]
-        source_code: String,
+        source_code: NamedSource<String>,
         #[label("Declared here")]
         span: Range<usize>,
     },
@@ -241,7 +436,7 @@ pub enum VersionedFileError {
     Missing {
         path: PathBuf,
         #[source_code]
-        source_code: String,
+        source_code: NamedSource<String>,
         #[label("Declared here")]
         span: Range<usize>,
     },
@@ -261,9 +456,62 @@ pub(crate) enum CargoWorkspaceError {
     #[error("Could not get parent directory of Cargo.toml file: {0}")]
     #[diagnostic(code(workspace::parent))]
     Parent(RelativePathBuf),
-    #[error("The Cargo workspace members array should contain only strings")]
+    #[error("The Cargo workspace members and exclude arrays should contain only strings")]
     #[diagnostic(code(workspace::members))]
     Members,
+    #[error("Invalid glob pattern in the Cargo workspace members: {0}")]
+    #[diagnostic(code(workspace::pattern))]
+    Pattern(#[from] glob::PatternError),
+    #[error("Could not read a Cargo workspace member: {0}")]
+    #[diagnostic(code(workspace::glob))]
+    Glob(#[from] glob::GlobError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnknownFile(#[from] UnknownFile),
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum GoWorkspaceError {
+    #[error("Could not find a module path in {0}")]
+    #[diagnostic(
+        code(go_workspace::no_module_name),
+        help("Every module listed in go.work must have a `module` line in its go.mod"),
+        url("https://knope.tech/reference/default-config/#go-workspaces")
+    )]
+    NoModuleName(RelativePathBuf),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    UnknownFile(#[from] UnknownFile),
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum DiscoveryError {
+    #[error("Invalid glob pattern in `packages.discovery.globs`: {0}")]
+    #[diagnostic(
+        code(discovery::pattern),
+        url("https://knope.tech/reference/config-file/packages/#discovery")
+    )]
+    Pattern(#[from] glob::PatternError),
+    #[error("Could not read a file matching `packages.discovery.globs`: {0}")]
+    #[diagnostic(code(discovery::glob))]
+    Glob(#[from] glob::GlobError),
+    #[error("{0} is not in a directory that can name a package")]
+    #[diagnostic(
+        code(discovery::no_directory),
+        help("Discovered packages are named after the directory of their versioned file, so it can't be in the root."),
+        url("https://knope.tech/reference/config-file/packages/#discovery")
+    )]
+    NoDirectory(String),
+    #[error("More than one discovered package is named {0}")]
+    #[diagnostic(
+        code(discovery::duplicate_name),
+        help("Discovered packages are named after the directory of their versioned file, so each must be in a different directory."),
+        url("https://knope.tech/reference/config-file/packages/#discovery")
+    )]
+    DuplicateName(String),
     #[error(transparent)]
     #[diagnostic(transparent)]
     UnknownFile(#[from] UnknownFile),
@@ -274,4 +522,10 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     CargoWorkspace(#[from] CargoWorkspaceError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GoWorkspace(#[from] GoWorkspaceError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Discovery(#[from] DiscoveryError),
 }
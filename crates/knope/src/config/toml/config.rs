@@ -1,17 +1,28 @@
+use std::fmt;
+
 use indexmap::IndexMap;
 use itertools::Itertools;
 use knope_config::Package;
-use serde::{Deserialize, Serialize};
+use knope_versioning::release_notes::Forge;
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
 use toml::Spanned;
 
-use crate::workflow::Workflow;
+use crate::{integrations::retry, workflow::Workflow};
 
 /// Loads a `crate::Config` from a TOML file with as much span information as possible for better
 /// error messages.
 #[derive(Debug, Deserialize)]
 pub(crate) struct ConfigLoader {
+    /// Another config file which this one builds on, like `../shared/knope-base.toml`
+    pub(crate) extends: Option<Spanned<String>>,
+    /// More config files which this one builds on, applied in order after `extends`
+    #[serde(default)]
+    pub(crate) include: Vec<Spanned<String>>,
     pub(crate) package: Option<Spanned<Package>>,
-    pub(crate) packages: Option<IndexMap<String, Spanned<Package>>>,
+    pub(crate) packages: Option<Packages>,
     /// The list of defined workflows that are selectable
     pub(crate) workflows: Option<Spanned<Vec<Spanned<Workflow>>>>,
     /// Optional configuration for Jira
@@ -20,6 +31,53 @@ pub(crate) struct ConfigLoader {
     pub(crate) github: Option<Spanned<GitHub>>,
     /// Optional configuration to talk to a Gitea instance
     pub(crate) gitea: Option<Spanned<Gitea>>,
+    /// Optional configuration to sign tags and commits
+    pub(crate) signing: Option<Spanned<Signing>>,
+    /// Optional configuration for how Knope uses the Git repository
+    pub(crate) git: Option<Spanned<Git>>,
+}
+
+/// The `[packages]` table: packages by name, along with the optional `discovery` table.
+#[derive(Debug, Default)]
+pub(crate) struct Packages {
+    pub(crate) defined: IndexMap<String, Spanned<Package>>,
+    pub(crate) discovery: Option<Spanned<Discovery>>,
+}
+
+impl<'de> Deserialize<'de> for Packages {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PackagesVisitor;
+
+        impl<'de> Visitor<'de> for PackagesVisitor {
+            type Value = Packages;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a table of packages")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Packages, A::Error> {
+                let mut packages = Packages::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "discovery" {
+                        packages.discovery = Some(map.next_value()?);
+                    } else {
+                        packages.defined.insert(key, map.next_value()?);
+                    }
+                }
+                Ok(packages)
+            }
+        }
+
+        deserializer.deserialize_map(PackagesVisitor)
+    }
+}
+
+/// Finds packages by their versioned files, instead of defining each one in `[packages]`.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Discovery {
+    /// Glob patterns for versioned files, like `crates/*/Cargo.toml`. Each match is a package
+    /// named after its directory.
+    pub(crate) globs: Vec<String>,
 }
 
 #[cfg(test)]
@@ -45,12 +103,27 @@ mod test_package_configs {
         let toml_str = format!("[packages.something]\n[packages.blah]{REQUIRED_CONFIG_STUFF}");
         let config: ConfigLoader = toml::from_str(&toml_str).unwrap();
         assert!(config.package.is_none());
-        let packages = config.packages.unwrap();
+        let packages = config.packages.unwrap().defined;
         assert_eq!(packages.len(), 2);
         assert!(packages.contains_key("something"));
         assert!(packages.contains_key("blah"));
     }
 
+    #[test]
+    fn discovery() {
+        let toml_str = format!(
+            "[packages.something]\n[packages.discovery]\nglobs = [\"crates/*/Cargo.toml\"]{REQUIRED_CONFIG_STUFF}"
+        );
+        let config: ConfigLoader = toml::from_str(&toml_str).unwrap();
+        let packages = config.packages.unwrap();
+        assert_eq!(packages.defined.len(), 1);
+        assert!(packages.defined.contains_key("something"));
+        assert_eq!(
+            packages.discovery.unwrap().into_inner().globs,
+            vec!["crates/*/Cargo.toml"]
+        );
+    }
+
     #[test]
     fn single_package_with_help() {
         let toml_str =
@@ -77,6 +150,80 @@ pub(crate) struct GitHub {
     pub(crate) owner: String,
     /// The name of the repository in GitHub that this project is utilizing
     pub(crate) repo: String,
+    /// How many times to retry requests which fail temporarily, defaults to
+    /// [`retry::DEFAULT_RETRIES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) retries: Option<u32>,
+    /// The name of the Git remote for this repository, defaults to the first remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) remote: Option<String>,
+    /// Create releases as drafts, to be published later with the `PublishRelease` step.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) draft: bool,
+    /// Whether published releases become the latest release, defaults to letting GitHub decide.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) make_latest: Option<bool>,
+    /// Start a discussion in this category for every release.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) discussion_category: Option<String>,
+    /// Add GitHub's generated release notes beneath Knope's, not only when Knope has none.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) append_generated_notes: bool,
+    /// Where notes that are too long for GitHub link to, defaults to `CHANGELOG.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) truncated_notes_url: Option<String>,
+}
+
+impl GitHub {
+    pub(crate) fn retries(&self) -> u32 {
+        self.retries.unwrap_or(retry::DEFAULT_RETRIES)
+    }
+
+    /// The `make_latest` value for GitHub's release API, if it was set.
+    pub(crate) fn make_latest(&self) -> Option<&'static str> {
+        self.make_latest
+            .map(|latest| if latest { "true" } else { "false" })
+    }
+}
+
+impl From<&GitHub> for Forge {
+    fn from(github: &GitHub) -> Self {
+        Self::GitHub {
+            owner: github.owner.clone(),
+            repo: github.repo.clone(),
+        }
+    }
+}
+
+/// How to sign the Git tags (and optionally commits) that Knope creates.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Signing {
+    /// The key to sign with, defaults to Git's `user.signingKey`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) key: Option<String>,
+    /// The type of `key`, defaults to Git's `gpg.format`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) format: Option<SigningFormat>,
+    /// Whether to also sign commits from the `Commit` step
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) commits: bool,
+}
+
+/// How Knope uses the Git repository it runs in.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Git {
+    /// Whether to fetch all tags and history when the repository is a shallow clone
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) fetch_tags: bool,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SigningFormat {
+    /// Sign with `gpg`
+    OpenPgp,
+    /// Sign with `ssh-keygen`
+    Ssh,
 }
 
 /// Details needed to use steps that interact with a Gitea instance.
@@ -89,16 +236,47 @@ pub(crate) struct Gitea {
     pub(crate) repo: String,
     /// The domain or IP of the Gitea instance
     pub(crate) host: String,
+    /// How many times to retry requests which fail temporarily, defaults to
+    /// [`retry::DEFAULT_RETRIES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) retries: Option<u32>,
+    /// The name of the Git remote for this repository, defaults to the first remote.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) remote: Option<String>,
+}
+
+impl From<&Gitea> for Forge {
+    fn from(gitea: &Gitea) -> Self {
+        Self::Gitea {
+            host: gitea.host.clone(),
+            owner: gitea.owner.clone(),
+            repo: gitea.repo.clone(),
+        }
+    }
 }
 
 impl Gitea {
     /// This lists all known gitea hosts, so we can easily generate the gitea config
     pub(crate) const KNOWN_PUBLIC_GITEA_HOSTS: &'static [&'static str] = &["codeberg.org"];
 
+    pub(crate) fn retries(&self) -> u32 {
+        self.retries.unwrap_or(retry::DEFAULT_RETRIES)
+    }
+
     fn get_base_url(&self) -> String {
         format!("{host}/api/v1", host = self.host)
     }
 
+    /// Get the URL to read details of the repository
+    pub(crate) fn get_repo_url(&self) -> String {
+        format!(
+            "{base_url}/repos/{owner}/{repo}",
+            base_url = self.get_base_url(),
+            owner = self.owner,
+            repo = self.repo
+        )
+    }
+
     /// get the base url to create and list PRs
     pub(crate) fn get_pulls_url(&self) -> String {
         format!(
@@ -114,6 +292,16 @@ impl Gitea {
         format!("{pulls_url}/{pr_number}", pulls_url = self.get_pulls_url())
     }
 
+    /// Get the URL to read the combined status of every check on a commit
+    pub(crate) fn get_commit_status_url(&self, sha: &str) -> String {
+        format!(
+            "{base_url}/repos/{owner}/{repo}/commits/{sha}/status",
+            base_url = self.get_base_url(),
+            owner = self.owner,
+            repo = self.repo
+        )
+    }
+
     /// Get the URL to create/read releases
     pub(crate) fn get_releases_url(&self) -> String {
         format!(
@@ -152,6 +340,8 @@ impl Gitea {
                 owner: owner.to_string(),
                 repo: repo.strip_suffix(".git").unwrap_or(repo).to_string(),
                 host: format!("https://{host}", host = scheme.strip_prefix("git@")?),
+                retries: None,
+                remote: None,
             })
         } else {
             // HTTP(s) remote
@@ -167,6 +357,8 @@ impl Gitea {
                 host: format!("https://{host}"),
                 owner: owner.to_string(),
                 repo: repo.strip_suffix(".git").unwrap_or(repo).to_string(),
+                retries: None,
+                remote: None,
             })
         }
     }
@@ -183,7 +375,9 @@ mod test_gitea_try_from_remote {
             Some(Gitea {
                 owner: "knope-dev".to_string(),
                 repo: "knope".to_string(),
-                host: "https://codeberg.org".to_string()
+                host: "https://codeberg.org".to_string(),
+                retries: None,
+                remote: None,
             }),
             config
         );
@@ -196,7 +390,9 @@ mod test_gitea_try_from_remote {
             Some(Gitea {
                 owner: "knope-dev".to_string(),
                 repo: "knope".to_string(),
-                host: "https://codeberg.org".to_string()
+                host: "https://codeberg.org".to_string(),
+                retries: None,
+                remote: None,
             }),
             config
         );
@@ -209,7 +405,9 @@ mod test_gitea_try_from_remote {
             Some(Gitea {
                 owner: "knope-dev".to_string(),
                 repo: "knope".to_string(),
-                host: "https://codeberg.org".to_string()
+                host: "https://codeberg.org".to_string(),
+                retries: None,
+                remote: None,
             }),
             config
         );
@@ -222,7 +420,9 @@ mod test_gitea_try_from_remote {
             Some(Gitea {
                 owner: "knope-dev".to_string(),
                 repo: "knope".to_string(),
-                host: "https://codeberg.org".to_string()
+                host: "https://codeberg.org".to_string(),
+                retries: None,
+                remote: None,
             }),
             config
         );
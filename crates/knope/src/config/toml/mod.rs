@@ -1,4 +1,4 @@
 mod config;
 
 pub(super) use config::ConfigLoader;
-pub(crate) use config::{GitHub, Gitea, Jira};
+pub(crate) use config::{Git, GitHub, Gitea, Jira, Signing, SigningFormat};
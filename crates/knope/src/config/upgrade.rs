@@ -0,0 +1,326 @@
+//! Migrations from deprecated config syntax.
+//!
+//! `--upgrade` uses these to rewrite the config file (keeping comments and formatting), and
+//! loading uses them in memory so that the old syntax keeps working in the meantime.
+
+use std::{fmt, path::Path};
+
+use miette::Diagnostic;
+use toml_edit::{DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+use super::{Embedded, FileFormat};
+use crate::{fs, fs::WriteType};
+
+/// One change made to a config file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Migration {
+    /// `[[packages]]` with a single, unnamed package became `[package]`
+    PackagesArrayToPackage,
+    /// `[[packages]]` with names became `[packages.<name>]`
+    PackagesArrayToTable,
+    /// A variable using an old spelling, like `changelog_entry` instead of `ChangelogEntry`
+    VariableSpelling { from: String, to: &'static str },
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PackagesArrayToPackage => write!(f, "Converted `[[packages]]` to `[package]`"),
+            Self::PackagesArrayToTable => {
+                write!(f, "Converted `[[packages]]` to `[packages.<name>]`")
+            }
+            Self::VariableSpelling { from, to } => {
+                write!(f, "Renamed the variable `{from}` to `{to}`")
+            }
+        }
+    }
+}
+
+/// Upgrade the config file at `path` in place, returning what changed.
+///
+/// Only TOML files (including `[package.metadata.knope]` in `Cargo.toml`) can be upgraded, since
+/// those are the only formats where comments can be kept.
+pub(crate) fn upgrade_file(path: &Path) -> Result<Vec<Migration>, Error> {
+    let Ok(source_code) = fs::read_to_string(path) else {
+        return Ok(Vec::new());
+    };
+    let mut document: DocumentMut = source_code
+        .parse()
+        .map_err(|err: toml_edit::TomlError| Error::Toml(err.to_string()))?;
+    let config = match (Embedded::from_path(path), FileFormat::from_path(path)) {
+        (Some(Embedded::CargoToml), _) => document
+            .get_mut("package")
+            .and_then(|package| package.get_mut("metadata"))
+            .and_then(|metadata| metadata.get_mut("knope"))
+            .and_then(Item::as_table_mut),
+        (None, FileFormat::Toml) => Some(document.as_table_mut()),
+        (Some(Embedded::PackageJson), _) | (None, FileFormat::Yaml | FileFormat::Json) => {
+            return Err(Error::UnsupportedFormat(path.display().to_string()));
+        }
+    };
+    let Some(config) = config else {
+        return Ok(Vec::new());
+    };
+    let migrations = migrate(config)?;
+    if !migrations.is_empty() {
+        fs::write(
+            WriteType::Real::<String, String>(document.to_string()),
+            path,
+            &mut None,
+        )?;
+    }
+    Ok(migrations)
+}
+
+/// Apply any migrations to the TOML `source_code` of a config file without writing it anywhere.
+///
+/// If the TOML is invalid, it's returned as-is so that parsing it reports the error.
+pub(crate) fn in_memory(source_code: String) -> Result<(String, Vec<Migration>), Error> {
+    let Ok(mut document) = source_code.parse::<DocumentMut>() else {
+        return Ok((source_code, Vec::new()));
+    };
+    let migrations = migrate(document.as_table_mut())?;
+    if migrations.is_empty() {
+        Ok((source_code, migrations))
+    } else {
+        Ok((document.to_string(), migrations))
+    }
+}
+
+/// Apply every migration to the root table of a config file.
+fn migrate(config: &mut Table) -> Result<Vec<Migration>, Error> {
+    let mut migrations = Vec::new();
+    migrations.extend(packages_array(config)?);
+    if let Some(workflows) = config.get_mut("workflows") {
+        variable_spellings(workflows, &mut migrations);
+    }
+    Ok(migrations)
+}
+
+/// `[[packages]]` (an array of tables) was how packages used to be defined.
+fn packages_array(config: &mut Table) -> Result<Option<Migration>, Error> {
+    let packages = match config.get("packages") {
+        Some(Item::ArrayOfTables(packages)) => packages.iter().cloned().collect::<Vec<_>>(),
+        Some(Item::Value(Value::Array(packages))) => {
+            let Some(packages) = packages
+                .iter()
+                .map(|package| {
+                    package
+                        .as_inline_table()
+                        .cloned()
+                        .map(InlineTable::into_table)
+                })
+                .collect::<Option<Vec<_>>>()
+            else {
+                return Ok(None);
+            };
+            packages
+        }
+        _ => return Ok(None),
+    };
+
+    let unnamed = packages
+        .iter()
+        .filter(|package| package.get("name").and_then(Item::as_str).is_none())
+        .count();
+    if packages.len() == 1 && unnamed == 1 {
+        config.remove("packages");
+        config.insert(
+            "package",
+            Item::Table(packages.into_iter().next().unwrap_or_default()),
+        );
+        return Ok(Some(Migration::PackagesArrayToPackage));
+    }
+    if unnamed > 0 {
+        return Err(Error::UnnamedPackages);
+    }
+
+    let mut table = Table::new();
+    table.set_implicit(true);
+    for mut package in packages {
+        let name = package
+            .remove("name")
+            .and_then(|name| name.as_str().map(String::from))
+            .unwrap_or_default();
+        table.insert(&name, Item::Table(package));
+    }
+    config.insert("packages", Item::Table(table));
+    Ok(Some(Migration::PackagesArrayToTable))
+}
+
+/// Look through every step for `variables`, fixing the spelling of each.
+fn variable_spellings(item: &mut Item, migrations: &mut Vec<Migration>) {
+    match item {
+        Item::ArrayOfTables(tables) => {
+            for table in tables.iter_mut() {
+                variables_in_table(table, migrations);
+            }
+        }
+        Item::Table(table) => variables_in_table(table, migrations),
+        Item::Value(Value::InlineTable(table)) => variables_in_table(table, migrations),
+        Item::Value(Value::Array(array)) => {
+            for value in array.iter_mut() {
+                if let Some(table) = value.as_inline_table_mut() {
+                    variables_in_table(table, migrations);
+                }
+            }
+        }
+        Item::Value(_) | Item::None => {}
+    }
+}
+
+fn variables_in_table(table: &mut dyn TableLike, migrations: &mut Vec<Migration>) {
+    for (key, item) in table.iter_mut() {
+        if key.get() == "variables" {
+            if let Some(variables) = item.as_table_like_mut() {
+                rename_variables(variables, migrations);
+            }
+        } else {
+            variable_spellings(item, migrations);
+        }
+    }
+}
+
+fn rename_variables(variables: &mut dyn TableLike, migrations: &mut Vec<Migration>) {
+    for (_, item) in variables.iter_mut() {
+        if let Some(value) = item.as_value_mut() {
+            if let Some((from, to)) = value
+                .as_str()
+                .and_then(|from| Some((from.to_string(), current_spelling(from)?)))
+            {
+                let decor = value.decor().clone();
+                *value = Value::from(to);
+                *value.decor_mut() = decor;
+                migrations.push(Migration::VariableSpelling { from, to });
+                continue;
+            }
+        }
+        // Variables with a value, like `{ output = "name" }`
+        let Some(variable) = item.as_table_like_mut() else {
+            continue;
+        };
+        let renames = variable
+            .iter()
+            .filter_map(|(from, _)| Some((from.to_string(), current_spelling(from)?)))
+            .collect::<Vec<_>>();
+        for (from, to) in renames {
+            if let Some(value) = variable.remove(&from) {
+                variable.insert(to, value);
+            }
+            migrations.push(Migration::VariableSpelling { from, to });
+        }
+    }
+}
+
+/// The current spelling of a variable which used an old `snake_case` spelling.
+fn current_spelling(variable: &str) -> Option<&'static str> {
+    match variable {
+        "version" => Some("Version"),
+        "issue_branch" => Some("IssueBranch"),
+        "changelog_entry" => Some("ChangelogEntry"),
+        "output" => Some("Output"),
+        "package_name" => Some("PackageName"),
+        "date" => Some("Date"),
+        "formatted_date" => Some("FormattedDate"),
+        "git_sha" => Some("GitSha"),
+        "branch" => Some("Branch"),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not parse the config file to upgrade it: {0}")]
+    #[diagnostic(code(config::upgrade::toml), help("Check the TOML is valid."))]
+    Toml(String),
+    #[error("Can't upgrade {0}, only TOML config files can be upgraded")]
+    #[diagnostic(
+        code(config::upgrade::unsupported_format),
+        help("Convert the config file to TOML, or update the deprecated syntax by hand."),
+        url("https://knope.tech/reference/command-line-arguments/#--upgrade")
+    )]
+    UnsupportedFormat(String),
+    #[error("Every package in `[[packages]]` needs a `name` when there's more than one")]
+    #[diagnostic(
+        code(config::upgrade::unnamed_packages),
+        help("Give each package a name, or define them as `[packages.<name>]` instead."),
+        url("https://knope.tech/reference/config-file/packages/")
+    )]
+    UnnamedPackages,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn single_package() {
+        let (upgraded, migrations) = in_memory(String::from(
+            "# The only package\n[[packages]]\nversioned_files = [\"Cargo.toml\"]\n",
+        ))
+        .unwrap();
+        assert_eq!(migrations, vec![Migration::PackagesArrayToPackage]);
+        assert_eq!(
+            upgraded,
+            "# The only package\n[package]\nversioned_files = [\"Cargo.toml\"]\n"
+        );
+    }
+
+    #[test]
+    fn named_packages() {
+        let (upgraded, migrations) = in_memory(String::from(
+            "[[packages]]\nname = \"first\"\nversioned_files = [\"first/Cargo.toml\"]\n\n[[packages]]\nname = \"second\"\nversioned_files = [\"second/Cargo.toml\"]\n",
+        ))
+        .unwrap();
+        assert_eq!(migrations, vec![Migration::PackagesArrayToTable]);
+        assert_eq!(
+            ::toml::from_str::<::toml::Value>(&upgraded).unwrap(),
+            ::toml::from_str::<::toml::Value>(
+                "[packages.first]\nversioned_files = [\"first/Cargo.toml\"]\n\n[packages.second]\nversioned_files = [\"second/Cargo.toml\"]\n"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn unnamed_packages() {
+        let result = in_memory(String::from(
+            "[[packages]]\nversioned_files = [\"a/Cargo.toml\"]\n\n[[packages]]\nversioned_files = [\"b/Cargo.toml\"]\n",
+        ));
+        assert!(matches!(result, Err(Error::UnnamedPackages)));
+    }
+
+    #[test]
+    fn variables() {
+        let (upgraded, migrations) = in_memory(String::from(
+            "[[workflows]]\nname = \"release\"\n\n[[workflows.steps]]\ntype = \"Command\"\ncommand = \"echo version\"\nvariables = { version = \"version\", output = { output = \"name\" } }\n",
+        ))
+        .unwrap();
+        assert_eq!(
+            migrations,
+            vec![
+                Migration::VariableSpelling {
+                    from: String::from("version"),
+                    to: "Version"
+                },
+                Migration::VariableSpelling {
+                    from: String::from("output"),
+                    to: "Output"
+                },
+            ]
+        );
+        assert_eq!(
+            ::toml::from_str::<::toml::Value>(&upgraded).unwrap(),
+            ::toml::from_str::<::toml::Value>(
+                "[[workflows]]\nname = \"release\"\n\n[[workflows.steps]]\ntype = \"Command\"\ncommand = \"echo version\"\nvariables = { version = \"Version\", output = { Output = \"name\" } }\n"
+            )
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Warnings for config which still works, but will stop working in a future release.
+//!
+//! Each deprecation has a stable code (like `deprecated::packages_array`) so that it can be
+//! looked up, and `--deny-deprecated` turns them all into errors.
+
+use std::path::Path;
+
+use miette::{Diagnostic, Report};
+use tracing::warn;
+
+use crate::config::Migration;
+
+/// Deprecations are logged with this target, so they're shown even during `--validate`.
+pub(crate) const TARGET: &str = "knope::deprecation";
+
+#[derive(Clone, Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Deprecation {
+    #[error("`[[packages]]` in {file} is deprecated")]
+    #[diagnostic(
+        severity(Warning),
+        code(deprecated::packages_array),
+        help("Run `knope --upgrade` to use `[package]` or `[packages.<name>]` instead."),
+        url("https://knope.tech/reference/config-file/packages/")
+    )]
+    PackagesArray { file: String },
+    #[error("The variable `{from}` in {file} is deprecated, it's now spelled `{to}`")]
+    #[diagnostic(
+        severity(Warning),
+        code(deprecated::variable_spelling),
+        help("Run `knope --upgrade` to use the new spelling."),
+        url("https://knope.tech/reference/config-file/variables/")
+    )]
+    VariableSpelling {
+        file: String,
+        from: String,
+        to: &'static str,
+    },
+}
+
+impl Deprecation {
+    /// The deprecated syntax that `migration` replaced in `file`.
+    pub(crate) fn from_migration(file: &Path, migration: Migration) -> Self {
+        let file = file.display().to_string();
+        match migration {
+            Migration::PackagesArrayToPackage | Migration::PackagesArrayToTable => {
+                Self::PackagesArray { file }
+            }
+            Migration::VariableSpelling { from, to } => Self::VariableSpelling { file, from, to },
+        }
+    }
+}
+
+/// Print a warning for each deprecation, or fail if `deny` (from `--deny-deprecated`).
+pub(crate) fn report(deprecations: Vec<Deprecation>, deny: bool) -> Result<(), Denied> {
+    if deny && !deprecations.is_empty() {
+        return Err(Denied { deprecations });
+    }
+    for deprecation in deprecations {
+        warn!(target: TARGET, "Warning: {:?}", Report::new(deprecation));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Deprecated config is not allowed with `--deny-deprecated`")]
+#[diagnostic(
+    code(deprecated::denied),
+    help("Run `knope --upgrade` to replace the deprecated config."),
+    url("https://knope.tech/reference/command-line-arguments/#--deny-deprecated")
+)]
+pub(crate) struct Denied {
+    #[related]
+    deprecations: Vec<Deprecation>,
+}
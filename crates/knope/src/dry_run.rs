@@ -0,0 +1,61 @@
+//! How a `--dry-run` shows what it would change.
+//!
+//! Changes to files are shown as unified diffs against what's currently on disk. With `--quiet`,
+//! only the summary of each action is shown, without any diffs or long bodies of text.
+
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use similar::TextDiff;
+
+/// Set once from `--quiet` before any workflow runs.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether details (like diffs and release notes) should be left out of dry-run output.
+pub(crate) fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// A unified diff (with three lines of context) of the file at `path` going from `old` to `new`.
+pub(crate) fn unified_diff(path: &Path, old: &str, new: &str) -> String {
+    let path = path.display().to_string();
+    let diff = TextDiff::from_lines(old, new);
+    let mut unified = diff.unified_diff();
+    unified.context_radius(3).header(&path, &path);
+    unified.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn diff_has_context() {
+        let diff = unified_diff(
+            Path::new("Cargo.toml"),
+            "[package]\nname = \"knope\"\nversion = \"1.0.0\"\nedition = \"2021\"\n",
+            "[package]\nname = \"knope\"\nversion = \"1.1.0\"\nedition = \"2021\"\n",
+        );
+        assert_eq!(
+            diff,
+            "--- Cargo.toml\n+++ Cargo.toml\n@@ -1,4 +1,4 @@\n [package]\n name = \"knope\"\n-version = \"1.0.0\"\n+version = \"1.1.0\"\n edition = \"2021\"\n"
+        );
+    }
+
+    #[test]
+    fn new_file() {
+        let diff = unified_diff(Path::new("CHANGELOG.md"), "", "# Changelog\n");
+        assert_eq!(
+            diff,
+            "--- CHANGELOG.md\n+++ CHANGELOG.md\n@@ -0,0 +1 @@\n+# Changelog\n"
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt::Write;
+
+use clap::{Arg, ArgMatches, Command};
+use itertools::Itertools;
+use knope_versioning::package::Name;
+use miette::{miette, Result};
+use tracing::info;
+
+use crate::{
+    config::{Config, Package},
+    step::Step,
+    variables::Variable,
+    workflow::{Workflow, WorkflowStep},
+};
+
+/// The name of the built-in subcommand, which is skipped if a workflow has the same name.
+pub(crate) const NAME: &str = "explain";
+/// The argument containing the name of the workflow to explain
+const WORKFLOW: &str = "workflow";
+
+pub(crate) fn subcommand(workflows: &[Workflow]) -> Command {
+    Command::new(NAME)
+        .about("Describe what a workflow does without running it")
+        .arg(
+            Arg::new(WORKFLOW).required(true).value_parser(
+                workflows
+                    .iter()
+                    .map(|workflow| workflow.name.clone())
+                    .collect_vec(),
+            ),
+        )
+}
+
+/// Print each step of the selected workflow with its options, the packages it affects, and the
+/// variables it replaces—without running anything.
+pub(crate) fn run(config: &Config, matches: Option<&ArgMatches>) -> Result<()> {
+    let name = matches
+        .and_then(|matches| matches.get_one::<String>(WORKFLOW))
+        .ok_or_else(|| miette!("No workflow selected to explain"))?;
+    let workflow = config
+        .workflows
+        .iter()
+        .find(|workflow| workflow.name == *name)
+        .ok_or_else(|| miette!("No workflow named {}", name))?;
+
+    let mut report = format!("Workflow {}", workflow.name);
+    if let Some(help_text) = &workflow.help_text {
+        write!(report, ": {help_text}").ok();
+    }
+    let packages = config.packages.iter().map(describe_package).join(", ");
+    let scope = workflow.packages.as_deref();
+    describe_steps(&mut report, &workflow.steps, &packages, scope);
+    if !workflow.on_error.is_empty() {
+        report.push_str("\nIf any step fails:");
+        describe_steps(&mut report, &workflow.on_error, &packages, scope);
+    }
+    info!("{report}");
+    Ok(())
+}
+
+/// `scope` is the `packages` of the workflow, which steps without their own `packages` use.
+fn describe_steps(
+    report: &mut String,
+    steps: &[WorkflowStep],
+    packages: &str,
+    scope: Option<&[String]>,
+) {
+    for (
+        index,
+        WorkflowStep {
+            condition,
+            packages: step_scope,
+            step,
+        },
+    ) in steps.iter().enumerate()
+    {
+        let (name, options) = step_options(step);
+        write!(report, "\n{number}. {name}", number = index + 1).ok();
+        if let Some(condition) = condition {
+            write!(report, "\n   Only if {condition}").ok();
+        }
+        for (key, value) in options {
+            write!(report, "\n   {key} = {value}").ok();
+        }
+        if let Some(scope) = step_scope.as_deref().or(scope) {
+            write!(report, "\n   Packages: {}", scope.join(", ")).ok();
+        } else if step.affects_packages() && !packages.is_empty() {
+            write!(report, "\n   Packages: {packages}").ok();
+        }
+        for (placeholder, variable) in step_variables(step) {
+            write!(report, "\n   Replaces {placeholder} with {variable:?}").ok();
+        }
+    }
+}
+
+fn describe_package(package: &Package) -> String {
+    match &package.name {
+        Name::Custom(name) => name.clone(),
+        Name::Default => package
+            .versioned_files
+            .iter()
+            .map(|versioned_file| versioned_file.as_path().to_string())
+            .join(" + "),
+    }
+}
+
+/// The type of the step and every option set for it, as they'd appear in `knope.toml`
+fn step_options(step: &Step) -> (String, Vec<(String, toml::Value)>) {
+    let Ok(toml::Value::Table(mut table)) = toml::Value::try_from(step) else {
+        return (String::from("Unknown step"), Vec::new());
+    };
+    let name = match table.remove("type") {
+        Some(toml::Value::String(name)) => name,
+        _ => String::from("Unknown step"),
+    };
+    (name, table.into_iter().collect())
+}
+
+fn step_variables(step: &Step) -> Vec<(&String, &Variable)> {
+    match step {
+        Step::Command {
+            variables: Some(variables),
+            ..
+        }
+        | Step::Commit {
+            variables: Some(variables),
+            ..
+        }
+        | Step::CreateBranch {
+            variables: Some(variables),
+            ..
+        } => variables.iter().collect(),
+        Step::CreatePullRequest { title, body, .. } => {
+            title.variables.iter().chain(body.variables()).collect()
+        }
+        Step::CreateJiraIssue {
+            summary,
+            description,
+            ..
+        } => summary
+            .variables
+            .iter()
+            .chain(
+                description
+                    .iter()
+                    .flat_map(|description| &description.variables),
+            )
+            .collect(),
+        Step::CommentOnJiraIssue { body } => body.variables.iter().collect(),
+        Step::BumpHomebrewFormula(options) => {
+            options.url.iter().flat_map(|url| &url.variables).collect()
+        }
+        _ => Vec::new(),
+    }
+}
@@ -10,16 +10,40 @@ use miette::Diagnostic;
 use thiserror::Error;
 use tracing::{info, trace};
 
-use crate::state::RunType;
+use crate::{dry_run, plan, plan::Plan, state::RunType};
 
-/// Writes to a file if this is not a dry run, or prints just the diff to stdout if it is.
-pub(crate) fn write<C: AsRef<[u8]> + Display, Diff: Display>(
-    to_write: WriteType<C, Diff>,
+/// Writes to a file if this is not a dry run, or prints a diff of what would change if it is (and
+/// records it in `plan`).
+pub(crate) fn write<C: AsRef<[u8]> + Display, DryRun: Display>(
+    to_write: WriteType<C, DryRun>,
     path: &Path,
+    plan: &mut Option<Plan>,
 ) -> Result<(), Error> {
     match to_write {
-        WriteType::DryRun(diff) => {
-            info!("Would add the following to {}: {diff}", path.display());
+        WriteType::DryRun(contents) => {
+            let old = std::fs::read_to_string(path).ok();
+            let verb = if old.is_some() { "change" } else { "create" };
+            let content = contents.to_string();
+            let diff = dry_run::unified_diff(path, old.as_deref().unwrap_or_default(), &content);
+            if dry_run::is_quiet() || diff.is_empty() {
+                info!("Would {verb} {}", path.display());
+            } else {
+                info!(
+                    "Would {verb} {}:\n{}",
+                    path.display(),
+                    diff.strip_suffix('\n').unwrap_or(&diff)
+                );
+            }
+            plan::record(plan, |plan| {
+                plan.files.push(plan::File {
+                    path: path.display().to_string(),
+                    diff,
+                });
+                plan.actions.push(plan::Action::WriteFile {
+                    path: path.display().to_string(),
+                    content,
+                });
+            });
             Ok(())
         }
         WriteType::Real(contents) => {
@@ -60,10 +84,16 @@ pub(crate) fn read_to_string<P: AsRef<Path> + Into<PathBuf>>(path: P) -> Result<
     })
 }
 
-pub(crate) fn remove_file(path: RunType<&Path>) -> Result<(), Error> {
+pub(crate) fn remove_file(path: RunType<&Path>, plan: &mut Option<Plan>) -> Result<(), Error> {
     match path {
         RunType::DryRun(path) => {
             info!("Would delete {}", path.display());
+            plan::record(plan, |plan| {
+                plan.deleted_files.push(path.display().to_string());
+                plan.actions.push(plan::Action::DeleteFile {
+                    path: path.display().to_string(),
+                });
+            });
             Ok(())
         }
         RunType::Real(path) => {
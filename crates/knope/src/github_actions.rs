@@ -0,0 +1,209 @@
+//! Shares the results of a workflow with the rest of a GitHub Actions job, through
+//! `$GITHUB_OUTPUT` and `$GITHUB_STEP_SUMMARY`.
+
+use std::{env, fs::OpenOptions, io::Write, path::PathBuf, sync::Mutex};
+
+use itertools::Itertools;
+use knope_versioning::{package::Name, ReleaseTag};
+use miette::Diagnostic;
+use serde::Serialize;
+
+use crate::state::{RunType, State};
+
+/// The URLs of releases created on forges, by tag. Only `Some` when running in GitHub Actions.
+static URLS: Mutex<Option<Vec<(String, String)>>> = Mutex::new(None);
+
+/// Start collecting release URLs if this is running in GitHub Actions.
+pub(crate) fn start() {
+    if env::var("GITHUB_ACTIONS").is_ok_and(|value| value == "true") {
+        if let Ok(mut urls) = URLS.lock() {
+            *urls = Some(Vec::new());
+        }
+    }
+}
+
+/// Remember the URL of the release for `tag`, if collecting.
+pub(crate) fn record_url(tag: &str, url: &str) {
+    if let Ok(mut urls) = URLS.lock() {
+        if let Some(urls) = urls.as_mut() {
+            urls.push((tag.to_string(), url.to_string()));
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Release {
+    /// `None` for the default package
+    package: Option<String>,
+    version: String,
+    tag: String,
+    url: Option<String>,
+}
+
+/// Write the releases in `state` to `$GITHUB_OUTPUT` and `$GITHUB_STEP_SUMMARY`, if collecting.
+pub(crate) fn finish(state: &RunType<State>) -> Result<(), Error> {
+    let Some(urls) = URLS.lock().ok().and_then(|mut urls| urls.take()) else {
+        return Ok(());
+    };
+    let releases = state
+        .inner()
+        .releases()
+        .into_iter()
+        .map(|release| {
+            let tag = ReleaseTag::new(&release.version, &release.package_name);
+            let url = urls
+                .iter()
+                .find(|(url_tag, _)| url_tag == tag.as_str())
+                .map(|(_, url)| url.clone());
+            Release {
+                package: match &release.package_name {
+                    Name::Custom(name) => Some(name.clone()),
+                    Name::Default => None,
+                },
+                version: release.version.to_string(),
+                tag: tag.as_str().to_string(),
+                url,
+            }
+        })
+        .collect_vec();
+    if let Some(path) = env::var_os("GITHUB_OUTPUT") {
+        append(path.into(), &outputs(&releases))?;
+    }
+    if let Some(path) = env::var_os("GITHUB_STEP_SUMMARY") {
+        if !releases.is_empty() {
+            let heading = match state {
+                RunType::DryRun(_) => "Knope would release",
+                RunType::Real(state) if state.released.is_empty() => "Knope prepared",
+                RunType::Real(_) => "Knope released",
+            };
+            append(path.into(), &summary(&releases, heading))?;
+        }
+    }
+    Ok(())
+}
+
+/// `name=value` lines for `$GITHUB_OUTPUT`. Outputs for a named package are prefixed with its
+/// name, like `knope-version`.
+fn outputs(releases: &[Release]) -> String {
+    let mut outputs = format!("released={}\n", !releases.is_empty());
+    for release in releases {
+        let prefix = release
+            .package
+            .as_ref()
+            .map_or_else(String::new, |package| format!("{package}-"));
+        outputs.push_str(&format!("{prefix}version={}\n", release.version));
+        outputs.push_str(&format!("{prefix}tag={}\n", release.tag));
+        if let Some(url) = &release.url {
+            outputs.push_str(&format!("{prefix}url={url}\n"));
+        }
+    }
+    if let Ok(json) = serde_json::to_string(releases) {
+        outputs.push_str(&format!("releases={json}\n"));
+    }
+    outputs
+}
+
+/// A Markdown table of `releases` under `heading`.
+fn summary(releases: &[Release], heading: &str) -> String {
+    let packages = releases.iter().any(|release| release.package.is_some());
+    let mut summary = format!("### {heading}\n\n");
+    if packages {
+        summary.push_str("| Package | Version | Tag |\n| --- | --- | --- |\n");
+    } else {
+        summary.push_str("| Version | Tag |\n| --- | --- |\n");
+    }
+    for release in releases {
+        let tag = release.url.as_ref().map_or_else(
+            || format!("`{}`", release.tag),
+            |url| format!("[`{}`]({url})", release.tag),
+        );
+        if packages {
+            summary.push_str(&format!(
+                "| {} | {} | {tag} |\n",
+                release.package.as_deref().unwrap_or_default(),
+                release.version
+            ));
+        } else {
+            summary.push_str(&format!("| {} | {tag} |\n", release.version));
+        }
+    }
+    summary.push('\n');
+    summary
+}
+
+fn append(path: PathBuf, contents: &str) -> Result<(), Error> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|source| Error { path, source })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("Could not write GitHub Actions output to {path}: {source}")]
+#[diagnostic(
+    code(github_actions::write),
+    help("Knope writes to the files in $GITHUB_OUTPUT and $GITHUB_STEP_SUMMARY when $GITHUB_ACTIONS is `true`."),
+    url("https://knope.tech/reference/environment-variables/#github_actions")
+)]
+pub(crate) struct Error {
+    path: PathBuf,
+    source: std::io::Error,
+}
+
+#[cfg(test)]
+mod test_summary {
+    use pretty_assertions::assert_eq;
+
+    use super::{summary, Release};
+
+    #[test]
+    fn single_package() {
+        let releases = [Release {
+            package: None,
+            version: "1.2.3".to_string(),
+            tag: "v1.2.3".to_string(),
+            url: Some("https://github.com/knope-dev/knope/releases/tag/v1.2.3".to_string()),
+        }];
+        assert_eq!(
+            summary(&releases, "Knope released"),
+            "### Knope released
+
+| Version | Tag |
+| --- | --- |
+| 1.2.3 | [`v1.2.3`](https://github.com/knope-dev/knope/releases/tag/v1.2.3) |
+
+"
+        );
+    }
+
+    #[test]
+    fn multiple_packages() {
+        let releases = [
+            Release {
+                package: Some("first".to_string()),
+                version: "1.2.3".to_string(),
+                tag: "first/v1.2.3".to_string(),
+                url: None,
+            },
+            Release {
+                package: Some("second".to_string()),
+                version: "0.1.0".to_string(),
+                tag: "second/v0.1.0".to_string(),
+                url: None,
+            },
+        ];
+        assert_eq!(
+            summary(&releases, "Knope would release"),
+            "### Knope would release
+
+| Package | Version | Tag |
+| --- | --- | --- |
+| first | 1.2.3 | `first/v1.2.3` |
+| second | 0.1.0 | `second/v0.1.0` |
+
+"
+        );
+    }
+}
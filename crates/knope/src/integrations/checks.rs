@@ -0,0 +1,158 @@
+//! Waiting for the CI checks that a forge reports for a commit to finish.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use miette::Diagnostic;
+use tracing::info;
+
+/// How long to wait between asking the forge for the status of checks.
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long to keep waiting when no checks are reported at all, since CI may not have started yet
+/// for a commit that was just pushed.
+const NO_CHECKS_GRACE: Duration = Duration::from_secs(60);
+
+/// The result of a single check (or commit status) on a forge.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Check {
+    Pending,
+    Passed,
+    Failed,
+}
+
+/// The combined result of every check on a commit.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Status {
+    /// The forge doesn't know about any checks for the commit
+    None,
+    Pending,
+    Success,
+    /// The names of the checks that failed
+    Failure(Vec<String>),
+}
+
+impl Status {
+    /// Combine named checks, any failure fails everything, otherwise any pending check is still
+    /// pending.
+    pub(crate) fn from_checks(checks: impl IntoIterator<Item = (String, Check)>) -> Self {
+        let mut status = Self::None;
+        let mut failed = Vec::new();
+        for (name, check) in checks {
+            match check {
+                Check::Failed => failed.push(name),
+                Check::Pending => status = Self::Pending,
+                Check::Passed if status == Self::None => status = Self::Success,
+                Check::Passed => {}
+            }
+        }
+        if failed.is_empty() {
+            status
+        } else {
+            Self::Failure(failed)
+        }
+    }
+}
+
+/// Call `get_status` until checks finish or `timeout` passes, returning an error if any fail.
+pub(crate) fn wait<E: From<Error>>(
+    forge: &'static str,
+    timeout: Duration,
+    mut get_status: impl FnMut() -> Result<Status, E>,
+) -> Result<(), E> {
+    let start = Instant::now();
+    loop {
+        match get_status()? {
+            Status::Success => {
+                info!("All checks passed on {forge}");
+                return Ok(());
+            }
+            Status::None if start.elapsed() >= NO_CHECKS_GRACE => {
+                info!("No checks found on {forge}, continuing");
+                return Ok(());
+            }
+            Status::Failure(names) => {
+                return Err(Error::Failed {
+                    forge,
+                    names: names.join(", "),
+                }
+                .into())
+            }
+            Status::None | Status::Pending => {}
+        }
+        if start.elapsed() + POLL_INTERVAL > timeout {
+            return Err(Error::TimedOut {
+                forge,
+                seconds: timeout.as_secs(),
+            }
+            .into());
+        }
+        info!(
+            "Waiting for checks on {forge}, checking again in {seconds} seconds",
+            seconds = POLL_INTERVAL.as_secs()
+        );
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Checks failed on {forge}: {names}")]
+    #[diagnostic(
+        code(checks::failed),
+        help("Fix the failing checks and run this workflow again."),
+        url("https://knope.tech/reference/config-file/steps/wait-for-checks/")
+    )]
+    Failed { forge: &'static str, names: String },
+    #[error("Checks on {forge} didn't finish within {seconds} seconds")]
+    #[diagnostic(
+        code(checks::timed_out),
+        help("Increase the `timeout` of the WaitForChecks step, or check that CI is running."),
+        url("https://knope.tech/reference/config-file/steps/wait-for-checks/")
+    )]
+    TimedOut { forge: &'static str, seconds: u64 },
+}
+
+#[cfg(test)]
+mod test_status_from_checks {
+    use super::*;
+
+    fn named(checks: &[(&str, Check)]) -> Vec<(String, Check)> {
+        checks
+            .iter()
+            .map(|(name, check)| ((*name).to_string(), *check))
+            .collect()
+    }
+
+    #[test]
+    fn no_checks() {
+        assert_eq!(Status::from_checks(Vec::new()), Status::None);
+    }
+
+    #[test]
+    fn all_passed() {
+        let checks = named(&[("build", Check::Passed), ("test", Check::Passed)]);
+        assert_eq!(Status::from_checks(checks), Status::Success);
+    }
+
+    #[test]
+    fn pending_wins_over_passed() {
+        let checks = named(&[("build", Check::Passed), ("test", Check::Pending)]);
+        assert_eq!(Status::from_checks(checks), Status::Pending);
+    }
+
+    #[test]
+    fn failures_win_over_everything() {
+        let checks = named(&[
+            ("build", Check::Failed),
+            ("test", Check::Pending),
+            ("lint", Check::Failed),
+        ]);
+        assert_eq!(
+            Status::from_checks(checks),
+            Status::Failure(vec!["build".to_string(), "lint".to_string()])
+        );
+    }
+}
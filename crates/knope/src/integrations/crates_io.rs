@@ -0,0 +1,101 @@
+//! Checking which versions of a crate are available on crates.io.
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use knope_versioning::semver::Version;
+use miette::Diagnostic;
+use serde::Deserialize;
+use tracing::info;
+
+use super::retry;
+
+/// How long to wait for a published version to be available before giving up.
+const TIMEOUT: Duration = Duration::from_secs(300);
+/// The longest to wait between checks of the index.
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Wait until `version` of the crate `name` is in the crates.io index, so that crates depending on
+/// it can be published.
+pub(crate) fn wait_for_version(name: &str, version: &Version) -> Result<(), Error> {
+    let url = format!("https://index.crates.io/{}", index_path(name));
+    let version = version.to_string();
+    let start = Instant::now();
+    let mut attempt = 0;
+    while !is_published(&url, &version) {
+        if start.elapsed() > TIMEOUT {
+            return Err(Error::Timeout {
+                name: name.to_string(),
+                version,
+            });
+        }
+        info!("Waiting for {name} {version} to be available on crates.io");
+        thread::sleep(retry::backoff(attempt).min(MAX_DELAY));
+        attempt += 1;
+    }
+    Ok(())
+}
+
+/// Whether the index file at `url` has `version`. Failed requests count as not published yet,
+/// since the file doesn't exist until the first version is.
+fn is_published(url: &str, version: &str) -> bool {
+    ureq::get(url)
+        .call()
+        .ok()
+        .and_then(|response| response.into_string().ok())
+        .is_some_and(|index| {
+            index.lines().any(|line| {
+                serde_json::from_str::<IndexEntry>(line).is_ok_and(|entry| entry.vers == version)
+            })
+        })
+}
+
+/// One line of a crate's file in the index, describing a single version.
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: String,
+}
+
+/// The path to the index file for the crate `name`, which depends on the length of the name.
+fn index_path(name: &str) -> String {
+    let name = name.to_lowercase();
+    let prefix = |range: std::ops::Range<usize>| name.get(range).unwrap_or_default();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", prefix(0..1)),
+        _ => format!("{}/{}/{name}", prefix(0..2), prefix(2..4)),
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("{name} {version} still isn't available on crates.io")]
+    #[diagnostic(
+        code(crates_io::timeout),
+        help(
+            "Crates which depend on it can't be published until it is. Check https://status.crates.io, \
+            then run this step again to publish the rest."
+        )
+    )]
+    Timeout { name: String, version: String },
+}
+
+#[cfg(test)]
+mod test_index_path {
+    use super::index_path;
+
+    #[test]
+    fn short_names() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("ab"), "2/ab");
+        assert_eq!(index_path("abc"), "3/a/abc");
+    }
+
+    #[test]
+    fn long_names_are_lowercase() {
+        assert_eq!(index_path("Knope-Versioning"), "kn/op/knope-versioning");
+    }
+}
@@ -1,17 +1,24 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{HashMap, VecDeque},
     env::current_dir,
+    process::{Command, ExitStatus},
     str::FromStr,
+    time::Instant,
 };
 
-use git2::{build::CheckoutBuilder, Branch, BranchType, IndexAddOption, Repository};
+use git2::{build::CheckoutBuilder, Branch, BranchType, IndexAddOption, ObjectType, Repository};
 use gix::{object::Kind, refs::transaction::PreviousValue, ObjectId};
 use itertools::Itertools;
+use knope_versioning::changes::{Author, Commit};
 use miette::Diagnostic;
 use relative_path::RelativePathBuf;
 use tracing::{debug, info};
 
-use crate::{fs, prompt, prompt::select, state, state::State, step::issues::Issue, RunType};
+use super::signing;
+use crate::{
+    config::Signing, dry_run, fs, plan, plan::Plan, prompt, prompt::select, state, state::State,
+    step::issues::Issue, RunType,
+};
 
 /// Based on the selected issue, either checks out an existing branch matching the name or creates
 /// a new one, prompting for which branch to base it on.
@@ -46,6 +53,32 @@ pub(crate) fn switch_branches(state: RunType<State>) -> Result<RunType<State>, E
     Ok(RunType::Real(state))
 }
 
+/// Create a branch named `name` at the current commit and switch to it, like `git switch -c`.
+///
+/// The working tree doesn't change, so any uncommitted changes (like those from `PrepareRelease`)
+/// carry over to the new branch.
+pub(crate) fn create_branch_at_head(name: RunType<&str>) -> Result<(), Error> {
+    let name = match name {
+        RunType::DryRun(name) => {
+            info!("Would create and switch to a branch named {name}");
+            return Ok(());
+        }
+        RunType::Real(name) => name,
+    };
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    if repo.find_branch(name, BranchType::Local).is_ok() {
+        return Err(ErrorKind::BranchExists(name.to_string()).into());
+    }
+    let head = repo.head()?.peel_to_commit()?;
+    let branch = repo.branch(name, &head, false)?;
+    let ref_name = branch
+        .get()
+        .name()
+        .ok_or(Error::from(ErrorKind::BadGitBranchName))?;
+    repo.set_head(ref_name)?;
+    Ok(())
+}
+
 #[derive(Debug, Diagnostic, thiserror::Error)]
 #[error(transparent)]
 #[diagnostic(transparent)]
@@ -150,6 +183,42 @@ enum ErrorKind {
     PeelOid(#[from] gix::reference::peel::Error),
     #[error("Could not walk commits back from HEAD: {0}")]
     RevisionWalk(#[from] gix::revision::walk::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Signing(#[from] signing::Error),
+    #[error("Could not find the Git revision {0}")]
+    #[diagnostic(
+        code(git::revision_not_found),
+        help("Make sure the branch, tag, or commit exists locally (for example, by fetching it).")
+    )]
+    RevisionNotFound(String),
+    #[error("A branch named {0} already exists")]
+    #[diagnostic(
+        code(git::branch_exists),
+        help("Delete the existing branch, or include something unique (like the version) in the name."),
+        url("https://knope.tech/reference/config-file/steps/create-branch/")
+    )]
+    BranchExists(String),
+    #[error("Could not run git: {0}")]
+    #[diagnostic(
+        code(git::could_not_run),
+        help("Make sure Git is installed and on your PATH.")
+    )]
+    RunGit(#[source] std::io::Error),
+    #[error("Could not fetch the history of this shallow clone, git exited with {0}")]
+    #[diagnostic(
+        code(git::fetch_failed),
+        help("Make sure the remote is reachable, or fetch everything yourself with `git fetch --tags --unshallow`."),
+        url("https://knope.tech/reference/config-file/git/")
+    )]
+    Fetch(ExitStatus),
+    #[error("This is still a shallow clone after fetching")]
+    #[diagnostic(
+        code(git::still_shallow),
+        help("Make sure the repository has a remote with its full history, like the `remote` in the forge config."),
+        url("https://knope.tech/reference/config-file/git/")
+    )]
+    StillShallow,
 }
 
 /// Rebase the current branch onto the selected one.
@@ -205,14 +274,40 @@ pub(crate) fn current_branch() -> Result<String, Error> {
     Ok(ref_name.to_owned())
 }
 
-/// Get the first remote of the Git repo, if any.
-pub(crate) fn get_first_remote() -> Option<String> {
+/// The full hash of the commit that `HEAD` points to.
+pub(crate) fn head_commit_id() -> Result<String, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    let commit = repo.head()?.peel_to_commit()?;
+    Ok(commit.id().to_string())
+}
+
+/// The names of every remote of the Git repo, in the order Git lists them.
+pub(crate) fn get_remote_names() -> Vec<String> {
+    Repository::open(".")
+        .and_then(|repo| repo.remotes())
+        .map(|remotes| remotes.iter().flatten().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Get the URL of the remote called `name`, if there is one.
+pub(crate) fn get_remote_url(name: &str) -> Option<String> {
     let repo = Repository::open(".").ok()?;
-    let remotes = repo.remotes().ok()?;
-    let remote_name = remotes.get(0)?;
-    repo.find_remote(remote_name)
-        .ok()
-        .and_then(|remote| remote.url().map(String::from))
+    let remote = repo.find_remote(name).ok()?;
+    remote.url().map(String::from)
+}
+
+/// Delete a tag from the local repository.
+pub(crate) fn delete_tag(name: RunType<&str>) -> Result<(), Error> {
+    match name {
+        RunType::DryRun(name) => {
+            info!("Would delete Git tag {name}");
+            Ok(())
+        }
+        RunType::Real(name) => {
+            let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+            repo.tag_delete(name).map_err(Error::from)
+        }
+    }
 }
 
 fn select_issue_from_branch_name(ref_name: &str) -> Result<Issue, Error> {
@@ -379,61 +474,183 @@ pub(crate) fn add_files(file_names: &[RelativePathBuf]) -> Result<(), Error> {
     index.write().map_err(Error::from)
 }
 
+/// Stage `file_names` and commit everything staged to the current branch, signing the commit if
+/// `signing` is set.
+pub(crate) fn commit(
+    message: RunType<&str>,
+    file_names: &[RelativePathBuf],
+    signing: Option<&Signing>,
+    plan: &mut Option<Plan>,
+) -> Result<(), Error> {
+    let message = match message {
+        RunType::DryRun(message) => {
+            if signing.is_some() {
+                info!("Would create a signed commit with message \"{message}\"");
+            } else {
+                info!("Would commit with message \"{message}\"");
+            }
+            plan::record(plan, |plan| {
+                plan.commits.push(message.to_string());
+                plan.actions.push(plan::Action::Commit {
+                    message: message.to_string(),
+                    files: file_names.iter().map(ToString::to_string).collect(),
+                    signed: signing.is_some(),
+                });
+            });
+            return Ok(());
+        }
+        RunType::Real(message) => message,
+    };
+    add_files(file_names)?;
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    let signature = repo.signature().map_err(|_| ErrorKind::NoCommitter)?;
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    // There's no parent when committing to an empty repository
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents = parent.iter().collect_vec();
+    let Some(signing) = signing else {
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+        return Ok(());
+    };
+    let content = repo.commit_create_buffer(&signature, &signature, message, &tree, &parents)?;
+    let content = String::from_utf8_lossy(&content);
+    let gpg_signature = signing::sign(&content, signing, &repo.config()?, "git")?;
+    let id = repo.commit_signed(&content, &gpg_signature, None)?;
+    // `commit_signed` doesn't move any references, so update the current branch like `commit` does
+    let head = repo.find_reference("HEAD")?;
+    let branch = head.symbolic_target().unwrap_or("HEAD").to_string();
+    repo.reference(&branch, id, true, message)?;
+    Ok(())
+}
+
+/// The paths of every file changed by each of `commits`, compared to its first parent.
+pub(crate) fn get_changed_files(commits: &[Commit]) -> Result<Vec<Vec<RelativePathBuf>>, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    commits
+        .iter()
+        .map(|commit| {
+            let Some(hash) = &commit.hash else {
+                return Ok(Vec::new());
+            };
+            let commit = repo.find_commit(git2::Oid::from_str(hash)?)?;
+            let parent_tree = commit
+                .parents()
+                .next()
+                .map(|parent| parent.tree())
+                .transpose()?;
+            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+            Ok(diff
+                .deltas()
+                .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+                .filter_map(|path| RelativePathBuf::from_path(path).ok())
+                .collect())
+        })
+        .collect()
+}
+
 /// Find every commit that appears only _after_ a specific tag.
 ///
-/// This builds a complete set of every commit in the repository, because branching and merging
-/// means that there could be paths which jump _behind_ the target tag... and we want to exclude
-/// those as well. There's probably a way to optimize performance with some cool graph magic
-/// eventually, but this is good enough for now.
-pub(crate) fn get_commit_messages_after_tag(tag: &str) -> Result<Vec<String>, Error> {
-    let repo = gix::open(".")?;
-
-    let reference = repo.find_reference(&format!("refs/tags/{tag}")).ok();
-    if reference.is_some() {
+/// Branching and merging means that there could be paths which jump _behind_ the target tag, so
+/// every commit reachable from the tag is excluded, not only the tag itself.
+pub(crate) fn get_commits_after_tag(tag: &str) -> Result<Vec<Commit>, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+
+    let base = repo
+        .find_reference(&format!("refs/tags/{tag}"))
+        .and_then(|reference| reference.peel_to_commit())
+        .ok()
+        .map(|commit| commit.id());
+    if base.is_some() {
         debug!("Using commits since tag {tag}");
     } else {
         debug!("Tag {tag} not found, using ALL commits");
     }
-    let commits_to_exclude = reference
-        .map(gix::Reference::into_fully_peeled_id)
-        .transpose()?
-        .and_then(|tag_oid| repo.find_object(tag_oid).ok().map(gix::Object::into_commit))
-        .and_then(|commit| {
-            commit.ancestors().all().ok().map(|ancestors| {
-                ancestors
-                    .into_iter()
-                    .filter_map(Result::ok)
-                    .map(|info| info.id)
-                    .collect::<HashSet<ObjectId>>()
-            })
-        })
-        .unwrap_or_default();
-    let head_commit = repo.head_commit()?;
-    let mut reverse_commits = head_commit
-        .ancestors()
-        .all()?
+    get_commits_after(&repo, base)
+}
+
+/// Find every commit reachable from `HEAD` which isn't reachable from `revision` (like a branch
+/// name or tag).
+pub(crate) fn get_commits_after_revision(revision: &str) -> Result<Vec<Commit>, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    let base = repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| ErrorKind::RevisionNotFound(revision.to_string()))?
+        .id();
+    debug!("Using commits since {revision}");
+    get_commits_after(&repo, Some(base))
+}
+
+/// Every commit reachable from `HEAD` but not from `base`, oldest first.
+///
+/// The walk stops where the history of `HEAD` meets the history of `base` (their merge base),
+/// instead of visiting every commit in the repository.
+fn get_commits_after(repo: &Repository, base: Option<git2::Oid>) -> Result<Vec<Commit>, Error> {
+    let start = Instant::now();
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    walk.push_head()?;
+    if let Some(base) = base {
+        walk.hide(base)?;
+    }
+    let commits: Vec<Commit> = walk
         .filter_map(Result::ok)
-        .filter(|info| !commits_to_exclude.contains(&info.id))
-        .filter_map(|info| {
-            info.object().ok().and_then(|commit| {
-                commit
-                    .decode()
-                    .ok()
-                    .map(|commit| commit.message.to_string())
-            })
+        .filter_map(|id| repo.find_commit(id).ok())
+        .map(|commit| {
+            let author = commit.author();
+            Commit {
+                hash: Some(commit.id().to_string()),
+                author: Some(Author {
+                    name: String::from_utf8_lossy(author.name_bytes()).into_owned(),
+                    email: String::from_utf8_lossy(author.email_bytes()).into_owned(),
+                }),
+                message: String::from_utf8_lossy(commit.message_raw_bytes()).into_owned(),
+            }
         })
-        .collect_vec();
-    reverse_commits.reverse();
-    Ok(reverse_commits)
+        .collect();
+    debug!(
+        "Walked {count} commits in {elapsed:.2?}",
+        count = commits.len(),
+        elapsed = start.elapsed()
+    );
+    Ok(commits)
 }
 
-pub(crate) fn create_tag(name: RunType<&str>) -> Result<(), Error> {
-    match name {
-        RunType::DryRun(name) => {
-            info!("Would create Git tag {name}");
+/// Create a tag on `HEAD` with `message` (which may be empty), a signed annotated tag if `signing`
+/// is set.
+pub(crate) fn create_tag(
+    name: RunType<&str>,
+    message: &str,
+    signing: Option<&Signing>,
+    plan: &mut Option<Plan>,
+) -> Result<(), Error> {
+    match (name, signing) {
+        (RunType::DryRun(name), _) => {
+            let signed = if signing.is_some() { "signed " } else { "" };
+            if message.is_empty() || dry_run::is_quiet() {
+                info!("Would create {signed}Git tag {name}");
+            } else {
+                info!("Would create {signed}Git tag {name} with message:\n{message}");
+            }
+            plan::record(plan, |plan| {
+                plan.tags.push(name.to_string());
+                plan.actions.push(plan::Action::Tag {
+                    name: name.to_string(),
+                    message: message.to_string(),
+                    signed: signing.is_some(),
+                });
+            });
             Ok(())
         }
-        RunType::Real(name) => {
+        (RunType::Real(name), Some(signing)) => create_signed_tag(name, message, signing),
+        (RunType::Real(name), None) => {
             let repo = gix::open(current_dir().map_err(ErrorKind::CurrentDirectory)?)?;
             let head = repo.head_commit()?;
             repo.tag(
@@ -443,7 +660,7 @@ pub(crate) fn create_tag(name: RunType<&str>) -> Result<(), Error> {
                 repo.committer()
                     .transpose()
                     .map_err(|_| ErrorKind::NoCommitter)?,
-                "",
+                message,
                 PreviousValue::Any,
             )?;
             Ok(())
@@ -451,7 +668,73 @@ pub(crate) fn create_tag(name: RunType<&str>) -> Result<(), Error> {
     }
 }
 
-/// Get all tags on the current branch.
+/// Write the tag object by hand, since neither `gix` nor `git2` can sign one.
+fn create_signed_tag(name: &str, message: &str, signing: &Signing) -> Result<(), Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    let target = repo.head()?.peel_to_commit()?;
+    let tagger = repo.signature().map_err(|_| ErrorKind::NoCommitter)?;
+    let when = tagger.when();
+    let offset = when.offset_minutes();
+    // Signed tags need a message for the signature to follow
+    let message = if message.is_empty() { name } else { message };
+    let mut content = format!(
+        "object {id}\ntype commit\ntag {name}\ntagger {tagger_name} <{email}> {seconds} \
+         {sign}{hours:02}{minutes:02}\n\n{message}\n",
+        id = target.id(),
+        tagger_name = String::from_utf8_lossy(tagger.name_bytes()),
+        email = String::from_utf8_lossy(tagger.email_bytes()),
+        seconds = when.seconds(),
+        sign = if offset < 0 { '-' } else { '+' },
+        hours = offset.abs() / 60,
+        minutes = offset.abs() % 60,
+    );
+    // The signature of a tag goes at the end of its message
+    content.push_str(&signing::sign(&content, signing, &repo.config()?, "git")?);
+    let id = repo.odb()?.write(ObjectType::Tag, content.as_bytes())?;
+    repo.reference(&format!("refs/tags/{name}"), id, true, "")?;
+    Ok(())
+}
+
+/// Sign the contents of a file which isn't part of the repository, using its Git config for
+/// anything not set in `signing`.
+pub(crate) fn sign_file(contents: &str, signing: &Signing) -> Result<String, Error> {
+    let repo = Repository::open(".").map_err(ErrorKind::OpenRepo)?;
+    Ok(signing::sign(contents, signing, &repo.config()?, "file")?)
+}
+
+/// Whether the repository is a shallow clone, so some commits (and likely tags) are missing.
+pub(crate) fn is_shallow() -> bool {
+    gix::open(".").is_ok_and(|repo| repo.is_shallow())
+}
+
+/// If the repository is a shallow clone, fetch the rest of its history and all of its tags from
+/// `remote` (or the default remote of the current branch).
+///
+/// This happens in dry runs too, since it doesn't change any files and versions can't be
+/// determined without it.
+pub(crate) fn fetch_missing_history(remote: Option<&str>) -> Result<(), Error> {
+    if !is_shallow() {
+        return Ok(());
+    }
+    info!("Fetching all tags and history, since this is a shallow clone");
+    let status = Command::new("git")
+        .args(["fetch", "--tags", "--unshallow"])
+        .args(remote)
+        .status()
+        .map_err(ErrorKind::RunGit)?;
+    if !status.success() {
+        return Err(ErrorKind::Fetch(status).into());
+    }
+    if is_shallow() {
+        return Err(ErrorKind::StillShallow.into());
+    }
+    Ok(())
+}
+
+/// Get all version tags (like `v1.2.3` or `prefix/v1.2.3`) on the current branch.
+///
+/// Other tags are skipped by name, before resolving what they point to, since they can never be a
+/// package's version.
 pub(crate) fn all_tags_on_branch() -> Result<Vec<String>, Error> {
     let repo = gix::open(current_dir().map_err(ErrorKind::CurrentDirectory)?)?;
     let mut all_tags: HashMap<ObjectId, Vec<String>> = HashMap::new();
@@ -460,16 +743,18 @@ pub(crate) fn all_tags_on_branch() -> Result<Vec<String>, Error> {
         .tags()?
         .filter_map(Result::ok)
         .filter_map(|mut reference| {
-            reference.peel_to_id_in_place().ok().map(|id| {
-                (
-                    id.detach(),
-                    reference
-                        .name()
-                        .as_bstr()
-                        .to_string()
-                        .replace("refs/tags/", ""),
-                )
-            })
+            let tag = reference
+                .name()
+                .as_bstr()
+                .to_string()
+                .replace("refs/tags/", "");
+            if !is_version_tag(&tag) {
+                return None;
+            }
+            reference
+                .peel_to_id_in_place()
+                .ok()
+                .map(|id| (id.detach(), tag))
         })
     {
         all_tags.entry(id).or_default().push(tag);
@@ -494,3 +779,32 @@ pub(crate) fn all_tags_on_branch() -> Result<Vec<String>, Error> {
     }
     Ok(tags)
 }
+
+/// Whether a tag name could hold a version for some package, regardless of its prefix.
+fn is_version_tag(tag: &str) -> bool {
+    let name = tag.rsplit_once('/').map_or(tag, |(_, name)| name);
+    name.strip_prefix('v')
+        .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod test_is_version_tag {
+    use super::is_version_tag;
+
+    #[test]
+    fn version_tags() {
+        assert!(is_version_tag("v1.2.3"));
+        assert!(is_version_tag("v0.1.0-rc.0"));
+        assert!(is_version_tag("first/v1.2.3"));
+        assert!(is_version_tag("some/go/module/v2.0.0"));
+    }
+
+    #[test]
+    fn other_tags() {
+        assert!(!is_version_tag("latest"));
+        assert!(!is_version_tag("1.2.3"));
+        assert!(!is_version_tag("first/1.2.3"));
+        assert!(!is_version_tag("very-important"));
+        assert!(!is_version_tag("v/latest"));
+    }
+}
@@ -0,0 +1,78 @@
+use miette::Diagnostic;
+use tracing::info;
+
+use super::initialize_state;
+use crate::{
+    app_config, config,
+    integrations::{retry, ureq_err_to_string},
+    state,
+    state::RunType,
+};
+
+/// Leave `comment` on the issue numbered `number`, then close it.
+pub(crate) fn close_issue(
+    number: u64,
+    comment: &str,
+    gitea_state: RunType<state::Gitea>,
+    gitea_config: &config::Gitea,
+) -> Result<state::Gitea, Error> {
+    let gitea_state = match gitea_state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would comment \"{comment}\" on issue #{number} on Gitea [{host}] and close it",
+                host = gitea_config.host
+            );
+            return Ok(state);
+        }
+        RunType::Real(gitea_state) => gitea_state,
+    };
+
+    let (token, agent) = initialize_state(&gitea_config.host, gitea_state)?;
+    let issue_url = format!(
+        "{issues_url}/{number}",
+        issues_url = gitea_config.get_issues_url()
+    );
+
+    retry::send_non_idempotent(gitea_config.retries(), || {
+        agent
+            .post(&format!("{issue_url}/comments"))
+            .query("access_token", &token)
+            .send_json(ureq::json!({ "body": comment }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "commenting on an issue",
+        host: gitea_config.host.clone(),
+    })?;
+    retry::send(gitea_config.retries(), || {
+        agent
+            .patch(&issue_url)
+            .query("access_token", &token)
+            .send_json(ureq::json!({ "state": "closed" }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "closing an issue",
+        host: gitea_config.host.clone(),
+    })?;
+    Ok(state::Gitea::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with the Gitea instance while {activity}: {err}")]
+    #[diagnostic(
+        code(gitea::api_request_error),
+        help(
+            "There was a problem communicating with the Gitea instance {host}, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest {
+        err: String,
+        activity: &'static str,
+        host: String,
+    },
+}
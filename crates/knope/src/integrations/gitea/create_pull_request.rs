@@ -5,16 +5,18 @@ use ureq::Agent;
 
 use super::initialize_state;
 use crate::{
-    app_config, config,
-    integrations::{git, ureq_err_to_string, PullRequest},
+    app_config, config, dry_run,
+    integrations::{git, retry, ureq_err_to_string, PullRequest},
     state,
     state::RunType,
+    step::create_pull_request::Metadata,
 };
 
 pub(crate) fn create_or_update_pull_request(
     title: &str,
     body: &str,
     base: &str,
+    metadata: &Metadata,
     state: RunType<state::Gitea>,
     config: &config::Gitea,
 ) -> Result<state::Gitea, Error> {
@@ -24,44 +26,97 @@ pub(crate) fn create_or_update_pull_request(
         RunType::DryRun(state) => {
             info!("Would create or update a pull request from {current_branch} to {base}:");
             info!("\tTitle: {title}");
-            info!("\tBody: {body}");
+            if !dry_run::is_quiet() {
+                info!("\tBody: {body}");
+            }
+            metadata.log_dry_run();
             return Ok(state);
         }
         RunType::Real(state) => state,
     };
     let (token, agent) = initialize_state(&config.host, state)?;
 
-    let existing_pulls: Vec<PullRequest> = agent
-        .get(&config.get_pulls_url())
-        .set("Accept", "application/json")
-        .query("state", "open")
-        .query(
-            "head",
-            &format!("{owner}:{current_branch}", owner = config.owner),
-        )
-        .query("base", base)
-        .query("access_token", &token)
-        .call()
-        .map_err(|err| Error::ApiRequest {
-            err: ureq_err_to_string(err),
-            activity: "fetching existing pull requests".to_string(),
-            host: config.host.clone(),
-        })?
-        .into_json()
-        .map_err(|source| Error::ApiResponse {
-            source,
-            activity: "fetching existing pull requests",
-            host: config.host.clone(),
-        })?;
+    let existing_pulls: Vec<PullRequest> = retry::send(config.retries(), || {
+        agent
+            .get(&config.get_pulls_url())
+            .set("Accept", "application/json")
+            .query("state", "open")
+            .query(
+                "head",
+                &format!("{owner}:{current_branch}", owner = config.owner),
+            )
+            .query("base", base)
+            .query("access_token", &token)
+            .call()
+    })
+    .map_err(|err| Error::ApiRequest {
+        err: ureq_err_to_string(*err),
+        activity: "fetching existing pull requests".to_string(),
+        host: config.host.clone(),
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "fetching existing pull requests",
+        host: config.host.clone(),
+    })?;
 
     // Update the existing PR
-    if let Some(pr) = existing_pulls.first() {
+    let number = if let Some(pr) = existing_pulls.first() {
         debug!("Updating existing pull request: {}", pr.url);
-        update_pull_request(&agent, config, &token, pr.number, title, body)?;
+        update_pull_request(&agent, config, &token, pr.number, title, body, metadata)?;
+        pr.number
     // Create a new PR
     } else {
         debug!("No matching existing pull request found, creating a new one.");
-        create_pull_request(&agent, config, &token, base, current_branch, title, body)?;
+        create_pull_request(
+            &agent,
+            config,
+            &token,
+            base,
+            current_branch,
+            title,
+            body,
+            metadata,
+        )?
+    };
+
+    let requests = [
+        (
+            &metadata.labels,
+            format!(
+                "{issues_url}/{number}/labels",
+                issues_url = config.get_issues_url()
+            ),
+            "labels",
+            "adding labels",
+        ),
+        (
+            &metadata.reviewers,
+            format!(
+                "{pull_url}/requested_reviewers",
+                pull_url = config.get_pull_url(number)
+            ),
+            "reviewers",
+            "requesting reviewers",
+        ),
+    ];
+    for (values, url, key, activity) in requests {
+        if values.is_empty() {
+            continue;
+        }
+        retry::send(config.retries(), || {
+            agent
+                .post(&url)
+                .set("Accept", "application/json")
+                .query("access_token", &token)
+                .send_json(json!({ key: values }))
+        })
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity: activity.to_string(),
+            host: config.host.clone(),
+        })?;
     }
 
     Ok(state::Gitea::Initialized { token, agent })
@@ -74,23 +129,31 @@ fn update_pull_request(
     number: u32,
     title: &str,
     body: &str,
+    metadata: &Metadata,
 ) -> Result<(), Error> {
-    agent
-        .patch(&config.get_pull_url(number))
-        .set("Accept", "application/json")
-        .query("access_token", token)
-        .send_json(json!({
-            "body": body,
-            "title": title
-        }))
-        .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
-            activity: "updating pull request".to_string(),
-            host: config.host.clone(),
-        })?;
+    let mut fields = serde_json::Map::new();
+    fields.insert("body".to_string(), json!(body));
+    fields.insert("title".to_string(), json!(title));
+    // Sending no assignees would remove any that were added by hand
+    if !metadata.assignees.is_empty() {
+        fields.insert("assignees".to_string(), json!(metadata.assignees));
+    }
+    retry::send(config.retries(), || {
+        agent
+            .patch(&config.get_pull_url(number))
+            .set("Accept", "application/json")
+            .query("access_token", token)
+            .send_json(&fields)
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "updating pull request".to_string(),
+        host: config.host.clone(),
+    })?;
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)] // All the parts of the request
 fn create_pull_request(
     agent: &Agent,
     config: &config::Gitea,
@@ -99,31 +162,41 @@ fn create_pull_request(
     head: &str,
     title: &str,
     body: &str,
-) -> Result<(), Error> {
-    let new_pr = agent
-        .post(&config.get_pulls_url())
-        .set("Accept", "application/json")
-        .query("access_token", token)
-        .send_json(json!({
-            "title": title,
-            "body": body,
-            "head": head,
-            "base": base
-        }))
-        .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
-            activity: "creating pull request".to_string(),
-            host: config.host.clone(),
-        })?
-        .into_json::<PullRequest>()
-        .map_err(|source| Error::ApiResponse {
-            source,
-            activity: "creating pull request",
-            host: config.host.clone(),
-        })?;
+    metadata: &Metadata,
+) -> Result<u32, Error> {
+    // Gitea marks pull requests as drafts with a prefix in the title
+    let title = if metadata.draft {
+        format!("WIP: {title}")
+    } else {
+        title.to_string()
+    };
+    let new_pr = retry::send_non_idempotent(config.retries(), || {
+        agent
+            .post(&config.get_pulls_url())
+            .set("Accept", "application/json")
+            .query("access_token", token)
+            .send_json(json!({
+                "title": title,
+                "body": body,
+                "head": head,
+                "base": base,
+                "assignees": metadata.assignees,
+            }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "creating pull request".to_string(),
+        host: config.host.clone(),
+    })?
+    .into_json::<PullRequest>()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "creating pull request",
+        host: config.host.clone(),
+    })?;
 
     debug!("Created new pull request: {pr_url}", pr_url = new_pr.url);
-    Ok(())
+    Ok(new_pr.number)
 }
 
 #[derive(Debug, Diagnostic, thiserror::Error)]
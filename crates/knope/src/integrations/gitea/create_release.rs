@@ -3,8 +3,10 @@ use tracing::info;
 
 use super::initialize_state;
 use crate::{
-    app_config, config,
-    integrations::{ureq_err_to_string, CreateReleaseInput, CreateReleaseResponse},
+    app_config, config, dry_run, github_actions,
+    integrations::{retry, ureq_err_to_string, CreateReleaseInput, CreateReleaseResponse},
+    plan,
+    plan::Plan,
     state,
     state::RunType,
 };
@@ -16,12 +18,13 @@ pub(crate) fn create_release(
     prerelease: bool,
     gitea_state: RunType<state::Gitea>,
     gitea_config: &config::Gitea,
+    plan: &mut Option<Plan>,
 ) -> Result<state::Gitea, Error> {
     let gitea_release = CreateReleaseInput::new(tag_name, name, body, prerelease, false);
 
     let gitea_state = match gitea_state {
         RunType::DryRun(state) => {
-            gitea_release_dry_run(name, gitea_config, &gitea_release);
+            gitea_release_dry_run(name, gitea_config, &gitea_release, plan);
             return Ok(state);
         }
         RunType::Real(gitea_state) => gitea_state,
@@ -29,40 +32,59 @@ pub(crate) fn create_release(
 
     let (token, agent) = initialize_state(&gitea_config.host, gitea_state)?;
 
-    agent
-        .post(&gitea_config.get_releases_url())
-        .query("access_token", &token)
-        .send_json(gitea_release)
-        .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
-            activity: "creating a release".to_string(),
-            host: gitea_config.host.clone(),
-        })?
-        .into_json::<CreateReleaseResponse>()
-        .map_err(|source| Error::ApiResponse {
-            source,
-            activity: "creating a release",
-            host: gitea_config.host.clone(),
-        })?;
+    let response = retry::send_non_idempotent(gitea_config.retries(), || {
+        agent
+            .post(&gitea_config.get_releases_url())
+            .query("access_token", &token)
+            .send_json(&gitea_release)
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "creating a release".to_string(),
+        host: gitea_config.host.clone(),
+    })?
+    .into_json::<CreateReleaseResponse>()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "creating a release",
+        host: gitea_config.host.clone(),
+    })?;
+    github_actions::record_url(tag_name, &response.html_url);
 
     Ok(state::Gitea::Initialized { token, agent })
 }
 
-fn gitea_release_dry_run(name: &str, config: &config::Gitea, gitea_release: &CreateReleaseInput) {
+fn gitea_release_dry_run(
+    name: &str,
+    config: &config::Gitea,
+    gitea_release: &CreateReleaseInput,
+    plan: &mut Option<Plan>,
+) {
     let release_type = if gitea_release.prerelease {
         "prerelease"
     } else {
         "release"
     };
-    let body = gitea_release.body.as_ref().map_or_else(
-        || String::from("autogenerated body"),
-        |body| format!("body:\n{body}"),
-    );
+    let body = match &gitea_release.body {
+        None => String::from(" and autogenerated body"),
+        Some(_) if dry_run::is_quiet() => String::new(),
+        Some(body) => format!(" and body:\n{body}"),
+    };
     info!(
-        "Would create a {release_type} on Gitea [{host}] with name {name} and tag {tag} and {body}",
+        "Would create a {release_type} on Gitea [{host}] with name {name} and tag {tag}{body}",
         tag = gitea_release.tag_name,
         host = config.host
     );
+    plan::record(plan, |plan| {
+        plan.releases.push(plan::Release {
+            forge: "Gitea",
+            name: name.to_string(),
+            tag: gitea_release.tag_name.to_string(),
+            body: gitea_release.body.map(String::from),
+            prerelease: gitea_release.prerelease,
+            assets: Vec::new(),
+        });
+    });
 }
 
 #[derive(Debug, Diagnostic, thiserror::Error)]
@@ -0,0 +1,117 @@
+use miette::Diagnostic;
+use tracing::info;
+
+use super::initialize_state;
+use crate::{
+    app_config, config,
+    integrations::{retry, ureq_err_to_string, ExistingRelease},
+    state,
+    state::RunType,
+};
+
+/// Delete the release for `tag_name`, or turn it back into a draft if `draft`.
+///
+/// It's not an error if there's no such release, since it may never have been created.
+pub(crate) fn delete_release(
+    tag_name: &str,
+    draft: bool,
+    gitea_state: RunType<state::Gitea>,
+    gitea_config: &config::Gitea,
+) -> Result<state::Gitea, Error> {
+    let gitea_state = match gitea_state {
+        RunType::DryRun(state) => {
+            let host = &gitea_config.host;
+            if draft {
+                info!("Would convert the release on Gitea [{host}] with tag {tag_name} to a draft");
+            } else {
+                info!("Would delete the release on Gitea [{host}] with tag {tag_name}");
+            }
+            return Ok(state);
+        }
+        RunType::Real(gitea_state) => gitea_state,
+    };
+
+    let (token, agent) = initialize_state(&gitea_config.host, gitea_state)?;
+    let releases_url = gitea_config.get_releases_url();
+
+    let response = retry::send(gitea_config.retries(), || {
+        agent
+            .get(&format!("{releases_url}/tags/{tag_name}"))
+            .query("access_token", &token)
+            .call()
+    });
+    let release: ExistingRelease = match response {
+        Ok(response) => response.into_json().map_err(|source| Error::ApiResponse {
+            source,
+            activity: "finding a release",
+            host: gitea_config.host.clone(),
+        })?,
+        Err(err) if matches!(*err, ureq::Error::Status(404, _)) => {
+            info!("No release on Gitea with tag {tag_name}");
+            return Ok(state::Gitea::Initialized { token, agent });
+        }
+        Err(err) => {
+            return Err(Error::ApiRequest {
+                err: ureq_err_to_string(*err),
+                activity: "finding a release",
+                host: gitea_config.host.clone(),
+            })
+        }
+    };
+
+    let existing_url = format!("{releases_url}/{id}", id = release.id);
+    retry::send(gitea_config.retries(), || {
+        if draft {
+            agent
+                .patch(&existing_url)
+                .query("access_token", &token)
+                .send_json(ureq::json!({ "draft": true }))
+        } else {
+            agent
+                .delete(&existing_url)
+                .query("access_token", &token)
+                .call()
+        }
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: if draft {
+            "converting a release to a draft"
+        } else {
+            "deleting a release"
+        },
+        host: gitea_config.host.clone(),
+    })?;
+    Ok(state::Gitea::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with the Gitea instance while {activity}: {err}")]
+    #[diagnostic(
+        code(gitea::api_request_error),
+        help(
+            "There was a problem communicating with the Gitea instance {host}, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest {
+        err: String,
+        activity: &'static str,
+        host: String,
+    },
+    #[error("Trouble decoding the response from Gitea while {activity}: {source}")]
+    #[diagnostic(
+        code(gitea::api_response_error),
+        help(
+            "Failure to decode a response from the Gitea instance at {host} is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+        host: String,
+    },
+}
@@ -3,7 +3,7 @@ use miette::Diagnostic;
 use super::initialize_state;
 use crate::{
     app_config, config,
-    integrations::{ureq_err_to_string, ResponseIssue},
+    integrations::{retry, ureq_err_to_string, ResponseIssue},
     prompt, state,
     step::issues::Issue,
 };
@@ -19,31 +19,33 @@ pub(crate) fn list_issues(
     let (token, agent) = initialize_state(&config.host, state)?;
     let labels = labels.unwrap_or(&[]).join(",");
 
-    let issues: Vec<Issue> = agent
-        .get(&config.get_issues_url())
-        .set("Accept", "aplication/json")
-        .query("access_token", &token)
-        .query("labels", &labels)
-        .query("state", "open")
-        .query("limit", "30")
-        .call()
-        .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
-            activity: "listing issues".to_string(),
-            host: config.host.clone(),
-        })?
-        .into_json::<Vec<ResponseIssue>>()
-        .map_err(|source| Error::ApiResponse {
-            source,
-            activity: "listing issues",
-            host: config.host.clone(),
-        })?
-        .into_iter()
-        .map(|response| Issue {
-            key: response.number.to_string(),
-            summary: response.title,
-        })
-        .collect();
+    let issues: Vec<Issue> = retry::send(config.retries(), || {
+        agent
+            .get(&config.get_issues_url())
+            .set("Accept", "aplication/json")
+            .query("access_token", &token)
+            .query("labels", &labels)
+            .query("state", "open")
+            .query("limit", "30")
+            .call()
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "listing issues".to_string(),
+        host: config.host.clone(),
+    })?
+    .into_json::<Vec<ResponseIssue>>()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "listing issues",
+        host: config.host.clone(),
+    })?
+    .into_iter()
+    .map(|response| Issue {
+        key: response.number.to_string(),
+        summary: response.title,
+    })
+    .collect();
 
     Ok((state::Gitea::Initialized { token, agent }, issues))
 }
@@ -0,0 +1,131 @@
+use miette::Diagnostic;
+use serde_json::json;
+use tracing::{debug, info};
+
+use super::initialize_state;
+use crate::{
+    app_config, config,
+    integrations::{git, retry, ureq_err_to_string, PullRequest},
+    state,
+    state::RunType,
+    step::merge_pull_request::MergeMethod,
+};
+
+/// Merge the open pull request from the current branch into `base`, or schedule it to merge once
+/// checks succeed if `auto_merge`.
+pub(crate) fn merge_pull_request(
+    base: &str,
+    method: MergeMethod,
+    auto_merge: bool,
+    state: RunType<state::Gitea>,
+    config: &config::Gitea,
+) -> Result<state::Gitea, Error> {
+    let branch_ref = git::current_branch()?;
+    let current_branch = branch_ref
+        .strip_prefix("refs/heads/")
+        .unwrap_or(&branch_ref);
+    let state = match state {
+        RunType::DryRun(state) => {
+            if auto_merge {
+                info!("Would enable auto-merge ({method}) on the pull request from {current_branch} to {base}");
+            } else {
+                info!("Would {method} the pull request from {current_branch} to {base}");
+            }
+            return Ok(state);
+        }
+        RunType::Real(state) => state,
+    };
+    let (token, agent) = initialize_state(&config.host, state)?;
+
+    let open_pulls: Vec<PullRequest> = retry::send(config.retries(), || {
+        agent
+            .get(&config.get_pulls_url())
+            .set("Accept", "application/json")
+            .query("state", "open")
+            .query(
+                "head",
+                &format!("{owner}:{current_branch}", owner = config.owner),
+            )
+            .query("base", base)
+            .query("access_token", &token)
+            .call()
+    })
+    .map_err(|err| Error::ApiRequest {
+        err: ureq_err_to_string(*err),
+        activity: "fetching open pull requests",
+        host: config.host.clone(),
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "fetching open pull requests",
+        host: config.host.clone(),
+    })?;
+    let pull = open_pulls.first().ok_or_else(|| Error::NoPullRequest {
+        head: current_branch.to_string(),
+        base: base.to_string(),
+    })?;
+
+    debug!("Merging pull request {url}", url = pull.url);
+    retry::send_non_idempotent(config.retries(), || {
+        agent
+            .post(&format!(
+                "{pull_url}/merge",
+                pull_url = config.get_pull_url(pull.number)
+            ))
+            .set("Accept", "application/json")
+            .query("access_token", &token)
+            .send_json(json!({
+                "Do": method,
+                "merge_when_checks_succeed": auto_merge,
+            }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "merging pull request",
+        host: config.host.clone(),
+    })?;
+
+    Ok(state::Gitea::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with the Gitea instance while {activity}: {err}")]
+    #[diagnostic(
+        code(gitea::api_request_error),
+        help(
+            "There was a problem communicating with the Gitea instance {host}, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest {
+        err: String,
+        activity: &'static str,
+        host: String,
+    },
+    #[error("Trouble decoding the response from Gitea while {activity}: {source}")]
+    #[diagnostic(
+        code(gitea::api_response_error),
+        help(
+            "Failure to decode a response from the Gitea instance at {host} is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+        host: String,
+    },
+    #[error("No open pull request from {head} to {base} on Gitea")]
+    #[diagnostic(
+        code(gitea::no_pull_request),
+        help("Create one first, for example with the CreatePullRequest step."),
+        url("https://knope.tech/reference/config-file/steps/merge-pull-request/")
+    )]
+    NoPullRequest { head: String, base: String },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+}
@@ -5,15 +5,23 @@ use crate::{
     state,
 };
 
+mod close_issue;
 pub(crate) mod create_pull_request;
 pub(crate) mod create_release;
+mod delete_release;
 mod list_issues;
+mod merge_pull_request;
+mod wait_for_checks;
 
+pub(crate) use close_issue::{close_issue, Error as CloseIssueError};
 pub(crate) use create_pull_request::{
     create_or_update_pull_request, Error as CreatePullRequestError,
 };
 pub(crate) use create_release::{create_release, Error as CreateReleaseError};
+pub(crate) use delete_release::{delete_release, Error as DeleteReleaseError};
 pub(crate) use list_issues::{list_issues, Error as ListIssuesError};
+pub(crate) use merge_pull_request::{merge_pull_request, Error as MergePullRequestError};
+pub(crate) use wait_for_checks::{wait_for_checks, Error as WaitForChecksError};
 
 fn initialize_state(host: &str, state: state::Gitea) -> Result<(String, Agent), app_config::Error> {
     Ok(match state {
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use miette::Diagnostic;
+use serde::Deserialize;
+use tracing::info;
+
+use super::initialize_state;
+use crate::{
+    app_config, config,
+    integrations::{
+        checks,
+        checks::{Check, Status},
+        retry, ureq_err_to_string,
+    },
+    state,
+    state::RunType,
+};
+
+#[derive(Deserialize)]
+struct CombinedStatus {
+    statuses: Option<Vec<CommitStatus>>,
+}
+
+#[derive(Deserialize)]
+struct CommitStatus {
+    context: String,
+    status: String,
+}
+
+impl From<CommitStatus> for (String, Check) {
+    fn from(status: CommitStatus) -> Self {
+        let check = match status.status.as_str() {
+            "pending" => Check::Pending,
+            "success" | "warning" => Check::Passed,
+            _ => Check::Failed,
+        };
+        (status.context, check)
+    }
+}
+
+/// Wait until every commit status on `sha` passes, failing if any fail or if they take longer
+/// than `timeout`.
+pub(crate) fn wait_for_checks(
+    sha: &str,
+    timeout: Duration,
+    state: RunType<state::Gitea>,
+    config: &config::Gitea,
+) -> Result<state::Gitea, Error> {
+    let state = match state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would wait up to {seconds} seconds for checks on the current commit to pass on Gitea",
+                seconds = timeout.as_secs()
+            );
+            return Ok(state);
+        }
+        RunType::Real(state) => state,
+    };
+    let (token, agent) = initialize_state(&config.host, state)?;
+
+    checks::wait::<Error>("Gitea", timeout, || {
+        let combined_status: CombinedStatus = retry::send(config.retries(), || {
+            agent
+                .get(&config.get_commit_status_url(sha))
+                .set("Accept", "application/json")
+                .query("access_token", &token)
+                .call()
+        })
+        .map_err(|err| Error::ApiRequest {
+            err: ureq_err_to_string(*err),
+            activity: "fetching commit statuses",
+            host: config.host.clone(),
+        })?
+        .into_json()
+        .map_err(|source| Error::ApiResponse {
+            source,
+            activity: "fetching commit statuses",
+            host: config.host.clone(),
+        })?;
+        Ok(Status::from_checks(
+            combined_status
+                .statuses
+                .into_iter()
+                .flatten()
+                .map(<(String, Check)>::from),
+        ))
+    })?;
+
+    Ok(state::Gitea::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with the Gitea instance while {activity}: {err}")]
+    #[diagnostic(
+        code(gitea::api_request_error),
+        help(
+            "There was a problem communicating with the Gitea instance {host}, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest {
+        err: String,
+        activity: &'static str,
+        host: String,
+    },
+    #[error("Trouble decoding the response from Gitea while {activity}: {source}")]
+    #[diagnostic(
+        code(gitea::api_response_error),
+        help(
+            "Failure to decode a response from the Gitea instance at {host} is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+        host: String,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Checks(#[from] checks::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+}
@@ -0,0 +1,100 @@
+//! GitHub rejects bodies over a certain number of characters, so long release notes have to be
+//! cut down (or spread across comments) before they're sent.
+
+use std::borrow::Cow;
+
+use crate::config;
+
+/// The most characters GitHub accepts in the body of a release.
+pub(super) const RELEASE_LIMIT: usize = 125_000;
+/// The most characters GitHub accepts in the body of a pull request or a comment.
+pub(super) const PULL_REQUEST_LIMIT: usize = 65_536;
+
+/// Where truncated bodies link to for the full notes: the configured URL, or `CHANGELOG.md` at
+/// `git_ref`.
+pub(super) fn full_notes_url(github_config: &config::GitHub, git_ref: &str) -> String {
+    github_config
+        .truncated_notes_url
+        .clone()
+        .unwrap_or_else(|| {
+            format!(
+                "https://github.com/{owner}/{repo}/blob/{git_ref}/CHANGELOG.md",
+                owner = github_config.owner,
+                repo = github_config.repo,
+            )
+        })
+}
+
+/// Cut `body` down to `limit` characters (at a line break, if possible), ending with a link to
+/// `full_notes_url`. Bodies that already fit are unchanged.
+pub(super) fn truncate<'a>(body: &'a str, limit: usize, full_notes_url: &str) -> Cow<'a, str> {
+    if body.chars().count() <= limit {
+        return Cow::Borrowed(body);
+    }
+    let notice =
+        format!("\n\n*These notes are too long for GitHub, [see the rest]({full_notes_url}).*");
+    let (kept, _) = body.split_at(prefix_len(
+        body,
+        limit.saturating_sub(notice.chars().count()),
+    ));
+    Cow::Owned(format!("{kept}{notice}", kept = kept.trim_end()))
+}
+
+/// Split `body` into parts of at most `limit` characters, breaking between lines where possible.
+pub(super) fn split(body: &str, limit: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = body.trim();
+    while !rest.is_empty() {
+        let (part, after) = rest.split_at(prefix_len(rest, limit));
+        parts.push(part.trim_end());
+        rest = after.trim_start();
+    }
+    parts
+}
+
+/// The length in bytes of the longest prefix of `text` with at most `limit` characters, ending at
+/// the last line break in that prefix if there is one.
+fn prefix_len(text: &str, limit: usize) -> usize {
+    let Some((end, _)) = text.char_indices().nth(limit) else {
+        return text.len();
+    };
+    let (prefix, _) = text.split_at(end);
+    match prefix.rfind('\n') {
+        Some(newline) if newline > 0 => newline,
+        _ if end > 0 => end,
+        _ => text.chars().next().map_or(0, char::len_utf8),
+    }
+}
+
+#[cfg(test)]
+mod test_body {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn short_body_is_unchanged() {
+        assert_eq!(truncate("- A fix", 100, "url"), "- A fix");
+    }
+
+    #[test]
+    fn truncate_at_line() {
+        let body = format!("- First\n- Second\n- {}", "Third".repeat(100));
+        let notice = "\n\n*These notes are too long for GitHub, [see the rest](url).*";
+        let limit = notice.chars().count() + "- First\n- Sec".len();
+        assert_eq!(truncate(&body, limit, "url"), format!("- First{notice}"));
+    }
+
+    #[test]
+    fn split_at_lines() {
+        assert_eq!(
+            split("- First\n- Second\n\n- Third", 10),
+            vec!["- First", "- Second", "- Third"]
+        );
+    }
+
+    #[test]
+    fn split_long_line() {
+        assert_eq!(split("ääää", 3), vec!["äää", "ä"]);
+    }
+}
@@ -0,0 +1,198 @@
+use std::io::Read;
+
+use miette::Diagnostic;
+use tracing::info;
+use ureq::Agent;
+
+use crate::{
+    app_config, config,
+    integrations::{
+        github::{
+            create_release::sha256,
+            initialize_state, repository,
+            repository::{Change, Repository},
+        },
+        retry, ureq_err_to_string,
+    },
+    state,
+    state::RunType,
+};
+
+/// The new version of a Homebrew formula.
+pub(crate) struct Formula<'a> {
+    /// The tap repository, like `knope-dev/homebrew-tap`
+    pub(crate) tap: &'a str,
+    /// The name of the formula, which lives at `Formula/{name}.rb` in the tap
+    pub(crate) name: &'a str,
+    pub(crate) version: String,
+    /// Where Homebrew should download the new version from
+    pub(crate) url: String,
+}
+
+/// Open a pull request against the tap which points `formula` at its new `url` and `sha256`.
+///
+/// The `url` is downloaded to compute the checksum, so it must already exist.
+pub(crate) fn bump_formula(
+    formula: &Formula,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let path = format!("Formula/{name}.rb", name = formula.name);
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would open a pull request against {tap} setting the url in {path} to {url}",
+                tap = formula.tap,
+                url = formula.url
+            );
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let (token, agent) = initialize_state(github_state)?;
+    let token_header = format!("token {}", &token);
+    let tap = Repository::new(&agent, &token_header, formula.tap, github_config.retries());
+
+    let checksum = checksum(&agent, &formula.url, github_config.retries())?;
+    let default_branch = tap.default_branch()?;
+    let existing = tap.file(&path, &default_branch)?;
+    let updated = update_formula(&existing.contents, &formula.url, &checksum)
+        .ok_or_else(|| Error::UnrecognizedFormula(path.clone()))?;
+    let title = format!(
+        "{name} {version}",
+        name = formula.name,
+        version = formula.version
+    );
+    let branch = format!(
+        "bump-{name}-{version}",
+        name = formula.name,
+        version = formula.version
+    );
+    tap.propose(
+        &tap,
+        &default_branch,
+        &branch,
+        &title,
+        &[Change {
+            path,
+            contents: updated,
+            sha: Some(existing.sha),
+        }],
+    )?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+/// Replace the first `url` and `sha256` in `formula`, which belong to the formula itself rather
+/// than any of its resources. Returns `None` if either is missing.
+fn update_formula(formula: &str, url: &str, sha256: &str) -> Option<String> {
+    let mut replaced_url = false;
+    let mut replaced_sha256 = false;
+    let mut lines: Vec<String> = Vec::new();
+    for line in formula.lines() {
+        let trimmed = line.trim_start();
+        let indent = line.strip_suffix(trimmed).unwrap_or_default();
+        if !replaced_url && trimmed.starts_with("url \"") {
+            lines.push(format!("{indent}url \"{url}\""));
+            replaced_url = true;
+        } else if !replaced_sha256 && trimmed.starts_with("sha256 \"") {
+            lines.push(format!("{indent}sha256 \"{sha256}\""));
+            replaced_sha256 = true;
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    if !(replaced_url && replaced_sha256) {
+        return None;
+    }
+    let mut updated = lines.join("\n");
+    if formula.ends_with('\n') {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+/// Download `url` and return its SHA-256.
+fn checksum(agent: &Agent, url: &str, retries: u32) -> Result<String, Error> {
+    let mut contents = Vec::new();
+    retry::send(retries, || agent.get(url).call())
+        .map_err(|source| Error::Download {
+            url: url.to_string(),
+            err: ureq_err_to_string(*source),
+        })?
+        .into_reader()
+        .read_to_end(&mut contents)
+        .map_err(|source| Error::Download {
+            url: url.to_string(),
+            err: source.to_string(),
+        })?;
+    Ok(sha256(&contents))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Repository(#[from] repository::Error),
+    #[error("Could not download {url}: {err}")]
+    #[diagnostic(
+        code(github::bump_formula::download),
+        help("The url must exist before the formula can point to it, so this step should come after the release is created."),
+        url("https://knope.tech/reference/config-file/steps/bump-homebrew-formula/")
+    )]
+    Download { url: String, err: String },
+    #[error("Could not find the `url` and `sha256` of the formula in {0}")]
+    #[diagnostic(
+        code(github::bump_formula::unrecognized_formula),
+        help("The formula must declare its `url` and `sha256` on their own lines."),
+        url("https://knope.tech/reference/config-file/steps/bump-homebrew-formula/")
+    )]
+    UnrecognizedFormula(String),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_update_formula {
+    use pretty_assertions::assert_eq;
+
+    use super::update_formula;
+
+    #[test]
+    fn replaces_only_the_formula_source() {
+        let formula = r#"class Knope < Formula
+  desc "A command line tool"
+  url "https://example.com/knope-0.1.0.tar.gz"
+  sha256 "old"
+
+  resource "extra" do
+    url "https://example.com/extra.tar.gz"
+    sha256 "extra"
+  end
+end
+"#;
+        let expected = r#"class Knope < Formula
+  desc "A command line tool"
+  url "https://example.com/knope-0.2.0.tar.gz"
+  sha256 "new"
+
+  resource "extra" do
+    url "https://example.com/extra.tar.gz"
+    sha256 "extra"
+  end
+end
+"#;
+        assert_eq!(
+            update_formula(formula, "https://example.com/knope-0.2.0.tar.gz", "new").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn missing_sha256() {
+        let formula = "class Knope < Formula\n  url \"https://example.com\"\nend\n";
+        assert_eq!(update_formula(formula, "https://example.com", "new"), None);
+    }
+}
@@ -0,0 +1,70 @@
+use miette::Diagnostic;
+use tracing::info;
+
+use crate::{
+    app_config, config,
+    integrations::{github::initialize_state, retry, ureq_err_to_string},
+    state,
+    state::RunType,
+};
+
+/// Leave `comment` on the issue numbered `number`, then close it.
+pub(crate) fn close_issue(
+    number: u64,
+    comment: &str,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            info!("Would comment \"{comment}\" on issue #{number} on GitHub and close it");
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let (token, agent) = initialize_state(github_state)?;
+    let issue_url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/issues/{number}",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let token_header = format!("token {}", &token);
+
+    retry::send_non_idempotent(github_config.retries(), || {
+        agent
+            .post(&format!("{issue_url}/comments"))
+            .set("Authorization", &token_header)
+            .send_json(ureq::json!({ "body": comment }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "commenting on an issue",
+    })?;
+    retry::send(github_config.retries(), || {
+        agent
+            .patch(&issue_url)
+            .set("Authorization", &token_header)
+            .send_json(ureq::json!({ "state": "closed" }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "closing an issue",
+    })?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+}
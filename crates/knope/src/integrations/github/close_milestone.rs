@@ -0,0 +1,212 @@
+use miette::Diagnostic;
+use serde::Deserialize;
+use tracing::info;
+use ureq::Agent;
+
+use crate::{
+    app_config, config,
+    integrations::{github::initialize_state, retry, ureq_err_to_string},
+    state,
+    state::RunType,
+};
+
+#[derive(Deserialize)]
+struct Milestone {
+    number: u64,
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+}
+
+/// Close the open milestone whose title is one of `titles`, first moving its open issues to the
+/// `move_open_issues_to` milestone (which is created if it doesn't exist).
+///
+/// It's not an error if there's no such milestone, since not every project uses them.
+pub(crate) fn close_milestone(
+    titles: &[&str],
+    move_open_issues_to: Option<&str>,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would close the milestone on GitHub named {titles}",
+                titles = titles.join(" or ")
+            );
+            if let Some(target) = move_open_issues_to {
+                info!("Would move its open issues to the milestone {target}");
+            }
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let (token, agent) = initialize_state(github_state)?;
+    let repo_url = format!(
+        "https://api.github.com/repos/{owner}/{repo}",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let token_header = format!("token {}", &token);
+
+    let milestones: Vec<Milestone> = retry::send(github_config.retries(), || {
+        agent
+            .get(&format!("{repo_url}/milestones"))
+            .query("state", "open")
+            .query("per_page", "100")
+            .set("Authorization", &token_header)
+            .call()
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "listing milestones",
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "listing milestones",
+    })?;
+
+    let Some(milestone) = milestones
+        .iter()
+        .find(|milestone| titles.contains(&milestone.title.as_str()))
+    else {
+        info!(
+            "No open milestone on GitHub named {titles}",
+            titles = titles.join(" or ")
+        );
+        return Ok(state::GitHub::Initialized { token, agent });
+    };
+
+    if let Some(target) = move_open_issues_to {
+        let api = Api {
+            agent: &agent,
+            token_header: &token_header,
+            repo_url: &repo_url,
+            retries: github_config.retries(),
+        };
+        api.move_open_issues(milestone.number, target, &milestones)?;
+    }
+
+    retry::send(github_config.retries(), || {
+        agent
+            .patch(&format!(
+                "{repo_url}/milestones/{number}",
+                number = milestone.number
+            ))
+            .set("Authorization", &token_header)
+            .send_json(ureq::json!({ "state": "closed" }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "closing a milestone",
+    })?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+/// The pieces needed to make more requests after the milestone is found.
+struct Api<'a> {
+    agent: &'a Agent,
+    token_header: &'a str,
+    repo_url: &'a str,
+    retries: u32,
+}
+
+impl Api<'_> {
+    /// Move the open issues in the milestone numbered `from` to the one titled `target`.
+    fn move_open_issues(
+        &self,
+        from: u64,
+        target: &str,
+        milestones: &[Milestone],
+    ) -> Result<(), Error> {
+        let target = if let Some(existing) = milestones
+            .iter()
+            .find(|milestone| milestone.title == target)
+        {
+            existing.number
+        } else {
+            retry::send_non_idempotent(self.retries, || {
+                self.agent
+                    .post(&format!("{repo_url}/milestones", repo_url = self.repo_url))
+                    .set("Authorization", self.token_header)
+                    .send_json(ureq::json!({ "title": target }))
+            })
+            .map_err(|source| Error::ApiRequest {
+                err: ureq_err_to_string(*source),
+                activity: "creating a milestone",
+            })?
+            .into_json::<Milestone>()
+            .map_err(|source| Error::ApiResponse {
+                source,
+                activity: "creating a milestone",
+            })?
+            .number
+        };
+        let issues: Vec<Issue> = retry::send(self.retries, || {
+            self.agent
+                .get(&format!("{repo_url}/issues", repo_url = self.repo_url))
+                .query("milestone", &from.to_string())
+                .query("state", "open")
+                .query("per_page", "100")
+                .set("Authorization", self.token_header)
+                .call()
+        })
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity: "listing the open issues of a milestone",
+        })?
+        .into_json()
+        .map_err(|source| Error::ApiResponse {
+            source,
+            activity: "listing the open issues of a milestone",
+        })?;
+        for issue in issues {
+            retry::send(self.retries, || {
+                self.agent
+                    .patch(&format!(
+                        "{repo_url}/issues/{number}",
+                        repo_url = self.repo_url,
+                        number = issue.number
+                    ))
+                    .set("Authorization", self.token_header)
+                    .send_json(ureq::json!({ "milestone": target }))
+            })
+            .map_err(|source| Error::ApiRequest {
+                err: ureq_err_to_string(*source),
+                activity: "moving an issue to another milestone",
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+    #[error("Trouble decoding the response from GitHub while {activity}: {source}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+    },
+}
@@ -1,57 +1,98 @@
+use std::borrow::Cow;
+
+use itertools::{EitherOrBoth, Itertools};
 use miette::Diagnostic;
+use serde::Deserialize;
 use serde_json::json;
 use tracing::{debug, info};
 use ureq::Agent;
 
 use crate::{
-    app_config, config,
-    integrations::{git, github::initialize_state, ureq_err_to_string, PullRequest},
+    app_config, config, dry_run,
+    integrations::{
+        git,
+        github::{body, initialize_state},
+        retry, ureq_err_to_string, PullRequest,
+    },
     state,
     state::RunType,
+    step::create_pull_request::Metadata,
 };
 
+/// Starts each comment holding part of a split body, so that they can be found and edited when the
+/// pull request is updated.
+const CONTINUED_MARKER: &str = "<!-- knope: continued body -->\n";
+
 pub(crate) fn create_or_update_pull_request(
     title: &str,
     body: &str,
     base: &str,
+    metadata: &Metadata,
     state: RunType<state::GitHub>,
     config: &config::GitHub,
 ) -> Result<state::GitHub, Error> {
     let current_branch = git::current_branch()?;
+    let (body, comments) = if metadata.split_long_body {
+        let limit = body::PULL_REQUEST_LIMIT - CONTINUED_MARKER.len();
+        let mut parts = body::split(body, limit).into_iter();
+        (Cow::from(parts.next().unwrap_or_default()), parts.collect())
+    } else {
+        let full_notes_url = body::full_notes_url(config, &current_branch);
+        let body = body::truncate(body, body::PULL_REQUEST_LIMIT, &full_notes_url);
+        (body, Vec::new())
+    };
+    let body = body.as_ref();
     let state = match state {
         RunType::DryRun(state) => {
             info!("Would create or update a pull request from {current_branch} to {base}:");
             info!("\tTitle: {title}");
-            info!("\tBody: {body}");
+            if !dry_run::is_quiet() {
+                info!("\tBody: {body}");
+                for comment in &comments {
+                    info!("\tComment: {comment}");
+                }
+            }
+            metadata.log_dry_run();
             return Ok(state);
         }
         RunType::Real(state) => state,
     };
 
     let (token, agent) = initialize_state(state)?;
-    let config::GitHub { owner, repo } = config;
+    let config::GitHub { owner, repo, .. } = config;
     let base_url = format!("https://api.github.com/repos/{owner}/{repo}/pulls");
     let authorization_header = format!("Bearer {}", &token);
 
-    let existing_pulls: Vec<PullRequest> = agent
-        .get(&base_url)
-        .set("Accept", "application/vnd.github+json")
-        .set("Authorization", &authorization_header)
-        .query("head", &format!("{owner}:{current_branch}"))
-        .query("base", base)
-        .call()
-        .map_err(|err| Error::ApiRequest {
-            err: ureq_err_to_string(err),
-            activity: "fetching existing pull requests".to_string(),
-        })?
-        .into_json()
-        .map_err(|source| Error::ApiResponse {
-            source,
-            activity: "fetching existing pull requests",
-        })?;
-    let agent = if let Some(existing) = existing_pulls.first() {
+    let existing_pulls: Vec<PullRequest> = retry::send(config.retries(), || {
+        agent
+            .get(&base_url)
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", &authorization_header)
+            .query("head", &format!("{owner}:{current_branch}"))
+            .query("base", base)
+            .call()
+    })
+    .map_err(|err| Error::ApiRequest {
+        err: ureq_err_to_string(*err),
+        activity: "fetching existing pull requests".to_string(),
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "fetching existing pull requests",
+    })?;
+    let updating = !existing_pulls.is_empty();
+    let (agent, number) = if let Some(existing) = existing_pulls.first() {
         debug!("Updating existing pull request: {}", existing.url);
-        update_pull_request(&existing.url, title, body, &authorization_header, agent)
+        let agent = update_pull_request(
+            &existing.url,
+            title,
+            body,
+            &authorization_header,
+            agent,
+            config.retries(),
+        )?;
+        (agent, existing.number)
     } else {
         debug!("No matching existing pull request found, creating a new one.");
         create_pull_request(
@@ -60,10 +101,31 @@ pub(crate) fn create_or_update_pull_request(
             body,
             base,
             &current_branch,
+            metadata.draft,
             &authorization_header,
             agent,
-        )
-    }?;
+            config.retries(),
+        )?
+    };
+    let issue_url = format!("https://api.github.com/repos/{owner}/{repo}/issues/{number}");
+    if metadata.split_long_body {
+        sync_comments(
+            &format!("{issue_url}/comments"),
+            &comments,
+            updating,
+            &authorization_header,
+            &agent,
+            config.retries(),
+        )?;
+    }
+    add_metadata(
+        metadata,
+        &issue_url,
+        &format!("{base_url}/{number}"),
+        &authorization_header,
+        &agent,
+        config.retries(),
+    )?;
     Ok(state::GitHub::Initialized { token, agent })
 }
 
@@ -73,55 +135,214 @@ fn update_pull_request(
     body: &str,
     auth_header: &str,
     agent: Agent,
+    retries: u32,
 ) -> Result<Agent, Error> {
-    agent
-        .patch(url)
-        .set("Accept", "application/vnd.github+json")
-        .set("Authorization", auth_header)
-        .send_json(json!({
-            "title": title,
-            "body": body,
-        }))
+    retry::send(retries, || {
+        agent
+            .patch(url)
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", auth_header)
+            .send_json(json!({
+                "title": title,
+                "body": body,
+            }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "updating pull request".to_string(),
+    })?;
+    Ok(agent)
+}
+
+/// Add the labels and assignees and request the reviewers in `metadata`.
+fn add_metadata(
+    metadata: &Metadata,
+    issue_url: &str,
+    pull_url: &str,
+    auth_header: &str,
+    agent: &Agent,
+    retries: u32,
+) -> Result<(), Error> {
+    let requests = [
+        (
+            &metadata.labels,
+            format!("{issue_url}/labels"),
+            "labels",
+            "adding labels",
+        ),
+        (
+            &metadata.assignees,
+            format!("{issue_url}/assignees"),
+            "assignees",
+            "adding assignees",
+        ),
+        (
+            &metadata.reviewers,
+            format!("{pull_url}/requested_reviewers"),
+            "reviewers",
+            "requesting reviewers",
+        ),
+    ];
+    for (values, url, key, activity) in requests {
+        if values.is_empty() {
+            continue;
+        }
+        retry::send(retries, || {
+            agent
+                .post(&url)
+                .set("Accept", "application/vnd.github+json")
+                .set("Authorization", auth_header)
+                .send_json(json!({ key: values }))
+        })
         .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
-            activity: "updating pull request".to_string(),
+            err: ureq_err_to_string(*source),
+            activity: activity.to_string(),
         })?;
-    Ok(agent)
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Comment {
+    url: String,
+    body: Option<String>,
 }
 
+/// Find the comments which hold the rest of an earlier body of the pull request.
+fn fetch_continued_comments(
+    url: &str,
+    auth_header: &str,
+    agent: &Agent,
+    retries: u32,
+) -> Result<Vec<Comment>, Error> {
+    let comments: Vec<Comment> = retry::send(retries, || {
+        agent
+            .get(url)
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", auth_header)
+            .query("per_page", "100")
+            .call()
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "fetching comments".to_string(),
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "fetching comments",
+    })?;
+    Ok(comments
+        .into_iter()
+        .filter(|comment| {
+            comment
+                .body
+                .as_deref()
+                .is_some_and(|body| body.starts_with(CONTINUED_MARKER))
+        })
+        .collect())
+}
+
+/// Post each of `comments`, like the parts of a body that's too long for the pull request itself.
+///
+/// When `updating` an existing pull request, the comments posted for its earlier body are edited
+/// instead, and any which aren't needed anymore are deleted.
+fn sync_comments(
+    url: &str,
+    comments: &[&str],
+    updating: bool,
+    auth_header: &str,
+    agent: &Agent,
+    retries: u32,
+) -> Result<(), Error> {
+    let earlier = if updating {
+        fetch_continued_comments(url, auth_header, agent, retries)?
+    } else {
+        Vec::new()
+    };
+    for pair in comments.iter().zip_longest(earlier) {
+        let (result, activity) = match pair {
+            EitherOrBoth::Both(comment, earlier) => {
+                let body = format!("{CONTINUED_MARKER}{comment}");
+                if earlier.body.as_ref() == Some(&body) {
+                    continue;
+                }
+                let result = retry::send(retries, || {
+                    agent
+                        .patch(&earlier.url)
+                        .set("Accept", "application/vnd.github+json")
+                        .set("Authorization", auth_header)
+                        .send_json(json!({ "body": body }))
+                });
+                (result, "editing the rest of the body")
+            }
+            EitherOrBoth::Left(comment) => {
+                let body = format!("{CONTINUED_MARKER}{comment}");
+                let result = retry::send_non_idempotent(retries, || {
+                    agent
+                        .post(url)
+                        .set("Accept", "application/vnd.github+json")
+                        .set("Authorization", auth_header)
+                        .send_json(json!({ "body": body }))
+                });
+                (result, "commenting the rest of the body")
+            }
+            EitherOrBoth::Right(earlier) => {
+                let result = retry::send(retries, || {
+                    agent
+                        .delete(&earlier.url)
+                        .set("Accept", "application/vnd.github+json")
+                        .set("Authorization", auth_header)
+                        .call()
+                });
+                (result, "deleting an outdated part of the body")
+            }
+        };
+        result.map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity: activity.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)] // All the parts of the request
 fn create_pull_request(
     url: &str,
     title: &str,
     body: &str,
     base: &str,
     current_branch: &str,
+    draft: bool,
     auth_header: &str,
     agent: Agent,
-) -> Result<Agent, Error> {
-    let response = agent
-        .post(url)
-        .set("Accept", "application/vnd.github+json")
-        .set("Authorization", auth_header)
-        .send_json(json!({
-            "title": title,
-            "body": body,
-            "head": current_branch,
-            "base": base,
-        }))
-        .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
-            activity: "creating pull request".to_string(),
-        })?;
-    let json_data = response
-        .into_json::<serde_json::Value>()
+    retries: u32,
+) -> Result<(Agent, u32), Error> {
+    let response = retry::send_non_idempotent(retries, || {
+        agent
+            .post(url)
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", auth_header)
+            .send_json(json!({
+                "title": title,
+                "body": body,
+                "head": current_branch,
+                "base": base,
+                "draft": draft,
+            }))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "creating pull request".to_string(),
+    })?;
+    let new_pr = response
+        .into_json::<PullRequest>()
         .map_err(|source| Error::ApiResponse {
             source,
             activity: "creating pull request",
         })?;
-    if let Some(new_pr_url) = json_data.get("url") {
-        debug!("Created new pull request: {new_pr_url}");
-    }
-    Ok(agent)
+    debug!("Created new pull request: {url}", url = new_pr.url);
+    Ok((agent, new_pr.number))
 }
 
 #[derive(Debug, Diagnostic, thiserror::Error)]
@@ -1,19 +1,52 @@
+use std::fmt::Write;
+
 use datta::UriTemplate;
-use glob::glob;
-use knope_config::{Asset, AssetNameError, Assets};
+use glob::{glob, Pattern};
+use knope_config::{Asset, AssetNameError, Assets, Checksums};
+use knope_versioning::semver::Version;
 use miette::Diagnostic;
 use relative_path::RelativePathBuf;
+use ring::digest::{digest, SHA256};
+use serde::Serialize;
 use tracing::info;
 
 use crate::{
-    app_config, config,
+    app_config, config, dry_run, github_actions,
     integrations::{
-        github::initialize_state, ureq_err_to_string, CreateReleaseInput, CreateReleaseResponse,
+        git,
+        github::{body, initialize_state},
+        retry, sigstore, ureq_err_to_string, CreateReleaseInput, CreateReleaseResponse,
     },
+    plan,
+    plan::Plan,
     state,
     state::RunType,
 };
 
+/// The shared release input plus the options only GitHub supports.
+#[derive(Serialize)]
+struct GitHubReleaseInput<'a> {
+    #[serde(flatten)]
+    release: &'a CreateReleaseInput<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    make_latest: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    discussion_category_name: Option<&'a str>,
+}
+
+/// Everything to upload along with a release.
+pub(crate) struct Uploads<'a> {
+    pub(crate) assets: &'a Assets,
+    /// The version being released, for any asset variables
+    pub(crate) version: &'a Version,
+    pub(crate) checksums: Option<&'a Checksums>,
+    /// How to sign the checksums, defaults to the Git config
+    pub(crate) signing: Option<&'a config::Signing>,
+    /// Whether to upload a Sigstore bundle for each asset
+    pub(crate) sigstore: bool,
+}
+
+#[allow(clippy::too_many_arguments)] // All the parts of the release
 pub(crate) fn create_release(
     name: &str,
     tag_name: &str,
@@ -21,19 +54,34 @@ pub(crate) fn create_release(
     prerelease: bool,
     github_state: RunType<state::GitHub>,
     github_config: &config::GitHub,
-    assets: Option<&Assets>,
+    uploads: Option<&Uploads>,
+    plan: &mut Option<Plan>,
 ) -> Result<state::GitHub, Error> {
-    let github_release =
-        CreateReleaseInput::new(tag_name, name, body, prerelease, assets.is_some());
+    let body = body::truncate(
+        body,
+        body::RELEASE_LIMIT,
+        &body::full_notes_url(github_config, tag_name),
+    );
+    let mut github_release = CreateReleaseInput::new(
+        tag_name,
+        name,
+        &body,
+        prerelease,
+        uploads.is_some() || github_config.draft,
+    );
+    // GitHub puts the body above the notes it generates
+    github_release.generate_release_notes |= github_config.append_generated_notes;
 
     let github_state = match github_state {
         RunType::DryRun(state) => {
-            github_release_dry_run(name, assets, &github_release)?;
+            github_release_dry_run(name, uploads, &github_release, github_config, plan)?;
             return Ok(state);
         }
         RunType::Real(github_state) => github_state,
     };
 
+    // Sign everything first, so a failure there doesn't leave a half-finished release behind
+    let files = uploads.map(prepare_uploads).transpose()?;
     let (token, agent) = initialize_state(github_state)?;
 
     let url = format!(
@@ -43,12 +91,19 @@ pub(crate) fn create_release(
     );
     let token_header = format!("token {}", &token);
 
-    let response: CreateReleaseResponse = agent
-        .post(&url)
-        .set("Authorization", &token_header)
-        .send_json(github_release)
+    let response: CreateReleaseResponse =
+        retry::send_non_idempotent(github_config.retries(), || {
+            agent
+                .post(&url)
+                .set("Authorization", &token_header)
+                .send_json(GitHubReleaseInput {
+                    release: &github_release,
+                    make_latest: github_config.make_latest(),
+                    discussion_category_name: github_config.discussion_category.as_deref(),
+                })
+        })
         .map_err(|source| Error::ApiRequest {
-            err: ureq_err_to_string(source),
+            err: ureq_err_to_string(*source),
             activity: "creating a release".to_string(),
         })?
         .into_json()
@@ -57,86 +112,229 @@ pub(crate) fn create_release(
             activity: "creating a release",
         })?;
 
-    if let Some(assets) = assets {
+    if let Some(files) = &files {
         let mut upload_template = UriTemplate::new(&response.upload_url);
-        for asset in resolve_assets(assets)? {
-            let file = std::fs::read(asset.path.to_path("")).map_err(|source| {
-                Error::CouldNotReadAssetFile {
-                    path: asset.path.clone(),
-                    source,
-                }
-            })?;
-            let asset_name = asset.name()?;
+        for (asset_name, contents) in files {
             let upload_url = upload_template.set("name", asset_name.as_str()).build();
-            agent
-                .post(&upload_url)
-                .set("Authorization", &token_header)
-                .set("Content-Type", "application/octet-stream")
-                .set("Content-Length", &file.len().to_string())
-                .send_bytes(&file)
-                .map_err(|source| Error::ApiRequest {
-                    err: ureq_err_to_string(source),
-                    activity: format!(
-                        "uploading asset {asset_name}. Release has been created but not published!",
-                    ),
-                })?;
-        }
-        agent
-            .patch(&response.url)
-            .set("Authorization", &token_header)
-            .send_json(ureq::json!({
-                "draft": false
-            }))
+            retry::send_non_idempotent(github_config.retries(), || {
+                agent
+                    .post(&upload_url)
+                    .set("Authorization", &token_header)
+                    .set("Content-Type", "application/octet-stream")
+                    .set("Content-Length", &contents.len().to_string())
+                    .send_bytes(contents)
+            })
             .map_err(|source| Error::ApiRequest {
-                err: ureq_err_to_string(source),
-                activity: "publishing release".to_string(),
+                err: ureq_err_to_string(*source),
+                activity: format!(
+                    "uploading asset {asset_name}. Release has been created but not published!",
+                ),
             })?;
+        }
+    }
+
+    if files.is_some() && !github_config.draft {
+        retry::send(github_config.retries(), || {
+            agent
+                .patch(&response.url)
+                .set("Authorization", &token_header)
+                .send_json(publish_input(github_config))
+        })
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity: "publishing release".to_string(),
+        })?;
     }
+    github_actions::record_url(tag_name, &response.html_url);
 
     Ok(state::GitHub::Initialized { token, agent })
 }
 
+/// The name and contents of every file to upload: each asset (and its Sigstore bundle), then any
+/// checksums and signatures of them.
+fn prepare_uploads(uploads: &Uploads) -> Result<Vec<(String, Vec<u8>)>, Error> {
+    let mut files = Vec::new();
+    let mut sums = String::new();
+    for asset in resolve_assets(uploads)? {
+        let file = std::fs::read(asset.path.to_path("")).map_err(|source| {
+            Error::CouldNotReadAssetFile {
+                path: asset.path.clone(),
+                source,
+            }
+        })?;
+        let asset_name = asset.name()?;
+        if uploads.checksums.is_some() {
+            sums.push_str(&format!("{}  {asset_name}\n", sha256(&file)));
+        }
+        let bundle = if uploads.sigstore {
+            Some(sigstore::sign_file(&asset.path.to_path(""))?)
+        } else {
+            None
+        };
+        files.push((asset_name.clone(), file));
+        if let Some(bundle) = bundle {
+            files.push((format!("{asset_name}.sigstore.json"), bundle));
+        }
+    }
+    if let Some(checksums) = uploads.checksums {
+        let bundle = if uploads.sigstore {
+            Some(sigstore::sign_contents(sums.as_bytes())?)
+        } else {
+            None
+        };
+        let signature = if checksums.sign {
+            Some(match uploads.signing {
+                Some(signing) => git::sign_file(&sums, signing)?,
+                None => git::sign_file(&sums, &config::Signing::default())?,
+            })
+        } else {
+            None
+        };
+        files.push((checksums.name().to_string(), sums.into_bytes()));
+        if let Some(bundle) = bundle {
+            files.push((format!("{}.sigstore.json", checksums.name()), bundle));
+        }
+        if let Some(signature) = signature {
+            files.push((format!("{}.sig", checksums.name()), signature.into_bytes()));
+        }
+    }
+    Ok(files)
+}
+
 fn github_release_dry_run(
     name: &str,
-    assets: Option<&Assets>,
+    uploads: Option<&Uploads>,
     github_release: &CreateReleaseInput,
+    github_config: &config::GitHub,
+    plan: &mut Option<Plan>,
 ) -> Result<(), Error> {
-    let release_type = if github_release.prerelease {
-        "prerelease"
-    } else {
-        "release"
+    let release_type = match (github_config.draft, github_release.prerelease) {
+        (true, true) => "draft prerelease",
+        (true, false) => "draft release",
+        (false, true) => "prerelease",
+        (false, false) => "release",
+    };
+    let body = match github_release.body {
+        None => String::from(" and autogenerated body"),
+        Some(_) if dry_run::is_quiet() => String::new(),
+        Some(body) if github_release.generate_release_notes => {
+            format!(" and body (followed by autogenerated notes):\n{body}")
+        }
+        Some(body) => format!(" and body:\n{body}"),
     };
-    let body = github_release.body.as_ref().map_or_else(
-        || String::from("autogenerated body"),
-        |body| format!("body:\n{body}"),
-    );
     info!(
-        "Would create a {release_type} on GitHub with name {name} and tag {tag} and {body}",
+        "Would create a {release_type} on GitHub with name {name} and tag {tag}{body}",
         tag = github_release.tag_name
     );
+    if github_config.make_latest == Some(false) {
+        info!("Would not mark the release as the latest");
+    }
+    if let Some(category) = &github_config.discussion_category {
+        info!("Would start a discussion in the {category} category");
+    }
 
-    let Some(assets) = assets else {
-        return Ok(());
-    };
-    info!("Would upload assets to GitHub:");
-
-    let assets = resolve_assets(assets)?;
-    for asset in assets {
-        let asset_name = asset.name()?;
-        info!("- {asset_name} from {path}", path = asset.path);
+    let mut asset_names = Vec::new();
+    if let Some(uploads) = uploads {
+        info!("Would upload assets to GitHub:");
+        for asset in resolve_assets(uploads)? {
+            let asset_name = asset.name()?;
+            info!("- {asset_name} from {path}", path = asset.path);
+            asset_names.push(asset_name.clone());
+            if uploads.sigstore {
+                info!("- {asset_name}.sigstore.json with a Sigstore bundle for {asset_name}");
+                asset_names.push(format!("{asset_name}.sigstore.json"));
+            }
+        }
+        if let Some(checksums) = uploads.checksums {
+            let name = checksums.name();
+            info!("- {name} with the SHA-256 checksums of those assets");
+            asset_names.push(name.to_string());
+            if uploads.sigstore {
+                info!("- {name}.sigstore.json with a Sigstore bundle for {name}");
+                asset_names.push(format!("{name}.sigstore.json"));
+            }
+            if checksums.sign {
+                info!("- {name}.sig with a signature of {name}");
+                asset_names.push(format!("{name}.sig"));
+            }
+        }
     }
+    plan::record(plan, |plan| {
+        plan.releases.push(plan::Release {
+            forge: "GitHub",
+            name: name.to_string(),
+            tag: github_release.tag_name.to_string(),
+            body: github_release.body.map(String::from),
+            prerelease: github_release.prerelease,
+            assets: asset_names,
+        });
+    });
     Ok(())
 }
 
-fn resolve_assets(assets: &Assets) -> Result<Vec<Asset>, Error> {
-    match assets {
-        Assets::Glob(pattern) => glob(pattern)?
-            .map(|path| {
-                let path = RelativePathBuf::from_path(&path?)?;
-                Ok(Asset { path, name: None })
-            })
-            .collect(),
-        Assets::List(assets) => Ok(assets.clone()),
+/// The body which turns a draft release into a published one.
+pub(super) fn publish_input(github_config: &config::GitHub) -> serde_json::Value {
+    let mut input = serde_json::Map::new();
+    input.insert("draft".to_string(), false.into());
+    if let Some(make_latest) = github_config.make_latest() {
+        input.insert("make_latest".to_string(), make_latest.into());
+    }
+    input.into()
+}
+
+/// The lowercase hex SHA-256 of `contents`, as `sha256sum` prints it.
+pub(super) fn sha256(contents: &[u8]) -> String {
+    digest(&SHA256, contents)
+        .as_ref()
+        .iter()
+        .fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+pub(crate) fn resolve_assets(uploads: &Uploads) -> Result<Vec<Asset>, Error> {
+    match uploads.assets {
+        Assets::Glob(pattern) => expand_glob(pattern, None),
+        Assets::List(assets) => {
+            let mut resolved = Vec::with_capacity(assets.len());
+            for asset in assets {
+                let asset = asset.resolve_variables(uploads.version);
+                let path = asset.path.as_str();
+                if Pattern::escape(path) == path {
+                    resolved.push(asset);
+                } else {
+                    resolved.extend(expand_glob(path, asset.name.as_deref())?);
+                }
+            }
+            Ok(resolved)
+        }
+    }
+}
+
+/// Find every file matching `pattern`, which must match at least one.
+///
+/// A `name` can only be used if the pattern matches exactly one file.
+fn expand_glob(pattern: &str, name: Option<&str>) -> Result<Vec<Asset>, Error> {
+    let assets = glob(pattern)?
+        .map(|path| {
+            let path = RelativePathBuf::from_path(&path?)?;
+            Ok(Asset::from(path))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    match (assets.as_slice(), name) {
+        ([], _) => Err(Error::NoMatchingAssets {
+            pattern: pattern.to_string(),
+        }),
+        ([asset], Some(name)) => Ok(vec![Asset {
+            name: Some(name.to_string()),
+            ..asset.clone()
+        }]),
+        (_, Some(_)) => Err(Error::AmbiguousAssetName {
+            pattern: pattern.to_string(),
+            count: assets.len(),
+        }),
+        (_, None) => Ok(assets),
     }
 }
 
@@ -156,6 +354,12 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     AppConfig(#[from] app_config::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Sigstore(#[from] sigstore::Error),
     #[error("Trouble communicating with GitHub while {activity}: {err}")]
     #[diagnostic(
         code(github::api_request_error),
@@ -182,6 +386,22 @@ pub(crate) enum Error {
         url("https://knope.tech/reference/config-file/packages/#assets")
     )]
     AssetName(#[from] AssetNameError),
+    #[error("No files match the asset pattern {pattern}")]
+    #[diagnostic(
+        code(github::no_matching_assets),
+        help("Make sure the assets are built before the release, and that the pattern is relative to the current working directory."),
+        url("https://knope.tech/reference/config-file/packages/#assets")
+    )]
+    NoMatchingAssets { pattern: String },
+    #[error("The asset pattern {pattern} has a name but matches {count} files")]
+    #[diagnostic(
+        code(github::ambiguous_asset_name),
+        help(
+            "Remove the `name` so each file uses its own, or make the pattern match only one file."
+        ),
+        url("https://knope.tech/reference/config-file/packages/#assets")
+    )]
+    AmbiguousAssetName { pattern: String, count: usize },
     #[error("Invalid glob pattern: {0}")]
     Pattern(#[from] glob::PatternError),
     #[error("Could not evaluate glob pattern: {0}")]
@@ -193,3 +413,16 @@ pub(crate) enum Error {
     )]
     AssetPath(#[from] relative_path::FromPathError),
 }
+
+#[cfg(test)]
+mod test_sha256 {
+    use super::sha256;
+
+    #[test]
+    fn matches_sha256sum() {
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
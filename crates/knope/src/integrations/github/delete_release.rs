@@ -0,0 +1,112 @@
+use miette::Diagnostic;
+use tracing::info;
+
+use crate::{
+    app_config, config,
+    integrations::{github::initialize_state, retry, ureq_err_to_string, ExistingRelease},
+    state,
+    state::RunType,
+};
+
+/// Delete the release for `tag_name`, or turn it back into a draft if `draft`.
+///
+/// It's not an error if there's no such release, since it may never have been created.
+pub(crate) fn delete_release(
+    tag_name: &str,
+    draft: bool,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            if draft {
+                info!("Would convert the release on GitHub with tag {tag_name} to a draft");
+            } else {
+                info!("Would delete the release on GitHub with tag {tag_name}");
+            }
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let (token, agent) = initialize_state(github_state)?;
+    let releases_url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/releases",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let token_header = format!("token {}", &token);
+
+    let response = retry::send(github_config.retries(), || {
+        agent
+            .get(&format!("{releases_url}/tags/{tag_name}"))
+            .set("Authorization", &token_header)
+            .call()
+    });
+    let release: ExistingRelease = match response {
+        Ok(response) => response.into_json().map_err(|source| Error::ApiResponse {
+            source,
+            activity: "finding a release",
+        })?,
+        Err(err) if matches!(*err, ureq::Error::Status(404, _)) => {
+            info!("No release on GitHub with tag {tag_name}");
+            return Ok(state::GitHub::Initialized { token, agent });
+        }
+        Err(err) => {
+            return Err(Error::ApiRequest {
+                err: ureq_err_to_string(*err),
+                activity: "finding a release",
+            })
+        }
+    };
+
+    let existing_url = format!("{releases_url}/{id}", id = release.id);
+    retry::send(github_config.retries(), || {
+        if draft {
+            agent
+                .patch(&existing_url)
+                .set("Authorization", &token_header)
+                .send_json(ureq::json!({ "draft": true }))
+        } else {
+            agent
+                .delete(&existing_url)
+                .set("Authorization", &token_header)
+                .call()
+        }
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: if draft {
+            "converting a release to a draft"
+        } else {
+            "deleting a release"
+        },
+    })?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+    #[error("Trouble decoding the response from GitHub while {activity}: {source}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+    },
+}
@@ -0,0 +1,157 @@
+use miette::Diagnostic;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info};
+
+use crate::{
+    app_config, config,
+    integrations::{git, github::initialize_state, retry, ureq_err_to_string},
+    state,
+    state::RunType,
+    step::merge_pull_request::MergeMethod,
+};
+
+/// Just enough of a pull request to merge it with either API
+#[derive(Deserialize)]
+struct OpenPullRequest {
+    number: u32,
+    node_id: String,
+}
+
+/// Merge the open pull request from the current branch into `base`, or enable auto-merge on it
+/// if `auto_merge` so GitHub merges it once required checks pass.
+pub(crate) fn merge_pull_request(
+    base: &str,
+    method: MergeMethod,
+    auto_merge: bool,
+    state: RunType<state::GitHub>,
+    config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let branch_ref = git::current_branch()?;
+    let current_branch = branch_ref
+        .strip_prefix("refs/heads/")
+        .unwrap_or(&branch_ref);
+    let state = match state {
+        RunType::DryRun(state) => {
+            if auto_merge {
+                info!("Would enable auto-merge ({method}) on the pull request from {current_branch} to {base}");
+            } else {
+                info!("Would {method} the pull request from {current_branch} to {base}");
+            }
+            return Ok(state);
+        }
+        RunType::Real(state) => state,
+    };
+
+    let (token, agent) = initialize_state(state)?;
+    let config::GitHub { owner, repo, .. } = config;
+    let pulls_url = format!("https://api.github.com/repos/{owner}/{repo}/pulls");
+    let authorization_header = format!("Bearer {}", &token);
+
+    let open_pulls: Vec<OpenPullRequest> = retry::send(config.retries(), || {
+        agent
+            .get(&pulls_url)
+            .set("Accept", "application/vnd.github+json")
+            .set("Authorization", &authorization_header)
+            .query("head", &format!("{owner}:{current_branch}"))
+            .query("base", base)
+            .call()
+    })
+    .map_err(|err| Error::ApiRequest {
+        err: ureq_err_to_string(*err),
+        activity: "fetching open pull requests",
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "fetching open pull requests",
+    })?;
+    let pull = open_pulls.first().ok_or_else(|| Error::NoPullRequest {
+        head: current_branch.to_string(),
+        base: base.to_string(),
+    })?;
+
+    if auto_merge {
+        debug!("Enabling auto-merge on pull request #{}", pull.number);
+        let response: serde_json::Value = retry::send(config.retries(), || {
+            agent
+                .post("https://api.github.com/graphql")
+                .set("Authorization", &authorization_header)
+                .send_json(json!({
+                    "query": "mutation($id: ID!, $method: PullRequestMergeMethod!) { \
+                        enablePullRequestAutoMerge(input: {pullRequestId: $id, mergeMethod: $method}) \
+                        { clientMutationId } }",
+                    "variables": {
+                        "id": pull.node_id,
+                        "method": method.to_string().to_uppercase(),
+                    },
+                }))
+        })
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity: "enabling auto-merge",
+        })?
+        .into_json()
+        .map_err(|source| Error::ApiResponse {
+            source,
+            activity: "enabling auto-merge",
+        })?;
+        // GraphQL reports errors in the body of a successful response
+        if let Some(errors) = response.get("errors") {
+            return Err(Error::ApiRequest {
+                err: errors.to_string(),
+                activity: "enabling auto-merge",
+            });
+        }
+    } else {
+        debug!("Merging pull request #{}", pull.number);
+        retry::send(config.retries(), || {
+            agent
+                .put(&format!("{pulls_url}/{number}/merge", number = pull.number))
+                .set("Accept", "application/vnd.github+json")
+                .set("Authorization", &authorization_header)
+                .send_json(json!({ "merge_method": method }))
+        })
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity: "merging pull request",
+        })?;
+    }
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+    #[error("Trouble decoding the response from GitHub while {activity}: {source}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+    },
+    #[error("No open pull request from {head} to {base} on GitHub")]
+    #[diagnostic(
+        code(github::no_pull_request),
+        help("Create one first, for example with the CreatePullRequest step."),
+        url("https://knope.tech/reference/config-file/steps/merge-pull-request/")
+    )]
+    NoPullRequest { head: String, base: String },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+}
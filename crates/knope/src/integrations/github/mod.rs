@@ -1,13 +1,35 @@
+pub(crate) use bump_formula::{bump_formula, Error as BumpFormulaError, Formula};
+pub(crate) use close_issue::{close_issue, Error as CloseIssueError};
+pub(crate) use close_milestone::{close_milestone, Error as CloseMilestoneError};
 pub(crate) use create_pull_request::{
     create_or_update_pull_request, Error as CreatePullRequestError,
 };
-pub(crate) use create_release::{create_release, Error as CreateReleaseError};
+pub(crate) use create_release::{
+    create_release, resolve_assets, Error as CreateReleaseError, Uploads,
+};
+pub(crate) use delete_release::{delete_release, Error as DeleteReleaseError};
+pub(crate) use merge_pull_request::{merge_pull_request, Error as MergePullRequestError};
+pub(crate) use publish_release::{publish_release, Error as PublishReleaseError};
 use ureq::Agent;
+pub(crate) use wait_for_checks::{wait_for_checks, Error as WaitForChecksError};
+pub(crate) use windows_manifests::{
+    update_scoop_manifest, update_winget_manifest, Error as WindowsManifestError, Installer,
+};
 
 use crate::{app_config, app_config::get_or_prompt_for_github_token, state};
 
+mod body;
+mod bump_formula;
+mod close_issue;
+mod close_milestone;
 mod create_pull_request;
 mod create_release;
+mod delete_release;
+mod merge_pull_request;
+mod publish_release;
+mod repository;
+mod wait_for_checks;
+mod windows_manifests;
 
 fn initialize_state(state: state::GitHub) -> Result<(String, Agent), app_config::Error> {
     Ok(match state {
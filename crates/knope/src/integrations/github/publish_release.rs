@@ -0,0 +1,115 @@
+use miette::Diagnostic;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{
+    app_config, config,
+    integrations::{
+        github::{create_release, initialize_state},
+        retry, ureq_err_to_string,
+    },
+    state,
+    state::RunType,
+};
+
+/// Just enough of a listed release to find the draft for a tag.
+#[derive(Deserialize)]
+struct ListedRelease {
+    id: u64,
+    tag_name: String,
+    draft: bool,
+}
+
+/// Publish the draft release for `tag_name`.
+///
+/// GitHub won't look up drafts by tag, so this searches the most recent releases instead.
+pub(crate) fn publish_release(
+    tag_name: &str,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            info!("Would publish the draft release on GitHub with tag {tag_name}");
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let (token, agent) = initialize_state(github_state)?;
+    let releases_url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/releases",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let token_header = format!("token {}", &token);
+
+    let releases: Vec<ListedRelease> = retry::send(github_config.retries(), || {
+        agent
+            .get(&releases_url)
+            .query("per_page", "100")
+            .set("Authorization", &token_header)
+            .call()
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "listing releases",
+    })?
+    .into_json()
+    .map_err(|source| Error::ApiResponse {
+        source,
+        activity: "listing releases",
+    })?;
+
+    let release = releases
+        .into_iter()
+        .find(|release| release.draft && release.tag_name == tag_name)
+        .ok_or_else(|| Error::NoDraft {
+            tag: tag_name.to_string(),
+        })?;
+
+    retry::send(github_config.retries(), || {
+        agent
+            .patch(&format!("{releases_url}/{id}", id = release.id))
+            .set("Authorization", &token_header)
+            .send_json(create_release::publish_input(github_config))
+    })
+    .map_err(|source| Error::ApiRequest {
+        err: ureq_err_to_string(*source),
+        activity: "publishing a release",
+    })?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+    #[error("Trouble decoding the response from GitHub while {activity}: {source}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+    },
+    #[error("There is no draft release on GitHub with tag {tag}")]
+    #[diagnostic(
+        code(github::no_draft_release),
+        help("Draft releases are created by the Release step when `draft = true` is set in the `[github]` config."),
+        url("https://knope.tech/reference/config-file/steps/publish-release/")
+    )]
+    NoDraft { tag: String },
+}
@@ -0,0 +1,232 @@
+use base64::{prelude::BASE64_STANDARD as base64, Engine};
+use miette::Diagnostic;
+use serde::Deserialize;
+use ureq::Agent;
+
+use crate::integrations::{retry, ureq_err_to_string};
+
+/// A repository on GitHub which Knope proposes changes to with a pull request, like a Homebrew
+/// tap or a Scoop bucket.
+pub(super) struct Repository<'a> {
+    agent: &'a Agent,
+    token_header: &'a str,
+    /// Like `knope-dev/homebrew-tap`
+    full_name: &'a str,
+    retries: u32,
+}
+
+/// A file in a [`Repository`].
+pub(super) struct File {
+    pub(super) contents: String,
+    /// The blob SHA, which is needed to replace the file
+    pub(super) sha: String,
+}
+
+/// A file to write as part of a pull request.
+pub(super) struct Change {
+    pub(super) path: String,
+    pub(super) contents: String,
+    /// The blob SHA of the file being replaced, if it already exists
+    pub(super) sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Entry {
+    pub(super) name: String,
+    #[serde(rename = "type")]
+    pub(super) kind: String,
+}
+
+#[derive(Deserialize)]
+struct Details {
+    default_branch: String,
+}
+
+#[derive(Deserialize)]
+struct Ref {
+    object: Object,
+}
+
+#[derive(Deserialize)]
+struct Object {
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct Contents {
+    content: String,
+    sha: String,
+}
+
+impl<'a> Repository<'a> {
+    pub(super) fn new(
+        agent: &'a Agent,
+        token_header: &'a str,
+        full_name: &'a str,
+        retries: u32,
+    ) -> Self {
+        Self {
+            agent,
+            token_header,
+            full_name,
+            retries,
+        }
+    }
+
+    pub(super) fn default_branch(&self) -> Result<String, Error> {
+        self.get::<Details>("", "fetching a repository")
+            .map(|details| details.default_branch)
+    }
+
+    /// Read the text file at `path` on the branch `git_ref`.
+    pub(super) fn file(&self, path: &str, git_ref: &str) -> Result<File, Error> {
+        let contents: Contents = self.get(
+            &format!("/contents/{path}?ref={git_ref}"),
+            "fetching a file",
+        )?;
+        let text = base64
+            .decode(contents.content.split_whitespace().collect::<String>())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| Error::NotText(path.to_string()))?;
+        Ok(File {
+            contents: text,
+            sha: contents.sha,
+        })
+    }
+
+    /// List the directory at `path` on the branch `git_ref`.
+    pub(super) fn directory(&self, path: &str, git_ref: &str) -> Result<Vec<Entry>, Error> {
+        self.get(
+            &format!("/contents/{path}?ref={git_ref}"),
+            "listing a directory",
+        )
+    }
+
+    /// Write `changes` to a new `branch` of `head`—this repository or a fork of it—starting from
+    /// the `base` branch of this repository, then open a pull request from it into `base`.
+    pub(super) fn propose(
+        &self,
+        head: &Repository,
+        base: &str,
+        branch: &str,
+        title: &str,
+        changes: &[Change],
+    ) -> Result<(), Error> {
+        let start: Ref = self.get(
+            &format!("/git/ref/heads/{base}"),
+            "fetching the base branch",
+        )?;
+        head.send(
+            "POST",
+            "/git/refs",
+            &ureq::json!({ "ref": format!("refs/heads/{branch}"), "sha": start.object.sha }),
+            "creating a branch",
+        )?;
+        for change in changes {
+            let mut body = ureq::json!({
+                "message": title,
+                "content": base64.encode(&change.contents),
+                "branch": branch,
+            });
+            if let (Some(sha), Some(body)) = (&change.sha, body.as_object_mut()) {
+                body.insert("sha".to_string(), sha.clone().into());
+            }
+            head.send(
+                "PUT",
+                &format!("/contents/{path}", path = change.path),
+                &body,
+                "writing a file",
+            )?;
+        }
+        let head_branch = if head.full_name == self.full_name {
+            branch.to_string()
+        } else {
+            let owner = head.full_name.split('/').next().unwrap_or_default();
+            format!("{owner}:{branch}")
+        };
+        self.send(
+            "POST",
+            "/pulls",
+            &ureq::json!({ "title": title, "head": head_branch, "base": base }),
+            "creating a pull request",
+        )
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!(
+            "https://api.github.com/repos/{full_name}{path}",
+            full_name = self.full_name
+        )
+    }
+
+    fn get<T: for<'de> Deserialize<'de>>(
+        &self,
+        path: &str,
+        activity: &'static str,
+    ) -> Result<T, Error> {
+        retry::send(self.retries, || {
+            self.agent
+                .get(&self.url(path))
+                .set("Authorization", self.token_header)
+                .call()
+        })
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity,
+        })?
+        .into_json()
+        .map_err(|source| Error::ApiResponse { source, activity })
+    }
+
+    fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: &serde_json::Value,
+        activity: &'static str,
+    ) -> Result<(), Error> {
+        let request = || {
+            self.agent
+                .request(method, &self.url(path))
+                .set("Authorization", self.token_header)
+                .send_json(body)
+        };
+        if method == "POST" {
+            retry::send_non_idempotent(self.retries, request)
+        } else {
+            retry::send(self.retries, request)
+        }
+        .map_err(|source| Error::ApiRequest {
+            err: ureq_err_to_string(*source),
+            activity,
+        })?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+    #[error("Trouble decoding the response from GitHub while {activity}: {source}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+    },
+    #[error("{0} is not a text file")]
+    #[diagnostic(code(github::not_text), help("Only UTF-8 text files can be updated."))]
+    NotText(String),
+}
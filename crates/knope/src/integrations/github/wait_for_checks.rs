@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use miette::Diagnostic;
+use serde::Deserialize;
+use tracing::info;
+
+use crate::{
+    app_config, config,
+    integrations::{
+        checks,
+        checks::{Check, Status},
+        github::initialize_state,
+        retry, ureq_err_to_string,
+    },
+    state,
+    state::RunType,
+};
+
+#[derive(Deserialize)]
+struct CheckRuns {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+}
+
+impl From<CheckRun> for (String, Check) {
+    fn from(run: CheckRun) -> Self {
+        let check = if run.status != "completed" {
+            Check::Pending
+        } else if matches!(
+            run.conclusion.as_deref(),
+            Some("success" | "neutral" | "skipped")
+        ) {
+            Check::Passed
+        } else {
+            Check::Failed
+        };
+        (run.name, check)
+    }
+}
+
+/// Older integrations report commit statuses instead of check runs.
+#[derive(Deserialize)]
+struct CombinedStatus {
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Deserialize)]
+struct CommitStatus {
+    context: String,
+    state: String,
+}
+
+impl From<CommitStatus> for (String, Check) {
+    fn from(status: CommitStatus) -> Self {
+        let check = match status.state.as_str() {
+            "pending" => Check::Pending,
+            "success" => Check::Passed,
+            _ => Check::Failed,
+        };
+        (status.context, check)
+    }
+}
+
+/// Wait until every check run and commit status on `sha` passes, failing if any fail or if they
+/// take longer than `timeout`.
+pub(crate) fn wait_for_checks(
+    sha: &str,
+    timeout: Duration,
+    state: RunType<state::GitHub>,
+    config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let state = match state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would wait up to {seconds} seconds for checks on the current commit to pass on GitHub",
+                seconds = timeout.as_secs()
+            );
+            return Ok(state);
+        }
+        RunType::Real(state) => state,
+    };
+
+    let (token, agent) = initialize_state(state)?;
+    let config::GitHub { owner, repo, .. } = config;
+    let commit_url = format!("https://api.github.com/repos/{owner}/{repo}/commits/{sha}");
+    let authorization_header = format!("Bearer {}", &token);
+
+    checks::wait::<Error>("GitHub", timeout, || {
+        let check_runs: CheckRuns = retry::send(config.retries(), || {
+            agent
+                .get(&format!("{commit_url}/check-runs"))
+                .set("Accept", "application/vnd.github+json")
+                .set("Authorization", &authorization_header)
+                .query("per_page", "100")
+                .call()
+        })
+        .map_err(|err| Error::ApiRequest {
+            err: ureq_err_to_string(*err),
+            activity: "fetching check runs",
+        })?
+        .into_json()
+        .map_err(|source| Error::ApiResponse {
+            source,
+            activity: "fetching check runs",
+        })?;
+        let combined_status: CombinedStatus = retry::send(config.retries(), || {
+            agent
+                .get(&format!("{commit_url}/status"))
+                .set("Accept", "application/vnd.github+json")
+                .set("Authorization", &authorization_header)
+                .call()
+        })
+        .map_err(|err| Error::ApiRequest {
+            err: ureq_err_to_string(*err),
+            activity: "fetching commit statuses",
+        })?
+        .into_json()
+        .map_err(|source| Error::ApiResponse {
+            source,
+            activity: "fetching commit statuses",
+        })?;
+        Ok(Status::from_checks(
+            check_runs
+                .check_runs
+                .into_iter()
+                .map(<(String, Check)>::from)
+                .chain(combined_status.statuses.into_iter().map(Into::into)),
+        ))
+    })?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Trouble communicating with GitHub while {activity}: {err}")]
+    #[diagnostic(
+        code(github::api_request_error),
+        help(
+            "There was a problem communicating with GitHub, this may be a network issue or a permissions issue."
+        )
+    )]
+    ApiRequest { err: String, activity: &'static str },
+    #[error("Trouble decoding the response from GitHub while {activity}: {source}")]
+    #[diagnostic(
+        code(github::api_response_error),
+        help(
+            "Failure to decode a response from GitHub is probably a bug. Please report it at https://github.com/knope-dev/knope"
+        )
+    )]
+    ApiResponse {
+        source: std::io::Error,
+        activity: &'static str,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Checks(#[from] checks::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+}
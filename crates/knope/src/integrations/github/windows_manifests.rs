@@ -0,0 +1,407 @@
+use std::str::FromStr;
+
+use knope_versioning::semver::Version;
+use miette::Diagnostic;
+use relative_path::RelativePath;
+use serde::Serialize;
+use serde_json::{ser::PrettyFormatter, Serializer, Value};
+use tracing::info;
+
+use crate::{
+    app_config, config,
+    integrations::github::{
+        create_release::sha256,
+        initialize_state, repository,
+        repository::{Change, Repository},
+    },
+    state,
+    state::RunType,
+};
+
+/// The repository that all winget manifests are submitted to.
+const WINGET_PKGS: &str = "microsoft/winget-pkgs";
+
+/// A new version of a Windows package, installed from an asset of a GitHub release.
+pub(crate) struct Installer<'a> {
+    pub(crate) version: String,
+    /// Where to download the installer from
+    pub(crate) url: String,
+    /// The local copy of the asset, to compute its checksum
+    pub(crate) path: &'a RelativePath,
+}
+
+impl Installer<'_> {
+    fn sha256(&self) -> Result<String, Error> {
+        std::fs::read(self.path.to_path(""))
+            .map(|contents| sha256(&contents))
+            .map_err(|source| Error::ReadAsset {
+                path: self.path.to_string(),
+                source,
+            })
+    }
+}
+
+/// Open a pull request against a Scoop bucket which points the manifest at `installer`.
+pub(crate) fn update_scoop_manifest(
+    bucket: &str,
+    manifest: &str,
+    installer: &Installer,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let path = format!("bucket/{manifest}.json");
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would open a pull request against {bucket} setting the url in {path} to {url}",
+                url = installer.url
+            );
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let hash = installer.sha256()?;
+    let (token, agent) = initialize_state(github_state)?;
+    let token_header = format!("token {}", &token);
+    let bucket = Repository::new(&agent, &token_header, bucket, github_config.retries());
+    let default_branch = bucket.default_branch()?;
+    let existing = bucket.file(&path, &default_branch)?;
+    let updated = update_scoop(
+        &existing.contents,
+        &installer.version,
+        &installer.url,
+        &hash,
+    )
+    .ok_or_else(|| Error::UnrecognizedManifest(path.clone()))?;
+    bucket.propose(
+        &bucket,
+        &default_branch,
+        &format!("{manifest}-{version}", version = installer.version),
+        &format!(
+            "{manifest}: Update to version {version}",
+            version = installer.version
+        ),
+        &[Change {
+            path,
+            contents: updated,
+            sha: Some(existing.sha),
+        }],
+    )?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+/// Open a pull request against `microsoft/winget-pkgs` from `fork` which adds manifests for
+/// `installer`, copied from the latest existing version of the package.
+pub(crate) fn update_winget_manifest(
+    identifier: &str,
+    fork: &str,
+    installer: &Installer,
+    github_state: RunType<state::GitHub>,
+    github_config: &config::GitHub,
+) -> Result<state::GitHub, Error> {
+    let directory = winget_directory(identifier);
+    let github_state = match github_state {
+        RunType::DryRun(state) => {
+            info!(
+                "Would open a pull request against {WINGET_PKGS} from {fork} adding {directory}/{version} with the installer {url}",
+                version = installer.version,
+                url = installer.url
+            );
+            return Ok(state);
+        }
+        RunType::Real(github_state) => github_state,
+    };
+
+    let hash = installer.sha256()?.to_uppercase();
+    let (token, agent) = initialize_state(github_state)?;
+    let token_header = format!("token {}", &token);
+    let upstream = Repository::new(&agent, &token_header, WINGET_PKGS, github_config.retries());
+    let fork = Repository::new(&agent, &token_header, fork, github_config.retries());
+    let default_branch = upstream.default_branch()?;
+    let latest = upstream
+        .directory(&directory, &default_branch)?
+        .into_iter()
+        .filter(|entry| entry.kind == "dir")
+        .filter_map(|entry| Some((Version::from_str(&entry.name).ok()?, entry.name)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, name)| name)
+        .ok_or_else(|| Error::NoExistingManifest(directory.clone()))?;
+    let mut changes = Vec::new();
+    for entry in upstream.directory(&format!("{directory}/{latest}"), &default_branch)? {
+        let path = format!("{directory}/{latest}/{name}", name = entry.name);
+        let existing = upstream.file(&path, &default_branch)?;
+        let contents = update_winget(
+            &existing.contents,
+            &installer.version,
+            &installer.url,
+            &hash,
+        )
+        .ok_or(Error::MultipleInstallers(path))?;
+        changes.push(Change {
+            path: format!(
+                "{directory}/{version}/{name}",
+                version = installer.version,
+                name = entry.name
+            ),
+            contents,
+            sha: None,
+        });
+    }
+    upstream.propose(
+        &fork,
+        &default_branch,
+        &format!("{identifier}-{version}", version = installer.version),
+        &format!(
+            "New version: {identifier} version {version}",
+            version = installer.version
+        ),
+        &changes,
+    )?;
+    Ok(state::GitHub::Initialized { token, agent })
+}
+
+/// Where the manifests for `identifier` live, like `manifests/k/Knope/Knope` for `Knope.Knope`.
+fn winget_directory(identifier: &str) -> String {
+    let first = identifier
+        .chars()
+        .next()
+        .map(|first| first.to_lowercase().to_string())
+        .unwrap_or_default();
+    format!("manifests/{first}/{}", identifier.replace('.', "/"))
+}
+
+/// Set the version and the `url` and `hash` of a Scoop manifest, which are in `architecture`
+/// for manifests that don't have a top-level `url`.
+fn update_scoop(manifest: &str, version: &str, url: &str, hash: &str) -> Option<String> {
+    let mut manifest: Value = serde_json::from_str(manifest).ok()?;
+    let root = manifest.as_object_mut()?;
+    root.insert("version".to_string(), version.into());
+    let target = if root.contains_key("url") {
+        root
+    } else if let Some(architecture) = root
+        .get_mut("architecture")
+        .and_then(|architecture| architecture.get_mut("64bit"))
+        .and_then(Value::as_object_mut)
+    {
+        architecture
+    } else {
+        return None;
+    };
+    target.insert("url".to_string(), url.into());
+    target.insert("hash".to_string(), hash.into());
+
+    let mut updated = Vec::new();
+    let mut serializer =
+        Serializer::with_formatter(&mut updated, PrettyFormatter::with_indent(b"    "));
+    manifest.serialize(&mut serializer).ok()?;
+    let mut updated = String::from_utf8(updated).ok()?;
+    updated.push('\n');
+    Some(updated)
+}
+
+/// Set the `PackageVersion`, `InstallerUrl`, and `InstallerSha256` in a winget manifest file.
+///
+/// Returns `None` if the manifest has more than one installer, since there's only one asset to
+/// point them at.
+fn update_winget(manifest: &str, version: &str, url: &str, hash: &str) -> Option<String> {
+    let mut installers = 0;
+    let mut updated = manifest
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let field = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+            let prefix = line.strip_suffix(field).unwrap_or_default();
+            let Some((key, _)) = field.split_once(':') else {
+                return line.to_string();
+            };
+            let value = match key {
+                "PackageVersion" => version,
+                "InstallerUrl" => {
+                    installers += 1;
+                    url
+                }
+                "InstallerSha256" => hash,
+                _ => return line.to_string(),
+            };
+            format!("{prefix}{key}: {value}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if installers > 1 {
+        return None;
+    }
+    if manifest.ends_with('\n') {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    AppConfig(#[from] app_config::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Repository(#[from] repository::Error),
+    #[error("Could not read asset file {path}: {source}")]
+    #[diagnostic(
+        code(github::windows_manifests::read_asset),
+        help("The asset must exist relative to the current working directory to compute its checksum.")
+    )]
+    ReadAsset {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Could not find the `url` and `hash` of the manifest in {0}")]
+    #[diagnostic(
+        code(github::windows_manifests::unrecognized_manifest),
+        help(
+            "The manifest must have a `url`, either at the top level or in `architecture.64bit`."
+        ),
+        url("https://knope.tech/reference/config-file/steps/update-scoop-manifest/")
+    )]
+    UnrecognizedManifest(String),
+    #[error("There are no existing manifests in {0}")]
+    #[diagnostic(
+        code(github::windows_manifests::no_existing_manifest),
+        help("Submit the first version of the package to winget by hand, then Knope can copy it for new versions."),
+        url("https://knope.tech/reference/config-file/steps/update-winget-manifest/")
+    )]
+    NoExistingManifest(String),
+    #[error("The winget manifest {0} has more than one installer")]
+    #[diagnostic(
+        code(github::windows_manifests::multiple_installers),
+        help("Knope points every installer at the same asset, so it can only update packages with a single installer."),
+        url("https://knope.tech/reference/config-file/steps/update-winget-manifest/")
+    )]
+    MultipleInstallers(String),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_update_scoop {
+    use pretty_assertions::assert_eq;
+
+    use super::update_scoop;
+
+    #[test]
+    fn top_level_url() {
+        let manifest = r#"{
+    "version": "1.0.0",
+    "description": "A command line tool",
+    "url": "https://example.com/1.0.0.zip",
+    "hash": "old",
+    "bin": "knope.exe"
+}
+"#;
+        let expected = r#"{
+    "version": "1.1.0",
+    "description": "A command line tool",
+    "url": "https://example.com/1.1.0.zip",
+    "hash": "new",
+    "bin": "knope.exe"
+}
+"#;
+        assert_eq!(
+            update_scoop(manifest, "1.1.0", "https://example.com/1.1.0.zip", "new").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn architecture() {
+        let manifest = r#"{
+    "version": "1.0.0",
+    "architecture": {
+        "64bit": {
+            "url": "https://example.com/1.0.0.zip",
+            "hash": "old"
+        }
+    }
+}"#;
+        let expected = r#"{
+    "version": "1.1.0",
+    "architecture": {
+        "64bit": {
+            "url": "https://example.com/1.1.0.zip",
+            "hash": "new"
+        }
+    }
+}
+"#;
+        assert_eq!(
+            update_scoop(manifest, "1.1.0", "https://example.com/1.1.0.zip", "new").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn no_url() {
+        assert_eq!(
+            update_scoop(r#"{"version": "1.0.0"}"#, "1.1.0", "", ""),
+            None
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_update_winget {
+    use pretty_assertions::assert_eq;
+
+    use super::{update_winget, winget_directory};
+
+    #[test]
+    fn installer_manifest() {
+        let manifest = "PackageIdentifier: Knope.Knope
+PackageVersion: 1.0.0
+Installers:
+- Architecture: x64
+  InstallerUrl: https://example.com/1.0.0.zip
+  InstallerSha256: OLD
+ManifestType: installer
+";
+        let expected = "PackageIdentifier: Knope.Knope
+PackageVersion: 1.1.0
+Installers:
+- Architecture: x64
+  InstallerUrl: https://example.com/1.1.0.zip
+  InstallerSha256: NEW
+ManifestType: installer
+";
+        assert_eq!(
+            update_winget(manifest, "1.1.0", "https://example.com/1.1.0.zip", "NEW").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn first_installer_field() {
+        let manifest = "Installers:\n- InstallerUrl: https://example.com/1.0.0.zip\n";
+        assert_eq!(
+            update_winget(manifest, "1.1.0", "https://example.com/1.1.0.zip", "NEW").unwrap(),
+            "Installers:\n- InstallerUrl: https://example.com/1.1.0.zip\n"
+        );
+    }
+
+    #[test]
+    fn multiple_installers() {
+        let manifest = "Installers:
+- Architecture: x64
+  InstallerUrl: https://example.com/1.0.0-x64.zip
+- Architecture: arm64
+  InstallerUrl: https://example.com/1.0.0-arm64.zip
+";
+        assert_eq!(
+            update_winget(manifest, "1.1.0", "https://example.com/1.1.0.zip", "NEW"),
+            None
+        );
+    }
+
+    #[test]
+    fn directory() {
+        assert_eq!(winget_directory("Knope.Knope"), "manifests/k/Knope/Knope");
+    }
+}
@@ -1,8 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+pub(crate) mod checks;
+pub(crate) mod crates_io;
 pub mod git;
 pub mod gitea;
 pub mod github;
+pub(crate) mod retry;
+pub(crate) mod signing;
+pub(crate) mod sigstore;
+pub(crate) mod webhook;
 
 #[derive(Deserialize)]
 struct PullRequest {
@@ -41,9 +47,16 @@ impl<'a> CreateReleaseInput<'a> {
 #[derive(Deserialize)]
 struct CreateReleaseResponse {
     url: String,
+    html_url: String,
     upload_url: String,
 }
 
+/// Just enough of a release from the forge to change it.
+#[derive(Deserialize)]
+struct ExistingRelease {
+    id: u64,
+}
+
 #[derive(serde::Deserialize)]
 struct ResponseIssue {
     number: usize,
@@ -52,6 +65,12 @@ struct ResponseIssue {
 
 fn ureq_err_to_string(err: ureq::Error) -> String {
     match err {
+        ureq::Error::Status(code, response) if retry::is_rate_limited(code, &response) => {
+            let reset = retry::rate_limit_reset(&response)
+                .map(|wait| format!(", try again in {} seconds", wait.as_secs()))
+                .unwrap_or_default();
+            format!("{code}: rate limited{reset}")
+        }
         ureq::Error::Status(code, response) => {
             format!("{}: {}", code, response.into_string().unwrap_or_default())
         }
@@ -0,0 +1,222 @@
+//! Retrying requests which fail in ways that might be temporary, like server errors and rate
+//! limits.
+
+use std::{
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tracing::info;
+
+/// How many times to retry forge requests if the config doesn't say.
+pub(crate) const DEFAULT_RETRIES: u32 = 3;
+
+/// Rate limits which reset later than this fail right away instead of stalling the workflow.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Call `send` until it succeeds, fails in a way that can't be fixed by retrying, or has been
+/// retried `retries` times—waiting longer after each attempt.
+///
+/// `send` must be idempotent, like a `GET` or `PATCH`, since a request which reached the server
+/// may be sent again. Use [`send_non_idempotent`] for anything else.
+pub(crate) fn send(
+    retries: u32,
+    send: impl FnMut() -> Result<ureq::Response, ureq::Error>,
+) -> Result<ureq::Response, Box<ureq::Error>> {
+    send_with_retries(retries, true, send)
+}
+
+/// Like [`send`], but for requests which would do something twice if sent twice, like a `POST`
+/// which creates a release. These are only retried if the server never handled them.
+pub(crate) fn send_non_idempotent(
+    retries: u32,
+    send: impl FnMut() -> Result<ureq::Response, ureq::Error>,
+) -> Result<ureq::Response, Box<ureq::Error>> {
+    send_with_retries(retries, false, send)
+}
+
+fn send_with_retries(
+    retries: u32,
+    idempotent: bool,
+    mut send: impl FnMut() -> Result<ureq::Response, ureq::Error>,
+) -> Result<ureq::Response, Box<ureq::Error>> {
+    let mut attempt = 0;
+    loop {
+        let err = match send() {
+            Ok(response) => return Ok(response),
+            Err(err) => err,
+        };
+        let Some(delay) = retry_delay(&err, attempt, idempotent).filter(|_| attempt < retries)
+        else {
+            return Err(Box::new(err));
+        };
+        let seconds = delay.as_secs();
+        if matches!(&err, ureq::Error::Status(code, response) if is_rate_limited(*code, response)) {
+            info!("Rate limited, retrying in {seconds} seconds");
+        } else {
+            info!("Request failed, retrying in {seconds} seconds: {err}");
+        }
+        thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+/// How long to wait before retrying, doubling for each previous `attempt`.
+pub(crate) fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.min(6))
+}
+
+/// `None` if `err` won't be fixed by retrying the same request, or if the request isn't
+/// `idempotent` and the server might have handled it already.
+fn retry_delay(err: &ureq::Error, attempt: u32, idempotent: bool) -> Option<Duration> {
+    match err {
+        ureq::Error::Transport(transport) => {
+            (idempotent || never_sent(transport)).then(|| backoff(attempt))
+        }
+        ureq::Error::Status(code, response) if is_rate_limited(*code, response) => {
+            match rate_limit_reset(response) {
+                Some(wait) if wait > MAX_RATE_LIMIT_WAIT => None,
+                Some(wait) => Some(wait),
+                None => Some(backoff(attempt)),
+            }
+        }
+        ureq::Error::Status(code, response) => (idempotent && *code >= 500)
+            .then(|| retry_after(response).unwrap_or_else(|| backoff(attempt))),
+    }
+}
+
+/// Whether `transport` failed before the request could reach the server.
+fn never_sent(transport: &ureq::Transport) -> bool {
+    matches!(
+        transport.kind(),
+        ureq::ErrorKind::Dns | ureq::ErrorKind::ConnectionFailed | ureq::ErrorKind::ProxyConnect
+    )
+}
+
+/// Whether a response is from a rate limit—a 429, or one of GitHub's 403s for rate limits.
+pub(crate) fn is_rate_limited(code: u16, response: &ureq::Response) -> bool {
+    code == 429
+        || (code == 403
+            && (response.header("retry-after").is_some()
+                || response.header("x-ratelimit-remaining") == Some("0")))
+}
+
+/// How long until the rate limit of `response` resets, from `Retry-After` (in seconds) or else
+/// `x-ratelimit-reset` (a UTC epoch timestamp).
+pub(crate) fn rate_limit_reset(response: &ureq::Response) -> Option<Duration> {
+    if let Some(retry_after) = retry_after(response) {
+        return Some(retry_after);
+    }
+    let reset = response.header("x-ratelimit-reset")?.parse::<u64>().ok()?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    // Wait at least a second, in case the clocks disagree
+    Some(Duration::from_secs(reset.saturating_sub(now).max(1)))
+}
+
+fn retry_after(response: &ureq::Response) -> Option<Duration> {
+    response
+        .header("retry-after")
+        .and_then(|seconds| seconds.parse().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_retry_delay {
+    use super::*;
+
+    fn status(code: u16, headers: &str) -> ureq::Error {
+        let response = format!("HTTP/1.1 {code} Status\r\n{headers}\r\n")
+            .parse::<ureq::Response>()
+            .unwrap();
+        ureq::Error::Status(code, response)
+    }
+
+    #[test]
+    fn server_errors_back_off() {
+        assert_eq!(
+            retry_delay(&status(502, ""), 2, true),
+            Some(Duration::from_secs(4))
+        );
+    }
+
+    #[test]
+    fn rate_limits_use_retry_after() {
+        assert_eq!(
+            retry_delay(&status(403, "Retry-After: 30\r\n"), 0, true),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_delay(&status(429, ""), 0, true),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn rate_limits_wait_for_reset() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let delay = retry_delay(
+            &status(
+                403,
+                &format!(
+                    "x-ratelimit-remaining: 0\r\nx-ratelimit-reset: {}\r\n",
+                    now + 30
+                ),
+            ),
+            0,
+            true,
+        )
+        .unwrap();
+        assert!((29..=30).contains(&delay.as_secs()));
+    }
+
+    #[test]
+    fn long_rate_limits_are_not_retried() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(
+            retry_delay(
+                &status(
+                    403,
+                    &format!(
+                        "x-ratelimit-remaining: 0\r\nx-ratelimit-reset: {}\r\n",
+                        now + 3600
+                    ),
+                ),
+                0,
+                true,
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        assert_eq!(retry_delay(&status(403, ""), 0, true), None);
+        assert_eq!(retry_delay(&status(422, ""), 0, true), None);
+    }
+
+    #[test]
+    fn non_idempotent_requests_are_not_retried_after_reaching_the_server() {
+        assert_eq!(retry_delay(&status(502, ""), 0, false), None);
+        let transport = ureq::Error::from(std::io::Error::other("connection reset"));
+        assert_eq!(retry_delay(&transport, 0, false), None);
+        assert_eq!(
+            retry_delay(&transport, 0, true),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn non_idempotent_requests_are_retried_when_rate_limited() {
+        assert_eq!(
+            retry_delay(&status(429, ""), 0, false),
+            Some(Duration::from_secs(1))
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    io::Write,
+    process::{Command, ExitStatus, Stdio},
+};
+
+use miette::Diagnostic;
+use tracing::debug;
+
+use crate::config::{Signing, SigningFormat};
+
+/// Sign `payload` the way Git would, returning the armored signature to embed in a tag or commit.
+///
+/// Anything not set in `signing` comes from the Git config, like Git's own `-S` option.
+/// SSH signatures are made for `namespace`, which is `git` for tags and commits.
+pub(crate) fn sign(
+    payload: &str,
+    signing: &Signing,
+    git_config: &git2::Config,
+    namespace: &str,
+) -> Result<String, Error> {
+    let format = signing.format.unwrap_or_else(|| {
+        match git_config.get_string("gpg.format").ok().as_deref() {
+            Some("ssh") => SigningFormat::Ssh,
+            _ => SigningFormat::OpenPgp,
+        }
+    });
+    let key = signing
+        .key
+        .clone()
+        .or_else(|| git_config.get_string("user.signingkey").ok());
+    let mut command = match format {
+        SigningFormat::OpenPgp => {
+            let program = git_config
+                .get_string("gpg.openpgp.program")
+                .or_else(|_| git_config.get_string("gpg.program"))
+                .unwrap_or_else(|_| String::from("gpg"));
+            let mut command = Command::new(program);
+            command.args(["--detach-sign", "--armor"]);
+            if let Some(key) = key {
+                command.args(["--local-user", &key]);
+            }
+            command
+        }
+        SigningFormat::Ssh => {
+            let program = git_config
+                .get_string("gpg.ssh.program")
+                .unwrap_or_else(|_| String::from("ssh-keygen"));
+            let key = key.ok_or(Error::NoSshKey)?;
+            let mut command = Command::new(program);
+            command.args(["-Y", "sign", "-n", namespace, "-f", &key]);
+            command
+        }
+    };
+    debug!("Signing with {command:?}");
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|err| Error::Io(program.clone(), err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(payload.as_bytes())
+            .map_err(|err| Error::Io(program.clone(), err))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| Error::Io(program.clone(), err))?;
+    if !output.status.success() {
+        return Err(Error::Failed(program, output.status));
+    }
+    String::from_utf8(output.stdout).map_err(|_| Error::NotUtf8(program))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not run {0}: {1}")]
+    #[diagnostic(
+        code(signing::io),
+        help("Make sure the program is installed and on your PATH."),
+        url("https://knope.tech/reference/config-file/signing/")
+    )]
+    Io(String, #[source] std::io::Error),
+    #[error("Signing with {0} failed: {1}")]
+    #[diagnostic(
+        code(signing::failed),
+        help("Check the output above, the key may not exist or may need a passphrase."),
+        url("https://knope.tech/reference/config-file/signing/")
+    )]
+    Failed(String, ExitStatus),
+    #[error("{0} produced a signature which isn't valid UTF-8")]
+    #[diagnostic(code(signing::not_utf8))]
+    NotUtf8(String),
+    #[error("No SSH key to sign with")]
+    #[diagnostic(
+        code(signing::no_ssh_key),
+        help(
+            "Set `key` in the `[signing]` section or Git's `user.signingKey` to the path of a key."
+        ),
+        url("https://knope.tech/reference/config-file/signing/")
+    )]
+    NoSshKey,
+}
@@ -0,0 +1,69 @@
+use std::{
+    fs,
+    io::Write,
+    path::Path,
+    process::{Command, ExitStatus, Stdio},
+};
+
+use miette::Diagnostic;
+use tempfile::NamedTempFile;
+use tracing::debug;
+
+/// Sign the file at `path` with Sigstore keyless signing, returning the bundle to publish with it.
+///
+/// This runs `cosign`, which gets an identity from the CI provider or by opening a browser.
+pub(crate) fn sign_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let bundle = tempfile::Builder::new()
+        .prefix("knope-")
+        .suffix(".sigstore.json")
+        .tempfile()
+        .map_err(Error::TempFile)?;
+    let mut command = Command::new("cosign");
+    command
+        .args(["sign-blob", "--yes", "--bundle"])
+        .arg(bundle.path())
+        .arg(path);
+    debug!("Signing with {command:?}");
+    let status = command.stdout(Stdio::null()).status().map_err(Error::Io)?;
+    if !status.success() {
+        return Err(Error::Failed(status));
+    }
+    fs::read(bundle.path()).map_err(Error::ReadBundle)
+}
+
+/// Like [`sign_file`], for contents that aren't in a file yet.
+pub(crate) fn sign_contents(contents: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut file = NamedTempFile::with_prefix("knope-").map_err(Error::TempFile)?;
+    file.write_all(contents).map_err(Error::TempFile)?;
+    sign_file(file.path())
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not run cosign: {0}")]
+    #[diagnostic(
+        code(sigstore::io),
+        help("Make sure cosign is installed and on your PATH."),
+        url("https://knope.tech/reference/config-file/packages/#sigstore")
+    )]
+    Io(#[source] std::io::Error),
+    #[error("Could not create a temporary file for signing: {0}")]
+    #[diagnostic(code(sigstore::temp_file))]
+    TempFile(#[source] std::io::Error),
+    #[error("Could not read the Sigstore bundle written by cosign: {0}")]
+    #[diagnostic(
+        code(sigstore::read_bundle),
+        help("Check the output above, cosign may not have written a bundle."),
+        url("https://knope.tech/reference/config-file/packages/#sigstore")
+    )]
+    ReadBundle(#[source] std::io::Error),
+    #[error("Signing with cosign failed: {0}")]
+    #[diagnostic(
+        code(sigstore::failed),
+        help(
+            "Check the output above. In CI, the job may need permission to request an OIDC token."
+        ),
+        url("https://knope.tech/reference/config-file/packages/#sigstore")
+    )]
+    Failed(ExitStatus),
+}
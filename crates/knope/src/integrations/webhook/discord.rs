@@ -0,0 +1,18 @@
+use serde_json::json;
+
+use super::{flatten_headings, truncate};
+
+/// Discord's limit for the description of an embed
+const MAX_DESCRIPTION: usize = 4096;
+/// Discord's limit for the title of an embed
+const MAX_TITLE: usize = 256;
+
+/// An embed with the notes as its description, which supports everything but headings.
+pub(super) fn payload(title: &str, notes: &str) -> serde_json::Value {
+    json!({
+        "embeds": [{
+            "title": truncate(title.to_string(), MAX_TITLE),
+            "description": truncate(flatten_headings(notes), MAX_DESCRIPTION),
+        }]
+    })
+}
@@ -0,0 +1,134 @@
+//! Posting release notes to chat services through their incoming webhooks.
+
+use std::{env, fmt};
+
+use miette::Diagnostic;
+use tracing::info;
+
+use super::retry;
+use crate::{dry_run, state::RunType};
+
+mod discord;
+mod teams;
+
+/// A chat service which can receive release notes through a webhook.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Service {
+    Discord,
+    Teams,
+}
+
+impl Service {
+    /// The body to send to the webhook, with `notes` converted from Markdown to what the service
+    /// renders.
+    fn payload(self, title: &str, notes: &str) -> serde_json::Value {
+        match self {
+            Self::Discord => discord::payload(title, notes),
+            Self::Teams => teams::payload(title, notes),
+        }
+    }
+}
+
+impl fmt::Display for Service {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Discord => write!(f, "Discord"),
+            Self::Teams => write!(f, "Microsoft Teams"),
+        }
+    }
+}
+
+/// Post `title` and `notes` (in Markdown) to `webhook`, which is either the URL or an environment
+/// variable containing it, like `$DISCORD_WEBHOOK`.
+pub(crate) fn notify(
+    service: Service,
+    webhook: &str,
+    title: &str,
+    notes: &str,
+    run_type: RunType<()>,
+) -> Result<(), Error> {
+    if let RunType::DryRun(()) = run_type {
+        if dry_run::is_quiet() {
+            info!("Would post {title} to {service}");
+            return Ok(());
+        }
+        info!(
+            "Would post {title} to {service}:\n{}",
+            flatten_headings(notes)
+        );
+        return Ok(());
+    }
+    let url = match webhook.strip_prefix('$') {
+        Some(var) => env::var(var).map_err(|_| Error::MissingEnvVar(var.to_string()))?,
+        None => webhook.to_string(),
+    };
+    let payload = service.payload(title, notes);
+    retry::send(retry::DEFAULT_RETRIES, || {
+        ureq::post(&url).send_json(&payload)
+    })
+    .map_err(|err| Error::Request { service, err })?;
+    info!("Posted {title} to {service}");
+    Ok(())
+}
+
+/// Chat services don't render Markdown headings, so make them bold lines instead.
+fn flatten_headings(markdown: &str) -> String {
+    markdown
+        .trim()
+        .lines()
+        .map(|line| {
+            let heading = line.trim_start_matches('#');
+            if heading.len() < line.len() && heading.starts_with(' ') {
+                format!("**{}**", heading.trim())
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cut `text` down to at most `max` characters, ending with an ellipsis if any were removed.
+fn truncate(text: String, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("The environment variable {0} is not set")]
+    #[diagnostic(
+        code(webhook::missing_env_var),
+        help("Set the variable to the webhook URL, or put the URL in the step instead.")
+    )]
+    MissingEnvVar(String),
+    #[error("Failed to post to {service}: {err}")]
+    #[diagnostic(
+        code(webhook::request),
+        help("Check that the webhook URL is correct and that the webhook hasn't been deleted.")
+    )]
+    Request {
+        service: Service,
+        #[source]
+        err: Box<ureq::Error>,
+    },
+}
+
+#[cfg(test)]
+mod test_flatten_headings {
+    use pretty_assertions::assert_eq;
+
+    use super::flatten_headings;
+
+    #[test]
+    fn headings_become_bold() {
+        assert_eq!(
+            flatten_headings("## Features\n\n- A #1 feature\n\n### Details\n#hashtag"),
+            "**Features**\n\n- A #1 feature\n\n**Details**\n#hashtag"
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use serde_json::json;
+
+use super::flatten_headings;
+
+/// An Adaptive Card, whose text blocks support lists, links, and emphasis but not headings.
+///
+/// Teams ignores single line breaks, so every line becomes its own paragraph.
+pub(super) fn payload(title: &str, notes: &str) -> serde_json::Value {
+    let text = flatten_headings(notes)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": [
+                    {
+                        "type": "TextBlock",
+                        "text": title,
+                        "size": "Large",
+                        "weight": "Bolder",
+                        "wrap": true,
+                    },
+                    {
+                        "type": "TextBlock",
+                        "text": text,
+                        "wrap": true,
+                    },
+                ],
+            },
+        }],
+    })
+}
@@ -0,0 +1,140 @@
+//! Logs as JSON lines with `--log-format json`, so runs in CI can be ingested by log pipelines.
+//!
+//! Every event is one line with its level, target, fields, and the spans (workflows and steps) it
+//! happened in. When a span closes, another line records how long it took.
+
+use std::{
+    io::{stdout, Write},
+    time::Instant,
+};
+
+use serde_json::{json, Map, Value};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// A [`Layer`] which prints everything as JSON lines on stdout.
+pub(crate) struct JsonLayer;
+
+/// Stored with each span so it can be logged with events and timed.
+struct SpanData {
+    fields: Map<String, Value>,
+    start: Instant,
+}
+
+impl<S> Layer<S> for JsonLayer
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        fields.insert(String::from("name"), Value::from(span.name()));
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(SpanData {
+            fields,
+            start: Instant::now(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(data) = extensions.get_mut::<SpanData>() {
+            values.record(&mut FieldVisitor(&mut data.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Map::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .filter_map(|span| {
+                        span.extensions()
+                            .get::<SpanData>()
+                            .map(|data| Value::Object(data.fields.clone()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let metadata = event.metadata();
+        write_line(&json!({
+            "timestamp": timestamp(),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "fields": fields,
+            "spans": spans,
+        }));
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(data) = extensions.get::<SpanData>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        write_line(&json!({
+            "timestamp": timestamp(),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "span": data.fields,
+            "elapsed_ms": data.start.elapsed().as_secs_f64() * 1000.0,
+        }));
+    }
+}
+
+fn timestamp() -> String {
+    OffsetDateTime::now_utc()
+        .format(&Rfc3339)
+        .unwrap_or_default()
+}
+
+fn write_line(line: &Value) {
+    // There's nowhere to report a failure to log
+    let _ = writeln!(stdout().lock(), "{line}");
+}
+
+/// Collects the fields of an event or span into a JSON object.
+struct FieldVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), Value::from(format!("{value:?}")));
+    }
+}
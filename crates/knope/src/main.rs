@@ -1,8 +1,12 @@
-use std::str::FromStr;
+use std::{env, ffi::OsString, mem, path::PathBuf, str::FromStr};
 
 use clap::{arg, command, value_parser, Arg, ArgAction, ArgMatches, Command};
 use itertools::Itertools;
-use knope_versioning::{package, semver::Version};
+use knope_versioning::{
+    package,
+    release_notes::Forge,
+    semver::{StableRule, Version},
+};
 use miette::{miette, Result};
 use tracing::info;
 use tracing_subscriber::{
@@ -14,18 +18,28 @@ use tracing_subscriber::{
 
 use crate::{
     config::{Config, ConfigSource},
-    integrations::git::all_tags_on_branch,
+    integrations::git::{all_tags_on_branch, fetch_missing_history},
     state::{RunType, State},
     step::{releases::Package, Step},
     workflow::Workflow,
 };
 
 mod app_config;
+mod apply;
+mod check_remote;
 mod config;
+mod deprecation;
+mod dry_run;
+mod explain;
 mod fs;
+mod github_actions;
 mod integrations;
+mod json_log;
+mod plan;
+mod progress;
 mod prompt;
 mod state;
+mod status;
 mod step;
 mod variables;
 mod workflow;
@@ -39,109 +53,236 @@ mod workflow;
 /// 3. Selected workflow not found
 /// 4. Passthrough errors of selected workflow
 pub fn main() -> Result<()> {
-    let config = Config::load()?;
+    if let Some(dir) = early_arg(CWD, Some("-C")) {
+        env::set_current_dir(&dir).map_err(|err| {
+            miette!(
+                "Could not change to directory {}: {err}",
+                PathBuf::from(dir).display()
+            )
+        })?;
+    }
+    let config_path = early_arg(CONFIG, None).map_or_else(config_path_from_env, PathBuf::from);
+    let config = Config::load(&config_path)?;
 
     let mut matches = build_cli(&config).get_matches();
 
     let mut config = config.into_inner();
-    let verbose: bool = matches.get_flag(VERBOSE);
-    let level_filter = if verbose {
-        LevelFilter::DEBUG
-    } else {
-        LevelFilter::INFO
-    };
     let validate = *matches
         .try_get_one("validate")
         .ok()
         .flatten()
         .unwrap_or(&false);
-
-    tracing_subscriber::registry()
-        .with(level_filter)
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_target(false)
-                .without_time()
-                .with_level(false)
-                .with_filter(filter_fn(move |metadata| {
-                    !validate && metadata.target().starts_with("knope")
-                })),
-        )
-        .init();
+    let check_remote = *matches
+        .try_get_one(CHECK_REMOTE)
+        .ok()
+        .flatten()
+        .unwrap_or(&false);
+    let json = matches
+        .get_one::<String>(FORMAT)
+        .is_some_and(|format| format == "json");
+    init_tracing(&matches, validate, json);
 
     if let Ok(Some(true)) = matches.try_get_one("generate") {
         info!("Generating a knope.toml file");
         let config = config::generate()?;
-        return config.write_out();
+        return config.write_out(&config_path);
     }
 
     if let Ok(Some(true)) = matches.try_get_one("upgrade") {
-        // If adding new upgrade, make a function to detect and call here.
-        let upgraded = false;
-        return if upgraded {
-            config.write_out()
-        } else {
+        let migrations = config::upgrade_file(&config_path)?;
+        if migrations.is_empty() {
             info!("Nothing to upgrade");
-            Ok(())
-        };
+        }
+        for migration in migrations {
+            info!("{migration}");
+        }
+        return Ok(());
     }
 
+    report_deprecations(&matches, &mut config)?;
+
     let (subcommand, mut sub_matches) = matches.remove_subcommand().unzip();
 
-    sub_matches.as_ref().and_then(|matches| {
-        matches
-            .try_get_one::<String>("prerelease-label")
-            .ok()
-            .flatten()
-            .map(|prerelease_label| {
-                config.set_prerelease_label(prerelease_label);
-            })
-    });
+    if let Some(matches) = &sub_matches {
+        override_prepare_release(&mut config, matches);
+    }
+
+    if subcommand.as_deref() == Some(explain::NAME)
+        && config.workflows.iter().all(|w| w.name != explain::NAME)
+    {
+        return explain::run(&config, sub_matches.as_ref());
+    }
 
-    let (state, workflows) = create_state(config, sub_matches.as_mut())?;
+    let (mut state, workflows) = create_state(config, sub_matches.as_mut())?;
 
     if validate {
-        workflow::validate(workflows, state)?;
-        return Ok(());
+        return validate_config(workflows, state, check_remote);
     }
 
     let subcommand = subcommand.ok_or_else(|| {
         miette!("No workflow selected. Run `knope --help` for a list of options.")
     })?;
+    if subcommand == status::NAME && workflows.iter().all(|w| w.name != subcommand) {
+        status::run(state, &workflows)?;
+        return Ok(());
+    }
+    if subcommand == apply::NAME && workflows.iter().all(|w| w.name != subcommand) {
+        let path = apply::plan_path(sub_matches.as_ref())
+            .ok_or_else(|| miette!("No plan given to apply"))?;
+        apply::run(state, &path)?;
+        return Ok(());
+    }
     let workflow = workflows
-        .into_iter()
+        .iter()
         .find(|w| w.name == subcommand)
         .ok_or_else(|| miette!("No workflow named {}", subcommand))?;
 
-    let state = if matches.get_flag("dry-run") {
+    let plan_path = matches.get_one::<PathBuf>(PLAN).cloned();
+    if plan_path.is_some() {
+        plan::check(workflow, &workflows)?;
+    }
+
+    let first_step = if matches.get_flag(RESUME) {
+        progress::resume(&workflow.name, &mut state)?
+    } else {
+        0
+    };
+
+    dry_run::set_quiet(matches.get_flag(QUIET));
+    if json || plan_path.is_some() {
+        state.plan = Some(plan::Plan::default());
+    }
+    let state = if matches.get_flag("dry-run") || plan_path.is_some() {
         RunType::DryRun(state)
     } else {
         RunType::Real(state)
     };
 
-    workflow::run(workflow, state)?;
+    github_actions::start();
+    let (run_type, mut state) = workflow::run(workflow, &workflows, state, first_step)?.take();
+    plan::finish(state.plan.take(), plan_path.as_deref(), &workflow.name)?;
+    github_actions::finish(&run_type.of(state))?;
+    Ok(())
+}
+
+/// Print logs as text, or as JSON lines with `--log-format json`.
+///
+/// Text logs are hidden while validating (except deprecations), and a `json` dry run only prints
+/// the plan.
+fn init_tracing(matches: &ArgMatches, validate: bool, json: bool) {
+    let verbose: bool = matches.get_flag(VERBOSE);
+    let level_filter = if verbose {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let json_logs = matches
+        .get_one::<String>(LOG_FORMAT)
+        .is_some_and(|format| format == "json");
+
+    tracing_subscriber::registry()
+        .with(level_filter)
+        .with((!json_logs).then(|| {
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .without_time()
+                .with_level(false)
+                .with_filter(filter_fn(move |metadata| {
+                    // Spans are only for JSON logs
+                    if metadata.is_span() {
+                        false
+                    } else if json {
+                        metadata.target() == plan::TARGET
+                    } else {
+                        metadata.target() == deprecation::TARGET
+                            || (!validate && metadata.target().starts_with("knope"))
+                    }
+                }))
+        }))
+        .with(json_logs.then(|| {
+            json_log::JsonLayer
+                .with_filter(filter_fn(|metadata| metadata.target().starts_with("knope")))
+        }))
+        .init();
+}
+
+/// Warn about any deprecated config, failing instead if `--deny-deprecated` was passed.
+fn report_deprecations(
+    matches: &ArgMatches,
+    config: &mut Config,
+) -> Result<(), deprecation::Denied> {
+    let deny = *matches
+        .try_get_one(DENY_DEPRECATED)
+        .ok()
+        .flatten()
+        .unwrap_or(&false);
+    deprecation::report(mem::take(&mut config.deprecations), deny)
+}
+
+/// Check the integrations that can be checked without prompting, then every workflow.
+///
+/// If `check_remote`, every configured forge (and Jira) must be usable with the stored
+/// credentials.
+fn validate_config(workflows: Vec<Workflow>, state: State, check_remote: bool) -> Result<()> {
+    if check_remote {
+        check_remote::run(
+            state.github_config.as_ref(),
+            state.gitea_config.as_ref(),
+            state.jira_config.as_ref(),
+            &workflows,
+        )?;
+    } else if let Some(jira_config) = &state.jira_config {
+        step::issues::jira::validate_project(jira_config)?;
+    }
+    workflow::validate(workflows, state)?;
     Ok(())
 }
 
 const OVERRIDE_ONE_VERSION: &str = "override-one-version";
 const OVERRIDE_MULTIPLE_VERSIONS: &str = "override-multiple-versions";
 const PRERELEASE_LABEL: &str = "prerelease-label";
+const RULE: &str = "rule";
+const PACKAGE: &str = "package";
 const VERBOSE: &str = "verbose";
+const CHECK_REMOTE: &str = "check-remote";
+const DENY_DEPRECATED: &str = "deny-deprecated";
+const QUIET: &str = "quiet";
+const FORMAT: &str = "format";
+const PLAN: &str = "plan";
+const RESUME: &str = "resume";
+const LOG_FORMAT: &str = "log-format";
+const CONFIG: &str = "config";
+const CONFIG_ENV: &str = "KNOPE_CONFIG";
+
+const CWD: &str = "cwd";
+
+/// Find the value of an argument which is needed before the CLI can be built (it depends on the
+/// config file, which defines the workflows). Supports `--long value`, `--long=value`, and
+/// `-s value`.
+fn early_arg(long: &str, short: Option<&str>) -> Option<OsString> {
+    let long_flag = format!("--{long}");
+    let long_prefix = format!("--{long}=");
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == *long_flag || short.is_some_and(|short| arg == short) {
+            return args.next();
+        }
+        if let Some(value) = arg.to_str().and_then(|arg| arg.strip_prefix(&long_prefix)) {
+            return Some(value.into());
+        }
+    }
+    None
+}
+
+fn config_path_from_env() -> PathBuf {
+    env::var_os(CONFIG_ENV).map_or_else(Config::default_path, PathBuf::from)
+}
 
 fn build_cli(config: &ConfigSource) -> Command {
-    let mut command = command!()
-        .propagate_version(true)
-        .arg(
-            Arg::new("dry-run").long("dry-run")
-                .help("Pretend to run a workflow, outputting what _would_ happen without actually doing it.")
-                .action(ArgAction::SetTrue)
-                .global(true)
-        ).arg(
-        Arg::new(VERBOSE).long(VERBOSE).short('v')
-            .help("Print extra information (for debugging)")
-            .action(ArgAction::SetTrue)
-            .global(true)
-    );
+    let mut command = command!().propagate_version(true).args(global_args());
     let config = match config {
         ConfigSource::Default(config) => {
             command = command
@@ -151,6 +292,18 @@ fn build_cli(config: &ConfigSource) -> Command {
         ConfigSource::File(config) | ConfigSource::Hybrid(config) => {
             command = command.arg(arg!(--upgrade "Upgrade to the latest `knope.toml` syntax from any deprecated (but still supported) syntax."));
             command = command.arg(arg!(--validate "Check that the `knope.toml` file is valid."));
+            command = command.arg(
+                Arg::new(CHECK_REMOTE).long(CHECK_REMOTE)
+                    .help("With `--validate`, also check that the configured forges and Jira can be used with the available tokens.")
+                    .action(ArgAction::SetTrue)
+                    .requires("validate"),
+            );
+            command = command.arg(
+                Arg::new(DENY_DEPRECATED).long(DENY_DEPRECATED)
+                    .help("Fail instead of warning when the config uses deprecated syntax, useful in CI.")
+                    .action(ArgAction::SetTrue)
+                    .global(true),
+            );
             config
         }
     };
@@ -171,6 +324,9 @@ fn build_cli(config: &ConfigSource) -> Command {
 
     for workflow in &config.workflows {
         let mut subcommand = Command::new(workflow.name.clone());
+        if config.packages.len() > 1 {
+            subcommand = subcommand.arg(package_arg());
+        }
 
         if let Some(help) = &workflow.help_text {
             subcommand = subcommand.about(help);
@@ -179,31 +335,148 @@ fn build_cli(config: &ConfigSource) -> Command {
         let contains_bump_version = workflow
             .steps
             .iter()
-            .any(|step| matches!(*step, Step::BumpVersion(_)));
+            .any(|step| matches!(step.step, Step::BumpVersion(_)));
         let contains_prepare_release = workflow
             .steps
             .iter()
-            .any(|step| matches!(*step, Step::PrepareRelease(_)));
+            .any(|step| matches!(step.step, Step::PrepareRelease(_)));
         if contains_bump_version || contains_prepare_release {
             if let Some(arg) = version_override_arg.clone() {
                 subcommand = subcommand.arg(arg);
             }
         }
         if contains_prepare_release {
-            subcommand = subcommand
-                .arg(
-                    Arg::new(PRERELEASE_LABEL)
-                        .long("prerelease-label")
-                        .help("Set the `prerelease_label` attribute of any `PrepareRelease` steps at runtime.")
-                        .env("KNOPE_PRERELEASE_LABEL")
-                );
+            subcommand = subcommand.args(prepare_release_args());
         }
 
         command = command.subcommand(subcommand);
     }
+    if !config.workflows.is_empty()
+        && config
+            .workflows
+            .iter()
+            .all(|workflow| workflow.name != explain::NAME)
+    {
+        command = command.subcommand(explain::subcommand(&config.workflows));
+    }
+    if config
+        .workflows
+        .iter()
+        .all(|workflow| workflow.name != status::NAME)
+    {
+        command = command.subcommand(status::subcommand());
+    }
+    if config
+        .workflows
+        .iter()
+        .all(|workflow| workflow.name != apply::NAME)
+    {
+        command = command.subcommand(apply::subcommand());
+    }
     command
 }
 
+/// Select some packages to operate on, instead of all of them.
+fn package_arg() -> Arg {
+    Arg::new(PACKAGE)
+        .long(PACKAGE)
+        .help("Only operate on this package, ignoring the rest. Can be set multiple times.")
+        .action(ArgAction::Append)
+}
+
+/// Arguments which every workflow accepts.
+fn global_args() -> [Arg; 9] {
+    [
+        Arg::new("dry-run")
+            .long("dry-run")
+            .help("Pretend to run a workflow, outputting what _would_ happen without actually doing it.")
+            .action(ArgAction::SetTrue)
+            .global(true),
+        Arg::new(VERBOSE)
+            .long(VERBOSE)
+            .short('v')
+            .help("Print extra information (for debugging)")
+            .action(ArgAction::SetTrue)
+            .global(true),
+        Arg::new(QUIET)
+            .long(QUIET)
+            .short('q')
+            .help("With `--dry-run`, print only a summary of each action, without diffs or release notes.")
+            .action(ArgAction::SetTrue)
+            .requires("dry-run")
+            .global(true),
+        Arg::new(FORMAT)
+            .long(FORMAT)
+            .help("The output format of a dry run. `json` prints only a plan of what would happen.")
+            .value_parser(["text", "json"])
+            .requires("dry-run")
+            .global(true),
+        Arg::new(PLAN)
+            .long(PLAN)
+            .help("Save what the workflow would do to this file (like `--dry-run`), to be carried out later with `knope apply`.")
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with(FORMAT)
+            .global(true),
+        Arg::new(RESUME)
+            .long(RESUME)
+            .help("Continue the workflow after the last step that finished in a run that failed, instead of starting over.")
+            .action(ArgAction::SetTrue)
+            .conflicts_with(PLAN)
+            .global(true),
+        Arg::new(LOG_FORMAT)
+            .long(LOG_FORMAT)
+            .help("Print logs as `text` (the default) or as `json` lines with levels, targets, and span timings.")
+            .value_parser(["text", "json"])
+            .conflicts_with(FORMAT)
+            .global(true),
+        Arg::new(CONFIG)
+            .long(CONFIG)
+            .help("The path to the config file to use instead of `knope.toml`.")
+            .env(CONFIG_ENV)
+            .value_parser(value_parser!(PathBuf))
+            .global(true),
+        Arg::new(CWD)
+            .long(CWD)
+            .short('C')
+            .help("Run as if Knope was started in this directory instead of the current one.")
+            .value_parser(value_parser!(PathBuf))
+            .global(true),
+    ]
+}
+
+/// Arguments which override options of `PrepareRelease` steps at runtime.
+fn prepare_release_args() -> [Arg; 2] {
+    [
+        Arg::new(PRERELEASE_LABEL)
+            .long("prerelease-label")
+            .help("Set the `prerelease_label` attribute of any `PrepareRelease` steps at runtime.")
+            .env("KNOPE_PRERELEASE_LABEL"),
+        Arg::new(RULE)
+            .long("rule")
+            .help("Bump by this rule in any `PrepareRelease` steps, no matter what the changes imply.")
+            .value_parser(["major", "minor", "patch"])
+            .env("KNOPE_RULE"),
+    ]
+}
+
+/// Apply the values of [`prepare_release_args`] to every `PrepareRelease` step in `config`.
+fn override_prepare_release(config: &mut Config, matches: &ArgMatches) {
+    if let Some(prerelease_label) = matches
+        .try_get_one::<String>(PRERELEASE_LABEL)
+        .ok()
+        .flatten()
+    {
+        config.set_prerelease_label(prerelease_label);
+    }
+    if let Some(rule) = matches.try_get_one::<String>(RULE).ok().flatten() {
+        config.set_rule(match rule.as_str() {
+            "major" => StableRule::Major,
+            "minor" => StableRule::Minor,
+            _ => StableRule::Patch,
+        });
+    }
+}
+
 fn create_state(
     config: Config,
     mut sub_matches: Option<&mut ArgMatches>,
@@ -214,14 +487,33 @@ fn create_state(
         jira,
         github,
         gitea,
+        signing,
+        git,
+        deprecations: _,
     } = config;
+    let selected = sub_matches
+        .as_deref_mut()
+        .and_then(|matches| matches.try_remove_many::<String>(PACKAGE).ok())
+        .flatten();
+    let packages = select_packages(packages, selected)?;
     let git_tags = if packages.is_empty() {
         // Don't mess with Git if there aren't any packages defined
         Vec::new()
     } else {
+        if git.is_some_and(|git| git.fetch_tags) {
+            let remote = github
+                .as_ref()
+                .and_then(|github| github.remote.as_deref())
+                .or_else(|| gitea.as_ref().and_then(|gitea| gitea.remote.as_deref()));
+            fetch_missing_history(remote)?;
+        }
         all_tags_on_branch().unwrap_or_default()
     };
-    let (mut packages, versioned_files) = Package::load(packages, &git_tags)?;
+    let forge = github
+        .as_ref()
+        .map(Forge::from)
+        .or_else(|| gitea.as_ref().map(Forge::from));
+    let (mut packages, versioned_files) = Package::load(packages, &git_tags, forge.as_ref())?;
     if let Some(version_override) = sub_matches
         .as_deref_mut()
         .and_then(|matches| matches.try_remove_one::<Version>(OVERRIDE_ONE_VERSION).ok())
@@ -265,10 +557,41 @@ fn create_state(
         }
     }
 
-    let state = State::new(jira, github, gitea, packages, versioned_files, git_tags);
+    let state = State::new(
+        jira,
+        github,
+        gitea,
+        signing,
+        packages,
+        versioned_files,
+        git_tags,
+    );
     Ok((state, workflows))
 }
 
+/// Keep only the `selected` packages (set by `--package`), or all of them if there's no selection.
+fn select_packages(
+    packages: Vec<config::Package>,
+    selected: Option<impl Iterator<Item = String>>,
+) -> Result<Vec<config::Package>> {
+    let Some(selected) = selected else {
+        return Ok(packages);
+    };
+    let selected = selected.map(package::Name::from).collect_vec();
+    let unknown = selected
+        .iter()
+        .filter(|name| packages.iter().all(|package| package.name != **name))
+        .join(", ");
+    if !unknown.is_empty() {
+        return Err(miette!("Unknown package(s) to select: {unknown}"));
+    }
+    let packages = packages
+        .into_iter()
+        .filter(|package| selected.contains(&package.name))
+        .collect_vec();
+    Ok(packages)
+}
+
 #[derive(Clone, Debug)]
 struct VersionOverride {
     package: package::Name,
@@ -0,0 +1,190 @@
+//! Collects everything a dry run _would_ do so it can be printed as JSON with `--format json`,
+//! or saved with `--plan` to be carried out later by `knope apply`.
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use knope_versioning::semver::Version;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{fs, fs::WriteType, integrations::git, step::Step, workflow::Workflow};
+
+/// The tracing target of the final JSON output, which is the only thing printed in JSON mode.
+pub(crate) const TARGET: &str = "knope::plan";
+
+/// Everything a dry run would do, collected in [`crate::state::State::plan`].
+#[derive(Clone, Debug, Default, Serialize)]
+pub(crate) struct Plan {
+    pub(crate) files: Vec<File>,
+    pub(crate) deleted_files: Vec<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) releases: Vec<Release>,
+    pub(crate) commands: Vec<String>,
+    /// The message of each commit
+    pub(crate) commits: Vec<String>,
+    /// The new version of each package, for `--plan`
+    #[serde(skip)]
+    pub(crate) versions: IndexMap<String, Version>,
+    /// Everything above (and more), in order, for `--plan`
+    #[serde(skip)]
+    pub(crate) actions: Vec<Action>,
+}
+
+/// A plan saved by `--plan`, which `knope apply` carries out.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct Saved {
+    /// The workflow that the plan was made from
+    pub(crate) workflow: String,
+    /// The commit that the plan was made at, it can only be applied there
+    pub(crate) head: Option<String>,
+    pub(crate) versions: IndexMap<String, Version>,
+    pub(crate) actions: Vec<Action>,
+}
+
+/// One thing that a workflow would do, with everything needed to do it later.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Action {
+    WriteFile {
+        path: String,
+        content: String,
+    },
+    DeleteFile {
+        path: String,
+    },
+    StageFiles {
+        paths: Vec<String>,
+    },
+    Commit {
+        message: String,
+        files: Vec<String>,
+        signed: bool,
+    },
+    Tag {
+        name: String,
+        message: String,
+        signed: bool,
+    },
+    /// A release on every configured forge
+    Release {
+        package: Option<String>,
+        version: Version,
+        title: String,
+        notes: String,
+    },
+    Command {
+        command: String,
+        shell: bool,
+        env: IndexMap<String, String>,
+    },
+    Push,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct File {
+    pub(crate) path: String,
+    /// A unified diff of the change (same as the human-readable output)
+    pub(crate) diff: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Release {
+    pub(crate) forge: &'static str,
+    pub(crate) name: String,
+    pub(crate) tag: String,
+    /// `None` if the forge should generate the body
+    pub(crate) body: Option<String>,
+    pub(crate) prerelease: bool,
+    pub(crate) assets: Vec<String>,
+}
+
+/// Add to `plan`, if one is being collected.
+pub(crate) fn record(plan: &mut Option<Plan>, update: impl FnOnce(&mut Plan)) {
+    if let Some(plan) = plan {
+        update(plan);
+    }
+}
+
+/// Write `plan` to `path` for `knope apply`.
+fn save(plan: Plan, path: &Path, workflow: &str) -> Result<(), Error> {
+    let saved = Saved {
+        workflow: workflow.to_string(),
+        head: git::head_commit_id().ok(),
+        versions: plan.versions,
+        actions: plan.actions,
+    };
+    let json = serde_json::to_string_pretty(&saved).map_err(Error::Serialize)?;
+    fs::write(
+        WriteType::Real::<String, String>(json + "\n"),
+        path,
+        &mut None,
+    )?;
+    info!("Saved the plan to {}", path.display());
+    Ok(())
+}
+
+/// Make sure that every step `workflow` runs (including through `RunWorkflow`) can be planned.
+pub(crate) fn check(workflow: &Workflow, workflows: &[Workflow]) -> Result<(), Error> {
+    check_steps(workflow, workflows, &mut Vec::new())
+}
+
+/// Check the steps of `workflow`, unless it's in `visited` (a recursive workflow is reported when
+/// it runs).
+fn check_steps<'a>(
+    workflow: &'a Workflow,
+    workflows: &'a [Workflow],
+    visited: &mut Vec<&'a str>,
+) -> Result<(), Error> {
+    if visited.contains(&workflow.name.as_str()) {
+        return Ok(());
+    }
+    visited.push(&workflow.name);
+    for workflow_step in workflow.steps.iter().chain(&workflow.on_error) {
+        let step = &workflow_step.step;
+        if !step.can_plan() {
+            return Err(Error::UnsupportedStep {
+                workflow: workflow.name.clone(),
+                step: step.name(),
+            });
+        }
+        if let Step::RunWorkflow { name } = step {
+            if let Some(other) = workflows.iter().find(|other| &other.name == name) {
+                check_steps(other, workflows, visited)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Save `plan` to `path` if there is one, otherwise print it as JSON.
+pub(crate) fn finish(plan: Option<Plan>, path: Option<&Path>, workflow: &str) -> Result<(), Error> {
+    match (plan, path) {
+        (Some(plan), Some(path)) => save(plan, path, workflow),
+        (Some(plan), None) => {
+            if let Ok(json) = serde_json::to_string_pretty(&plan) {
+                info!(target: TARGET, "{json}");
+            }
+            Ok(())
+        }
+        (None, _) => Ok(()),
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not serialize the plan: {0}")]
+    #[diagnostic(code(plan::serialize))]
+    Serialize(serde_json::Error),
+    #[error("The {step} step of the {workflow} workflow can't be planned")]
+    #[diagnostic(
+        code(plan::unsupported_step),
+        help("Steps that need input while running can't be planned, run this workflow without `--plan`."),
+        url("https://knope.tech/reference/command-line-arguments/#--plan")
+    )]
+    UnsupportedStep { workflow: String, step: String },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+}
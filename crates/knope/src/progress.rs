@@ -0,0 +1,199 @@
+//! Progress through a workflow, saved to `.knope/state.json` when a step fails so that `--resume`
+//! can continue after the last step that succeeded instead of starting over.
+
+use std::path::Path;
+
+use indexmap::IndexMap;
+use knope_versioning::{package::Name, release_notes, semver::Version, Action};
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    fs,
+    fs::WriteType,
+    integrations::git,
+    state::{RunType, State},
+    step::releases,
+};
+
+const DIR: &str = ".knope";
+const FILE: &str = ".knope/state.json";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Progress {
+    workflow: String,
+    /// How many steps of the workflow finished
+    completed: usize,
+    /// The commit after the last finished step, resuming from anywhere else could redo a release
+    head: Option<String>,
+    /// Whether there are releases prepared by an earlier step which haven't been released yet
+    prepared: bool,
+    released: Vec<Release>,
+    outputs: IndexMap<String, String>,
+    modified_files: Vec<RelativePathBuf>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Release {
+    package: Option<String>,
+    version: Version,
+    title: String,
+    notes: String,
+}
+
+impl From<&release_notes::Release> for Release {
+    fn from(release: &release_notes::Release) -> Self {
+        Self {
+            package: release.package_name.as_custom().map(String::from),
+            version: release.version.clone(),
+            title: release.title.clone(),
+            notes: release.notes.clone(),
+        }
+    }
+}
+
+impl From<Release> for release_notes::Release {
+    fn from(release: Release) -> Self {
+        Self {
+            title: release.title,
+            version: release.version,
+            notes: release.notes,
+            package_name: release.package.map_or(Name::Default, Name::Custom),
+        }
+    }
+}
+
+/// Record that the first `completed` steps of `workflow` finished, ending with `state`.
+///
+/// Nothing is saved for dry runs. Failing to save shouldn't fail the workflow, so any problem is
+/// only logged.
+pub(crate) fn save(workflow: &str, completed: usize, state: &RunType<State>) {
+    let RunType::Real(state) = state else {
+        return;
+    };
+    let progress = Progress {
+        workflow: workflow.to_string(),
+        completed,
+        head: git::head_commit_id().ok(),
+        prepared: state
+            .pending_actions
+            .iter()
+            .any(|action| matches!(action, Action::CreateRelease(_))),
+        released: state.released.iter().map(Release::from).collect(),
+        outputs: state.outputs.clone(),
+        modified_files: state.modified_files.clone(),
+    };
+    if let Err(err) = write(&progress) {
+        warn!("Could not save progress to {FILE}: {err}");
+    }
+}
+
+fn write(progress: &Progress) -> Result<(), Error> {
+    let dir = Path::new(DIR);
+    if !dir.exists() {
+        fs::create_dir(RunType::Real(dir))?;
+        // The progress of a run is never something to commit
+        fs::write(
+            WriteType::Real::<&str, String>("*\n"),
+            &dir.join(".gitignore"),
+            &mut None,
+        )?;
+    }
+    let json = serde_json::to_string_pretty(progress).map_err(Error::Serialize)?;
+    fs::write(
+        WriteType::Real::<String, String>(json + "\n"),
+        Path::new(FILE),
+        &mut None,
+    )?;
+    Ok(())
+}
+
+/// Forget any saved progress, once a workflow finishes or a new run starts.
+///
+/// `.knope` is removed too, unless something other than the progress is in it.
+pub(crate) fn clear() {
+    let path = Path::new(FILE);
+    if path.exists() {
+        // Leftover progress is harmless, it's checked before resuming
+        fs::remove_file(RunType::Real(path), &mut None).ok();
+    }
+    let dir = Path::new(DIR);
+    let only_progress = std::fs::read_dir(dir).is_ok_and(|mut entries| {
+        entries.all(|entry| entry.is_ok_and(|entry| entry.file_name() == ".gitignore"))
+    });
+    if only_progress {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+/// Restore what earlier steps of `workflow` left in `state`, returning how many steps to skip.
+pub(crate) fn resume(workflow: &str, state: &mut State) -> Result<usize, Error> {
+    let path = Path::new(FILE);
+    if !path.exists() {
+        return Err(Error::NothingToResume);
+    }
+    let progress: Progress =
+        serde_json::from_str(&fs::read_to_string(path)?).map_err(Error::Parse)?;
+    if progress.workflow != workflow {
+        return Err(Error::OtherWorkflow {
+            saved: progress.workflow,
+            requested: workflow.to_string(),
+        });
+    }
+    if let Some(saved) = progress.head {
+        let current = git::head_commit_id().ok();
+        if current.as_ref() != Some(&saved) {
+            return Err(Error::Moved {
+                saved,
+                current: current.unwrap_or_default(),
+            });
+        }
+    }
+    if progress.prepared {
+        releases::find_prepared_releases(state);
+    }
+    state.released = progress.released.into_iter().map(Into::into).collect();
+    state.outputs = progress.outputs;
+    state.modified_files = progress.modified_files;
+    info!("Resuming {workflow} from step {}", progress.completed + 1);
+    Ok(progress.completed)
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("There's no saved progress to resume")]
+    #[diagnostic(
+        code(resume::nothing_to_resume),
+        help("Progress is only saved when a workflow fails partway through, run it without `--resume`."),
+        url("https://knope.tech/reference/command-line-arguments/#--resume")
+    )]
+    NothingToResume,
+    #[error("The saved progress is for the {saved} workflow, not {requested}")]
+    #[diagnostic(
+        code(resume::other_workflow),
+        help("Resume the {saved} workflow, or run {requested} without `--resume`."),
+        url("https://knope.tech/reference/command-line-arguments/#--resume")
+    )]
+    OtherWorkflow { saved: String, requested: String },
+    #[error("The saved progress was at commit {saved}, but the current commit is {current}")]
+    #[diagnostic(
+        code(resume::moved),
+        help("Check out the commit that the workflow stopped at, or run it without `--resume`."),
+        url("https://knope.tech/reference/command-line-arguments/#--resume")
+    )]
+    Moved { saved: String, current: String },
+    #[error("Could not parse the saved progress in .knope/state.json: {0}")]
+    #[diagnostic(
+        code(resume::parse),
+        help("Delete .knope/state.json and run the workflow without `--resume`.")
+    )]
+    Parse(serde_json::Error),
+    #[error("Could not serialize progress: {0}")]
+    #[diagnostic(code(resume::serialize))]
+    Serialize(serde_json::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+}
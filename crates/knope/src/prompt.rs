@@ -1,12 +1,16 @@
 use std::fmt::Display;
 
-use inquire::{InquireError, Password, Select};
+use inquire::{InquireError, Password, Select, Text};
 use miette::{Diagnostic, Result};
 
 pub(crate) fn select<T: Display>(items: Vec<T>, prompt: &str) -> Result<T, Error> {
     Select::new(prompt, items).prompt().map_err(Error)
 }
 
+pub(crate) fn get_text(prompt: &str) -> Result<String, Error> {
+    Text::new(prompt).prompt().map_err(Error)
+}
+
 pub(crate) fn get_input(prompt: &str) -> Result<String, Error> {
     Password::new(prompt)
         .with_display_toggle_enabled()
@@ -1,9 +1,14 @@
 use std::fmt::Debug;
 
-use knope_versioning::{Action, VersionedFile};
+use indexmap::IndexMap;
+use itertools::Itertools;
+use knope_versioning::{package::Name, release_notes::Release, Action, VersionedFile};
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
 
 use crate::{
     config,
+    plan::Plan,
     step::{issues, releases},
 };
 
@@ -15,11 +20,20 @@ pub(crate) struct State {
     pub(crate) gitea: Gitea,
     pub(crate) gitea_config: Option<config::Gitea>,
     pub(crate) github_config: Option<config::GitHub>,
+    pub(crate) signing: Option<config::Signing>,
     pub(crate) issue: Issue,
     pub(crate) packages: Vec<releases::Package>,
     pub(crate) all_versioned_files: Vec<VersionedFile>,
     pub(crate) pending_actions: Vec<Action>,
+    /// Releases created by earlier `Release` steps, for later steps to announce
+    pub(crate) released: Vec<Release>,
     pub(crate) all_git_tags: Vec<String>,
+    /// Output of `Command` steps with `capture_output`, by name
+    pub(crate) outputs: IndexMap<String, String>,
+    /// Files written or removed by earlier steps, which the `Commit` step stages
+    pub(crate) modified_files: Vec<RelativePathBuf>,
+    /// Everything this dry run would do, only `Some` for `--format json` or `--plan`
+    pub(crate) plan: Option<Plan>,
 }
 
 impl State {
@@ -28,6 +42,7 @@ impl State {
         jira_config: Option<config::Jira>,
         github_config: Option<config::GitHub>,
         gitea_config: Option<config::Gitea>,
+        signing: Option<config::Signing>,
         packages: Vec<releases::Package>,
         all_versioned_files: Vec<VersionedFile>,
         all_git_tags: Vec<String>,
@@ -38,15 +53,99 @@ impl State {
             gitea_config,
             github: GitHub::New,
             github_config,
+            signing,
             issue: Issue::Initial,
             packages,
             all_versioned_files,
             all_git_tags,
             pending_actions: Vec::new(),
+            released: Vec::new(),
+            outputs: IndexMap::new(),
+            modified_files: Vec::new(),
+            plan: None,
+        }
+    }
+
+    /// The releases created by `Release` steps in this workflow, or if there aren't any, the ones
+    /// prepared by `PrepareRelease` steps.
+    pub(crate) fn releases(&self) -> Vec<&Release> {
+        if !self.released.is_empty() {
+            return self.released.iter().collect();
+        }
+        self.pending_actions
+            .iter()
+            .filter_map(|action| match action {
+                Action::CreateRelease(release) => Some(release),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The files that each of [`Self::releases`] sets the version of the package in (not those
+    /// which only version a dependency).
+    pub(crate) fn released_files(&self) -> Vec<(&Release, &VersionedFile)> {
+        let mut files = Vec::new();
+        for release in self.releases() {
+            let Some(package) = self
+                .packages
+                .iter()
+                .find(|package| package.name() == &release.package_name)
+            else {
+                continue;
+            };
+            for config in package.versioning.versioned_files() {
+                if config.dependency.is_some() {
+                    continue;
+                }
+                let path = config.as_path();
+                if let Some(versioned_file) = self
+                    .all_versioned_files
+                    .iter()
+                    .find(|versioned_file| *versioned_file.path() == path)
+                {
+                    files.push((release, versioned_file));
+                }
+            }
+        }
+        files
+    }
+
+    /// The one release from [`Self::releases`] that a step should act on, which must be named by
+    /// `package` if there's more than one.
+    pub(crate) fn single_release(
+        &self,
+        package: Option<&str>,
+    ) -> Result<Option<&Release>, TooManyReleases> {
+        let releases = self.releases();
+        if let Some(package) = package {
+            return Ok(releases
+                .into_iter()
+                .find(|release| release.package_name.as_custom() == Some(package)));
+        }
+        match releases.as_slice() {
+            [] => Ok(None),
+            [release] => Ok(Some(release)),
+            _ => Err(TooManyReleases(
+                releases
+                    .iter()
+                    .map(|release| match &release.package_name {
+                        Name::Custom(name) => name.as_str(),
+                        Name::Default => "",
+                    })
+                    .join(", "),
+            )),
         }
     }
 }
 
+#[derive(Debug, Diagnostic, thiserror::Error)]
+#[error("More than one package was released: {0}")]
+#[diagnostic(
+    code(too_many_releases),
+    help("Set `package` on the step to the name of the package to use.")
+)]
+pub(crate) struct TooManyReleases(String);
+
 /// The type of state—an outer enum to make sure that dry-runs are handled appropriately.
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum RunType<T> {
@@ -66,6 +165,12 @@ impl<T> RunType<T> {
         }
     }
 
+    pub(crate) fn inner(&self) -> &T {
+        match self {
+            RunType::DryRun(inner) | RunType::Real(inner) => inner,
+        }
+    }
+
     pub(crate) fn take(self) -> (RunType<()>, T) {
         match self {
             RunType::DryRun(inner) => (RunType::DryRun(()), inner),
@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use knope_versioning::semver::{RuleError, StableRule};
+use miette::Diagnostic;
+use tracing::info;
+
+use crate::{
+    state::State,
+    step::{releases, Step},
+    workflow::Workflow,
+};
+
+/// The name of the built-in subcommand, which is skipped if a workflow has the same name.
+pub(crate) const NAME: &str = "status";
+
+pub(crate) fn subcommand() -> clap::Command {
+    clap::Command::new(NAME).about("Show the pending changes and next version of each package")
+}
+
+/// Print the current version, pending changes, and next version of every package without
+/// changing anything.
+///
+/// The options of the first `PrepareRelease` step in `workflows` are used to find changes.
+pub(crate) fn run(mut state: State, workflows: &[Workflow]) -> Result<(), Error> {
+    if state.packages.is_empty() {
+        return Err(releases::package::Error::NoDefinedPackages.into());
+    }
+    let prepare_release = workflows
+        .iter()
+        .flat_map(|workflow| &workflow.steps)
+        .find_map(|workflow_step| match &workflow_step.step {
+            Step::PrepareRelease(prepare_release) => Some(prepare_release.clone()),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let pending = releases::pending_changes(&mut state, &prepare_release)?;
+    let mut report = String::new();
+    for (
+        package,
+        releases::PendingChanges {
+            changes,
+            minimum_rule,
+            version,
+        },
+    ) in state.packages.iter().zip(pending)
+    {
+        let versioning = &package.versioning;
+        let name = versioning
+            .name
+            .as_custom()
+            .map(|name| format!("{name} "))
+            .unwrap_or_default();
+        let current = versioning.versions.clone().into_latest();
+        if let (Some(next), Some(group)) = (version, &package.version_group) {
+            writeln!(report, "{name}{current} -> {next} (version group {group})").ok();
+        } else if changes.is_empty() {
+            writeln!(report, "{name}{current}\n  No pending changes").ok();
+            continue;
+        } else {
+            let stable_rule = prepare_release.rule.unwrap_or_else(|| {
+                StableRule::from(&changes).max(minimum_rule.unwrap_or_default())
+            });
+            let next =
+                package.next_version(prepare_release.prerelease_label.as_ref(), stable_rule)?;
+            writeln!(report, "{name}{current} -> {next} ({stable_rule})").ok();
+        }
+        for change in &changes {
+            writeln!(report, "  {}", change.original_source).ok();
+        }
+    }
+    info!("{}", report.trim_end());
+    Ok(())
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Release(#[from] releases::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] releases::package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Rule(#[from] RuleError),
+}
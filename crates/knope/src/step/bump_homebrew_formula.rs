@@ -0,0 +1,78 @@
+use knope_versioning::ReleaseTag;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    integrations::{github, github::Formula},
+    state::{RunType, State, TooManyReleases},
+    variables,
+    variables::{replace_variables, Template},
+};
+
+/// The inner content of a [`crate::step::Step::BumpHomebrewFormula`] step.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Options {
+    /// The GitHub repository of the tap, like `knope-dev/homebrew-tap`
+    pub(crate) tap: String,
+    /// The name of the formula, which lives at `Formula/{formula}.rb` in the tap
+    pub(crate) formula: String,
+    /// Where to download the new version from, the source archive of the release tag by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) url: Option<Template>,
+    /// The name of the package to use, if more than one is released
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) package: Option<String>,
+}
+
+/// Open a pull request against a Homebrew tap which updates the `url` and `sha256` of a formula
+/// to the release in this workflow.
+pub(crate) fn run(options: Options, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let Some(github_config) = state.github_config.clone() else {
+        return Err(Error::NotConfigured);
+    };
+    let Some(release) = state.single_release(options.package.as_deref())? else {
+        info!("No release to bump the Homebrew formula for");
+        return Ok(run_type.of(state));
+    };
+    let version = release.version.clone();
+    let tag = ReleaseTag::new(&version, &release.package_name);
+    let url = match options.url {
+        Some(template) => replace_variables(template, &mut state)?,
+        None => format!(
+            "https://github.com/{owner}/{repo}/archive/refs/tags/{tag}.tar.gz",
+            owner = github_config.owner,
+            repo = github_config.repo,
+            tag = tag.as_str(),
+        ),
+    };
+    let formula = Formula {
+        tap: &options.tap,
+        name: &options.formula,
+        version: version.to_string(),
+        url,
+    };
+    state.github = github::bump_formula(&formula, run_type.of(state.github), &github_config)?;
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("GitHub is not configured")]
+    #[diagnostic(
+        code(bump_homebrew_formula::github::not_configured),
+        help("GitHub must be configured in order to use the BumpHomebrewFormula step"),
+        url("https://knope.tech/reference/config-file/github/")
+    )]
+    NotConfigured,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    TooManyReleases(#[from] TooManyReleases),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Variables(#[from] variables::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::BumpFormulaError),
+}
@@ -0,0 +1,54 @@
+use knope_versioning::ReleaseTag;
+use miette::Diagnostic;
+
+use crate::{
+    integrations::github,
+    state::{RunType, State},
+    step::releases::package,
+};
+
+/// Close the GitHub milestone named after the current version of every package.
+pub(crate) fn run(
+    move_open_issues_to: Option<&str>,
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let Some(github_config) = &state.github_config else {
+        return Err(Error::NotConfigured);
+    };
+    if state.packages.is_empty() {
+        return Err(package::Error::NoDefinedPackages.into());
+    }
+    for package in &state.packages {
+        let version = package.versioning.versions.clone().into_latest();
+        let tag = ReleaseTag::new(&version, package.name());
+        let version = version.to_string();
+        let prefixed = format!("v{version}");
+        let mut titles = vec![version.as_str(), prefixed.as_str(), tag.as_str()];
+        titles.dedup();
+        state.github = github::close_milestone(
+            &titles,
+            move_open_issues_to,
+            run_type.of(state.github),
+            github_config,
+        )?;
+    }
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("GitHub is not configured")]
+    #[diagnostic(
+        code(close_milestone::github::not_configured),
+        help("GitHub must be configured in order to use the CloseMilestone step"),
+        url("https://knope.tech/reference/config-file/github/")
+    )]
+    NotConfigured,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::CloseMilestoneError),
+}
@@ -1,8 +1,13 @@
+use std::{process::Stdio, thread};
+
 use indexmap::IndexMap;
+use itertools::Itertools;
 use miette::Diagnostic;
 use tracing::info;
 
 use crate::{
+    integrations::retry,
+    plan,
     state::State,
     variables,
     variables::{replace_variables, Template, Variable},
@@ -11,14 +16,30 @@ use crate::{
 
 /// Run the command string `command` in the current shell after replacing the keys of `variables`
 /// with the values that the [`Variable`]s represent.
+///
+/// If `capture_output` is set, stdout is stored under that name for [`Variable::Output`].
+/// A failing command is run up to `retries` more times, waiting longer after each attempt.
+/// `variables` are also replaced in the values of `env`, which are set for the command.
 pub(crate) fn run_command(
     state: RunType<State>,
     mut command: String,
     shell: bool,
     variables: Option<IndexMap<String, Variable>>,
+    capture_output: Option<String>,
+    retries: u32,
+    mut env: IndexMap<String, String>,
 ) -> Result<RunType<State>, Error> {
     let (run_type, mut state) = state.take();
     if let Some(variables) = variables {
+        for value in env.values_mut() {
+            *value = replace_variables(
+                Template {
+                    template: std::mem::take(value),
+                    variables: variables.clone(),
+                },
+                &mut state,
+            )?;
+        }
         command = replace_variables(
             Template {
                 template: command,
@@ -28,18 +49,66 @@ pub(crate) fn run_command(
         )?;
     }
     if let RunType::DryRun(()) = run_type {
-        info!("Would run {command}");
+        let env_description = if env.is_empty() {
+            String::new()
+        } else {
+            let vars = env
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .join(" ");
+            format!(" with {vars}")
+        };
+        if let Some(name) = capture_output {
+            info!("Would run {command}{env_description} and capture its output as {name}");
+            state
+                .outputs
+                .insert(name.clone(), format!("<output of {name}>"));
+        } else {
+            info!("Would run {command}{env_description}");
+        }
+        plan::record(&mut state.plan, |plan| {
+            plan.commands.push(command.clone());
+            plan.actions.push(plan::Action::Command {
+                command,
+                shell,
+                env,
+            });
+        });
         return Ok(run_type.of(state));
     }
-    let status = if shell {
-        execute::shell(command).status()?
+    let mut command = if shell {
+        execute::shell(command)
     } else {
-        execute::command(command).status()?
+        execute::command(command)
     };
-    if status.success() {
-        return Ok(run_type.of(state));
+    let stdout = if capture_output.is_some() {
+        Stdio::piped()
+    } else {
+        Stdio::inherit()
+    };
+    command.envs(env).stdout(stdout).stderr(Stdio::inherit());
+    let mut attempt = 0;
+    let output = loop {
+        let output = command.output()?;
+        if output.status.success() {
+            break output;
+        }
+        if attempt >= retries {
+            return Err(Error::Command(output.status));
+        }
+        let delay = retry::backoff(attempt);
+        info!(
+            "Command failed, retrying in {seconds} seconds",
+            seconds = delay.as_secs()
+        );
+        thread::sleep(delay);
+        attempt += 1;
+    };
+    if let Some(name) = capture_output {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        state.outputs.insert(name, stdout.trim_end().to_string());
     }
-    Err(Error::Command(status))
+    Ok(run_type.of(state))
 }
 
 #[derive(Debug, Diagnostic, thiserror::Error)]
@@ -73,6 +142,7 @@ mod test_run_command {
                 None,
                 None,
                 None,
+                None,
                 Vec::new(),
                 Vec::new(),
                 Vec::new(),
@@ -80,6 +150,9 @@ mod test_run_command {
             command.to_string(),
             false,
             None,
+            None,
+            0,
+            IndexMap::new(),
         );
 
         assert!(result.is_ok());
@@ -89,6 +162,7 @@ mod test_run_command {
                 None,
                 None,
                 None,
+                None,
                 Vec::new(),
                 Vec::new(),
                 Vec::new(),
@@ -96,6 +170,9 @@ mod test_run_command {
             String::from("exit 1"),
             false,
             None,
+            None,
+            0,
+            IndexMap::new(),
         );
         assert!(result.is_err());
     }
@@ -0,0 +1,48 @@
+use indexmap::IndexMap;
+use itertools::Itertools;
+use miette::Diagnostic;
+
+use crate::{
+    integrations::git,
+    state::{RunType, State},
+    variables,
+    variables::{replace_variables, Template, Variable},
+};
+
+/// Commit the files that earlier steps (like `PrepareRelease`) changed, along with anything
+/// else already staged, after replacing `variables` in `message`.
+pub(crate) fn run(
+    state: RunType<State>,
+    mut message: String,
+    variables: Option<IndexMap<String, Variable>>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    if let Some(variables) = variables {
+        message = replace_variables(
+            Template {
+                template: message,
+                variables,
+            },
+            &mut state,
+        )?;
+    }
+    let modified_files = state.modified_files.drain(..).unique().collect_vec();
+    let signing = state.signing.as_ref().filter(|signing| signing.commits);
+    git::commit(
+        run_type.of(&message),
+        &modified_files,
+        signing,
+        &mut state.plan,
+    )?;
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Variables(#[from] variables::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+}
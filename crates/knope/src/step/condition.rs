@@ -0,0 +1,144 @@
+use std::{env, fmt, fmt::Display, str::FromStr};
+
+use knope_versioning::ReleaseTag;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+
+use crate::state::State;
+
+/// Decides whether a step should run, from the `if` field of a step.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) enum Condition {
+    /// The environment variable is set to something
+    Env(String),
+    /// The package has a version which hasn't been tagged yet (like after `PrepareRelease`)
+    PackageReleased(String),
+    /// The version of any package is a pre-release
+    VersionIsPrerelease,
+    /// Any other condition starting with `!`, met when that condition isn't
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Check the condition against the current state of the workflow.
+    pub(crate) fn is_met(&self, state: &State) -> Result<bool, Error> {
+        Ok(match self {
+            Self::Env(name) => env::var_os(name).is_some_and(|value| !value.is_empty()),
+            Self::PackageReleased(name) => {
+                let package = state
+                    .packages
+                    .iter()
+                    .find(|package| package.name().as_ref() == name)
+                    .ok_or_else(|| Error::UnknownPackage(name.clone()))?;
+                let version = package.versioning.versions.clone().into_latest();
+                let tag = ReleaseTag::new(&version, package.name());
+                !state
+                    .all_git_tags
+                    .iter()
+                    .any(|existing| existing == tag.as_str())
+            }
+            Self::VersionIsPrerelease => state.packages.iter().any(|package| {
+                package
+                    .versioning
+                    .versions
+                    .clone()
+                    .into_latest()
+                    .is_prerelease()
+            }),
+            Self::Not(condition) => !condition.is_met(state)?,
+        })
+    }
+}
+
+impl FromStr for Condition {
+    type Err = ParseError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Some(inner) = value.strip_prefix('!') {
+            return inner.parse().map(|inner| Self::Not(Box::new(inner)));
+        }
+        if value == "version_is_prerelease" {
+            return Ok(Self::VersionIsPrerelease);
+        }
+        let (function, argument) = value
+            .strip_suffix(')')
+            .and_then(|value| value.split_once('('))
+            .ok_or_else(|| ParseError(value.to_string()))?;
+        let argument = argument.trim();
+        if argument.is_empty() {
+            return Err(ParseError(value.to_string()));
+        }
+        match function.trim() {
+            "env" => Ok(Self::Env(argument.to_string())),
+            "package_released" => Ok(Self::PackageReleased(argument.to_string())),
+            _ => Err(ParseError(value.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for Condition {
+    type Error = ParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Env(name) => write!(f, "env({name})"),
+            Self::PackageReleased(name) => write!(f, "package_released({name})"),
+            Self::VersionIsPrerelease => write!(f, "version_is_prerelease"),
+            Self::Not(condition) => write!(f, "!{condition}"),
+        }
+    }
+}
+
+impl From<Condition> for String {
+    fn from(condition: Condition) -> Self {
+        condition.to_string()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown condition `{0}`, expected `env(VAR)`, `package_released(name)`, or `version_is_prerelease`")]
+pub(crate) struct ParseError(String);
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("The condition refers to package {0}, which isn't defined")]
+    #[diagnostic(
+        code(condition::unknown_package),
+        help("Use the name of a package from the `[packages]` section of `knope.toml`."),
+        url("https://knope.tech/reference/config-file/workflow/#if")
+    )]
+    UnknownPackage(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            "env(CI)".parse::<Condition>().ok(),
+            Some(Condition::Env("CI".into()))
+        );
+        assert_eq!(
+            "!package_released( knope )".parse::<Condition>().ok(),
+            Some(Condition::Not(Box::new(Condition::PackageReleased(
+                "knope".into()
+            ))))
+        );
+        assert_eq!(
+            "version_is_prerelease".parse::<Condition>().ok(),
+            Some(Condition::VersionIsPrerelease)
+        );
+        assert!("env()".parse::<Condition>().is_err());
+        assert!("released(knope)".parse::<Condition>().is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use indexmap::IndexMap;
+use miette::Diagnostic;
+
+use crate::{
+    integrations::git,
+    state::{RunType, State},
+    variables,
+    variables::{replace_variables, Template, Variable},
+};
+
+/// Create a new branch at the current commit and switch to it, after replacing `variables` in
+/// `name`.
+pub(crate) fn run(
+    state: RunType<State>,
+    mut name: String,
+    variables: Option<IndexMap<String, Variable>>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    if let Some(variables) = variables {
+        name = replace_variables(
+            Template {
+                template: name,
+                variables,
+            },
+            &mut state,
+        )?;
+    }
+    git::create_branch_at_head(run_type.of(&name))?;
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Variables(#[from] variables::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+}
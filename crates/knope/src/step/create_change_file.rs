@@ -60,7 +60,12 @@ pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
         .prompt()
         .map_err(prompt::Error::from)?;
     let unique_id = UniqueId::from(&summary);
-    let summary = format!("# {summary}");
+    let details = prompt_for_details()?;
+    let summary = if details.is_empty() {
+        format!("# {summary}")
+    } else {
+        format!("# {summary}\n\n{details}")
+    };
     let change = changesets::Change {
         unique_id,
         versioning,
@@ -83,6 +88,21 @@ pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
     Ok(RunType::Real(state))
 }
 
+/// Optionally get a longer description of the change, using the user's editor.
+fn prompt_for_details() -> Result<String, prompt::Error> {
+    let wants_details = inquire::Confirm::new("Do you want to add more details?")
+        .with_default(false)
+        .with_help_message("This opens your $VISUAL or $EDITOR for writing Markdown")
+        .prompt()?;
+    if !wants_details {
+        return Ok(String::new());
+    }
+    let details = inquire::Editor::new("Details of this change")
+        .with_file_extension(".md")
+        .prompt()?;
+    Ok(details.trim().to_string())
+}
+
 #[derive(Debug, Diagnostic, thiserror::Error)]
 pub(crate) enum Error {
     #[error(transparent)]
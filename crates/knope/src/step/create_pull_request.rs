@@ -1,21 +1,96 @@
+use indexmap::IndexMap;
 use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use serde::{Deserialize, Serialize};
+use tracing::info;
 
 use crate::{
+    fs,
     integrations::{gitea, github},
     state::{RunType, State},
     variables,
-    variables::{replace_variables, Template},
+    variables::{replace_variables, Template, Variable},
 };
 
+/// The body of a pull request, either written in the config file or read from a separate file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum Body {
+    File {
+        /// The path to a file containing the template, relative to the current directory
+        file: RelativePathBuf,
+        #[serde(default)]
+        variables: IndexMap<String, Variable>,
+    },
+    Inline(Template),
+}
+
+impl Body {
+    pub(crate) fn variables(&self) -> &IndexMap<String, Variable> {
+        match self {
+            Self::File { variables, .. } | Self::Inline(Template { variables, .. }) => variables,
+        }
+    }
+
+    fn into_template(self) -> Result<Template, fs::Error> {
+        match self {
+            Self::File { file, variables } => Ok(Template {
+                template: fs::read_to_string(file.to_path(""))?,
+                variables,
+            }),
+            Self::Inline(template) => Ok(template),
+        }
+    }
+}
+
+/// Everything about a pull request besides its title and body, which forges set separately.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Metadata {
+    /// Labels to add to the pull request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) labels: Vec<String>,
+    /// Users to request reviews from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) reviewers: Vec<String>,
+    /// Users to assign the pull request to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) assignees: Vec<String>,
+    /// Create new pull requests as drafts
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) draft: bool,
+    /// On GitHub, put the parts of a body that's too long in comments instead of truncating it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) split_long_body: bool,
+}
+
+impl Metadata {
+    /// Print anything that's set, after the title and body of a dry run
+    pub(crate) fn log_dry_run(&self) {
+        if !self.labels.is_empty() {
+            info!("\tLabels: {}", self.labels.join(", "));
+        }
+        if !self.reviewers.is_empty() {
+            info!("\tReviewers: {}", self.reviewers.join(", "));
+        }
+        if !self.assignees.is_empty() {
+            info!("\tAssignees: {}", self.assignees.join(", "));
+        }
+        if self.draft {
+            info!("\tDraft (if new)");
+        }
+    }
+}
+
 pub(super) fn run(
     base: &str,
     title: Template,
-    body: Template,
+    body: Body,
+    metadata: &Metadata,
     state: RunType<State>,
 ) -> Result<RunType<State>, Error> {
     let (run_type, mut state) = state.take();
     let title = replace_variables(title, &mut state)?;
-    let body = replace_variables(body, &mut state)?;
+    let body = replace_variables(body.into_template()?, &mut state)?;
 
     if state.github_config.is_none() && state.gitea_config.is_none() {
         return Err(Error::NotConfigured);
@@ -26,6 +101,7 @@ pub(super) fn run(
             &title,
             &body,
             base,
+            metadata,
             run_type.of(state.github),
             github_config,
         )?;
@@ -36,6 +112,7 @@ pub(super) fn run(
             &title,
             &body,
             base,
+            metadata,
             run_type.of(state.gitea),
             gitea_config,
         )?;
@@ -48,6 +125,9 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Variables(#[from] variables::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
     #[error("No forge has been configured")]
     #[diagnostic(
         code(create_pull_request::forge::not_configured),
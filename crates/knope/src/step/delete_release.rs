@@ -0,0 +1,69 @@
+use knope_versioning::{semver::PackageVersions, ReleaseTag};
+use miette::Diagnostic;
+
+use super::push;
+use crate::{
+    integrations::{git, gitea, github},
+    state::{RunType, State},
+    step::releases::package,
+};
+
+/// Undo the release of the current version of every package: delete (or, with `draft`, unpublish)
+/// its forge release, then delete its tag from the first remote and the local repository.
+///
+/// Anything that doesn't exist is skipped, so this can clean up after a release that only
+/// partially succeeded.
+pub(crate) fn run(state: RunType<State>, draft: bool) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    if state.packages.is_empty() {
+        return Err(package::Error::NoDefinedPackages.into());
+    }
+    let tags = state
+        .packages
+        .iter()
+        .map(|package| {
+            let version = package.versioning.versions.clone().into_latest();
+            ReleaseTag::new(&version, package.name())
+        })
+        .collect::<Vec<_>>();
+    for tag in tags {
+        let tag = tag.as_str();
+        if let Some(github_config) = &state.github_config {
+            state.github =
+                github::delete_release(tag, draft, run_type.of(state.github), github_config)?;
+        }
+        if let Some(gitea_config) = &state.gitea_config {
+            state.gitea =
+                gitea::delete_release(tag, draft, run_type.of(state.gitea), gitea_config)?;
+        }
+        push::delete_remote_tag(run_type.of(&mut state), tag)?;
+        if state.all_git_tags.iter().any(|existing| existing == tag) {
+            git::delete_tag(run_type.of(tag))?;
+            state.all_git_tags.retain(|existing| existing != tag);
+        }
+    }
+    for package in &mut state.packages {
+        package.tagged_versions =
+            PackageVersions::from_tags(package.name().as_custom(), &state.all_git_tags);
+    }
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::DeleteReleaseError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Gitea(#[from] gitea::DeleteReleaseError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Push(#[from] push::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+}
@@ -0,0 +1,119 @@
+use std::{env, path::PathBuf};
+
+use itertools::Itertools;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    fs,
+    fs::WriteType,
+    state::{RunType, State},
+    step::releases::package,
+};
+
+/// How [`crate::step::Step::GetVersion`] formats versions.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum Format {
+    /// Just the version for a single package, or `name version` lines for multiple packages
+    #[default]
+    Plain,
+    /// A JSON object of package name to version
+    Json,
+    /// `name=version` lines, like `$GITHUB_OUTPUT` expects.
+    KeyValue,
+}
+
+impl Format {
+    #[allow(clippy::trivially_copy_pass_by_ref)] // `skip_serializing_if` passes a reference
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Write the version of every package as it is at this point in the workflow—so after
+/// `PrepareRelease` or `BumpVersion`, this is the new version.
+///
+/// Output goes to `file` if set (which can be an environment variable like `$GITHUB_OUTPUT`),
+/// otherwise it's printed.
+pub(crate) fn run(
+    state: RunType<State>,
+    format: Format,
+    file: Option<&str>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    if state.packages.is_empty() {
+        return Err(package::Error::NoDefinedPackages.into());
+    }
+    let versions = state
+        .packages
+        .iter()
+        .map(|package| {
+            let key = package
+                .name()
+                .as_custom()
+                .map_or_else(|| String::from("version"), String::from);
+            let version = package.versioning.versions.clone().into_latest();
+            (key, version.to_string())
+        })
+        .collect_vec();
+    let output = match format {
+        Format::Plain => match versions.as_slice() {
+            [(_, version)] => version.clone(),
+            _ => versions
+                .iter()
+                .map(|(name, version)| format!("{name} {version}"))
+                .join("\n"),
+        },
+        Format::KeyValue => versions
+            .iter()
+            .map(|(name, version)| format!("{name}={version}"))
+            .join("\n"),
+        Format::Json => serde_json::Value::Object(
+            versions
+                .into_iter()
+                .map(|(name, version)| (name, version.into()))
+                .collect(),
+        )
+        .to_string(),
+    };
+
+    let Some(file) = file else {
+        info!("{output}");
+        return Ok(run_type.of(state));
+    };
+    // Files from the environment (like `$GITHUB_OUTPUT`) collect output from many steps
+    let (path, append) = if let Some(var) = file.strip_prefix('$') {
+        let path = env::var_os(var).ok_or_else(|| Error::MissingEnvVar(var.to_string()))?;
+        (PathBuf::from(path), true)
+    } else {
+        (PathBuf::from(file), false)
+    };
+    let mut contents = format!("{output}\n");
+    if append && path.exists() {
+        contents.insert_str(0, &fs::read_to_string(&path)?);
+    }
+    let to_write = match run_type {
+        RunType::DryRun(()) => WriteType::DryRun(contents),
+        RunType::Real(()) => WriteType::Real(contents),
+    };
+    fs::write(to_write, &path, &mut state.plan)?;
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] package::Error),
+    #[error("The environment variable {0} is not set")]
+    #[diagnostic(
+        code(get_version::missing_env_var),
+        help("Set the variable, or write to a file path instead."),
+        url("https://knope.tech/reference/config-file/steps/get-version/")
+    )]
+    MissingEnvVar(String),
+}
@@ -0,0 +1,28 @@
+use tracing::info;
+
+use crate::{
+    prompt,
+    state::{RunType, State},
+};
+
+/// Ask the user for an answer (free text, or one of `options`) and store it as `name` for the
+/// [`crate::variables::Variable::Output`] variable.
+pub(crate) fn run(
+    state: RunType<State>,
+    name: String,
+    message: &str,
+    options: Option<Vec<String>>,
+) -> Result<RunType<State>, prompt::Error> {
+    let (run_type, mut state) = state.take();
+    let answer = match (run_type, options) {
+        (RunType::DryRun(()), options) => {
+            info!("Would prompt user: {message}");
+            let placeholder = options.and_then(|options| options.into_iter().next());
+            placeholder.unwrap_or_else(|| format!("<answer to {name}>"))
+        }
+        (RunType::Real(()), Some(options)) => prompt::select(options, message)?,
+        (RunType::Real(()), None) => prompt::get_text(message)?,
+    };
+    state.outputs.insert(name, answer);
+    Ok(run_type.of(state))
+}
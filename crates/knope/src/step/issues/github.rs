@@ -6,7 +6,9 @@ use super::Issue;
 use crate::{
     app_config,
     app_config::get_or_prompt_for_github_token,
-    config, prompt,
+    config,
+    integrations::retry,
+    prompt,
     prompt::select,
     state,
     state::{RunType, State},
@@ -116,21 +118,23 @@ fn list_issues(
         state::GitHub::Initialized { token, agent } => (token, agent),
         state::GitHub::New => (get_or_prompt_for_github_token()?, Agent::new()),
     };
-    let response = agent
-        .post("https://api.github.com/graphql")
-        .set("Authorization", &format!("bearer {token}"))
-        .send_json(ureq::json!({
-            "query": ISSUES_QUERY,
-            "variables": {
-                "repo": github_config.repo,
-                "owner": github_config.owner,
-                "labels": labels
-            }
-        }))
-        .map_err(|source| Error::Api {
-            source: Box::new(source),
-            context: "loading issues",
-        })?;
+    let response = retry::send(github_config.retries(), || {
+        agent
+            .post("https://api.github.com/graphql")
+            .set("Authorization", &format!("bearer {token}"))
+            .send_json(ureq::json!({
+                "query": ISSUES_QUERY,
+                "variables": {
+                    "repo": github_config.repo,
+                    "owner": github_config.owner,
+                    "labels": labels
+                }
+            }))
+    })
+    .map_err(|source| Error::Api {
+        source,
+        context: "loading issues",
+    })?;
 
     let gh_issues = decode_github_response(response)?;
 
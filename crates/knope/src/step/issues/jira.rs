@@ -1,17 +1,19 @@
 use base64::{prelude::BASE64_STANDARD as base64, Engine};
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{debug, info};
 
 use super::Issue;
 use crate::{
     app_config,
-    app_config::{get_or_prompt_for_email, get_or_prompt_for_jira_token},
+    app_config::{get_or_prompt_for_email, get_or_prompt_for_jira_token, jira_credentials},
     config::Jira,
     prompt,
     prompt::select,
     state,
     state::{RunType, State},
+    variables,
+    variables::{replace_variables, Template},
 };
 
 pub(crate) fn select_issue(status: &str, state: RunType<State>) -> Result<RunType<State>, Error> {
@@ -57,12 +59,94 @@ pub(crate) fn transition_issue(
     Ok(RunType::Real(state))
 }
 
+pub(crate) fn create_issue(
+    summary: Template,
+    description: Option<Template>,
+    issue_type: Option<&str>,
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let summary = replace_variables(summary, &mut state)?;
+    let description = description
+        .map(|description| replace_variables(description, &mut state))
+        .transpose()?;
+    let issue_type = issue_type.unwrap_or(DEFAULT_ISSUE_TYPE);
+    let jira_config = state.jira_config.as_ref().ok_or(Error::NotConfigured)?;
+
+    if let RunType::DryRun(()) = run_type {
+        let project = &jira_config.project;
+        info!("Would create a {issue_type} in Jira project {project} with summary {summary}");
+        if let Some(description) = description {
+            info!("And description:\n{description}");
+        }
+        info!("Would move workflow to IssueSelected state with the new issue.");
+        state.issue = state::Issue::Selected(Issue {
+            key: "FAKE-123".to_string(),
+            summary,
+        });
+        return Ok(RunType::DryRun(state));
+    }
+
+    let key = post_issue(jira_config, &summary, description.as_deref(), issue_type)?;
+    info!("Created issue {key}");
+    state.issue = state::Issue::Selected(Issue { key, summary });
+    Ok(RunType::Real(state))
+}
+
+pub(crate) fn comment_on_issue(
+    body: Template,
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let body = replace_variables(body, &mut state)?;
+    let issue = match &state.issue {
+        state::Issue::Selected(issue) => issue,
+        state::Issue::Initial => return Err(Error::NoIssueSelected),
+    };
+    let jira_config = state.jira_config.as_ref().ok_or(Error::NotConfigured)?;
+
+    if let RunType::DryRun(()) = run_type {
+        info!("Would comment on currently selected issue:\n{body}");
+        return Ok(RunType::DryRun(state));
+    }
+
+    post_comment(jira_config, &issue.key, &body)?;
+    let key = &issue.key;
+    info!("Commented on {key}");
+    Ok(RunType::Real(state))
+}
+
+/// Check that `jira_config` points at a project which the stored credentials can see.
+///
+/// This is skipped if there are no credentials yet, since it runs without prompting.
+pub(crate) fn validate_project(jira_config: &Jira) -> Result<(), Error> {
+    let Some((email, token)) = jira_credentials() else {
+        debug!("No Jira credentials found, skipping validation of the Jira project");
+        return Ok(());
+    };
+    let Jira { url, project } = jira_config;
+    ureq::get(&format!("{url}/rest/api/3/project/{project}"))
+        .set("Authorization", &basic_auth(&email, &token))
+        .call()
+        .map_err(|err| match err {
+            ureq::Error::Status(404, _) => Error::ProjectNotFound {
+                project: project.clone(),
+                url: url.clone(),
+            },
+            err => api_error("validating the project")(err),
+        })?;
+    Ok(())
+}
+
+/// The issue type to use for `CreateJiraIssue` when none is set
+const DEFAULT_ISSUE_TYPE: &str = "Task";
+
 #[derive(Debug, Diagnostic, thiserror::Error)]
 pub(crate) enum Error {
     #[error("Jira is not configured")]
     #[diagnostic(
         code(issues::jira::not_configured),
-        help("Jira must be configured in order to select a Jira issue"),
+        help("Jira must be configured in order to use Jira steps"),
         url("https://knope.tech/reference/config-file/jira/")
     )]
     NotConfigured,
@@ -74,6 +158,27 @@ pub(crate) enum Error {
         #[source]
         inner: Box<ureq::Error>,
     },
+    #[error("Jira rejected the email and API token")]
+    #[diagnostic(
+        code(issues::jira::unauthorized),
+        help(
+            "Set JIRA_EMAIL and JIRA_API_TOKEN, or delete the stored `email` and `jira_token` \
+            values to be prompted again. API tokens are generated at \
+            https://id.atlassian.com/manage-profile/security/api-tokens"
+        ),
+        url("https://knope.tech/reference/config-file/jira/")
+    )]
+    Unauthorized,
+    #[error("Could not find the Jira project {project} at {url}")]
+    #[diagnostic(
+        code(issues::jira::project_not_found),
+        help(
+            "The `project` in the [jira] config must be the key of a project (like PRJ in PRJ-123) \
+            which your account can see"
+        ),
+        url("https://knope.tech/reference/config-file/jira/")
+    )]
+    ProjectNotFound { project: String, url: String },
     #[error("The specified transition name was not found in the Jira project")]
     #[diagnostic(
         code(issues::jira::transition),
@@ -85,7 +190,7 @@ pub(crate) enum Error {
     #[diagnostic(
         code(issues::jira::no_issue_selected),
         help(
-            "You must use the SelectJiraIssue step before TransitionJiraIssue in the same workflow"
+            "You must use the SelectJiraIssue or CreateJiraIssue step before TransitionJiraIssue or CommentOnJiraIssue in the same workflow"
         )
     )]
     NoIssueSelected,
@@ -95,6 +200,9 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Prompt(#[from] prompt::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Variables(#[from] variables::Error),
 }
 
 #[derive(Deserialize, Debug)]
@@ -116,10 +224,22 @@ struct SearchResponse {
 fn get_auth() -> Result<String, Error> {
     let email = get_or_prompt_for_email()?;
     let token = get_or_prompt_for_jira_token()?;
-    Ok(format!(
-        "Basic {}",
-        base64.encode(format!("{email}:{token}"))
-    ))
+    Ok(basic_auth(&email, &token))
+}
+
+fn basic_auth(email: &str, token: &str) -> String {
+    format!("Basic {}", base64.encode(format!("{email}:{token}")))
+}
+
+/// Convert a failed request into an [`Error`], pointing at the credentials if Jira rejected them.
+fn api_error(activity: &'static str) -> impl FnOnce(ureq::Error) -> Error {
+    move |inner| match inner {
+        ureq::Error::Status(401, _) => Error::Unauthorized,
+        inner => Error::Api {
+            activity,
+            inner: Box::new(inner),
+        },
+    }
 }
 
 pub(crate) fn get_issues(jira_config: &Jira, status: &str) -> Result<Vec<Issue>, Error> {
@@ -130,10 +250,7 @@ pub(crate) fn get_issues(jira_config: &Jira, status: &str) -> Result<Vec<Issue>,
     Ok(ureq::post(&url)
         .set("Authorization", &auth)
         .send_json(ureq::json!({"jql": jql, "fields": ["summary"]}))
-        .map_err(|inner| Error::Api {
-            inner: Box::new(inner),
-            activity: "querying for issues",
-        })?
+        .map_err(api_error("querying for issues"))?
         .into_json::<SearchResponse>()?
         .issues
         .into_iter()
@@ -153,10 +270,7 @@ fn run_transition(jira_config: &Jira, issue_key: &str, status: &str) -> Result<(
         .get(&url)
         .set("Authorization", &auth)
         .call()
-        .map_err(|inner| Error::Api {
-            inner: Box::new(inner),
-            activity: "getting transitions",
-        })?;
+        .map_err(api_error("getting transitions"))?;
     let response = response.into_json::<GetTransitionResponse>()?;
     let transition = response
         .transitions
@@ -167,10 +281,7 @@ fn run_transition(jira_config: &Jira, issue_key: &str, status: &str) -> Result<(
         .post(&url)
         .set("Authorization", &auth)
         .send_json(ureq::json!({"transition": {"id": transition.id}}))
-        .map_err(|inner| Error::Api {
-            inner: Box::new(inner),
-            activity: "transitioning issue",
-        })?;
+        .map_err(api_error("transitioning issue"))?;
     Ok(())
 }
 
@@ -184,3 +295,61 @@ struct Transition {
     id: String,
     name: String,
 }
+
+fn post_issue(
+    jira_config: &Jira,
+    summary: &str,
+    description: Option<&str>,
+    issue_type: &str,
+) -> Result<String, Error> {
+    let auth = get_auth()?;
+    let url = format!("{}/rest/api/3/issue", jira_config.url);
+    let fields = NewIssueFields {
+        project: ureq::json!({"key": jira_config.project}),
+        summary,
+        issuetype: ureq::json!({"name": issue_type}),
+        description: description.map(document),
+    };
+    Ok(ureq::post(&url)
+        .set("Authorization", &auth)
+        .send_json(ureq::json!({ "fields": fields }))
+        .map_err(api_error("creating an issue"))?
+        .into_json::<CreatedIssue>()?
+        .key)
+}
+
+fn post_comment(jira_config: &Jira, issue_key: &str, body: &str) -> Result<(), Error> {
+    let auth = get_auth()?;
+    let base_url = &jira_config.url;
+    let url = format!("{base_url}/rest/api/3/issue/{issue_key}/comment");
+    ureq::post(&url)
+        .set("Authorization", &auth)
+        .send_json(ureq::json!({ "body": document(body) }))
+        .map_err(api_error("commenting on issue"))?;
+    Ok(())
+}
+
+/// Jira's v3 API only accepts rich text in the Atlassian Document Format, so each non-empty
+/// line of plain text becomes a paragraph.
+fn document(text: &str) -> serde_json::Value {
+    let paragraphs: Vec<_> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| ureq::json!({"type": "paragraph", "content": [{"type": "text", "text": line}]}))
+        .collect();
+    ureq::json!({"type": "doc", "version": 1, "content": paragraphs})
+}
+
+#[derive(Debug, Serialize)]
+struct NewIssueFields<'a> {
+    project: serde_json::Value,
+    summary: &'a str,
+    issuetype: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedIssue {
+    key: String,
+}
@@ -0,0 +1,92 @@
+use std::fmt::{self, Display};
+
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    integrations::{gitea, github},
+    state::{RunType, State},
+};
+
+/// How [`crate::step::Step::MergePullRequest`] merges the pull request.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum MergeMethod {
+    /// Create a merge commit
+    #[default]
+    Merge,
+    /// Combine every commit into one
+    Squash,
+    /// Add each commit to the base branch without a merge commit
+    Rebase,
+}
+
+impl MergeMethod {
+    #[allow(clippy::trivially_copy_pass_by_ref)] // `skip_serializing_if` passes a reference
+    pub(crate) fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Display for MergeMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Merge => write!(f, "merge"),
+            Self::Squash => write!(f, "squash"),
+            Self::Rebase => write!(f, "rebase"),
+        }
+    }
+}
+
+/// Merge the pull request from the current branch into `base` on every configured forge, or
+/// have the forge merge it once checks pass if `auto_merge`.
+pub(super) fn run(
+    base: &str,
+    method: MergeMethod,
+    auto_merge: bool,
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+
+    if state.github_config.is_none() && state.gitea_config.is_none() {
+        return Err(Error::NotConfigured);
+    }
+
+    if let Some(github_config) = &state.github_config {
+        state.github = github::merge_pull_request(
+            base,
+            method,
+            auto_merge,
+            run_type.of(state.github),
+            github_config,
+        )?;
+    }
+
+    if let Some(gitea_config) = &state.gitea_config {
+        state.gitea = gitea::merge_pull_request(
+            base,
+            method,
+            auto_merge,
+            run_type.of(state.gitea),
+            gitea_config,
+        )?;
+    }
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("No forge has been configured")]
+    #[diagnostic(
+        code(merge_pull_request::forge::not_configured),
+        help("A forge must be configured in order to use the MergePullRequest step"),
+        url("https://knope.tech/reference/concepts/forge/")
+    )]
+    NotConfigured,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::MergePullRequestError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Gitea(#[from] gitea::MergePullRequestError),
+}
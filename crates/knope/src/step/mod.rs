@@ -1,25 +1,46 @@
 use indexmap::IndexMap;
-use knope_versioning::semver::{Label, Rule};
+use knope_versioning::semver::{Label, Rule, StableRule};
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    integrations::git,
+    integrations::{git, webhook},
     prompt,
     state::{RunType, State},
     variables::{Template, Variable},
+    workflow::Workflow,
 };
 
+pub(crate) mod bump_homebrew_formula;
+mod close_milestone;
 pub mod command;
+mod commit;
+pub(crate) mod condition;
+mod create_branch;
 pub(crate) mod create_change_file;
-mod create_pull_request;
+pub(crate) mod create_pull_request;
+mod delete_release;
+pub(crate) mod get_version;
+mod input;
 pub mod issues;
+pub(crate) mod merge_pull_request;
+mod notify;
+mod publish_cargo;
+pub(crate) mod publish_docker;
+pub(crate) mod publish_npm;
+mod publish_release;
+pub(crate) mod push;
 pub mod releases;
+pub(crate) mod run_workflow;
+pub(crate) mod scope;
+mod validate_commits;
+mod wait_for_checks;
+pub(crate) mod windows_package;
 
 /// Each variant describes an action you can take using knope, they are used when defining your
 /// [`crate::Workflow`] via whatever config format is being utilized.
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Clone, Deserialize, Debug, Serialize)]
 #[serde(tag = "type")]
 pub(crate) enum Step {
     /// Search for Jira issues by status and display the list of them in the terminal.
@@ -34,6 +55,22 @@ pub(crate) enum Step {
         /// The status to transition the current issue to.
         status: String,
     },
+    /// Create a new issue in the configured Jira project and select it, like `SelectJiraIssue`.
+    CreateJiraIssue {
+        /// The summary (title) of the new issue
+        summary: Template,
+        /// The description of the new issue
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        description: Option<Template>,
+        /// The name of the type of issue to create, "Task" by default
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        issue_type: Option<String>,
+    },
+    /// Comment on the selected Jira issue.
+    CommentOnJiraIssue {
+        /// The text of the comment
+        body: Template,
+    },
     /// Search for GitHub issues by status and display the list of them in the terminal.
     /// User is allowed to select one issue which will then change the workflow's state to
     /// [`State::IssueSelected`].
@@ -61,6 +98,14 @@ pub(crate) enum Step {
         /// The branch to rebase onto.
         to: String,
     },
+    /// Create a new branch at the current commit and switch to it.
+    CreateBranch {
+        /// The name of the new branch, with any variable keys you wish to replace.
+        name: String,
+        /// A map of value-to-replace to [`Variable`] to replace it with.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variables: Option<IndexMap<String, Variable>>,
+    },
     /// Bump the version of the project in any supported formats found using a
     /// [Semantic Versioning](https://semver.org) rule.
     BumpVersion(Rule),
@@ -74,6 +119,15 @@ pub(crate) enum Step {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         /// Whether to run the command in the platform's shell or not
         shell: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        /// If set, store the command's stdout in this variable instead of printing it
+        capture_output: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        /// How many times to run the command again if it fails
+        retries: Option<u32>,
+        /// Environment variables to set for the command, which can contain `variables`
+        #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+        env: IndexMap<String, String>,
     },
     /// This will look through all commits since the last tag and parse any
     /// [Stable Commits](https://www.conventionalcommits.org/en/v1.0.0/) it finds. It will
@@ -84,7 +138,17 @@ pub(crate) enum Step {
     /// This will create a new release on GitHub using the current project version.
     ///
     /// Requires that GitHub details be configured.
-    Release,
+    Release {
+        /// Create releases on forges for multiple packages at the same time
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        parallel: bool,
+        /// Use the release notes as the message of Git tags that Knope creates
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        annotate_tags: bool,
+        /// Comment on and close the issues that each release fixes
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        close_fixed_issues: bool,
+    },
     /// Create a new change file to be included in the next release.
     ///
     /// This step is interactive and will prompt the user for the information needed to create the
@@ -93,15 +157,129 @@ pub(crate) enum Step {
     CreatePullRequest {
         base: String,
         title: Template,
-        body: Template,
+        body: create_pull_request::Body,
+        #[serde(flatten)]
+        metadata: create_pull_request::Metadata,
+    },
+    /// Merge the pull request from the current branch into `base`, like one from
+    /// `CreatePullRequest`.
+    MergePullRequest {
+        /// The branch the pull request merges into
+        base: String,
+        #[serde(
+            default,
+            skip_serializing_if = "merge_pull_request::MergeMethod::is_default"
+        )]
+        method: merge_pull_request::MergeMethod,
+        /// Have the forge merge the pull request once required checks pass, instead of right away
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        auto_merge: bool,
+    },
+    /// Wait for the checks (like CI) on the current commit to pass on every configured forge.
+    WaitForChecks {
+        /// How many seconds to wait before failing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout: Option<u64>,
+    },
+    /// Check that every commit since the last release of each package is a conventional commit,
+    /// failing with a list of any that aren't.
+    ValidateCommits {
+        /// Check commits since this Git revision (like `origin/main`) instead of the last release.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        base: Option<String>,
+    },
+    /// Write the version of each package (the new version, if after `PrepareRelease` or
+    /// `BumpVersion`).
+    GetVersion {
+        #[serde(default, skip_serializing_if = "get_version::Format::is_default")]
+        format: get_version::Format,
+        /// Write here instead of printing. Values like `$GITHUB_OUTPUT` are read from the
+        /// environment and appended to.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file: Option<String>,
+    },
+    /// Commit the files changed by earlier steps, after replacing variables in the message.
+    Commit {
+        /// The commit message, with any variable keys you wish to replace.
+        message: String,
+        /// A map of value-to-replace to [`Variable`] to replace it with.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        variables: Option<IndexMap<String, Variable>>,
+    },
+    /// Push the current branch and any new tags, using forge tokens if available.
+    Push,
+    /// Delete the forge release and Git tags for the current version of every package.
+    DeleteRelease {
+        /// Convert forge releases back to drafts instead of deleting them
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        draft: bool,
+    },
+    /// Publish the draft GitHub release for the current version of every package.
+    PublishRelease,
+    /// Close the GitHub milestone named after the current version of every package.
+    CloseMilestone {
+        /// Move the open issues of each milestone to this one, creating it if needed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        move_open_issues_to: Option<String>,
+    },
+    /// Publish every Cargo package released in this workflow to crates.io, dependencies first.
+    PublishCargo,
+    /// Publish every npm package released in this workflow with `npm publish`.
+    PublishNpm(publish_npm::Options),
+    /// Build a Docker image for the release in this workflow, then push tags derived from its
+    /// version.
+    PublishDocker(publish_docker::Options),
+    /// Open a pull request against a Homebrew tap which points a formula at the release in this
+    /// workflow.
+    BumpHomebrewFormula(bump_homebrew_formula::Options),
+    /// Open a pull request against a Scoop bucket which points a manifest at an asset of the
+    /// release in this workflow.
+    UpdateScoopManifest(windows_package::Scoop),
+    /// Open a pull request against `microsoft/winget-pkgs` which adds manifests for an asset of the
+    /// release in this workflow.
+    UpdateWingetManifest(windows_package::Winget),
+    /// Post the notes of every release in this workflow to a Discord channel.
+    NotifyDiscord {
+        /// The webhook URL, or an environment variable containing it, like `$DISCORD_WEBHOOK`
+        webhook: String,
+    },
+    /// Post the notes of every release in this workflow to a Microsoft Teams channel.
+    NotifyTeams {
+        /// The webhook URL, or an environment variable containing it, like `$TEAMS_WEBHOOK`
+        webhook: String,
+    },
+    /// Ask the user a question, storing the answer for later steps.
+    Prompt {
+        /// The name to use with the `Output` variable.
+        name: String,
+        /// The question to ask
+        message: String,
+        /// If set, the user picks one of these instead of typing an answer.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        options: Option<Vec<String>>,
+    },
+    /// Run every step of another workflow, as if they were in this one.
+    RunWorkflow {
+        /// The `name` of the workflow to run
+        name: String,
     },
 }
 
 impl Step {
-    pub(crate) fn run(self, state: RunType<State>) -> Result<RunType<State>, Error> {
+    pub(crate) fn run(
+        self,
+        state: RunType<State>,
+        workflows: &[Workflow],
+    ) -> Result<RunType<State>, Error> {
         Ok(match self {
             Step::SelectJiraIssue { status } => issues::jira::select_issue(&status, state)?,
             Step::TransitionJiraIssue { status } => issues::jira::transition_issue(&status, state)?,
+            Step::CreateJiraIssue {
+                summary,
+                description,
+                issue_type,
+            } => issues::jira::create_issue(summary, description, issue_type.as_deref(), state)?,
+            Step::CommentOnJiraIssue { body } => issues::jira::comment_on_issue(body, state)?,
             Step::SelectGitHubIssue { labels } => {
                 issues::github::select_issue(labels.as_deref(), state)?
             }
@@ -113,30 +291,166 @@ impl Step {
                 git::rebase_branch(&state.of(to))?;
                 state
             }
+            Step::CreateBranch { name, variables } => create_branch::run(state, name, variables)?,
             Step::BumpVersion(rule) => releases::bump_version(state, &rule)?,
             Step::Command {
                 command,
                 variables,
                 shell,
-            } => command::run_command(state, command, shell.is_some_and(|it| it), variables)?,
+                capture_output,
+                retries,
+                env,
+            } => command::run_command(
+                state,
+                command,
+                shell.is_some_and(|it| it),
+                variables,
+                capture_output,
+                retries.unwrap_or_default(),
+                env,
+            )?,
             Step::PrepareRelease(prepare_release) => {
                 releases::prepare_release(state, &prepare_release)?
             }
             Step::SelectIssueFromBranch => git::select_issue_from_current_branch(state)?,
-            Step::Release => releases::release(state)?,
+            Step::Release {
+                parallel,
+                annotate_tags,
+                close_fixed_issues,
+            } => releases::release(state, parallel, annotate_tags, close_fixed_issues)?,
             Step::CreateChangeFile => create_change_file::run(state)?,
-            Step::CreatePullRequest { base, title, body } => {
-                create_pull_request::run(&base, title, body, state)?
+            Step::CreatePullRequest {
+                base,
+                title,
+                body,
+                metadata,
+            } => create_pull_request::run(&base, title, body, &metadata, state)?,
+            Step::MergePullRequest {
+                base,
+                method,
+                auto_merge,
+            } => merge_pull_request::run(&base, method, auto_merge, state)?,
+            Step::WaitForChecks { timeout } => wait_for_checks::run(timeout, state)?,
+            Step::ValidateCommits { base } => validate_commits::run(state, base.as_deref())?,
+            Step::GetVersion { format, file } => get_version::run(state, format, file.as_deref())?,
+            Step::Commit { message, variables } => commit::run(state, message, variables)?,
+            Step::Push => push::run(state)?,
+            Step::DeleteRelease { draft } => delete_release::run(state, draft)?,
+            Step::PublishRelease => publish_release::run(state)?,
+            Step::CloseMilestone {
+                move_open_issues_to,
+            } => close_milestone::run(move_open_issues_to.as_deref(), state)?,
+            Step::PublishCargo => publish_cargo::run(state)?,
+            Step::PublishNpm(options) => publish_npm::run(&options, state)?,
+            Step::PublishDocker(options) => publish_docker::run(&options, state)?,
+            Step::BumpHomebrewFormula(options) => bump_homebrew_formula::run(options, state)?,
+            Step::UpdateScoopManifest(options) => windows_package::run_scoop(&options, state)?,
+            Step::UpdateWingetManifest(options) => windows_package::run_winget(&options, state)?,
+            Step::NotifyDiscord { webhook } => {
+                notify::run(webhook::Service::Discord, &webhook, state)?
             }
+            Step::NotifyTeams { webhook } => notify::run(webhook::Service::Teams, &webhook, state)?,
+            Step::Prompt {
+                name,
+                message,
+                options,
+            } => input::run(state, name, &message, options)?,
+            Step::RunWorkflow { name } => run_workflow::run(&name, workflows, state)?,
         })
     }
 
+    /// Whether this step reads or changes the configured packages.
+    pub(crate) fn affects_packages(&self) -> bool {
+        matches!(
+            self,
+            Step::BumpVersion(_)
+                | Step::PrepareRelease(_)
+                | Step::Release { .. }
+                | Step::DeleteRelease { .. }
+                | Step::PublishRelease
+                | Step::CloseMilestone { .. }
+                | Step::CreateChangeFile
+                | Step::ValidateCommits { .. }
+                | Step::GetVersion { .. }
+                | Step::PublishCargo
+                | Step::PublishNpm(_)
+                | Step::PublishDocker(_)
+                | Step::BumpHomebrewFormula(_)
+                | Step::UpdateScoopManifest(_)
+                | Step::UpdateWingetManifest(_)
+                | Step::NotifyDiscord { .. }
+                | Step::NotifyTeams { .. }
+        )
+    }
+
+    /// Whether this step needs permission to write to a forge repository, like creating releases
+    /// or pull requests.
+    pub(crate) fn writes_to_forge(&self) -> bool {
+        matches!(
+            self,
+            Step::Release { .. }
+                | Step::CreatePullRequest { .. }
+                | Step::MergePullRequest { .. }
+                | Step::DeleteRelease { .. }
+                | Step::PublishRelease
+                | Step::CloseMilestone { .. }
+                | Step::BumpHomebrewFormula(_)
+                | Step::UpdateScoopManifest(_)
+                | Step::UpdateWingetManifest(_)
+        )
+    }
+
+    /// Whether everything this step does can be saved with `--plan` and done later by
+    /// `knope apply`.
+    ///
+    /// Steps which need answers from a person or a forge _while_ running can't be planned.
+    pub(crate) fn can_plan(&self) -> bool {
+        match self {
+            Step::BumpVersion(_)
+            | Step::PrepareRelease(_)
+            | Step::ValidateCommits { .. }
+            | Step::Commit { .. }
+            | Step::Push
+            | Step::RunWorkflow { .. } => true,
+            Step::Command { capture_output, .. } => capture_output.is_none(),
+            Step::GetVersion { file, .. } => file.is_none(),
+            Step::Release {
+                close_fixed_issues, ..
+            } => !close_fixed_issues,
+            _ => false,
+        }
+    }
+
+    /// The `type` of the step, as it's written in `knope.toml`.
+    pub(crate) fn name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.get("type")?.as_str().map(String::from))
+            .unwrap_or_default()
+    }
+
+    /// Add `workflow_env` to the `env` of a `Command` step, unless the step sets the same keys.
+    pub(crate) fn inherit_env(&mut self, workflow_env: &IndexMap<String, String>) {
+        if let Step::Command { env, .. } = self {
+            for (key, value) in workflow_env {
+                env.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
     /// Set `prerelease_label` if `self` is `PrepareRelease`.
     pub(crate) fn set_prerelease_label(&mut self, prerelease_label: &str) {
         if let Step::PrepareRelease(prepare_release) = self {
             prepare_release.prerelease_label = Some(Label::from(prerelease_label));
         }
     }
+
+    /// Set `rule` if `self` is `PrepareRelease`.
+    pub(crate) fn set_rule(&mut self, rule: StableRule) {
+        if let Step::PrepareRelease(prepare_release) = self {
+            prepare_release.rule = Some(rule);
+        }
+    }
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -168,18 +482,96 @@ pub(super) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     CreatePullRequest(#[from] create_pull_request::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MergePullRequest(#[from] merge_pull_request::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    WaitForChecks(#[from] wait_for_checks::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ValidateCommits(#[from] validate_commits::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GetVersion(#[from] get_version::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    RunWorkflow(#[from] run_workflow::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    CreateBranch(#[from] create_branch::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Commit(#[from] commit::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Push(#[from] push::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    DeleteRelease(#[from] delete_release::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PublishRelease(#[from] publish_release::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    CloseMilestone(#[from] close_milestone::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PublishCargo(#[from] publish_cargo::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PublishNpm(#[from] publish_npm::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PublishDocker(#[from] publish_docker::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    BumpHomebrewFormula(#[from] bump_homebrew_formula::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    WindowsPackage(#[from] windows_package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Notify(#[from] notify::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Condition(#[from] condition::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Scope(#[from] scope::Error),
 }
 
 /// The inner content of a [`Step::PrepareRelease`] step.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[allow(clippy::struct_excessive_bools)] // Each is an independent option in the config file
 pub(crate) struct PrepareRelease {
     /// If set, the user wants to create a pre-release version using the selected label.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) prerelease_label: Option<Label>,
+    /// Bump by this rule instead of the one implied by the changes, set at runtime with `--rule`
+    #[serde(skip)]
+    pub(crate) rule: Option<StableRule>,
     /// Should this step continue if there are no changes to release? If not, it causes an error.
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub(crate) allow_empty: bool,
     /// If set to true, conventional commits are ignored
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub(crate) ignore_conventional_commits: bool,
+    /// If set to true, bulleted conventional commits in the body of squash-merge commits are parsed
+    /// as separate changes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) split_squash_merges: bool,
+    /// Commits from these authors (by name or email) are ignored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) ignore_authors: Vec<String>,
+    /// Commits with messages matching any of these regexes are ignored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) ignore_commit_patterns: Vec<String>,
+    /// If set to true, changes are written to an "Unreleased" section of the changelog instead of
+    /// creating a new version
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) unreleased: bool,
+    /// If set to true, find the changes for each package at the same time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) parallel: bool,
 }
@@ -0,0 +1,42 @@
+use miette::Diagnostic;
+
+use crate::{
+    integrations::webhook,
+    state::{RunType, State},
+};
+
+/// Post the notes of every release in this workflow to `service`.
+pub(crate) fn run(
+    service: webhook::Service,
+    webhook: &str,
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    let releases = state.releases();
+    if releases.is_empty() {
+        return Err(Error::NoReleases);
+    }
+    for release in releases {
+        webhook::notify(
+            service,
+            webhook,
+            &release.full_title(),
+            &release.notes,
+            run_type,
+        )?;
+    }
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("There are no releases to post")]
+    #[diagnostic(
+        code(notify::no_releases),
+        help("Use this step after `PrepareRelease` or `Release` in the same workflow.")
+    )]
+    NoReleases,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Webhook(#[from] webhook::Error),
+}
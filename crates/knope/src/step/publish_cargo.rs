@@ -0,0 +1,131 @@
+use std::process::{Command, ExitStatus};
+
+use knope_versioning::{semver::Version, versioned_file::cargo::Cargo, VersionedFile};
+use miette::Diagnostic;
+use relative_path::RelativePathBuf;
+use tracing::{debug, info};
+
+use crate::{
+    integrations::crates_io,
+    state::{RunType, State},
+};
+
+/// Publish every Cargo package released in this workflow to crates.io, each after the others
+/// it depends on.
+pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    let crates = in_dependency_order(crates_to_publish(&state));
+    if crates.is_empty() {
+        info!("No Cargo packages to publish");
+    }
+    for (index, krate) in crates.iter().enumerate() {
+        publish(krate, run_type)?;
+        let needed_later = crates
+            .iter()
+            .skip(index + 1)
+            .any(|later| later.cargo.depends_on(krate.name));
+        if needed_later && matches!(run_type, RunType::Real(())) {
+            crates_io::wait_for_version(krate.name, krate.version)?;
+        }
+    }
+    Ok(run_type.of(state))
+}
+
+/// A `Cargo.toml` file for a package released in this workflow.
+struct Crate<'a> {
+    name: &'a str,
+    version: &'a Version,
+    path: &'a RelativePathBuf,
+    cargo: &'a Cargo,
+}
+
+fn crates_to_publish(state: &State) -> Vec<Crate> {
+    state
+        .released_files()
+        .into_iter()
+        .filter_map(|(release, versioned_file)| {
+            let VersionedFile::Cargo(cargo) = versioned_file else {
+                return None;
+            };
+            // Workspace roots without a `[package]` can't be published
+            Some(Crate {
+                name: cargo.name()?,
+                version: &release.version,
+                path: versioned_file.path(),
+                cargo,
+            })
+        })
+        .collect()
+}
+
+/// Sort `crates` so that each comes after any of the others it depends on, keeping the original
+/// order where it doesn't matter (or where dependencies are circular).
+fn in_dependency_order(mut crates: Vec<Crate>) -> Vec<Crate> {
+    let mut sorted = Vec::with_capacity(crates.len());
+    while !crates.is_empty() {
+        let ready = crates
+            .iter()
+            .position(|krate| {
+                !crates
+                    .iter()
+                    .any(|other| other.name != krate.name && krate.cargo.depends_on(other.name))
+            })
+            .unwrap_or(0);
+        sorted.push(crates.remove(ready));
+    }
+    sorted
+}
+
+fn publish(krate: &Crate, run_type: RunType<()>) -> Result<(), Error> {
+    let Crate {
+        name,
+        version,
+        path,
+        ..
+    } = krate;
+    let mut command = Command::new("cargo");
+    command
+        .arg("publish")
+        .arg("--manifest-path")
+        .arg(path.to_path(""));
+    if let RunType::DryRun(()) = run_type {
+        info!("Checking that {name} can be published with `cargo publish --dry-run`");
+        command.arg("--dry-run");
+    } else {
+        info!("Publishing {name} {version} to crates.io");
+    }
+    debug!("Running {command:?}");
+    let status = command.status().map_err(Error::Io)?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Failed {
+            name: (*name).to_string(),
+            status,
+        })
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not run cargo: {0}")]
+    #[diagnostic(
+        code(publish_cargo::io),
+        help("Make sure cargo is installed and on your PATH."),
+        url("https://knope.tech/reference/config-file/steps/publish-cargo/")
+    )]
+    Io(#[source] std::io::Error),
+    #[error("Publishing {name} failed: {status}")]
+    #[diagnostic(
+        code(publish_cargo::failed),
+        help(
+            "Check the output above. To publish, cargo needs a token in CARGO_REGISTRY_TOKEN or \
+            from `cargo login`."
+        ),
+        url("https://knope.tech/reference/config-file/steps/publish-cargo/")
+    )]
+    Failed { name: String, status: ExitStatus },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    CratesIo(#[from] crates_io::Error),
+}
@@ -0,0 +1,174 @@
+use std::process::{Command, ExitStatus};
+
+use itertools::Itertools;
+use knope_versioning::semver::Version;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::state::{RunType, State, TooManyReleases};
+
+/// The inner content of a [`crate::step::Step::PublishDocker`] step.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Options {
+    /// The image to tag and push, without a tag, like `ghcr.io/knope-dev/knope`
+    pub(crate) image: String,
+    /// The directory to build, the current directory by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) context: Option<String>,
+    /// The path to the Dockerfile, if it isn't `Dockerfile` in `context`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) dockerfile: Option<String>,
+    /// Which tags to push, derived from the version
+    #[serde(default = "Tag::defaults", skip_serializing_if = "Tag::are_defaults")]
+    pub(crate) tags: Vec<Tag>,
+    /// The name of the package to build, if more than one is released
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) package: Option<String>,
+}
+
+/// A tag for the image, derived from the version of a release.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum Tag {
+    /// The full version, like `1.2.3` or `1.2.3-rc.0`
+    Version,
+    /// The major and minor components, like `1.2`
+    MajorMinor,
+    /// Only the major component, like `1`
+    Major,
+    /// `latest`
+    Latest,
+}
+
+impl Tag {
+    fn defaults() -> Vec<Self> {
+        vec![Self::Version, Self::MajorMinor, Self::Latest]
+    }
+
+    #[allow(clippy::ptr_arg)] // `skip_serializing_if` passes a reference to the field
+    fn are_defaults(tags: &Vec<Self>) -> bool {
+        *tags == Self::defaults()
+    }
+
+    /// The tag for `version`, or `None` if the tag shouldn't move to a pre-release.
+    fn for_version(self, version: &Version) -> Option<String> {
+        if self != Self::Version && version.is_prerelease() {
+            return None;
+        }
+        let stable = version.stable_component().to_string();
+        let mut components = stable.split('.');
+        match self {
+            Self::Version => Some(version.to_string()),
+            Self::MajorMinor => Some(components.take(2).join(".")),
+            Self::Major => components.next().map(String::from),
+            Self::Latest => Some(String::from("latest")),
+        }
+    }
+}
+
+/// Build a Docker image for the release in this workflow, then push it with each of the tags.
+///
+/// The version is available to the Dockerfile as the `VERSION` build argument.
+pub(crate) fn run(options: &Options, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    let Some(release) = state.single_release(options.package.as_deref())? else {
+        info!("No release to build a Docker image for");
+        return Ok(run_type.of(state));
+    };
+    let image = &options.image;
+    let tags = options
+        .tags
+        .iter()
+        .filter_map(|tag| tag.for_version(&release.version))
+        .map(|tag| format!("{image}:{tag}"))
+        .collect_vec();
+    let context = options.context.as_deref().unwrap_or(".");
+
+    if let RunType::DryRun(()) = run_type {
+        info!("Would build a Docker image from {context} and push it as:");
+        for tag in &tags {
+            info!("  {tag}");
+        }
+        return Ok(run_type.of(state));
+    }
+
+    let mut build = Command::new("docker");
+    build.arg("build");
+    for tag in &tags {
+        build.args(["--tag", tag]);
+    }
+    if let Some(dockerfile) = &options.dockerfile {
+        build.args(["--file", dockerfile]);
+    }
+    build
+        .arg("--build-arg")
+        .arg(format!("VERSION={}", release.version))
+        .arg(context);
+    docker(build)?;
+    for tag in &tags {
+        info!("Pushing {tag}");
+        let mut push = Command::new("docker");
+        push.args(["push", tag]);
+        docker(push)?;
+    }
+    Ok(run_type.of(state))
+}
+
+fn docker(mut command: Command) -> Result<(), Error> {
+    debug!("Running {command:?}");
+    let status = command.status().map_err(Error::Io)?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Failed(status))
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not run docker: {0}")]
+    #[diagnostic(
+        code(publish_docker::io),
+        help("Make sure Docker is installed and on your PATH."),
+        url("https://knope.tech/reference/config-file/steps/publish-docker/")
+    )]
+    Io(#[source] std::io::Error),
+    #[error("docker failed: {0}")]
+    #[diagnostic(
+        code(publish_docker::failed),
+        help("Check the output above. To push, you may need to run `docker login` first."),
+        url("https://knope.tech/reference/config-file/steps/publish-docker/")
+    )]
+    Failed(ExitStatus),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    TooManyReleases(#[from] TooManyReleases),
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_tag {
+    use std::str::FromStr;
+
+    use knope_versioning::semver::Version;
+
+    use super::Tag;
+
+    #[test]
+    fn stable() {
+        let version = Version::from_str("1.2.3").unwrap();
+        assert_eq!(Tag::Version.for_version(&version).unwrap(), "1.2.3");
+        assert_eq!(Tag::MajorMinor.for_version(&version).unwrap(), "1.2");
+        assert_eq!(Tag::Major.for_version(&version).unwrap(), "1");
+        assert_eq!(Tag::Latest.for_version(&version).unwrap(), "latest");
+    }
+
+    #[test]
+    fn prerelease_only_has_full_version() {
+        let version = Version::from_str("1.2.3-rc.0").unwrap();
+        assert_eq!(Tag::Version.for_version(&version).unwrap(), "1.2.3-rc.0");
+        assert_eq!(Tag::MajorMinor.for_version(&version), None);
+        assert_eq!(Tag::Major.for_version(&version), None);
+        assert_eq!(Tag::Latest.for_version(&version), None);
+    }
+}
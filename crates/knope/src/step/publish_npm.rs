@@ -0,0 +1,143 @@
+use std::{
+    fmt::{self, Display},
+    process::{Command, ExitStatus},
+};
+
+use knope_versioning::{semver::Version, VersionedFile};
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::state::{RunType, State};
+
+/// Who can install a scoped package published by [`crate::step::Step::PublishNpm`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Access {
+    Public,
+    Restricted,
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Public => write!(f, "public"),
+            Self::Restricted => write!(f, "restricted"),
+        }
+    }
+}
+
+/// The inner content of a [`crate::step::Step::PublishNpm`] step, options for `npm publish`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Options {
+    /// The dist-tag to publish with, instead of `latest` (or `next` for pre-releases)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tag: Option<String>,
+    /// Whether a scoped package is public or restricted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) access: Option<Access>,
+    /// Publish a provenance statement, linking the package to the CI run that built it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) provenance: bool,
+}
+
+/// Publish every npm package released in this workflow with `npm publish`.
+pub(crate) fn run(options: &Options, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    let packages = state
+        .released_files()
+        .into_iter()
+        .filter(|(_, versioned_file)| matches!(versioned_file, VersionedFile::PackageJson(_)))
+        .collect::<Vec<_>>();
+    if packages.is_empty() {
+        info!("No npm packages to publish");
+    }
+    for (release, versioned_file) in packages {
+        let directory = versioned_file
+            .path()
+            .parent()
+            .map(|parent| parent.to_path("."))
+            .unwrap_or_default();
+        let tag = options
+            .tag
+            .as_deref()
+            .unwrap_or_else(|| dist_tag(&release.version));
+        let mut command = Command::new("npm");
+        command.arg("publish").arg(&directory).args(["--tag", tag]);
+        if let Some(access) = options.access {
+            command.arg("--access").arg(access.to_string());
+        }
+        if options.provenance {
+            command.arg("--provenance");
+        }
+        let path = versioned_file.path();
+        if let RunType::DryRun(()) = run_type {
+            info!("Checking that {path} can be published with `npm publish --dry-run`");
+            command.arg("--dry-run");
+        } else {
+            info!(
+                "Publishing {path} {version} to npm with the tag {tag}",
+                version = release.version
+            );
+        }
+        debug!("Running {command:?}");
+        let status = command.status().map_err(Error::Io)?;
+        if !status.success() {
+            return Err(Error::Failed {
+                path: path.to_string(),
+                status,
+            });
+        }
+    }
+    Ok(run_type.of(state))
+}
+
+/// npm installs the `latest` tag by default, so pre-releases go to `next` instead.
+fn dist_tag(version: &Version) -> &'static str {
+    if version.is_prerelease() {
+        "next"
+    } else {
+        "latest"
+    }
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not run npm: {0}")]
+    #[diagnostic(
+        code(publish_npm::io),
+        help("Make sure npm is installed and on your PATH."),
+        url("https://knope.tech/reference/config-file/steps/publish-npm/")
+    )]
+    Io(#[source] std::io::Error),
+    #[error("Publishing {path} failed: {status}")]
+    #[diagnostic(
+        code(publish_npm::failed),
+        help(
+            "Check the output above. To publish, npm needs a token, for example in an `.npmrc` \
+            file or from `npm login`."
+        ),
+        url("https://knope.tech/reference/config-file/steps/publish-npm/")
+    )]
+    Failed { path: String, status: ExitStatus },
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_dist_tag {
+    use std::str::FromStr;
+
+    use knope_versioning::semver::Version;
+
+    use super::dist_tag;
+
+    #[test]
+    fn stable_is_latest() {
+        assert_eq!(dist_tag(&Version::from_str("1.2.3").unwrap()), "latest");
+    }
+
+    #[test]
+    fn prerelease_is_next() {
+        assert_eq!(dist_tag(&Version::from_str("1.2.3-rc.0").unwrap()), "next");
+    }
+}
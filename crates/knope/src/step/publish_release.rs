@@ -0,0 +1,43 @@
+use knope_versioning::ReleaseTag;
+use miette::Diagnostic;
+
+use crate::{
+    integrations::github,
+    state::{RunType, State},
+    step::releases::package,
+};
+
+/// Publish the draft GitHub release for the current version of every package.
+pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let Some(github_config) = &state.github_config else {
+        return Err(Error::NotConfigured);
+    };
+    if state.packages.is_empty() {
+        return Err(package::Error::NoDefinedPackages.into());
+    }
+    for package in &state.packages {
+        let version = package.versioning.versions.clone().into_latest();
+        let tag = ReleaseTag::new(&version, package.name());
+        state.github =
+            github::publish_release(tag.as_str(), run_type.of(state.github), github_config)?;
+    }
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("GitHub is not configured")]
+    #[diagnostic(
+        code(publish_release::github::not_configured),
+        help("GitHub must be configured in order to use the PublishRelease step"),
+        url("https://knope.tech/reference/config-file/github/")
+    )]
+    NotConfigured,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Package(#[from] package::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::PublishReleaseError),
+}
@@ -0,0 +1,295 @@
+use std::process::{Command, Stdio};
+
+use base64::{prelude::BASE64_STANDARD as base64, Engine};
+use miette::Diagnostic;
+use tracing::{debug, info, warn};
+
+use crate::{
+    app_config,
+    integrations::git,
+    plan, state,
+    state::{RunType, State},
+};
+
+/// Push the current branch and new tags (which are all annotated when Knope creates them)
+/// pointing at pushed commits.
+///
+/// Without a `remote` in the forge config, this follows the upstream of the current branch like
+/// a plain `git push`. If the remote is an HTTPS URL for the configured GitHub or Gitea, the same
+/// token that those steps use is sent along, otherwise Git's own credentials are used.
+pub(crate) fn run(state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let mut args = vec!["push", "--follow-tags"];
+    let configured_remote = configured_remote(&state);
+    if let Some(remote) = configured_remote {
+        args.push(remote);
+    }
+    let command = format!("git {}", args.join(" "));
+    if let RunType::DryRun(()) = run_type {
+        info!("Would run {command}");
+        plan::record(&mut state.plan, |plan| {
+            plan.commands.push(command);
+            plan.actions.push(plan::Action::Push);
+        });
+        return Ok(run_type.of(state));
+    }
+
+    let status = git(&state, &args).status()?;
+    if status.success() {
+        Ok(run_type.of(state))
+    } else {
+        Err(Error::Push(status))
+    }
+}
+
+/// Delete `tag` from the forge's remote (or the first one), if it's there.
+pub(crate) fn delete_remote_tag(state: RunType<&mut State>, tag: &str) -> Result<(), Error> {
+    let Some(remote) = remote(state.inner()) else {
+        debug!("No remote to delete {tag} from");
+        return Ok(());
+    };
+    let (run_type, state) = state.take();
+    let command = format!("git push {remote} --delete {tag}");
+    if let RunType::DryRun(()) = run_type {
+        info!("Would run {command}");
+        plan::record(&mut state.plan, |plan| plan.commands.push(command));
+        return Ok(());
+    }
+
+    let tag_ref = format!("refs/tags/{tag}");
+    let listed = git(
+        state,
+        &["ls-remote", "--exit-code", "--tags", &remote, &tag_ref],
+    )
+    .stdout(Stdio::null())
+    .status()?;
+    // `--exit-code` exits with 2 when there's no matching ref
+    if listed.code() == Some(2) {
+        debug!("{remote} has no tag {tag}");
+        return Ok(());
+    }
+    if !listed.success() {
+        return Err(Error::ListRemote(listed));
+    }
+    let status = git(state, &["push", &remote, "--delete", tag]).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Push(status))
+    }
+}
+
+/// A lock on a workflow, taken by [`lock`] and released by [`unlock`].
+pub(crate) struct Lock {
+    remote: String,
+    reference: String,
+    /// The commit that the lock points at, so that only this run's lock is removed
+    commit: String,
+    credentials: Option<Credentials>,
+}
+
+/// Make sure no other run of `workflow` is in progress by creating `refs/knope/lock/<workflow>`
+/// on the remote, which fails if another run already created it.
+///
+/// Git refs are updated atomically by the remote, so this works across machines (like
+/// simultaneous CI jobs). Nothing is locked in a dry run.
+pub(crate) fn lock(state: RunType<&State>, workflow: &str) -> Result<Option<Lock>, Error> {
+    let (run_type, state) = state.take();
+    let remote = remote(state).ok_or_else(|| Error::NoRemoteToLock(workflow.to_string()))?;
+    let reference = format!("refs/knope/lock/{workflow}");
+    if let RunType::DryRun(()) = run_type {
+        info!("Would lock the {workflow} workflow with {reference} on {remote}");
+        return Ok(None);
+    }
+
+    let commit = git::head_commit_id().map_err(|err| Error::Lock(err.to_string()))?;
+    let credentials = forge_credentials(state);
+    // An empty lease means the ref must not exist yet
+    let lease = format!("--force-with-lease={reference}:");
+    let destination = format!("HEAD:{reference}");
+    let output = git_with(
+        credentials.as_ref(),
+        &["push", "--porcelain", &lease, &remote, &destination],
+    )
+    .output()?;
+    // If the lock already points at HEAD, the push succeeds without checking the lease—so the
+    // lock was only taken if the ref is new
+    let created = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line.starts_with('*'));
+    if output.status.success() && created {
+        debug!("Locked {workflow} with {reference} on {remote}");
+        return Ok(Some(Lock {
+            remote,
+            reference,
+            commit,
+            credentials,
+        }));
+    }
+    let listed = git_with(
+        credentials.as_ref(),
+        &["ls-remote", "--exit-code", &remote, &reference],
+    )
+    .stdout(Stdio::null())
+    .status()?;
+    if listed.success() {
+        Err(Error::Locked {
+            workflow: workflow.to_string(),
+            remote,
+            reference,
+        })
+    } else {
+        Err(Error::Lock(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Release a lock from [`lock`], whether the workflow succeeded or not.
+///
+/// Failing to unlock shouldn't hide the result of the workflow, so problems are only logged.
+pub(crate) fn unlock(lock: Lock) {
+    let Lock {
+        remote,
+        reference,
+        commit,
+        credentials,
+    } = lock;
+    let lease = format!("--force-with-lease={reference}:{commit}");
+    let destination = format!(":{reference}");
+    let unlocked = git_with(
+        credentials.as_ref(),
+        &["push", "--quiet", &lease, &remote, &destination],
+    )
+    .status()
+    .is_ok_and(|status| status.success());
+    if unlocked {
+        debug!("Unlocked {reference} on {remote}");
+    } else {
+        warn!("Could not remove the lock, delete it with `git push {remote} --delete {reference}`");
+    }
+}
+
+/// The `remote` set in the forge config, if any.
+fn configured_remote(state: &State) -> Option<&str> {
+    state
+        .github_config
+        .as_ref()
+        .and_then(|github| github.remote.as_deref())
+        .or_else(|| {
+            state
+                .gitea_config
+                .as_ref()
+                .and_then(|gitea| gitea.remote.as_deref())
+        })
+}
+
+/// The name of the remote that the forge is at: the configured one, otherwise the first.
+fn remote(state: &State) -> Option<String> {
+    configured_remote(state)
+        .map(String::from)
+        .or_else(|| git::get_remote_names().into_iter().next())
+}
+
+/// A `git` command with `args`, which sends forge tokens when talking to that forge.
+fn git(state: &State, args: &[&str]) -> Command {
+    git_with(forge_credentials(state).as_ref(), args)
+}
+
+fn git_with(credentials: Option<&Credentials>, args: &[&str]) -> Command {
+    let mut git = Command::new("git");
+    git.args(args);
+    if let Some(Credentials { url, authorization }) = credentials {
+        debug!("Using forge token to connect to {url}");
+        // Environment config keeps the token out of the process arguments
+        git.env("GIT_CONFIG_COUNT", "1")
+            .env("GIT_CONFIG_KEY_0", format!("http.{url}.extraHeader"))
+            .env(
+                "GIT_CONFIG_VALUE_0",
+                format!("Authorization: Basic {authorization}"),
+            );
+    }
+    git
+}
+struct Credentials {
+    /// Only send the token to URLs starting with this
+    url: String,
+    /// The base64 encoded `user:password`
+    authorization: String,
+}
+
+fn forge_credentials(state: &State) -> Option<Credentials> {
+    let remote = git::get_remote_url(&remote(state)?)?;
+    if !remote.starts_with("https://") {
+        return None;
+    }
+    if state.github_config.is_some() && remote.starts_with("https://github.com/") {
+        let token = match &state.github {
+            state::GitHub::Initialized { token, .. } => Some(token.clone()),
+            state::GitHub::New => app_config::github_token(),
+        }?;
+        return Some(Credentials {
+            url: String::from("https://github.com/"),
+            authorization: base64.encode(format!("x-access-token:{token}")),
+        });
+    }
+    let gitea = state
+        .gitea_config
+        .as_ref()
+        .filter(|gitea| remote.starts_with(&gitea.host))?;
+    let token = match &state.gitea {
+        state::Gitea::Initialized { token, .. } => Some(token.clone()),
+        state::Gitea::New => app_config::gitea_token(),
+    }?;
+    Some(Credentials {
+        url: format!("{}/", gitea.host.trim_end_matches('/')),
+        authorization: base64.encode(format!("{token}:x-oauth-basic")),
+    })
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Could not run git: {0}")]
+    #[diagnostic(code(push::io), help("Make sure Git is installed and on your PATH."))]
+    Io(#[from] std::io::Error),
+    #[error("git push failed: {0}")]
+    #[diagnostic(
+        code(push::failed),
+        help(
+            "Check the output from Git above, like whether the branch has an upstream to push to."
+        ),
+        url("https://knope.tech/reference/config-file/steps/push/")
+    )]
+    Push(std::process::ExitStatus),
+    #[error("Could not list the tags of the remote: {0}")]
+    #[diagnostic(
+        code(push::list_remote),
+        help("Check the output from Git above, the remote may not be reachable.")
+    )]
+    ListRemote(std::process::ExitStatus),
+    #[error("Another run of the {workflow} workflow is in progress")]
+    #[diagnostic(
+        code(push::locked),
+        help("Wait for the other run to finish. If it stopped without unlocking, delete the lock with `git push {remote} --delete {reference}`."),
+        url("https://knope.tech/reference/config-file/workflow/#lock")
+    )]
+    Locked {
+        workflow: String,
+        remote: String,
+        reference: String,
+    },
+    #[error("Could not lock the workflow: {0}")]
+    #[diagnostic(
+        code(push::lock),
+        help("Check that the remote is reachable and that Git can push to it."),
+        url("https://knope.tech/reference/config-file/workflow/#lock")
+    )]
+    Lock(String),
+    #[error("The {0} workflow can't be locked without a Git remote")]
+    #[diagnostic(
+        code(push::no_remote_to_lock),
+        help("Add a remote (like `origin`), or set `remote` in the forge config."),
+        url("https://knope.tech/reference/config-file/workflow/#lock")
+    )]
+    NoRemoteToLock(String),
+}
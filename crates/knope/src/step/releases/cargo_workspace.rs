@@ -0,0 +1,181 @@
+use itertools::Itertools;
+use knope_versioning::{package::Name, VersionedFile, VersionedFileConfig};
+use relative_path::{RelativePath, RelativePathBuf};
+
+use super::package::Error;
+use crate::{config, fs::read_to_string};
+
+/// A workspace root which a package's members inherit their version from.
+pub(super) struct InheritedRoot {
+    path: RelativePathBuf,
+    package: Name,
+    version_group: Option<String>,
+}
+
+/// Cargo workspace members that use `version.workspace = true` store their version in the
+/// workspace root, so that file has to be versioned too.
+///
+/// Each root is added to `inherited`. Only one package can write the version of a root, unless
+/// every package inheriting it is in the same version group (so they always have the same version).
+pub(super) fn add_workspace_roots(
+    package: &mut config::Package,
+    versioned_files: &mut Vec<VersionedFile>,
+    git_tags: &[String],
+    inherited: &mut Vec<InheritedRoot>,
+) -> Result<(), Error> {
+    let members = package
+        .versioned_files
+        .iter()
+        .filter(|config| config.dependency.is_none())
+        .filter(|config| {
+            versioned_files
+                .iter()
+                .any(|file| *config == file.path() && file.inherits_version())
+        })
+        .map(VersionedFileConfig::as_path)
+        .collect_vec();
+    for member in members {
+        let (root, content) =
+            find_workspace_root(&member)?.ok_or(Error::CargoWorkspaceRootNotFound(member))?;
+        let path = root.as_path();
+        if let Some(other) = inherited.iter().find(|other| {
+            other.path == path
+                && other.package != package.name
+                && (other.version_group.is_none() || other.version_group != package.version_group)
+        }) {
+            return Err(Error::SharedCargoWorkspaceVersion {
+                root: path,
+                first: other.package.to_string(),
+                second: package.name.to_string(),
+            });
+        }
+        inherited.push(InheritedRoot {
+            path,
+            package: package.name.clone(),
+            version_group: package.version_group.clone(),
+        });
+        if !versioned_files.iter().any(|file| root == *file.path()) {
+            versioned_files
+                .push(VersionedFile::new(&root, content, git_tags).map_err(Error::VersionedFile)?);
+        }
+        if !package.versioned_files.contains(&root) {
+            package.versioned_files.push(root);
+        }
+    }
+    Ok(())
+}
+
+/// Look in the parent directories of `member` for a `Cargo.toml` containing a `[workspace]`.
+fn find_workspace_root(
+    member: &RelativePath,
+) -> Result<Option<(VersionedFileConfig, String)>, Error> {
+    let mut dir = member.parent().and_then(RelativePath::parent);
+    while let Some(current) = dir {
+        let path = current.join("Cargo.toml");
+        if let Some(config) = path
+            .to_path("")
+            .exists()
+            .then(|| VersionedFileConfig::new(path, None).ok())
+            .flatten()
+        {
+            let content = read_to_string(config.to_pathbuf())?;
+            if let Ok(VersionedFile::Cargo(cargo)) =
+                VersionedFile::new(&config, content.clone(), &[""])
+            {
+                if cargo.is_workspace_root() {
+                    return Ok(Some((config, content)));
+                }
+            }
+        }
+        dir = current.parent();
+    }
+    Ok(None)
+}
+
+/// When a package is versioned by a `Cargo.toml` in a workspace, any other manifests in that
+/// workspace which depend on it by `path` (with a `version`) need to be updated too.
+pub(super) fn add_path_dependencies(
+    package: &mut config::Package,
+    versioned_files: &mut Vec<VersionedFile>,
+    git_tags: &[String],
+) -> Result<(), Error> {
+    let crates = package
+        .versioned_files
+        .iter()
+        .filter(|config| config.dependency.is_none())
+        .filter_map(|config| versioned_files.iter().find(|file| config == file.path()))
+        .filter_map(|file| match file {
+            VersionedFile::Cargo(cargo) => Some((
+                file.path().clone(),
+                cargo.name()?.to_string(),
+                cargo.is_workspace_root(),
+            )),
+            _ => None,
+        })
+        .collect_vec();
+    for (path, name, is_root) in crates {
+        let root = if is_root {
+            let content = read_to_string(path.to_path(""))?;
+            VersionedFileConfig::new(path.clone(), None)
+                .ok()
+                .map(|config| (config, content))
+        } else {
+            find_workspace_root(&path)?
+        };
+        let Some((root, root_content)) = root else {
+            continue;
+        };
+        for (manifest, content) in workspace_manifests(&root, root_content, git_tags)? {
+            if manifest == path {
+                continue;
+            }
+            let Ok(config) = VersionedFileConfig::new(manifest, Some(name.clone())) else {
+                continue;
+            };
+            let cargo =
+                VersionedFile::new(&config, content, git_tags).map_err(Error::VersionedFile)?;
+            let VersionedFile::Cargo(manifest_cargo) = &cargo else {
+                continue;
+            };
+            if !manifest_cargo.contains_path_dependency(&name) {
+                continue;
+            }
+            if !versioned_files.iter().any(|file| config == *file.path()) {
+                versioned_files.push(cargo);
+            }
+            if !package.versioned_files.contains(&config) {
+                package.versioned_files.push(config);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The root `Cargo.toml` and the `Cargo.toml` of every listed member, with their contents.
+fn workspace_manifests(
+    root: &VersionedFileConfig,
+    root_content: String,
+    git_tags: &[String],
+) -> Result<Vec<(RelativePathBuf, String)>, Error> {
+    let root_path = root.as_path();
+    let members = match VersionedFile::new(root, root_content.clone(), git_tags)
+        .map_err(Error::VersionedFile)?
+    {
+        VersionedFile::Cargo(cargo) => cargo
+            .workspace_members()
+            .into_iter()
+            .map(String::from)
+            .collect_vec(),
+        _ => Vec::new(),
+    };
+    let root_dir = root_path.parent().unwrap_or(RelativePath::new(""));
+    let mut manifests = vec![(root_path.clone(), root_content)];
+    for member in members {
+        let path = root_dir.join(member).join("Cargo.toml");
+        if path.to_path("").exists() {
+            let content = read_to_string(path.to_path(""))?;
+            manifests.push((path, content));
+        }
+    }
+    Ok(manifests)
+}
@@ -0,0 +1,119 @@
+use knope_versioning::{release_notes::Release, ReleaseTag};
+use miette::Diagnostic;
+
+use super::{conventional_commits, Package};
+use crate::{
+    integrations::{git, gitea, github},
+    state::{RunType, State},
+};
+
+/// Words which, followed by `#<number>`, mean a change fixes that issue (the same as GitHub's).
+const KEYWORDS: [&str; 9] = [
+    "close", "closes", "closed", "fix", "fixes", "fixed", "resolve", "resolves", "resolved",
+];
+
+/// Comment on and close every issue that `releases` fix, according to their commits and notes.
+pub(super) fn close(
+    releases: &[(Release, ReleaseTag)],
+    run_type: RunType<()>,
+    state: &mut State,
+) -> Result<(), Error> {
+    for (release, tag) in releases {
+        let Some(package) = state
+            .packages
+            .iter()
+            .find(|package| package.name() == &release.package_name)
+        else {
+            continue;
+        };
+        let comment = format!("Released in {tag}", tag = tag.as_str());
+        for number in fixed_issues(package, release)? {
+            if let Some(github_config) = &state.github_config {
+                state.github = github::close_issue(
+                    number,
+                    &comment,
+                    run_type.of(state.github.clone()),
+                    github_config,
+                )?;
+            }
+            if let Some(gitea_config) = &state.gitea_config {
+                state.gitea = gitea::close_issue(
+                    number,
+                    &comment,
+                    run_type.of(state.gitea.clone()),
+                    gitea_config,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The issues fixed by the commits in `release`, or mentioned in its notes (from change files).
+fn fixed_issues(package: &Package, release: &Release) -> Result<Vec<u64>, git::Error> {
+    let commits = conventional_commits::get_conventional_commits_after_last_stable_version(
+        package.name(),
+        &package.tagged_versions,
+    )?;
+    let mut issues = commits
+        .iter()
+        .flat_map(|commit| referenced_issues(&commit.message))
+        .chain(referenced_issues(&release.notes))
+        .collect::<Vec<_>>();
+    issues.sort_unstable();
+    issues.dedup();
+    Ok(issues)
+}
+
+/// Find every issue number referenced like `Fixes #12` in `text`.
+fn referenced_issues(text: &str) -> Vec<u64> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    words
+        .iter()
+        .zip(words.iter().skip(1))
+        .filter(|(keyword, _)| {
+            let keyword = keyword.trim_end_matches(':').to_lowercase();
+            KEYWORDS.contains(&keyword.as_str())
+        })
+        .filter_map(|(_, reference)| {
+            let digits = reference.strip_prefix('#')?;
+            let end = digits
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(digits.len());
+            digits.get(..end)?.parse().ok()
+        })
+        .collect()
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::CloseIssueError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Gitea(#[from] gitea::CloseIssueError),
+}
+
+#[cfg(test)]
+mod test_referenced_issues {
+    use pretty_assertions::assert_eq;
+
+    use super::referenced_issues;
+
+    #[test]
+    fn footers_and_sentences() {
+        assert_eq!(
+            referenced_issues("fix: a bug\n\nThis fixes #12, and\n\nCloses: #3"),
+            vec![12, 3]
+        );
+    }
+
+    #[test]
+    fn ignores_other_references() {
+        assert!(referenced_issues("See #4, fixes a bug, fix #nope").is_empty());
+    }
+}
@@ -1,28 +1,23 @@
 pub(crate) use api::CreateReleaseError as Error;
-use knope_versioning::{package, release_notes::Release, ReleaseTag};
+use knope_versioning::{release_notes::Release, ReleaseTag};
 
-use crate::{config, integrations::gitea as api, state, state::RunType};
+use crate::{config, integrations::gitea as api, plan::Plan, state, state::RunType};
 
 pub(crate) fn release(
     release: &Release,
     gitea_state: RunType<state::Gitea>,
     gitea_config: &config::Gitea,
     tag: &ReleaseTag,
+    plan: &mut Option<Plan>,
 ) -> Result<state::Gitea, Error> {
     let version = &release.version;
-    let mut name = if let package::Name::Custom(package_name) = &release.package_name {
-        format!("{package_name} ")
-    } else {
-        String::new()
-    };
-    name.push_str(&release.title);
-
     api::create_release(
-        &name,
+        &release.full_title(),
         tag.as_str(),
         release.notes.trim(),
         version.is_prerelease(),
         gitea_state,
         gitea_config,
+        plan,
     )
 }
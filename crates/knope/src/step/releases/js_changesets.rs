@@ -0,0 +1,107 @@
+//! Reading the `.changeset/config.json` file of the JavaScript `@changesets/cli`, so that both
+//! tools can share one set of change files.
+
+use std::path::Path;
+
+use knope_versioning::{changes::Change, package, semver::StableRule};
+use miette::Diagnostic;
+use serde::Deserialize;
+
+use super::Package;
+use crate::fs;
+
+const CONFIG_PATH: &str = ".changeset/config.json";
+
+/// The options Knope understands from `.changeset/config.json`, everything else is ignored.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Config {
+    /// `false` disables changelogs, any other value (like a changelog generator) is ignored
+    #[serde(default)]
+    changelog: Option<serde_json::Value>,
+    /// Groups of packages which all release with the largest bump of any of them
+    #[serde(default)]
+    linked: Vec<Vec<String>>,
+    /// Treated the same as `linked`
+    #[serde(default)]
+    fixed: Vec<Vec<String>>,
+    /// Packages which are never released
+    #[serde(default)]
+    ignore: Vec<String>,
+}
+
+impl Config {
+    /// Load `.changeset/config.json`, if it exists.
+    pub(crate) fn load() -> Result<Option<Self>, Error> {
+        if !Path::new(CONFIG_PATH).exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(CONFIG_PATH)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(Error::Parse)
+    }
+
+    pub(crate) fn writes_changelogs(&self) -> bool {
+        self.changelog != Some(serde_json::Value::Bool(false))
+    }
+
+    pub(crate) fn is_ignored(&self, name: &package::Name) -> bool {
+        self.ignore.iter().any(|pattern| matches(pattern, name))
+    }
+
+    /// For each package, the largest rule implied by the changes of every package linked to it.
+    pub(crate) fn linked_rules(
+        &self,
+        packages: &[Package],
+        changes: &[Vec<Change>],
+    ) -> Vec<Option<StableRule>> {
+        let groups = self
+            .linked
+            .iter()
+            .chain(self.fixed.iter())
+            .collect::<Vec<_>>();
+        let in_group = |group: &[String], package: &Package| {
+            group.iter().any(|pattern| matches(pattern, package.name()))
+        };
+        packages
+            .iter()
+            .map(|package| {
+                groups
+                    .iter()
+                    .filter(|group| in_group(group, package))
+                    .flat_map(|group| {
+                        packages
+                            .iter()
+                            .zip(changes)
+                            .filter(|(other, changes)| {
+                                !changes.is_empty() && in_group(group, other)
+                            })
+                            .map(|(_, changes)| StableRule::from(changes.as_slice()))
+                    })
+                    .max()
+            })
+            .collect()
+    }
+}
+
+/// Package names in `config.json` can be globs, like `@scope/*`.
+fn matches(pattern: &str, name: &package::Name) -> bool {
+    let Some(name) = name.as_custom() else {
+        return false;
+    };
+    glob::Pattern::new(pattern).map_or_else(|_| pattern == name, |glob| glob.matches(name))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Fs(#[from] fs::Error),
+    #[error("Could not parse {CONFIG_PATH}: {0}")]
+    #[diagnostic(
+        code(releases::js_changesets::parse),
+        help("This file should follow the format of @changesets/cli"),
+        url("https://knope.tech/reference/concepts/changeset/#javascript-changesets-config")
+    )]
+    Parse(#[source] serde_json::Error),
+}
@@ -1,12 +1,12 @@
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, panic, path::PathBuf, thread};
 
 use changesets::ChangeSet;
 use itertools::Itertools;
 use knope_versioning::{
-    changes::CHANGESET_DIR,
+    changes::{Change, CHANGESET_DIR},
     package::Bump,
     release_notes::Release,
-    semver::{PackageVersions, Rule},
+    semver::{Rule, StableRule, Version},
     Action, ReleaseTag, VersionedFile,
 };
 use miette::Diagnostic;
@@ -16,17 +16,22 @@ pub(crate) use self::{package::Package, semver::bump_version_and_update_state};
 use crate::{
     fs,
     integrations::{git, git::create_tag},
+    plan,
     state::State,
     step::{releases::package::execute_prepare_actions, PrepareRelease},
     RunType,
 };
 
+mod cargo_workspace;
 pub(crate) mod changelog;
 pub(crate) mod conventional_commits;
+mod fixed_issues;
 pub(crate) mod gitea;
 pub(crate) mod github;
+mod js_changesets;
 pub(crate) mod package;
 pub(crate) mod semver;
+mod version_group;
 
 pub(crate) fn prepare_release(
     state: RunType<State>,
@@ -37,21 +42,19 @@ pub(crate) fn prepare_release(
         return Err(package::Error::NoDefinedPackages.into());
     }
 
-    let changeset_path = PathBuf::from(CHANGESET_DIR);
-    let changeset = if changeset_path.exists() {
-        ChangeSet::from_directory(&changeset_path)?.into()
-    } else {
-        Vec::new()
-    };
-
-    for package in &mut state.packages {
-        let (all_versioned_files, actions) = package.prepare_release(
-            prepare_release,
-            &state.all_git_tags,
-            state.all_versioned_files,
-            &changeset,
-        )?;
+    let pending = pending_changes(&mut state, prepare_release)?;
+    for (package, pending) in state.packages.iter_mut().zip(pending) {
+        let (all_versioned_files, actions) =
+            package.prepare_release(prepare_release, state.all_versioned_files, pending)?;
         state.all_versioned_files = all_versioned_files;
+        if !actions.is_empty() && !prepare_release.unreleased {
+            plan::record(&mut state.plan, |plan| {
+                plan.versions.insert(
+                    package.name().to_string(),
+                    package.versioning.versions.clone().into_latest(),
+                );
+            });
+        }
         state.pending_actions.extend(actions);
     }
 
@@ -63,12 +66,21 @@ pub(crate) fn prepare_release(
         .chain(state.pending_actions)
         .unique();
 
-    state.pending_actions = execute_prepare_actions(run_type.of(actions), true)?;
+    state.pending_actions = execute_prepare_actions(
+        run_type.of(actions),
+        true,
+        &mut state.modified_files,
+        &mut state.plan,
+    )?;
 
     match run_type {
         RunType::DryRun(()) => Ok(RunType::DryRun(state)),
         RunType::Real(()) => {
-            if !prepare_release.allow_empty && state.pending_actions.is_empty() {
+            // Unreleased changes don't leave anything for `Release` to do
+            if !prepare_release.allow_empty
+                && !prepare_release.unreleased
+                && state.pending_actions.is_empty()
+            {
                 Err(Error::NoRelease)
             } else {
                 Ok(RunType::Real(state))
@@ -81,6 +93,76 @@ pub(crate) fn bump_version(state: RunType<State>, rule: &Rule) -> Result<RunType
     bump_version_and_update_state(state, rule).map_err(Error::from)
 }
 
+/// Find the unreleased changes for every package in `state` (in the same order), along with the
+/// minimum rule each package must be bumped by (from linked packages).
+///
+/// Options in `.changeset/config.json` are applied here, which may disable changelogs.
+///
+/// In a shallow clone, the last release tag and the commits since then may be missing, so this
+/// refuses to guess.
+pub(crate) fn pending_changes(
+    state: &mut State,
+    prepare_release: &PrepareRelease,
+) -> Result<Vec<PendingChanges>, Error> {
+    if git::is_shallow() {
+        return Err(Error::ShallowClone);
+    }
+    let changeset_path = PathBuf::from(CHANGESET_DIR);
+    let changeset = if changeset_path.exists() {
+        ChangeSet::from_directory(&changeset_path)?.into()
+    } else {
+        Vec::new()
+    };
+
+    let js_config = js_changesets::Config::load()?;
+    let get_changes = |package: &Package| package.get_changes(prepare_release, &changeset);
+    let mut all_changes: Vec<Vec<Change>> = if prepare_release.parallel {
+        map_in_parallel(&state.packages, get_changes)
+            .into_iter()
+            .try_collect()?
+    } else {
+        state.packages.iter().map(get_changes).try_collect()?
+    };
+    let minimum_rules = if let Some(js_config) = &js_config {
+        for (package, changes) in state.packages.iter_mut().zip(&mut all_changes) {
+            if js_config.is_ignored(package.name()) {
+                debug!("{} is ignored in .changeset/config.json", package.name());
+                changes.clear();
+            }
+            if !js_config.writes_changelogs() {
+                package.versioning.release_notes.changelogs.clear();
+            }
+        }
+        js_config.linked_rules(&state.packages, &all_changes)
+    } else {
+        vec![None; state.packages.len()]
+    };
+    let versions = version_group::versions(
+        &state.packages,
+        &all_changes,
+        &minimum_rules,
+        prepare_release,
+    )?;
+    Ok(all_changes
+        .into_iter()
+        .zip(minimum_rules)
+        .zip(versions)
+        .map(|((changes, minimum_rule), version)| PendingChanges {
+            changes,
+            minimum_rule,
+            version,
+        })
+        .collect())
+}
+
+pub(crate) struct PendingChanges {
+    pub(crate) changes: Vec<Change>,
+    /// The smallest rule this package can be bumped by, because of linked packages
+    pub(crate) minimum_rule: Option<StableRule>,
+    /// The version this package must be released at, to match the rest of its version group
+    pub(crate) version: Option<Version>,
+}
+
 #[derive(Debug, Diagnostic, thiserror::Error)]
 pub(crate) enum Error {
     #[error("No packages are ready to release")]
@@ -90,6 +172,14 @@ pub(crate) enum Error {
         url("https://knope.tech/reference/config-file/steps/prepare-release/#errors"),
     )]
     NoRelease,
+    #[error("This Git repository is a shallow clone, so versions can't be determined")]
+    #[diagnostic(
+        code(releases::shallow_clone),
+        help("Fetch the full history with `git fetch --tags --unshallow` (or `fetch-depth: 0` in GitHub Actions), \
+            or set `fetch_tags = true` under `[git]` in `knope.toml` to do it automatically."),
+        url("https://knope.tech/reference/config-file/git/"),
+    )]
+    ShallowClone,
     #[error(transparent)]
     #[diagnostic(transparent)]
     Semver(#[from] semver::Error),
@@ -106,6 +196,9 @@ pub(crate) enum Error {
     #[diagnostic(transparent)]
     Gitea(#[from] gitea::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
+    FixedIssues(#[from] fixed_issues::Error),
+    #[error(transparent)]
     #[diagnostic(
         code(changesets::could_not_read_changeset),
         help(
@@ -116,43 +209,68 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Fs(#[from] fs::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    JsChangesets(#[from] js_changesets::Error),
+}
+
+/// Find releases prepared in an earlier workflow (with no `PrepareRelease` step in this one),
+/// including any Go module tags that go with them.
+pub(crate) fn find_prepared_releases(state: &mut State) {
+    for package in &mut state.packages {
+        let Some(release) = find_prepared_release(package) else {
+            continue;
+        };
+        state
+            .pending_actions
+            .push(Action::CreateRelease(release.clone()));
+        let go_tags = package
+            .versioning
+            .bump_version(
+                Bump::Manual(release.version.clone()),
+                package.go_versioning,
+                state.all_versioned_files.clone(),
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|versioned_file| {
+                versioned_file
+                    .write()?
+                    .into_iter()
+                    .find(|action| matches!(action, Action::AddTag { .. }))
+            });
+        state.pending_actions.extend(go_tags);
+    }
 }
 
 /// Create a release for the package.
 ///
 /// If GitHub config is present, this creates a GitHub release. Otherwise, it tags the Git repo.
-pub(crate) fn release(state: RunType<State>) -> Result<RunType<State>, Error> {
+///
+/// If `parallel`, forge releases after the first (which may prompt for a token) are created at
+/// the same time. Git tags and dry runs are always handled one at a time.
+///
+/// If `annotate_tags`, Git tags created for releases have the release notes as their message.
+///
+/// If `close_fixed_issues`, issues that each release fixes are commented on and closed on forges.
+pub(crate) fn release(
+    state: RunType<State>,
+    parallel: bool,
+    annotate_tags: bool,
+    close_fixed_issues: bool,
+) -> Result<RunType<State>, Error> {
     let (run_type, mut state) = state.take();
 
     if state.pending_actions.is_empty() {
-        for package in &mut state.packages {
-            let Some(release) = find_prepared_release(package, &state.all_git_tags) else {
-                continue;
-            };
-            state
-                .pending_actions
-                .push(Action::CreateRelease(release.clone()));
-            let go_tags = package
-                .versioning
-                .bump_version(
-                    Bump::Manual(release.version.clone()),
-                    package.go_versioning,
-                    state.all_versioned_files.clone(),
-                )
-                .unwrap_or_default()
-                .into_iter()
-                .filter_map(|versioned_file| {
-                    versioned_file
-                        .write()?
-                        .into_iter()
-                        .find(|action| matches!(action, Action::AddTag { .. }))
-                });
-            state.pending_actions.extend(go_tags);
-        }
+        find_prepared_releases(&mut state);
     }
 
     let github_config = state.github_config.as_ref();
     let gitea_config = state.gitea_config.as_ref();
+    let has_forge = github_config.is_some() || gitea_config.is_some();
+    let mut released_to_forge = false;
+    let mut deferred = Vec::new();
+    let mut released = Vec::new();
     for action in state.pending_actions.drain(..) {
         let release = match action {
             Action::AddTag { tag } => {
@@ -161,7 +279,12 @@ pub(crate) fn release(state: RunType<State>) -> Result<RunType<State>, Error> {
                     .iter()
                     .any(|package| ReleaseTag::is_release_tag(&tag, package.name()))
                 {
-                    create_tag(run_type.of(tag.as_str()))?;
+                    create_tag(
+                        run_type.of(tag.as_str()),
+                        "",
+                        state.signing.as_ref(),
+                        &mut state.plan,
+                    )?;
                 }
                 continue;
             }
@@ -169,46 +292,175 @@ pub(crate) fn release(state: RunType<State>) -> Result<RunType<State>, Error> {
             _ => continue,
         };
         let tag = ReleaseTag::new(&release.version, &release.package_name);
+        released.push((release.clone(), tag.clone()));
+        if has_forge {
+            plan::record(&mut state.plan, |plan| {
+                plan.actions.push(plan::Action::Release {
+                    package: release.package_name.as_custom().map(String::from),
+                    version: release.version.clone(),
+                    title: release.title.clone(),
+                    notes: release.notes.clone(),
+                });
+            });
+        }
+        if parallel && released_to_forge && matches!(run_type, RunType::Real(())) {
+            deferred.push((release, tag));
+            continue;
+        }
+        released_to_forge = has_forge;
         if let Some(github_config) = github_config {
+            let uploads = state
+                .packages
+                .iter()
+                .find(|package| package.name() == &release.package_name)
+                .and_then(|package| package.uploads(&release.version, state.signing.as_ref()));
             state.github = github::release(
                 &release,
                 run_type.of(state.github),
                 github_config,
-                state
-                    .packages
-                    .iter()
-                    .find(|package| package.name() == &release.package_name)
-                    .and_then(|package| package.assets.as_ref()),
+                uploads.as_ref(),
                 &tag,
+                &mut state.plan,
             )?;
         }
 
         if let Some(gitea_config) = gitea_config {
-            state.gitea = gitea::release(&release, run_type.of(state.gitea), gitea_config, &tag)?;
+            state.gitea = gitea::release(
+                &release,
+                run_type.of(state.gitea),
+                gitea_config,
+                &tag,
+                &mut state.plan,
+            )?;
         }
 
         // if neither is present, we fall back to just creating a tag
-        if github_config.is_none() && gitea_config.is_none() {
-            create_tag(run_type.of(tag.as_str()))?;
+        if !has_forge {
+            let message = if annotate_tags {
+                release.notes.trim()
+            } else {
+                ""
+            };
+            create_tag(
+                run_type.of(tag.as_str()),
+                message,
+                state.signing.as_ref(),
+                &mut state.plan,
+            )?;
         }
     }
 
+    release_in_parallel(&deferred, &state)?;
+
+    if close_fixed_issues && has_forge {
+        fixed_issues::close(&released, run_type, &mut state)?;
+    }
+    state
+        .released
+        .extend(released.into_iter().map(|(release, _)| release));
+
     Ok(run_type.of(state))
 }
 
+/// Create the forge releases in `deferred` at the same time, these are never dry runs.
+fn release_in_parallel(deferred: &[(Release, ReleaseTag)], state: &State) -> Result<(), Error> {
+    let github_config = state.github_config.as_ref();
+    let gitea_config = state.gitea_config.as_ref();
+    let signing = state.signing.as_ref();
+    for result in map_in_parallel(deferred, |(release, tag)| -> Result<(), Error> {
+        if let Some(github_config) = github_config {
+            let uploads = state
+                .packages
+                .iter()
+                .find(|package| package.name() == &release.package_name)
+                .and_then(|package| package.uploads(&release.version, signing));
+            github::release(
+                release,
+                RunType::Real(state.github.clone()),
+                github_config,
+                uploads.as_ref(),
+                tag,
+                &mut None,
+            )?;
+        }
+        if let Some(gitea_config) = gitea_config {
+            gitea::release(
+                release,
+                RunType::Real(state.gitea.clone()),
+                gitea_config,
+                tag,
+                &mut None,
+            )?;
+        }
+        Ok(())
+    }) {
+        result?;
+    }
+    Ok(())
+}
+
+/// Call `f` for each of `items` in parallel, returning the results in the same order.
+///
+/// There's at most one thread per CPU, each of which handles a chunk of `items` in turn.
+fn map_in_parallel<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    map_in_threads(items, threads, f)
+}
+
+fn map_in_threads<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let threads = threads.max(1);
+    let chunk_size = ((items.len() + threads - 1) / threads).max(1);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect_vec()))
+            .collect_vec();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| panic::resume_unwind(err))
+            })
+            .collect()
+    })
+}
+
 /// Given a package, figure out if there was a release prepared in a separate workflow. Basically,
 /// if the package version is newer than the latest tag, there's a release to release!
-fn find_prepared_release(package: &mut Package, all_tags: &[String]) -> Option<Release> {
+fn find_prepared_release(package: &mut Package) -> Option<Release> {
     let current_version = package.versioning.versions.clone().into_latest();
-    debug!("Searching for last package tag to determine if there's a release to release");
-    let last_tag = PackageVersions::from_tags(package.name().as_custom(), all_tags).into_latest();
+    let last_tag = package.tagged_versions.clone().into_latest();
     if last_tag == current_version {
         return None;
     }
     package
         .versioning
         .release_notes
-        .changelog
-        .as_ref()
-        .and_then(|changelog| changelog.get_release(&current_version, package.name()))
+        .get_release(&current_version, package.name())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test_map_in_threads {
+    use std::{collections::HashSet, sync::Mutex};
+
+    use super::*;
+
+    #[test]
+    fn more_items_than_threads() {
+        let thread_ids = Mutex::new(HashSet::new());
+        let items = (0..10).collect_vec();
+        let results = map_in_threads(&items, 3, |item| {
+            thread_ids.lock().unwrap().insert(thread::current().id());
+            item * 2
+        });
+        assert_eq!(results, (0..20).step_by(2).collect_vec());
+        assert_eq!(thread_ids.into_inner().unwrap().len(), 3);
+    }
 }
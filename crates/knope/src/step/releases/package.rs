@@ -1,24 +1,38 @@
-use std::{fmt, fmt::Display};
+use std::{collections::BTreeMap, fmt, fmt::Display, time::Instant};
 
 use itertools::Itertools;
-use knope_config::{changelog_section::convert_to_versioning, Assets};
+use knope_config::{changelog_section::convert_to_versioning, Assets, Checksums};
 use knope_versioning::{
+    changes::{
+        conventional_commit::{self, InvalidCommit},
+        Change, IgnoredCommits, InvalidPattern, RequireScope,
+    },
     package::{BumpError, ChangeConfig, Name},
-    release_notes::{ReleaseNotes, TimeError},
-    semver::Version,
-    Action, GoVersioning, PackageNewError, VersionedFile, VersionedFileError,
+    release_notes::{self, Forge, ReleaseNotes},
+    semver::{Label, PackageVersions, Rule, RuleError, StableRule, Version},
+    Action, GoVersioning, PackageNewError, VersionedFile, VersionedFileConfig, VersionedFileError,
 };
 use miette::Diagnostic;
-use relative_path::RelativePathBuf;
+use relative_path::{RelativePath, RelativePathBuf};
 use tracing::{debug, info};
 
-use super::{conventional_commits, semver};
+use super::{cargo_workspace, conventional_commits, semver};
 use crate::{
-    config, fs,
+    config,
+    config::Signing,
+    fs,
     fs::{read_to_string, WriteType},
-    integrations::git::{self, add_files},
+    integrations::{
+        git::{self, add_files},
+        github::Uploads,
+    },
+    plan,
+    plan::Plan,
     state::RunType,
-    step::{releases::changelog::load_changelog, PrepareRelease},
+    step::{
+        releases::{changelog::load_changelog, PendingChanges},
+        PrepareRelease,
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -27,25 +41,58 @@ pub(crate) struct Package {
     /// Version manually set by the caller to use instead of the one determined by semantic rule
     pub(crate) override_version: Option<Version>,
     pub(crate) assets: Option<Assets>,
+    pub(crate) checksums: Option<Checksums>,
+    pub(crate) sigstore: bool,
     pub(crate) go_versioning: GoVersioning,
+    /// If set, only commits which change a file matching one of these patterns are used
+    pub(crate) commit_paths: Option<Vec<glob::Pattern>>,
+    /// The versions in this package's Git tags (ignoring versioned files), parsed once and reused
+    /// by every step
+    pub(crate) tagged_versions: PackageVersions,
+    /// Every package in the same group is released with the same new version
+    pub(crate) version_group: Option<String>,
 }
 
 impl Package {
     pub(crate) fn load(
-        packages: Vec<config::Package>,
+        mut packages: Vec<config::Package>,
         git_tags: &[String],
+        forge: Option<&Forge>,
     ) -> Result<(Vec<Self>, Vec<VersionedFile>), Error> {
-        let versioned_files: Vec<VersionedFile> = packages
+        // Files shared between packages (like a workspace's `Cargo.lock`) are only read once so
+        // that every package's changes end up in the same write.
+        let mut unique_configs: Vec<&VersionedFileConfig> = Vec::new();
+        for config in packages
             .iter()
             .flat_map(|package| package.versioned_files.iter())
+        {
+            if !unique_configs
+                .iter()
+                .any(|existing| existing.is_same_file(config))
+            {
+                unique_configs.push(config);
+            }
+        }
+        let mut versioned_files: Vec<VersionedFile> = unique_configs
+            .into_iter()
             .map(|path| {
                 let content = read_to_string(path.to_pathbuf())?;
                 VersionedFile::new(path, content, git_tags).map_err(Error::VersionedFile)
             })
             .try_collect()?;
+        let mut inherited_roots = Vec::new();
+        for package in &mut packages {
+            cargo_workspace::add_workspace_roots(
+                package,
+                &mut versioned_files,
+                git_tags,
+                &mut inherited_roots,
+            )?;
+            cargo_workspace::add_path_dependencies(package, &mut versioned_files, git_tags)?;
+        }
         let packages = packages
             .into_iter()
-            .map(|package| Package::validate(package, git_tags, &versioned_files))
+            .map(|package| Package::validate(package, git_tags, &versioned_files, forge))
             .collect::<Result<Vec<_>, _>>()?;
         Ok((packages, versioned_files))
     }
@@ -54,71 +101,178 @@ impl Package {
         &self.versioning.name
     }
 
+    /// The files to upload with a GitHub release of this package, if there are any assets.
+    pub(crate) fn uploads<'a>(
+        &'a self,
+        version: &'a Version,
+        signing: Option<&'a Signing>,
+    ) -> Option<Uploads<'a>> {
+        self.assets.as_ref().map(|assets| Uploads {
+            assets,
+            version,
+            checksums: self.checksums.as_ref(),
+            signing,
+            sigstore: self.sigstore,
+        })
+    }
+
     fn validate(
         package: config::Package,
         git_tags: &[String],
         all_versioned_files: &[VersionedFile],
+        forge: Option<&Forge>,
     ) -> Result<Self, Error> {
         if let Name::Custom(package_name) = &package.name {
             debug!("Loading package {package_name}");
         } else {
             debug!("Loading package");
         }
-        let versioning = knope_versioning::Package::new(
+        let commit_paths = if package.filter_commits_by_path {
+            Some(commit_paths(&package.paths, &package.versioned_files)?)
+        } else {
+            None
+        };
+        let knope_config::ReleaseNotes {
+            template,
+            variables,
+            change_templates,
+            title,
+        } = package.release_notes.unwrap_or_default();
+        let mut versioning = knope_versioning::Package::new(
             package.name,
             git_tags,
             package.versioned_files,
             all_versioned_files,
             ReleaseNotes {
                 sections: convert_to_versioning(package.extra_changelog_sections),
-                changelog: package.changelog.map(load_changelog).transpose()?,
+                changelogs: package
+                    .changelogs
+                    .into_iter()
+                    .map(load_changelog)
+                    .try_collect()?,
+                forge: forge.cloned(),
+                release_json: package.release_json,
+                template: load_template(template, variables)?,
+                change_templates: change_templates.into_iter().map(Into::into).collect(),
+                title,
             },
             package.scopes,
         )?;
+        versioning.zero_major_breaking = package.zero_major_breaking;
+        versioning.require_scope = package.require_scope;
+        let tagged_versions = PackageVersions::from_tags(versioning.name.as_custom(), git_tags);
         Ok(Self {
+            commit_paths,
             versioning,
             assets: package.assets,
+            checksums: package.checksums,
+            sigstore: package.sigstore,
             go_versioning: if package.ignore_go_major_versioning {
                 GoVersioning::IgnoreMajorRules
             } else {
                 GoVersioning::default()
             },
             override_version: None,
+            tagged_versions,
+            version_group: package.version_group,
         })
     }
 
-    pub(crate) fn prepare_release(
-        &mut self,
+    /// Find every change to this package since its last stable release.
+    pub(crate) fn get_changes(
+        &self,
         prepare_release: &PrepareRelease,
-        all_tags: &[String],
-        versioned_files: Vec<VersionedFile>,
         changeset: &[changesets::Release],
-    ) -> Result<(Vec<VersionedFile>, Vec<Action>), Error> {
+    ) -> Result<Vec<Change>, Error> {
         let PrepareRelease {
-            prerelease_label,
             ignore_conventional_commits,
+            split_squash_merges,
+            ignore_authors,
+            ignore_commit_patterns,
             ..
         } = prepare_release;
 
-        let commit_messages = if *ignore_conventional_commits {
+        let commits = if *ignore_conventional_commits {
             Vec::new()
         } else {
             conventional_commits::get_conventional_commits_after_last_stable_version(
                 &self.versioning.name,
-                all_tags,
+                &self.tagged_versions,
             )?
         };
-        let changes = self.versioning.get_changes(changeset, &commit_messages);
+        let commits = if *split_squash_merges {
+            conventional_commit::split_squash_merges(commits)
+        } else {
+            commits
+        };
+        let ignored = IgnoredCommits::new(ignore_authors.clone(), ignore_commit_patterns)?;
+        let mut commits = commits
+            .into_iter()
+            .filter(|commit| !ignored.is_ignored(commit))
+            .collect_vec();
+        if let Some(commit_paths) = &self.commit_paths {
+            let changed_files = git::get_changed_files(&commits)?;
+            let mut changed_files = changed_files.into_iter();
+            commits.retain(|_| {
+                changed_files.next().is_some_and(|files| {
+                    files.iter().any(|file| {
+                        commit_paths
+                            .iter()
+                            .any(|pattern| pattern.matches(file.as_str()))
+                    })
+                })
+            });
+        }
+        if self.versioning.require_scope == RequireScope::Error {
+            let unscoped = commits
+                .iter()
+                .filter_map(|commit| conventional_commit::validate_scope(commit).err())
+                .collect_vec();
+            if !unscoped.is_empty() {
+                return Err(Error::UnscopedCommits {
+                    package: self.versioning.name.to_string(),
+                    unscoped,
+                });
+            }
+        }
+        let start = Instant::now();
+        let changes = self.versioning.get_changes(changeset, &commits);
+        debug!(
+            "Parsed {count} commits in {elapsed:.2?}",
+            count = commits.len(),
+            elapsed = start.elapsed()
+        );
+        Ok(changes)
+    }
 
-        if changes.is_empty() {
+    /// Release the `pending` changes, bumping by at least their `minimum_rule` or to exactly their
+    /// `version` if set.
+    pub(crate) fn prepare_release(
+        &mut self,
+        prepare_release: &PrepareRelease,
+        versioned_files: Vec<VersionedFile>,
+        pending: PendingChanges,
+    ) -> Result<(Vec<VersionedFile>, Vec<Action>), Error> {
+        let PendingChanges {
+            changes,
+            minimum_rule,
+            version,
+        } = pending;
+        if changes.is_empty() && version.is_none() {
             return Ok((versioned_files, Vec::new()));
         }
 
-        let change_config = match self.override_version.take() {
+        if prepare_release.unreleased {
+            return Ok((versioned_files, self.versioning.write_unreleased(&changes)));
+        }
+
+        let change_config = match self.override_version.take().or(version) {
             Some(version) => ChangeConfig::Force(version),
             None => ChangeConfig::Calculate {
-                prerelease_label: prerelease_label.clone(),
+                prerelease_label: prepare_release.prerelease_label.clone(),
                 go_versioning: self.go_versioning,
+                minimum_rule,
+                forced_rule: prepare_release.rule,
             },
         };
 
@@ -126,32 +280,92 @@ impl Package {
             .apply_changes(&changes, versioned_files, change_config)
             .map_err(Error::Bump)
     }
+
+    /// The version this package would be bumped to by `stable_rule`, as a pre-release if there's
+    /// a `prerelease_label`.
+    pub(crate) fn next_version(
+        &self,
+        prerelease_label: Option<&Label>,
+        stable_rule: StableRule,
+    ) -> Result<Version, RuleError> {
+        let rule = match prerelease_label {
+            Some(label) => Rule::Pre {
+                label: label.clone(),
+                stable_rule,
+            },
+            None => stable_rule.into(),
+        };
+        let mut versions = self.versioning.versions.clone();
+        versions.bump(rule, self.versioning.zero_major_breaking)?;
+        Ok(versions.into_latest())
+    }
+}
+
+/// Read the release notes `template` file, if there is one.
+fn load_template(
+    template: Option<RelativePathBuf>,
+    variables: BTreeMap<String, String>,
+) -> Result<Option<release_notes::Template>, fs::Error> {
+    let Some(path) = template else {
+        return Ok(None);
+    };
+    Ok(Some(release_notes::Template {
+        contents: read_to_string(path.to_path(""))?,
+        variables,
+    }))
+}
+
+/// Parse `paths` as glob patterns, defaulting to everything in the directory of the first
+/// versioned file.
+fn commit_paths(
+    paths: &[String],
+    versioned_files: &[VersionedFileConfig],
+) -> Result<Vec<glob::Pattern>, glob::PatternError> {
+    if !paths.is_empty() {
+        return paths.iter().map(|path| glob::Pattern::new(path)).collect();
+    }
+    let directory = versioned_files
+        .first()
+        .and_then(|file| {
+            file.as_path()
+                .parent()
+                .map(RelativePath::to_relative_path_buf)
+        })
+        .unwrap_or_default();
+    let pattern = if directory.as_str().is_empty() {
+        String::from("**")
+    } else {
+        format!("{}/**", glob::Pattern::escape(directory.as_str()))
+    };
+    glob::Pattern::new(&pattern).map(|pattern| vec![pattern])
 }
 
+/// Write and remove files, returning the actions left for `Release`.
+///
+/// The paths of every file changed are added to `modified_files`, and dry runs are recorded in
+/// `plan`.
 pub(crate) fn execute_prepare_actions(
     actions: RunType<impl Iterator<Item = Action>>,
     stage_to_git: bool,
+    modified_files: &mut Vec<RelativePathBuf>,
+    plan: &mut Option<Plan>,
 ) -> Result<Vec<Action>, git::Error> {
     let (run_type, actions) = actions.take();
     let mut remainder = Vec::new();
     let mut paths_to_stage = Vec::new();
     for action in actions {
         match action {
-            Action::WriteToFile {
-                path,
-                content,
-                diff,
-            } => {
+            Action::WriteToFile { path, content, .. } => {
                 let write_type = match run_type {
-                    RunType::DryRun(()) => WriteType::DryRun(diff),
+                    RunType::DryRun(()) => WriteType::DryRun(content),
                     RunType::Real(()) => WriteType::Real(content),
                 };
-                fs::write(write_type, &path.to_path(""))?;
+                fs::write(write_type, &path.to_path(""), plan)?;
                 paths_to_stage.push(path);
             }
             Action::RemoveFile { path } => {
                 // Ignore errors since we remove changesets per-package
-                fs::remove_file(run_type.of(&path.to_path(""))).ok();
+                fs::remove_file(run_type.of(&path.to_path("")), plan).ok();
                 paths_to_stage.push(path);
             }
             Action::AddTag { .. } | Action::CreateRelease(_) => {
@@ -160,12 +374,16 @@ pub(crate) fn execute_prepare_actions(
         }
     }
     if stage_to_git {
-        stage_changes_to_git(run_type.of(&paths_to_stage))?;
+        stage_changes_to_git(run_type.of(&paths_to_stage), plan)?;
     }
+    modified_files.extend(paths_to_stage);
     Ok(remainder)
 }
 
-fn stage_changes_to_git(paths: RunType<&[RelativePathBuf]>) -> Result<(), git::Error> {
+fn stage_changes_to_git(
+    paths: RunType<&[RelativePathBuf]>,
+    plan: &mut Option<Plan>,
+) -> Result<(), git::Error> {
     match paths {
         RunType::DryRun(paths) => {
             if paths.is_empty() {
@@ -175,6 +393,11 @@ fn stage_changes_to_git(paths: RunType<&[RelativePathBuf]>) -> Result<(), git::E
             for path in paths {
                 info!("  {path}");
             }
+            plan::record(plan, |plan| {
+                plan.actions.push(plan::Action::StageFiles {
+                    paths: paths.iter().map(ToString::to_string).collect(),
+                });
+            });
             Ok(())
         }
         RunType::Real(paths) => {
@@ -210,14 +433,24 @@ impl Package {
                 .unwrap()],
                 ReleaseNotes {
                     sections: knope_versioning::release_notes::Sections::default(),
-                    changelog: None,
+                    changelogs: Vec::new(),
+                    forge: None,
+                    release_json: None,
+                    template: None,
+                    change_templates: Vec::new(),
+                    title: None,
                 },
                 None,
             )
             .unwrap(),
             override_version: None,
             assets: None,
+            checksums: None,
+            sigstore: false,
             go_versioning: GoVersioning::default(),
+            commit_paths: None,
+            tagged_versions: PackageVersions::default(),
+            version_group: None,
         }
     }
 }
@@ -233,7 +466,7 @@ impl Display for Package {
 pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
-    Time(#[from] TimeError),
+    ReleaseNotes(#[from] release_notes::Error),
     #[error("Could not serialize generated TOML")]
     #[diagnostic(
         code(releases::package::could_not_serialize_toml),
@@ -252,6 +485,16 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    InvalidPattern(#[from] InvalidPattern),
+    #[error("Invalid glob pattern in package paths: {0}")]
+    #[diagnostic(
+        code(releases::package::invalid_paths),
+        help("Each entry in `paths` must be a valid glob pattern"),
+        url("https://knope.tech/reference/config-file/packages/#filter_commits_by_path")
+    )]
+    Paths(#[from] glob::PatternError),
     #[error("No packages to operate on")]
     #[diagnostic(
         code(package::no_defined_packages),
@@ -268,4 +511,35 @@ pub(crate) enum Error {
     #[error(transparent)]
     #[diagnostic(transparent)]
     New(#[from] Box<PackageNewError>),
+    #[error(
+        "{0} inherits its version from a Cargo workspace, but the workspace root couldn't be found"
+    )]
+    #[diagnostic(
+        code(releases::package::cargo_workspace_root_not_found),
+        help("Knope looks for a Cargo.toml with a [workspace] table in the parent directories of the member"),
+        url("https://knope.tech/reference/config-file/packages/#cargotoml")
+    )]
+    CargoWorkspaceRootNotFound(RelativePathBuf),
+    #[error("Packages {first} and {second} both inherit their version from {root}")]
+    #[diagnostic(
+        code(releases::package::shared_cargo_workspace_version),
+        help("Put the packages in the same `version_group` so they're always released with the same version, or give all but one of them their own `version`"),
+        url("https://knope.tech/reference/config-file/packages/#cargotoml")
+    )]
+    SharedCargoWorkspaceVersion {
+        root: RelativePathBuf,
+        first: String,
+        second: String,
+    },
+    #[error("Found commits without a scope for {package}, which requires one")]
+    #[diagnostic(
+        code(releases::package::unscoped_commits),
+        help("Reword these commits to include a scope, like `feat(my-package): a new feature`, or set `require_scope = \"ignore\"` to skip them"),
+        url("https://knope.tech/reference/config-file/packages/#require_scope")
+    )]
+    UnscopedCommits {
+        package: String,
+        #[related]
+        unscoped: Vec<InvalidCommit>,
+    },
 }
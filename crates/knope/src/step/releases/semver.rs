@@ -6,7 +6,8 @@ use knope_versioning::{
 use miette::Diagnostic;
 
 use crate::{
-    fs, integrations::git, state::State, step::releases::package::execute_prepare_actions, RunType,
+    fs, integrations::git, plan, state::State, step::releases::package::execute_prepare_actions,
+    RunType,
 };
 
 /// The implementation of [`crate::step::Step::BumpVersion`].
@@ -19,7 +20,12 @@ pub(crate) fn bump_version_and_update_state(
     let (run_type, mut state) = state.take();
 
     for package in &mut state.packages {
-        let (bump, go_versioning) = if let Some(version) = package.override_version.clone() {
+        // Exact versions are set the same way as `--override-version`
+        let override_version = package.override_version.clone().or_else(|| match rule {
+            Rule::Exact { version } => Some(version.clone()),
+            _ => None,
+        });
+        let (bump, go_versioning) = if let Some(version) = override_version {
             (Bump::Manual(version), GoVersioning::BumpMajor)
         } else {
             (Bump::Rule(rule.clone()), package.go_versioning)
@@ -28,13 +34,24 @@ pub(crate) fn bump_version_and_update_state(
             package
                 .versioning
                 .bump_version(bump, go_versioning, state.all_versioned_files)?;
+        plan::record(&mut state.plan, |plan| {
+            plan.versions.insert(
+                package.name().to_string(),
+                package.versioning.versions.clone().into_latest(),
+            );
+        });
     }
     let write_files = state
         .all_versioned_files
         .drain(..)
         .filter_map(VersionedFile::write)
         .flatten();
-    execute_prepare_actions(run_type.of(write_files), false)?;
+    execute_prepare_actions(
+        run_type.of(write_files),
+        false,
+        &mut state.modified_files,
+        &mut state.plan,
+    )?;
     Ok(run_type.of(state))
 }
 #[derive(Debug, Diagnostic, thiserror::Error)]
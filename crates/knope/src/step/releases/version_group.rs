@@ -0,0 +1,68 @@
+//! Packages with the same `version_group` are released in lockstep: whenever any of them has
+//! changes, every one of them is released with the same new version.
+
+use itertools::Itertools;
+use knope_versioning::{
+    changes::Change,
+    package::BumpError,
+    semver::{StableRule, Version},
+};
+
+use super::{package, Package};
+use crate::step::PrepareRelease;
+
+/// For each package (in the same order), the version it must be released at to match the rest of
+/// its version group.
+///
+/// The largest rule implied by the changes of any member is applied to every member, and the
+/// largest resulting version (or overridden version) is used for all of them.
+pub(super) fn versions(
+    packages: &[Package],
+    changes: &[Vec<Change>],
+    minimum_rules: &[Option<StableRule>],
+    prepare_release: &PrepareRelease,
+) -> Result<Vec<Option<Version>>, package::Error> {
+    let mut versions = vec![None; packages.len()];
+    if prepare_release.unreleased {
+        return Ok(versions);
+    }
+    let groups = packages
+        .iter()
+        .filter_map(|package| package.version_group.as_deref())
+        .unique();
+    for group in groups {
+        let members = packages
+            .iter()
+            .zip(changes.iter().zip(minimum_rules))
+            .enumerate()
+            .filter(|(_, (package, _))| package.version_group.as_deref() == Some(group))
+            .collect_vec();
+        let Some(rule) = members
+            .iter()
+            .filter(|(_, (_, (changes, _)))| !changes.is_empty())
+            .map(|(_, (_, (changes, minimum_rule)))| {
+                StableRule::from(changes.as_slice()).max(minimum_rule.unwrap_or_default())
+            })
+            .max()
+        else {
+            continue;
+        };
+        let rule = prepare_release.rule.unwrap_or(rule);
+        let mut version = None;
+        for (_, (package, _)) in &members {
+            let next = match &package.override_version {
+                Some(version) => version.clone(),
+                None => package
+                    .next_version(prepare_release.prerelease_label.as_ref(), rule)
+                    .map_err(BumpError::from)?,
+            };
+            version = version.max(Some(next));
+        }
+        for (index, _) in members {
+            if let Some(slot) = versions.get_mut(index) {
+                slot.clone_from(&version);
+            }
+        }
+    }
+    Ok(versions)
+}
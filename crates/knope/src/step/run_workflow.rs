@@ -0,0 +1,69 @@
+use miette::Diagnostic;
+
+use crate::{
+    state::{RunType, State},
+    step::Step,
+    workflow,
+    workflow::Workflow,
+};
+
+/// Run the steps of the workflow called `name`, continuing with the current state.
+pub(crate) fn run(
+    name: &str,
+    workflows: &[Workflow],
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    let workflow = find(name, workflows)?;
+    workflow::run_steps(workflow, workflows, state).map_err(|err| Error::Workflow(Box::new(err)))
+}
+
+/// Make sure that `workflow` doesn't (eventually) run itself and that every workflow it runs
+/// exists.
+///
+/// `stack` is the chain of workflows which led to this one.
+pub(crate) fn check_recursion<'a>(
+    workflow: &'a Workflow,
+    workflows: &'a [Workflow],
+    stack: &mut Vec<&'a str>,
+) -> Result<(), Error> {
+    let already_running = stack.contains(&workflow.name.as_str());
+    stack.push(&workflow.name);
+    if already_running {
+        return Err(Error::Recursion(stack.join(" -> ")));
+    }
+    for workflow_step in workflow.steps.iter().chain(&workflow.on_error) {
+        if let Step::RunWorkflow { name } = &workflow_step.step {
+            check_recursion(find(name, workflows)?, workflows, stack)?;
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+fn find<'a>(name: &str, workflows: &'a [Workflow]) -> Result<&'a Workflow, Error> {
+    workflows
+        .iter()
+        .find(|workflow| workflow.name == name)
+        .ok_or_else(|| Error::NotFound(name.to_string()))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("No workflow named {0}")]
+    #[diagnostic(
+        code(run_workflow::not_found),
+        help("The `name` of a `RunWorkflow` step must match the `name` of another workflow."),
+        url("https://knope.tech/reference/config-file/steps/run-workflow/")
+    )]
+    NotFound(String),
+    #[error("Workflows run each other forever: {0}")]
+    #[diagnostic(
+        code(run_workflow::recursion),
+        help("Remove one of the `RunWorkflow` steps to break the cycle."),
+        url("https://knope.tech/reference/config-file/steps/run-workflow/")
+    )]
+    Recursion(String),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Workflow(Box<workflow::Error>),
+}
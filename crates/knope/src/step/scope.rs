@@ -0,0 +1,101 @@
+//! Limiting a step to some of the configured packages, from the `packages` field of a step or
+//! workflow.
+
+use std::mem;
+
+use itertools::Itertools;
+use knope_versioning::{package::Name, release_notes::Release, Action};
+use miette::Diagnostic;
+
+use crate::{state::State, step::releases};
+
+/// The parts of [`State`] which belong to packages outside the scope of a step, set aside by
+/// [`enter`] until [`exit`] puts them back.
+pub(crate) struct OutOfScope {
+    /// The names of every package, in their original order
+    order: Vec<Name>,
+    packages: Vec<releases::Package>,
+    /// `CreateRelease` actions (from `PrepareRelease`) for packages outside the scope
+    pending_releases: Vec<Action>,
+    released: Vec<Release>,
+}
+
+/// Remove everything about packages not named in `names` from `state`, so the next step only sees
+/// the rest.
+pub(crate) fn enter(state: &mut State, names: &[String]) -> Result<OutOfScope, Error> {
+    let unknown = names
+        .iter()
+        .filter(|name| {
+            state
+                .packages
+                .iter()
+                .all(|package| package.name().as_custom() != Some(name.as_str()))
+        })
+        .join(", ");
+    if !unknown.is_empty() {
+        return Err(Error::UnknownPackage(unknown));
+    }
+    let in_scope = |name: &Name| {
+        name.as_custom()
+            .is_some_and(|name| names.iter().any(|it| it == name))
+    };
+
+    let order = state
+        .packages
+        .iter()
+        .map(|package| package.name().clone())
+        .collect();
+    let (packages, out_of_scope): (Vec<_>, Vec<_>) = mem::take(&mut state.packages)
+        .into_iter()
+        .partition(|package| in_scope(package.name()));
+    state.packages = packages;
+    let (pending_actions, pending_releases): (Vec<_>, Vec<_>) =
+        mem::take(&mut state.pending_actions)
+            .into_iter()
+            .partition(|action| match action {
+                Action::CreateRelease(release) => in_scope(&release.package_name),
+                _ => true,
+            });
+    state.pending_actions = pending_actions;
+    let (released, out_of_scope_released): (Vec<_>, Vec<_>) = mem::take(&mut state.released)
+        .into_iter()
+        .partition(|release| in_scope(&release.package_name));
+    state.released = released;
+
+    Ok(OutOfScope {
+        order,
+        packages: out_of_scope,
+        pending_releases,
+        released: out_of_scope_released,
+    })
+}
+
+/// Put back everything that [`enter`] set aside, keeping the packages in their original order.
+pub(crate) fn exit(state: &mut State, out_of_scope: OutOfScope) {
+    let OutOfScope {
+        order,
+        packages,
+        pending_releases,
+        released,
+    } = out_of_scope;
+    state.packages.extend(packages);
+    state.packages.sort_by_key(|package| {
+        order
+            .iter()
+            .position(|name| name == package.name())
+            .unwrap_or(usize::MAX)
+    });
+    state.pending_actions.extend(pending_releases);
+    state.released.splice(0..0, released);
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("Unknown package(s) in `packages`: {0}")]
+    #[diagnostic(
+        code(scope::unknown_package),
+        help("Every entry in the `packages` of a step or workflow must be the name of a package."),
+        url("https://knope.tech/reference/config-file/workflow/#packages")
+    )]
+    UnknownPackage(String),
+}
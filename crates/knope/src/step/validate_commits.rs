@@ -0,0 +1,84 @@
+use itertools::Itertools;
+use knope_versioning::changes::{
+    conventional_commit::{validate, validate_scope, InvalidCommit},
+    Commit, RequireScope,
+};
+use miette::Diagnostic;
+use tracing::info;
+
+use crate::{
+    integrations::git::{self, get_commits_after_revision},
+    state::{RunType, State},
+    step::releases::conventional_commits::get_conventional_commits_after_last_stable_version,
+};
+
+/// Check that every commit since `base` (or the last stable release of each package) is a
+/// conventional commit, with a scope if any package has `require_scope = true`.
+pub(crate) fn run(state: RunType<State>, base: Option<&str>) -> Result<RunType<State>, Error> {
+    let (run_type, state) = state.take();
+    let commits = if let Some(base) = base {
+        get_commits_after_revision(base)?
+    } else {
+        let mut commits: Vec<Commit> = Vec::new();
+        for package in &state.packages {
+            for commit in get_conventional_commits_after_last_stable_version(
+                package.name(),
+                &package.tagged_versions,
+            )? {
+                if !commits.contains(&commit) {
+                    commits.push(commit);
+                }
+            }
+        }
+        commits
+    };
+    let invalid = commits
+        .iter()
+        .filter_map(|commit| validate(commit).err())
+        .collect_vec();
+    if !invalid.is_empty() {
+        return Err(Error::InvalidCommits { invalid });
+    }
+    if state
+        .packages
+        .iter()
+        .any(|package| package.versioning.require_scope == RequireScope::Error)
+    {
+        let unscoped = commits
+            .iter()
+            .filter_map(|commit| validate_scope(commit).err())
+            .collect_vec();
+        if !unscoped.is_empty() {
+            return Err(Error::UnscopedCommits { unscoped });
+        }
+    }
+    info!("All {} commits are valid", commits.len());
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error("Found commits which aren't conventional commits")]
+    #[diagnostic(
+        code(validate_commits::invalid_commits),
+        help("Reword these commits to follow the conventional commit format, like `feat: a new feature`"),
+        url("https://knope.tech/reference/config-file/steps/validate-commits/")
+    )]
+    InvalidCommits {
+        #[related]
+        invalid: Vec<InvalidCommit>,
+    },
+    #[error("Found commits without a scope")]
+    #[diagnostic(
+        code(validate_commits::unscoped_commits),
+        help("Some packages have `require_scope = true`, reword these commits to include a scope, like `feat(my-package): a new feature`"),
+        url("https://knope.tech/reference/config-file/packages/#require_scope")
+    )]
+    UnscopedCommits {
+        #[related]
+        unscoped: Vec<InvalidCommit>,
+    },
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use miette::Diagnostic;
+
+use crate::{
+    integrations::{git, gitea, github},
+    state::{RunType, State},
+};
+
+/// How long to wait for checks if the step doesn't say.
+pub(crate) const DEFAULT_TIMEOUT_SECONDS: u64 = 30 * 60;
+
+/// Wait for the checks on the current commit to pass on every configured forge.
+pub(super) fn run(timeout: Option<u64>, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+
+    if state.github_config.is_none() && state.gitea_config.is_none() {
+        return Err(Error::NotConfigured);
+    }
+    let timeout = Duration::from_secs(timeout.unwrap_or(DEFAULT_TIMEOUT_SECONDS));
+    let sha = git::head_commit_id()?;
+
+    if let Some(github_config) = &state.github_config {
+        state.github =
+            github::wait_for_checks(&sha, timeout, run_type.of(state.github), github_config)?;
+    }
+
+    if let Some(gitea_config) = &state.gitea_config {
+        state.gitea =
+            gitea::wait_for_checks(&sha, timeout, run_type.of(state.gitea), gitea_config)?;
+    }
+    Ok(run_type.of(state))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("No forge has been configured")]
+    #[diagnostic(
+        code(wait_for_checks::forge::not_configured),
+        help("A forge must be configured in order to use the WaitForChecks step"),
+        url("https://knope.tech/reference/concepts/forge/")
+    )]
+    NotConfigured,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::WaitForChecksError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Gitea(#[from] gitea::WaitForChecksError),
+}
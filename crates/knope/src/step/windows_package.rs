@@ -0,0 +1,180 @@
+use itertools::Itertools;
+use knope_config::Asset;
+use knope_versioning::ReleaseTag;
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    config,
+    integrations::{github, github::Installer},
+    state::{RunType, State, TooManyReleases},
+};
+
+/// The inner content of a [`crate::step::Step::UpdateScoopManifest`] step.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Scoop {
+    /// The GitHub repository of the bucket, like `knope-dev/scoop-bucket`
+    pub(crate) bucket: String,
+    /// The name of the manifest, which lives at `bucket/{manifest}.json` in the bucket
+    pub(crate) manifest: String,
+    #[serde(flatten)]
+    pub(crate) source: Source,
+}
+
+/// The inner content of a [`crate::step::Step::UpdateWingetManifest`] step.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Winget {
+    /// The package identifier, like `Knope.Knope`
+    pub(crate) identifier: String,
+    /// A fork of `microsoft/winget-pkgs` to push the new manifests to, like `knope-bot/winget-pkgs`
+    pub(crate) fork: String,
+    #[serde(flatten)]
+    pub(crate) source: Source,
+}
+
+/// Where to find the installer of a Windows package.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Source {
+    /// Part of the name of the asset to install, if the package has more than one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) asset: Option<String>,
+    /// The name of the package to use, if more than one is released
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) package: Option<String>,
+}
+
+/// Open a pull request against a Scoop bucket which updates a manifest to the release in this
+/// workflow.
+pub(crate) fn run_scoop(options: &Scoop, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let github_config = github_config(&state, "UpdateScoopManifest")?;
+    let Some((version, url, asset)) = select_asset(&state, &github_config, &options.source)? else {
+        info!("No release to update the Scoop manifest for");
+        return Ok(run_type.of(state));
+    };
+    let installer = Installer {
+        version,
+        url,
+        path: &asset.path,
+    };
+    state.github = github::update_scoop_manifest(
+        &options.bucket,
+        &options.manifest,
+        &installer,
+        run_type.of(state.github),
+        &github_config,
+    )?;
+    Ok(run_type.of(state))
+}
+
+/// Open a pull request against `microsoft/winget-pkgs` which adds manifests for the release in
+/// this workflow.
+pub(crate) fn run_winget(options: &Winget, state: RunType<State>) -> Result<RunType<State>, Error> {
+    let (run_type, mut state) = state.take();
+    let github_config = github_config(&state, "UpdateWingetManifest")?;
+    let Some((version, url, asset)) = select_asset(&state, &github_config, &options.source)? else {
+        info!("No release to update the winget manifest for");
+        return Ok(run_type.of(state));
+    };
+    let installer = Installer {
+        version,
+        url,
+        path: &asset.path,
+    };
+    state.github = github::update_winget_manifest(
+        &options.identifier,
+        &options.fork,
+        &installer,
+        run_type.of(state.github),
+        &github_config,
+    )?;
+    Ok(run_type.of(state))
+}
+
+fn github_config(state: &State, step: &'static str) -> Result<config::GitHub, Error> {
+    state
+        .github_config
+        .clone()
+        .ok_or(Error::NotConfigured { step })
+}
+
+/// The version, download URL, and local copy of the asset to install.
+fn select_asset(
+    state: &State,
+    github_config: &config::GitHub,
+    source: &Source,
+) -> Result<Option<(String, String, Asset)>, Error> {
+    let Some(release) = state.single_release(source.package.as_deref())? else {
+        return Ok(None);
+    };
+    let assets = state
+        .packages
+        .iter()
+        .find(|package| package.name() == &release.package_name)
+        .and_then(|package| package.uploads(&release.version, None))
+        .map(|uploads| github::resolve_assets(&uploads))
+        .transpose()?
+        .unwrap_or_default();
+    let mut matching = Vec::with_capacity(assets.len());
+    for asset in assets {
+        let name = asset.name().map_err(github::CreateReleaseError::from)?;
+        if source
+            .asset
+            .as_deref()
+            .map_or(true, |pattern| name.contains(pattern))
+        {
+            matching.push((name, asset));
+        }
+    }
+    let (name, asset) = match matching.len() {
+        0 => return Err(Error::NoAsset),
+        1 => matching.remove(0),
+        _ => {
+            return Err(Error::TooManyAssets(
+                matching.iter().map(|(name, _)| name).join(", "),
+            ))
+        }
+    };
+    let tag = ReleaseTag::new(&release.version, &release.package_name);
+    let url = format!(
+        "https://github.com/{owner}/{repo}/releases/download/{tag}/{name}",
+        owner = github_config.owner,
+        repo = github_config.repo,
+        tag = tag.as_str(),
+    );
+    Ok(Some((release.version.to_string(), url, asset)))
+}
+
+#[derive(Debug, Diagnostic, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("GitHub is not configured")]
+    #[diagnostic(
+        code(windows_package::github::not_configured),
+        help("GitHub must be configured in order to use the {step} step"),
+        url("https://knope.tech/reference/config-file/github/")
+    )]
+    NotConfigured { step: &'static str },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    TooManyReleases(#[from] TooManyReleases),
+    #[error("The released package has no matching assets")]
+    #[diagnostic(
+        code(windows_package::no_asset),
+        help("The installer must be one of the `assets` of the package, and contain `asset` in its name if set."),
+        url("https://knope.tech/reference/config-file/packages/#assets")
+    )]
+    NoAsset,
+    #[error("More than one asset matches: {0}")]
+    #[diagnostic(
+        code(windows_package::too_many_assets),
+        help("Set `asset` to part of the name of the installer, like `windows`.")
+    )]
+    TooManyAssets(String),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Assets(#[from] github::CreateReleaseError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    GitHub(#[from] github::WindowsManifestError),
+}
@@ -1,10 +1,14 @@
+use std::str::FromStr;
+
 use indexmap::IndexMap;
+use itertools::Itertools;
 use knope_versioning::{release_notes::Release, semver::Version, Action};
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
+use time::{format_description, OffsetDateTime};
 
 use crate::{
-    integrations::git::branch_name_from_issue,
+    integrations::{git, git::branch_name_from_issue},
     state,
     state::State,
     step::releases::{package, semver, Package},
@@ -13,7 +17,7 @@ use crate::{
 /// Describes a value that can replace an arbitrary string in certain steps.
 ///
 /// <https://knope.tech/reference/config-file/variables//>
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) enum Variable {
     /// The version of the package, if only a single package is configured (error if multiple).
     Version,
@@ -22,6 +26,18 @@ pub(crate) enum Variable {
     IssueBranch,
     /// Get the current changelog entry from the latest release.
     ChangelogEntry,
+    /// The output of an earlier [`crate::step::Step::Command`] with a matching `capture_output`.
+    Output(String),
+    /// The name of the package being released, or of the only configured package.
+    PackageName,
+    /// Today's date (in UTC) as `YYYY-MM-DD`.
+    Date,
+    /// Today's date (in UTC) in a custom [`time` format](https://time-rs.github.io/book/api/format-description.html).
+    FormattedDate(String),
+    /// The full hash of the commit that `HEAD` points to.
+    GitSha,
+    /// The name of the current Git branch.
+    Branch,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -40,7 +56,7 @@ pub(crate) fn replace_variables(template: Template, state: &mut State) -> Result
         variables,
     } = template;
     for (var_name, var_type) in variables {
-        match var_type {
+        let value = match var_type {
             Variable::Version => {
                 let package = if let Some(package) = package_cache.take() {
                     package
@@ -48,8 +64,8 @@ pub(crate) fn replace_variables(template: Template, state: &mut State) -> Result
                     first_package(state)?
                 };
                 let version = package.versioning.versions.clone().into_latest();
-                template = template.replace(&var_name, &version.to_string());
                 package_cache = Some(package);
+                version.to_string()
             }
             Variable::ChangelogEntry => {
                 let package = if let Some(package) = package_cache.take() {
@@ -57,34 +73,47 @@ pub(crate) fn replace_variables(template: Template, state: &mut State) -> Result
                 } else {
                     first_package(state)?
                 };
-                if let Some(body) = state.pending_actions.iter().find_map(|action| {
+                let notes = if let Some(body) = state.pending_actions.iter().find_map(|action| {
                     if let Action::CreateRelease(Release { notes, .. }) = action {
                         Some(notes)
                     } else {
                         None
                     }
                 }) {
-                    template = template.replace(&var_name, body);
+                    body.clone()
                 } else {
                     let version = package.versioning.versions.clone().into_latest();
-                    let release = package
+                    package
                         .versioning
                         .release_notes
-                        .changelog
-                        .as_ref()
-                        .and_then(|changelog| changelog.get_release(&version, package.name()))
-                        .ok_or_else(|| Error::NoChangelogEntry(version))?;
-                    template = template.replace(&var_name, &release.notes);
-                }
+                        .get_release(&version, package.name())
+                        .ok_or_else(|| Error::NoChangelogEntry(version))?
+                        .notes
+                };
                 package_cache = Some(package);
+                notes
             }
+            Variable::Output(name) => state
+                .outputs
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| Error::NoOutput(name.clone()))?,
             Variable::IssueBranch => match &state.issue {
                 state::Issue::Initial => return Err(Error::NoIssueSelected),
-                state::Issue::Selected(issue) => {
-                    template = template.replace(&var_name, &branch_name_from_issue(issue));
-                }
+                state::Issue::Selected(issue) => branch_name_from_issue(issue),
             },
-        }
+            Variable::PackageName => package_name(state, package_cache.as_ref())?,
+            Variable::Date => today("[year]-[month]-[day]")?,
+            Variable::FormattedDate(format) => today(&format)?,
+            Variable::GitSha => git::head_commit_id()?,
+            Variable::Branch => {
+                let branch = git::current_branch()?;
+                branch
+                    .strip_prefix("refs/heads/")
+                    .map_or(branch.clone(), String::from)
+            }
+        };
+        template = replace(&template, &var_name, &value)?;
     }
     if let Some(package) = package_cache {
         state.packages.push(package);
@@ -92,6 +121,104 @@ pub(crate) fn replace_variables(template: Template, state: &mut State) -> Result
     Ok(template)
 }
 
+/// Replace every `placeholder` in `template` with `value`, transformed by any filters that follow
+/// the placeholder, like `$version | major-minor`.
+fn replace(template: &str, placeholder: &str, value: &str) -> Result<String, Error> {
+    if placeholder.is_empty() {
+        return Ok(template.replace(placeholder, value));
+    }
+    let mut replaced = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(placeholder) {
+        let (before, after) = rest.split_at(start);
+        replaced.push_str(before);
+        rest = after.strip_prefix(placeholder).unwrap_or(after);
+        let mut filtered = value.to_string();
+        while let Some((filter, after_filter)) = Filter::next(rest) {
+            filtered = filter.apply(&filtered)?;
+            rest = after_filter;
+        }
+        replaced.push_str(&filtered);
+    }
+    replaced.push_str(rest);
+    Ok(replaced)
+}
+
+/// A transformation of a variable's value, written after it in a template like `$version | major`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Filter {
+    /// The major component of a version, like `1` for `1.2.3`
+    Major,
+    /// The major and minor components of a version, like `1.2` for `1.2.3`
+    MajorMinor,
+    Uppercase,
+    Lowercase,
+    /// Replace anything which isn't allowed in a Docker tag with `-`, like `1.2.3-rc.0-build.1`
+    /// for `1.2.3-rc.0+build.1`
+    DockerSafe,
+}
+
+impl Filter {
+    /// Parse a filter (`| name`) at the start of `text`, returning it and the rest of `text`.
+    ///
+    /// Anything else, like a shell pipe to another command, is left alone.
+    fn next(text: &str) -> Option<(Self, &str)> {
+        let after_pipe = text
+            .trim_start_matches(' ')
+            .strip_prefix('|')?
+            .trim_start_matches(' ');
+        let end = after_pipe
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(after_pipe.len());
+        let (name, rest) = after_pipe.split_at(end);
+        let filter = match name {
+            "major" => Self::Major,
+            "major-minor" => Self::MajorMinor,
+            "uppercase" => Self::Uppercase,
+            "lowercase" => Self::Lowercase,
+            "docker-safe" => Self::DockerSafe,
+            _ => return None,
+        };
+        Some((filter, rest))
+    }
+
+    fn apply(self, value: &str) -> Result<String, Error> {
+        match self {
+            Self::Major | Self::MajorMinor => {
+                let version = Version::from_str(value).map_err(|_| Error::NotAVersion {
+                    filter: self.name(),
+                    value: value.to_string(),
+                })?;
+                let stable = version.stable_component().to_string();
+                let components = if self == Self::Major { 1 } else { 2 };
+                Ok(stable.split('.').take(components).join("."))
+            }
+            Self::Uppercase => Ok(value.to_uppercase()),
+            Self::Lowercase => Ok(value.to_lowercase()),
+            Self::DockerSafe => Ok(value
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                        c
+                    } else {
+                        '-'
+                    }
+                })
+                .collect()),
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::MajorMinor => "major-minor",
+            Self::Uppercase => "uppercase",
+            Self::Lowercase => "lowercase",
+            Self::DockerSafe => "docker-safe",
+        }
+    }
+}
+
 fn first_package(state: &mut State) -> Result<Package, Error> {
     if state.packages.len() > 1 {
         Err(Error::TooManyPackages)
@@ -102,6 +229,38 @@ fn first_package(state: &mut State) -> Result<Package, Error> {
     }
 }
 
+/// The name of the only release in `state`, or else the only package (which may already be in
+/// `package_cache`).
+fn package_name(state: &State, package_cache: Option<&Package>) -> Result<String, Error> {
+    let releases = state.releases();
+    let name = match (
+        releases.as_slice(),
+        package_cache,
+        state.packages.as_slice(),
+    ) {
+        ([release], _, _) => &release.package_name,
+        (_, Some(package), _) | (_, None, [package]) => package.name(),
+        (_, None, []) => return Err(package::Error::NoDefinedPackages.into()),
+        (_, None, _) => return Err(Error::TooManyPackages),
+    };
+    name.as_custom()
+        .map(String::from)
+        .ok_or(Error::UnnamedPackage)
+}
+
+/// Today's date (in UTC) in `format`.
+fn today(format: &str) -> Result<String, Error> {
+    let invalid = |err: &dyn std::fmt::Display| Error::DateFormat {
+        format: format.to_string(),
+        reason: err.to_string(),
+    };
+    let description = format_description::parse(format).map_err(|err| invalid(&err))?;
+    OffsetDateTime::now_utc()
+        .date()
+        .format(&description)
+        .map_err(|err| invalid(&err))
+}
+
 #[derive(Debug, Diagnostic, thiserror::Error)]
 pub(crate) enum Error {
     #[error("Too many packages defined")]
@@ -125,9 +284,42 @@ pub(crate) enum Error {
         help("The IssueBranch command variable requires selecting an issue first with SelectGitHubIssue or SelectJiraIssue")
     )]
     NoIssueSelected,
+    #[error("No output named {0} has been captured")]
+    #[diagnostic(
+        code(variables::no_output),
+        help(
+            "The Output variable requires an earlier Command step with a matching `capture_output`"
+        ),
+        url("https://knope.tech/reference/config-file/variables/#output")
+    )]
+    NoOutput(String),
     #[error(transparent)]
     #[diagnostic(transparent)]
     SemVer(#[from] semver::Error),
+    #[error("The package has no name")]
+    #[diagnostic(
+        code(variables::unnamed_package),
+        help("The PackageName variable needs a package defined with `[packages.<name>]`."),
+        url("https://knope.tech/reference/config-file/variables/#packagename")
+    )]
+    UnnamedPackage,
+    #[error("Invalid date format {format}: {reason}")]
+    #[diagnostic(
+        code(variables::date_format),
+        help("FormattedDate takes a format like `[year]-[month]-[day]`."),
+        url("https://time-rs.github.io/book/api/format-description.html")
+    )]
+    DateFormat { format: String, reason: String },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Git(#[from] git::Error),
+    #[error("The {filter} filter needs a version, but got {value}")]
+    #[diagnostic(
+        code(variables::not_a_version),
+        help("Only use the `major` and `major-minor` filters on variables that are versions, like `Version`."),
+        url("https://knope.tech/reference/config-file/variables/#filters")
+    )]
+    NotAVersion { filter: &'static str, value: String },
 }
 
 #[cfg(test)]
@@ -163,7 +355,12 @@ mod test_replace_variables {
                 &all_versioned_files,
                 ReleaseNotes {
                     sections: Sections::default(),
-                    changelog: Some(changelog),
+                    changelogs: vec![changelog],
+                    forge: None,
+                    release_json: None,
+                    template: None,
+                    change_templates: Vec::new(),
+                    title: None,
                 },
                 None,
             )
@@ -175,6 +372,7 @@ mod test_replace_variables {
             None,
             None,
             None,
+            None,
             vec![package],
             all_versioned_files,
             Vec::new(),
@@ -219,11 +417,16 @@ mod test_replace_variables {
             github_config: None,
             gitea: state::Gitea::New,
             gitea_config: None,
+            signing: None,
             issue: state::Issue::Selected(issue),
             packages: Vec::new(),
             all_git_tags: Vec::new(),
             all_versioned_files: Vec::new(),
             pending_actions: Vec::new(),
+            released: Vec::new(),
+            outputs: IndexMap::new(),
+            modified_files: Vec::new(),
+            plan: None,
         };
 
         let result = replace_variables(
@@ -264,4 +467,137 @@ mod test_replace_variables {
 
         assert_eq!(result, format!("blah {changelog_entry} other blah"));
     }
+
+    #[test]
+    fn replace_output() {
+        let template = "upload $path".to_string();
+        let mut variables = IndexMap::new();
+        variables.insert(
+            "$path".to_string(),
+            Variable::Output("artifact".to_string()),
+        );
+        let mut state = state();
+        state
+            .outputs
+            .insert("artifact".to_string(), "target/knope".to_string());
+
+        let result = replace_variables(
+            Template {
+                template,
+                variables,
+            },
+            &mut state,
+        )
+        .unwrap();
+
+        assert_eq!(result, "upload target/knope");
+    }
+
+    #[test]
+    fn replace_package_name_of_release() {
+        let mut variables = IndexMap::new();
+        variables.insert("$name".to_string(), Variable::PackageName);
+        let mut state = state();
+        state.pending_actions = vec![Action::CreateRelease(Release {
+            version: Version::new(1, 2, 3, None),
+            title: "title".to_string(),
+            notes: String::new(),
+            package_name: Name::Custom("knope".to_string()),
+        })];
+
+        let result = replace_variables(
+            Template {
+                template: "release $name".to_string(),
+                variables,
+            },
+            &mut state,
+        )
+        .unwrap();
+
+        assert_eq!(result, "release knope");
+    }
+
+    #[test]
+    fn unnamed_package() {
+        let mut variables = IndexMap::new();
+        variables.insert("$name".to_string(), Variable::PackageName);
+
+        let result = replace_variables(
+            Template {
+                template: "release $name".to_string(),
+                variables,
+            },
+            &mut state(),
+        );
+
+        assert!(matches!(result, Err(Error::UnnamedPackage)));
+    }
+
+    #[test]
+    fn invalid_date_format() {
+        let mut variables = IndexMap::new();
+        variables.insert(
+            "$date".to_string(),
+            Variable::FormattedDate("[year".to_string()),
+        );
+
+        let result = replace_variables(
+            Template {
+                template: "$date".to_string(),
+                variables,
+            },
+            &mut state(),
+        );
+
+        assert!(matches!(result, Err(Error::DateFormat { .. })));
+    }
+
+    #[test]
+    fn filters() {
+        let mut variables = IndexMap::new();
+        variables.insert("$version".to_string(), Variable::Version);
+        let mut state = state();
+        state.packages[0].versioning.versions = Version::from_str("1.2.3-rc.0").unwrap().into();
+
+        let result = replace_variables(
+            Template {
+                template: "$version | major-minor, $version|major, $version | uppercase | docker-safe, $version"
+                    .to_string(),
+                variables,
+            },
+            &mut state,
+        )
+        .unwrap();
+
+        assert_eq!(result, "1.2, 1, 1.2.3-RC.0, 1.2.3-rc.0");
+    }
+
+    #[test]
+    fn docker_safe() {
+        assert_eq!(
+            replace("tag:$v | docker-safe", "$v", "1.2.3+build/1").unwrap(),
+            "tag:1.2.3-build-1"
+        );
+    }
+
+    #[test]
+    fn unknown_filters_are_left_alone() {
+        assert_eq!(
+            replace("echo $v | lowercase | tr a b || true", "$v", "ABC").unwrap(),
+            "echo abc | tr a b || true"
+        );
+    }
+
+    #[test]
+    fn version_filter_on_non_version() {
+        let result = replace("$branch | major", "$branch", "main");
+
+        assert!(matches!(
+            result,
+            Err(Error::NotAVersion {
+                filter: "major",
+                ..
+            })
+        ));
+    }
 }
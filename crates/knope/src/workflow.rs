@@ -1,30 +1,82 @@
 use std::fmt::Debug;
 
+use indexmap::IndexMap;
 use itertools::Itertools;
+use knope_versioning::semver::StableRule;
 use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::{debug, info_span};
 
-use crate::{state::RunType, step, step::Step, State};
+use crate::{
+    progress,
+    state::RunType,
+    step,
+    step::{condition::Condition, push, run_workflow, scope, Step},
+    State,
+};
 
 /// A workflow is basically the state machine to run for a single execution of knope.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) struct Workflow {
     /// The display name of this Workflow. This is what you'll see when you go to select it.
     pub(crate) name: String,
     /// The help text for this workflow. When running `knope --help`, this will be displayed.
     pub(crate) help_text: Option<String>,
     /// A list of [`Step`]s to execute in order, stopping if any step fails.
-    pub(crate) steps: Vec<Step>,
+    pub(crate) steps: Vec<WorkflowStep>,
+    /// Steps to run if any of [`Self::steps`] fails, like cleaning up a partial release.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) on_error: Vec<WorkflowStep>,
+    /// Environment variables for every `Command` step in [`Self::steps`] and [`Self::on_error`].
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub(crate) env: IndexMap<String, String>,
+    /// If set, every step which doesn't set its own `packages` only operates on these packages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) packages: Option<Vec<String>>,
+    /// Fail instead of running at the same time as another run of this workflow, see
+    /// [`push::lock`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) lock: bool,
+}
+
+/// A [`Step`] along with the options that every step has.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct WorkflowStep {
+    /// If set, the step is skipped unless this condition is met when the step is reached.
+    #[serde(rename = "if", default, skip_serializing_if = "Option::is_none")]
+    pub(crate) condition: Option<Condition>,
+    /// If set, the step only operates on these packages, ignoring the rest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) packages: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub(crate) step: Step,
+}
+
+impl From<Step> for WorkflowStep {
+    fn from(step: Step) -> Self {
+        Self {
+            condition: None,
+            packages: None,
+            step,
+        }
+    }
 }
 
 impl Workflow {
     /// Set `prerelease_label` for any steps that are `PrepareRelease` steps.
     pub(crate) fn set_prerelease_label(&mut self, prerelease_label: &str) {
-        for step in &mut self.steps {
+        for WorkflowStep { step, .. } in &mut self.steps {
             step.set_prerelease_label(prerelease_label);
         }
     }
+
+    /// Set `rule` for any steps that are `PrepareRelease` steps.
+    pub(crate) fn set_rule(&mut self, rule: StableRule) {
+        for WorkflowStep { step, .. } in &mut self.steps {
+            step.set_rule(rule);
+        }
+    }
 }
 
 /// A collection of errors from running with the `--validate` option.
@@ -40,24 +92,136 @@ pub struct ValidationErrorCollection {
 #[error("Problem with workflow {name}")]
 pub struct Error {
     name: String,
+    /// The error from the failed step, followed by any error from the `on_error` steps
     #[related]
-    inner: Box<[step::Error; 1]>,
+    inner: Vec<step::Error>,
 }
 
 /// Run a series of [`Step`], each of which updates `state`.
-pub(crate) fn run(workflow: Workflow, mut state: RunType<State>) -> Result<(), Error> {
-    for step in workflow.steps {
-        state = match step.run(state) {
+///
+/// `workflows` are all the defined workflows, which can be run by [`Step::RunWorkflow`].
+///
+/// The first `first_step` steps are skipped, since `--resume` already ran them. If a step of a real
+/// run fails, the progress up to that step is saved, and it's forgotten once every step finishes.
+pub(crate) fn run(
+    workflow: &Workflow,
+    workflows: &[Workflow],
+    state: RunType<State>,
+    first_step: usize,
+) -> Result<RunType<State>, Error> {
+    check_recursion(workflow, workflows)?;
+    let lock = if workflow.lock {
+        push::lock(state.of(state.inner()), &workflow.name).map_err(|err| Error {
+            name: workflow.name.clone(),
+            inner: vec![err.into()],
+        })?
+    } else {
+        None
+    };
+    let real = matches!(state, RunType::Real(_));
+    if real && first_step == 0 {
+        progress::clear();
+    }
+    let result = run_steps_from(workflow, workflows, state, first_step, true);
+    if let Some(lock) = lock {
+        push::unlock(lock);
+    }
+    let state = result?;
+    if real {
+        progress::clear();
+    }
+    Ok(state)
+}
+
+fn check_recursion(workflow: &Workflow, workflows: &[Workflow]) -> Result<(), Error> {
+    run_workflow::check_recursion(workflow, workflows, &mut Vec::new()).map_err(|err| Error {
+        name: workflow.name.clone(),
+        inner: vec![err.into()],
+    })
+}
+
+/// Run the steps of `workflow` without checking for recursion first, returning the final state.
+///
+/// If a step fails, the `on_error` steps of `workflow` run, starting from the state before that
+/// step.
+pub(crate) fn run_steps(
+    workflow: &Workflow,
+    workflows: &[Workflow],
+    state: RunType<State>,
+) -> Result<RunType<State>, Error> {
+    run_steps_from(workflow, workflows, state, 0, false)
+}
+
+/// Like [`run_steps`], but skipping the first `first_step` steps (which already finished).
+///
+/// If `save_progress`, how far the workflow got is saved for `--resume` when a step fails.
+fn run_steps_from(
+    workflow: &Workflow,
+    workflows: &[Workflow],
+    mut state: RunType<State>,
+    first_step: usize,
+    save_progress: bool,
+) -> Result<RunType<State>, Error> {
+    let _span = info_span!("workflow", workflow = %workflow.name).entered();
+    for (index, workflow_step) in workflow.steps.iter().cloned().enumerate().skip(first_step) {
+        let before = (save_progress || !workflow.on_error.is_empty()).then(|| state.clone());
+        state = match run_step(workflow_step, workflow, workflows, state) {
             Ok(state) => state,
             Err(err) => {
+                let mut inner = vec![err];
+                if let Some(mut state) = before {
+                    if save_progress {
+                        progress::save(&workflow.name, index, &state);
+                    }
+                    for workflow_step in workflow.on_error.iter().cloned() {
+                        match run_step(workflow_step, workflow, workflows, state) {
+                            Ok(new_state) => state = new_state,
+                            Err(err) => {
+                                inner.push(err);
+                                break;
+                            }
+                        }
+                    }
+                }
                 return Err(Error {
-                    name: workflow.name,
-                    inner: Box::new([err]),
+                    name: workflow.name.clone(),
+                    inner,
                 });
             }
         };
     }
-    Ok(())
+    Ok(state)
+}
+
+/// Run a single step of `workflow`, unless it has a condition which isn't met.
+///
+/// If the step (or else `workflow`) sets `packages`, the step only sees those packages.
+fn run_step(
+    WorkflowStep {
+        condition,
+        packages,
+        mut step,
+    }: WorkflowStep,
+    workflow: &Workflow,
+    workflows: &[Workflow],
+    state: RunType<State>,
+) -> Result<RunType<State>, step::Error> {
+    let _span = info_span!("step", step = %step.name()).entered();
+    if let Some(condition) = condition {
+        if !condition.is_met(state.inner())? {
+            debug!("Skipping step because `{condition}` is not met");
+            return Ok(state);
+        }
+    }
+    step.inherit_env(&workflow.env);
+    let Some(packages) = packages.as_ref().or(workflow.packages.as_ref()) else {
+        return step.run(state, workflows);
+    };
+    let (run_type, mut state) = state.take();
+    let out_of_scope = scope::enter(&mut state, packages)?;
+    let (run_type, mut state) = step.run(run_type.of(state), workflows)?.take();
+    scope::exit(&mut state, out_of_scope);
+    Ok(run_type.of(state))
 }
 
 #[allow(clippy::needless_pass_by_value)] // Lifetime errors if State is passed by ref.
@@ -65,9 +229,14 @@ pub(crate) fn validate(
     workflows: Vec<Workflow>,
     state: State,
 ) -> Result<(), ValidationErrorCollection> {
+    // Workflows aren't locked, so validating works without a remote
     let errors = workflows
-        .into_iter()
-        .filter_map(|workflow| run(workflow, RunType::DryRun(state.clone())).err())
+        .iter()
+        .filter_map(|workflow| {
+            check_recursion(workflow, &workflows)
+                .and_then(|()| run_steps(workflow, &workflows, RunType::DryRun(state.clone())))
+                .err()
+        })
         .collect_vec();
 
     if errors.is_empty() {
@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Without a `url`, the formula points at the source archive of the new tag on GitHub.
+#[test]
+fn default_url() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run");
+}
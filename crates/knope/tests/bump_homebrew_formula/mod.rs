@@ -0,0 +1,2 @@
+mod default_url;
+mod no_github;
@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// The step needs a `[github]` config to open the pull request.
+#[test]
+fn no_github() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run");
+}
@@ -1,6 +1,8 @@
+mod exact;
 mod major;
 mod minor;
 mod r#override;
 mod patch;
 mod pre;
+mod promote;
 mod release;
@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn test() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.2.3"),
+            Tag("v1.3.0-alpha.0"),
+            Tag("v1.3.0-beta.2"),
+        ])
+        .run("bump");
+}
@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn capture_output() {
+    TestCase::new(file!()).run("upload");
+}
@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn env() {
+    TestCase::new(file!()).run("env");
+}
@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// The current commit, branch, and date can be used as variables.
+#[test]
+fn git_variables() {
+    TestCase::new(file!())
+        .git(&[Commit("Initial commit")])
+        .run("describe");
+}
@@ -1,3 +1,9 @@
+mod capture_output;
+#[cfg(not(windows))]
+mod env;
+mod git_variables;
 mod replace_version;
 #[cfg(not(windows))]
+mod retries;
+#[cfg(not(windows))]
 mod shell;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// The command fails the first time, then succeeds when retried.
+#[test]
+fn retries() {
+    TestCase::new(file!()).run("flaky");
+}
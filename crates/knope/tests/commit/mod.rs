@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Commits only the files Knope changed, with variables replaced in the message.
+#[test]
+fn commit() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: Existing feature"), Tag("v1.0.0")])
+        .run("release");
+}
@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// `[package.metadata.knope]` in `Cargo.toml` is used when there's no `knope.toml`
+#[test]
+fn cargo_metadata() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A feature"),
+        ])
+        .run("release");
+}
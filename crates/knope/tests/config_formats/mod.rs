@@ -0,0 +1,4 @@
+mod cargo_metadata;
+mod json;
+mod package_json;
+mod yaml;
@@ -0,0 +1,9 @@
+use crate::helpers::TestCase;
+
+/// `KNOPE_CONFIG` can set the config path, and errors refer to that file
+#[test]
+fn env() {
+    TestCase::new(file!())
+        .env(&[("KNOPE_CONFIG", "config/knope.toml")])
+        .run("release");
+}
@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// `--config` loads a config file from somewhere other than `knope.toml`
+#[test]
+fn flag() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v1.0.0"),
+            Commit("feat: A feature"),
+        ])
+        .run("--config .config/knope.toml release");
+}
@@ -0,0 +1,2 @@
+mod env;
+mod flag;
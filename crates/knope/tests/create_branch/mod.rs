@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Creates a branch with variables replaced in the name, bringing along uncommitted changes.
+#[test]
+fn create_branch() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: Existing feature"), Tag("v1.0.0")])
+        .run("release");
+}
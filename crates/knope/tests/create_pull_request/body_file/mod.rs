@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// The body template is read from a file, then has its variables replaced.
+#[test]
+fn body_file() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: New feature")])
+        .run("pull-request --dry-run");
+}
@@ -0,0 +1,2 @@
+mod body_file;
+mod metadata;
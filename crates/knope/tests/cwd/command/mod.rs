@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// `-C` loads the config and runs commands in another directory
+#[test]
+fn command() {
+    TestCase::new(file!()).run("-C project greet");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// A clear error if the `--cwd` directory doesn't exist
+#[test]
+fn missing() {
+    TestCase::new(file!()).run("--cwd missing greet");
+}
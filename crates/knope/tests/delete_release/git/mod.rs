@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Only the tag of the current version is deleted.
+#[test]
+fn delete_release() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+            Tag("v1.1.0"),
+        ])
+        .expected_tags(&[])
+        .run("rollback");
+}
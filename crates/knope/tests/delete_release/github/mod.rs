@@ -0,0 +1,12 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn delete_release_dry_run() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: New feature"), Tag("v1.1.0")])
+        .with_remote("https://github.com/knope-dev/knope.git")
+        .run("rollback --dry-run");
+}
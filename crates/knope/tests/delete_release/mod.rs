@@ -0,0 +1,2 @@
+mod git;
+mod github;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// `--deny-deprecated` turns deprecation warnings into an error
+#[test]
+fn deny() {
+    TestCase::new(file!()).run("--validate --deny-deprecated");
+}
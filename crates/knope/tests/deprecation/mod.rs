@@ -0,0 +1,2 @@
+mod deny;
+mod warn;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Deprecated config is still valid, but shows a warning
+#[test]
+fn warn() {
+    TestCase::new(file!()).run("--validate");
+}
@@ -0,0 +1,16 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `--dry-run --quiet` prints only a summary of each action, without diffs or release notes
+#[test]
+fn dry_run_quiet() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run --quiet");
+}
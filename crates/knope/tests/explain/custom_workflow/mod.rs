@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Every step is listed with its options, packages, and variables
+#[test]
+fn custom_workflow() {
+    TestCase::new(file!()).run("explain release");
+}
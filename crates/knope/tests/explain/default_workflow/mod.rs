@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// The generated workflows can be explained without a `knope.toml` file
+#[test]
+fn default_workflow() {
+    TestCase::new(file!()).run("explain release");
+}
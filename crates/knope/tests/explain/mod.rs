@@ -0,0 +1,2 @@
+mod custom_workflow;
+mod default_workflow;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `--format json` prints only a structured plan of what a dry run would do
+#[test]
+fn format_json() {
+    TestCase::new(file!())
+        .without_normalized_paths()
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run --format json");
+}
@@ -0,0 +1,8 @@
+use crate::helpers::TestCase;
+
+/// Expand glob patterns in the workspace members, skipping anything in `exclude` or without a
+/// `Cargo.toml`, and use any `CHANGELOG.md` next to each member.
+#[test]
+fn test() {
+    TestCase::new(file!()).run("--generate");
+}
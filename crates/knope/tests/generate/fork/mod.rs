@@ -0,0 +1,12 @@
+use crate::helpers::{GitCommand::Remote, TestCase};
+
+/// With multiple remotes, the forge is detected from `upstream`, which is recorded in the config.
+#[test]
+fn generate_fork() {
+    TestCase::new(file!())
+        .git(&[
+            Remote("origin", "https://github.com/someone/knope.git"),
+            Remote("upstream", "https://github.com/knope-dev/knope.git"),
+        ])
+        .run("--generate");
+}
@@ -0,0 +1,3 @@
+module github.com/knope-dev/example/tools/cli
+
+go 1.22
@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn test() {
+    TestCase::new(file!()).run("--generate");
+}
@@ -1,6 +1,9 @@
 mod cargo_workspace;
+mod cargo_workspace_globs;
+mod fork;
 mod gitea;
 mod github;
+mod go_workspace;
 mod no_remote;
 mod packages;
 mod packages_with_changelog;
@@ -0,0 +1,15 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// New versions are appended to the file named by an environment variable
+#[test]
+fn github_output() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.2.3"),
+            Tag("second/v0.4.6"),
+            Commit("feat(first): A feature"),
+        ])
+        .env(&[("GITHUB_OUTPUT", "github_output.txt")])
+        .run("release");
+}
@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn json() {
+    TestCase::new(file!()).run("get-version");
+}
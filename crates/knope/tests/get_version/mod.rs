@@ -0,0 +1,2 @@
+mod github_output;
+mod json;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// The release notes are the message of the new tag with `annotate_tags`.
+#[test]
+fn annotated_tags() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&["v1.1.0"])
+        .run("release");
+}
@@ -1,2 +1,3 @@
+mod annotated_tags;
 mod multiple_packages;
 mod single_package;
@@ -0,0 +1,22 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Outputs are prefixed with the package name, and only include packages with a new version.
+#[test]
+fn multiple_packages() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.2.3"),
+            Tag("second/v0.4.6"),
+            Commit("feat(first): A feature"),
+        ])
+        .env(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_OUTPUT", "github_output.txt"),
+            ("GITHUB_STEP_SUMMARY", "summary.md"),
+        ])
+        .run("prepare-release");
+}
@@ -0,0 +1,21 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// In GitHub Actions, the new version and tag are written to the step outputs and job summary.
+#[test]
+fn single_package() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .env(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_OUTPUT", "github_output.txt"),
+            ("GITHUB_STEP_SUMMARY", "summary.md"),
+        ])
+        .run("release");
+}
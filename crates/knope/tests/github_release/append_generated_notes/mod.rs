@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Ask GitHub to add its generated notes beneath the changelog section.
+///
+/// # Expected
+///
+/// The release should have both the changelog section and autogenerated notes.
+#[test]
+fn append_generated_notes() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run");
+}
@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Release a backported patch which shouldn't become the latest release, with a discussion.
+///
+/// # Expected
+///
+/// The release should not be marked as latest, and a discussion should be started.
+#[test]
+fn backport_release() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run");
+}
@@ -0,0 +1,21 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Comment on and close the issues fixed by a release.
+///
+/// # Expected
+///
+/// Only issues referenced with a keyword like `Fixes` should be closed.
+#[test]
+fn close_fixed_issues() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature\n\nFixes #12, see #13"),
+            Commit("fix: A bug\n\nCloses: #2"),
+        ])
+        .run("release --dry-run");
+}
@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Run a `PrepareRelease` then `Release` with `draft = true` in the GitHub config.
+///
+/// # Expected
+///
+/// The release should be created as a draft.
+#[test]
+fn draft_release() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run");
+}
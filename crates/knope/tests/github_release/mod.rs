@@ -1,4 +1,8 @@
+mod append_generated_notes;
 mod auto_generate_release_notes;
+mod backport;
+mod close_fixed_issues;
+mod draft;
 mod multiple_packages;
 mod no_previous_tag;
 mod release_assets;
@@ -1,2 +1,7 @@
+mod checksums;
 mod glob;
 mod list;
+mod list_of_globs;
+mod no_matches;
+mod sigstore;
+mod variables;
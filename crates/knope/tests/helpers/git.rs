@@ -44,12 +44,12 @@ pub fn init(path: &Path) {
 }
 
 /// Add a Git remote to the repo at `path`.
-pub fn add_remote(path: &Path, remote: &str) {
+pub fn add_remote(path: &Path, name: &str, url: &str) {
     let output = Command::new("git")
         .arg("remote")
         .arg("add")
-        .arg("origin")
-        .arg(remote)
+        .arg(name)
+        .arg(url)
         .current_dir(path)
         .output()
         .unwrap();
@@ -77,6 +77,29 @@ pub fn commit(path: &Path, message: &str) {
     );
 }
 
+/// Write each of `files` (relative to `path`) and commit them with `message`.
+pub fn commit_files(path: &Path, message: &str, files: &[&str]) {
+    for file in files {
+        let file_path = path.join(file);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&file_path, message).unwrap();
+    }
+    let output = Command::new("git")
+        .arg("add")
+        .args(files)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    commit(path, message);
+}
+
 /// Create a tag with `label` in the Git repo which exists in `path`.
 pub fn tag(path: &Path, label: &str) {
     let output = Command::new("git")
@@ -92,6 +115,22 @@ pub fn tag(path: &Path, label: &str) {
     );
 }
 
+/// Make the repo at `path` look like a shallow clone whose history starts at `HEAD`.
+pub fn mark_shallow(path: &Path) {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("HEAD")
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    std::fs::write(path.join(".git").join("shallow"), output.stdout).unwrap();
+}
+
 /// Create and switch to a new branch
 pub fn create_branch(path: &Path, name: &str) {
     let output = Command::new("git")
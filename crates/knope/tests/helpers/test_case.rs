@@ -9,14 +9,17 @@ use snapbox::{
 };
 use tempfile::TempDir;
 
-use crate::helpers::{add_remote, assert, commit, copy_dir_contents, get_tags, init, tag};
+use crate::helpers::{
+    add_remote, assert, commit, commit_files, copy_dir_contents, get_tags, init, mark_shallow, tag,
+};
 
 pub struct TestCase {
     file_name: &'static str,
     git: &'static [GitCommand],
-    env: Option<(&'static str, &'static str)>,
+    env: &'static [(&'static str, &'static str)],
     remote: Option<&'static str>,
     expected_tags: Option<&'static [&'static str]>,
+    normalize_paths: bool,
 }
 
 impl TestCase {
@@ -24,10 +27,11 @@ impl TestCase {
     pub const fn new(file_name: &'static str) -> Self {
         Self {
             file_name,
-            env: None,
+            env: &[],
             git: &[],
             remote: None,
             expected_tags: None,
+            normalize_paths: true,
         }
     }
 
@@ -36,8 +40,9 @@ impl TestCase {
             file_name: self.file_name,
             remote: self.remote,
             git: commands,
-            env: None,
+            env: &[],
             expected_tags: self.expected_tags,
+            normalize_paths: self.normalize_paths,
         }
     }
 
@@ -51,6 +56,13 @@ impl TestCase {
         self
     }
 
+    /// Compare output exactly, for output with backslashes that aren't path separators (like
+    /// escapes in JSON).
+    pub fn without_normalized_paths(mut self) -> Self {
+        self.normalize_paths = false;
+        self
+    }
+
     /// Set up a new temporary directory with the contents of the `in` directory (if any).
     /// Initialize a git repository and run the commands in `git`.
     pub fn arrange(&self) -> TempDir {
@@ -64,16 +76,25 @@ impl TestCase {
 
         init(path);
         if let Some(remote) = self.remote {
-            add_remote(path, remote);
+            add_remote(path, "origin", remote);
         }
         for command in self.git {
             match command {
                 GitCommand::Commit(message) => {
                     commit(path, message);
                 }
+                GitCommand::CommitFiles(message, files) => {
+                    commit_files(path, message, files);
+                }
                 GitCommand::Tag(name) => {
                     tag(path, name);
                 }
+                GitCommand::Remote(name, url) => {
+                    add_remote(path, name, url);
+                }
+                GitCommand::Shallow => {
+                    mark_shallow(path);
+                }
             }
         }
 
@@ -86,16 +107,19 @@ impl TestCase {
         let parts = command.split_whitespace().collect::<Vec<_>>();
         let mut real = Command::new(cargo_bin!("knope"))
             .current_dir(working_dir.path())
-            .with_assert(assert(true));
+            .with_assert(assert(self.normalize_paths));
         let mut dry_run = Command::new(cargo_bin!("knope"))
             .current_dir(working_dir.path())
-            .with_assert(assert(true));
+            .with_assert(assert(self.normalize_paths));
 
         for arg in parts {
             real = real.arg(arg);
             dry_run = dry_run.arg(arg);
         }
-        if let Some((key, value)) = self.env {
+        // Tests which run in GitHub Actions shouldn't write to the real job's outputs
+        real = real.env_remove("GITHUB_ACTIONS");
+        dry_run = dry_run.env_remove("GITHUB_ACTIONS");
+        for (key, value) in self.env {
             real = real.env(key, value);
             dry_run = dry_run.env(key, value);
         }
@@ -181,13 +205,15 @@ impl TestCase {
         self.assert(self.act(self.arrange(), command));
     }
 
-    pub fn env(self, key: &'static str, value: &'static str) -> TestCase {
+    /// Set environment variables for the knope command.
+    pub fn env(self, env: &'static [(&'static str, &'static str)]) -> TestCase {
         TestCase {
             file_name: self.file_name,
             git: self.git,
             remote: self.remote,
-            env: Some((key, value)),
+            env,
             expected_tags: self.expected_tags,
+            normalize_paths: self.normalize_paths,
         }
     }
 
@@ -212,5 +238,11 @@ pub struct Asserts {
 #[derive(Clone, Copy, Debug)]
 pub enum GitCommand {
     Commit(&'static str),
+    /// Write some files (relative to the working directory), then commit them
+    CommitFiles(&'static str, &'static [&'static str]),
     Tag(&'static str),
+    /// Add a remote with a name and URL
+    Remote(&'static str, &'static str),
+    /// Pretend the repo is a shallow clone, starting at the current commit
+    Shallow,
 }
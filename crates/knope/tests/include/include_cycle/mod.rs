@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Config files which include each other are an error instead of being loaded forever
+#[test]
+fn include_cycle() {
+    TestCase::new(file!()).run("release");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// A helpful error when a file in `include` doesn't exist
+#[test]
+fn missing_include() {
+    TestCase::new(file!()).run("release");
+}
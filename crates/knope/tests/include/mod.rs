@@ -0,0 +1,3 @@
+mod extends;
+mod include_cycle;
+mod missing_include;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Create a Jira issue for a release, then comment on it.
+#[test]
+fn create_and_comment_dry_run() {
+    TestCase::new(file!()).run("record-release --dry-run");
+}
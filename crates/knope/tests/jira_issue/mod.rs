@@ -0,0 +1,2 @@
+mod create_and_comment;
+mod no_issue_selected;
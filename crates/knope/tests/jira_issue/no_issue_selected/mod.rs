@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// `CommentOnJiraIssue` needs an issue from an earlier step.
+#[test]
+fn no_issue_selected() {
+    TestCase::new(file!()).run("comment --dry-run");
+}
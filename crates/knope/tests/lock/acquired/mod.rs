@@ -0,0 +1,10 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// A workflow with `lock` holds a ref on the remote while its steps run
+#[test]
+fn acquired() {
+    TestCase::new(file!())
+        .git(&[Commit("Initial commit")])
+        .with_remote(".")
+        .run("release");
+}
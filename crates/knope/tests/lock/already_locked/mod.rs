@@ -0,0 +1,19 @@
+use snapbox::cmd::Command;
+
+use crate::helpers::{commit, TestCase};
+
+/// A workflow fails if another run already holds its lock
+#[test]
+fn already_locked() {
+    let test = TestCase::new(file!()).with_remote(".");
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+    commit(temp_path, "Initial commit");
+    Command::new("git")
+        .args(["update-ref", "refs/knope/lock/release", "HEAD"])
+        .current_dir(temp_path)
+        .assert()
+        .success();
+
+    test.assert(test.act(temp_dir, "release"));
+}
@@ -0,0 +1,5 @@
+mod acquired;
+mod already_locked;
+mod no_remote;
+mod released_on_failure;
+mod validate_without_remote;
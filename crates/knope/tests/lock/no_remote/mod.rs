@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// A workflow can't be locked without a remote to hold the lock
+#[test]
+fn no_remote() {
+    TestCase::new(file!())
+        .git(&[Commit("Initial commit")])
+        .run("release");
+}
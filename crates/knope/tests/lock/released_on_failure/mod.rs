@@ -0,0 +1,21 @@
+use snapbox::cmd::{cargo_bin, Command};
+
+use crate::helpers::{commit, tag, TestCase};
+
+/// A failed step still releases the lock, so the workflow can be locked again by `--resume`
+#[test]
+fn released_on_failure() {
+    let test = TestCase::new(file!()).with_remote(".");
+    let temp_dir = test.arrange();
+    let temp_path = temp_dir.path();
+    commit(temp_path, "Initial commit");
+
+    Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_path)
+        .assert()
+        .failure();
+    tag(temp_path, "fixed");
+
+    test.assert(test.act(temp_dir, "release --resume"));
+}
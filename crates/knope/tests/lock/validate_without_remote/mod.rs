@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// Validating doesn't lock workflows, so it works without a remote
+#[test]
+fn validate_without_remote() {
+    TestCase::new(file!())
+        .git(&[Commit("Initial commit")])
+        .run("--validate");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// `--log-format json` prints each log as a line of JSON, along with how long each span took
+#[test]
+fn json() {
+    TestCase::new(file!()).run("hello --log-format json");
+}
@@ -1,14 +1,52 @@
 #![allow(clippy::unwrap_used)]
+mod bump_homebrew_formula;
 mod bump_version;
+mod close_milestone;
 mod command;
+mod commit;
+mod config_formats;
+mod config_path;
+mod create_branch;
+mod create_pull_request;
+mod cwd;
 mod default_workflows;
+mod delete_release;
+mod deprecation;
+mod dry_run_quiet;
+mod explain;
+mod format_json;
 mod generate;
+mod get_version;
 mod git_release;
 mod gitea_release;
+mod github_actions;
 mod github_release;
 mod helpers;
+mod include;
+mod jira_issue;
+mod lock;
+mod log_format;
+mod merge_pull_request;
 mod multi_forge_release;
 mod no_config;
+mod notify;
+mod on_error;
+mod plan;
 mod prepare_release;
+mod prompt;
+mod publish_cargo;
+mod publish_docker;
+mod publish_npm;
+mod publish_release;
+mod push;
+mod resume;
+mod run_workflow;
+mod signing;
+mod status;
 mod upgrade;
 mod validate;
+mod validate_commits;
+mod wait_for_checks;
+mod windows_package;
+mod workflow_conditions;
+mod workflow_packages;
@@ -0,0 +1,8 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+#[test]
+fn merge_pull_request_dry_run() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: New feature")])
+        .run("merge --dry-run");
+}
@@ -0,0 +1,2 @@
+mod gitea;
+mod github;
@@ -0,0 +1,3 @@
+mod discord;
+mod no_releases;
+mod teams;
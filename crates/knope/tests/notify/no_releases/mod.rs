@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// There's nothing to post without `PrepareRelease` or `Release` first.
+#[test]
+fn no_releases() {
+    TestCase::new(file!()).run("notify --dry-run");
+}
@@ -0,0 +1,10 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// When a step fails, `on_error` steps clean up what earlier steps did.
+#[test]
+fn cleanup() {
+    TestCase::new(file!())
+        .git(&[Commit("Initial commit")])
+        .expected_tags(&[])
+        .run("release");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Errors from `on_error` steps are reported along with the original error.
+#[test]
+fn cleanup_fails() {
+    TestCase::new(file!()).run("release");
+}
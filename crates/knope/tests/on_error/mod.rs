@@ -0,0 +1,2 @@
+mod cleanup;
+mod cleanup_fails;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `knope apply` does everything in a saved plan, exactly as it was saved
+#[test]
+fn apply() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&["v1.1.0"])
+        .run("apply plan.json");
+}
@@ -0,0 +1,4 @@
+mod apply;
+mod recursion;
+mod save;
+mod unsupported_step;
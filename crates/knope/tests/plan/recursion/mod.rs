@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Workflows which run each other are reported instead of being checked forever
+#[test]
+fn recursion() {
+    TestCase::new(file!()).run("release --plan plan.json");
+}
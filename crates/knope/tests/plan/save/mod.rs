@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `--plan` saves what the workflow would do without changing any files or creating tags
+#[test]
+fn save() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&[])
+        .run("release --plan plan.json");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Steps which need input while running can't be saved in a plan
+#[test]
+fn unsupported_step() {
+    TestCase::new(file!()).run("release --plan plan.json");
+}
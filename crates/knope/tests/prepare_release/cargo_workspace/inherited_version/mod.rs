@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `first-package` uses `version.workspace = true`, so its version is in the root `Cargo.toml`.
+/// `second-package` opts out with its own version.
+#[test]
+fn test_cargo_workspace_inherited_version() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first-package/v1.0.0"),
+            Tag("second-package/v0.1.0"),
+            Commit("feat(first-package): A feature"),
+            Commit("fix(second-package): A fix"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Packages which inherit their version from the same workspace root can share it when they're in
+/// the same `version_group`.
+#[test]
+fn inherited_version_group() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.0.0"),
+            Tag("second/v1.0.0"),
+            Commit("feat(first): A feature"),
+            Commit("fix(second): A fix"),
+        ])
+        .run("release");
+}
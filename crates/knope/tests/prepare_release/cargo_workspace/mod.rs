@@ -1,2 +1,7 @@
 mod full;
+mod inherited_version;
+mod inherited_version_group;
 mod no_lock;
+mod path_dependencies;
+mod shared_inherited_version;
+mod shared_lockfile;
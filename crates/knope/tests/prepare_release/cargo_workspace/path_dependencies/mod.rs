@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Dependencies on other workspace members by `path` are updated without being listed in
+/// `versioned_files`.
+#[test]
+fn test_cargo_workspace_path_dependencies() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first-package/v1.0.0"),
+            Tag("second-package/v0.1.0"),
+            Commit("feat(first-package): A feature"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Both packages inherit their version from the workspace root, so they'd each write a different
+/// version to it.
+#[test]
+fn shared_inherited_version() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.0.0"),
+            Tag("second/v1.0.0"),
+            Commit("feat(first): A feature"),
+            Commit("fix(second): A fix"),
+        ])
+        .run("release");
+}
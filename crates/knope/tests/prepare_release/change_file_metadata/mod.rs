@@ -0,0 +1,12 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `metadata.` keys in a change file's front matter are kept with the change
+#[test]
+fn change_file_metadata() {
+    TestCase::new(file!())
+        .git(&[Commit("Initial commit"), Tag("v1.0.0")])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Conventional commit types listed in `types` get their own section and a patch bump
+#[test]
+fn commit_types() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("perf: Faster"),
+            Commit("docs: Not in the changelog"),
+        ])
+        .run("release");
+}
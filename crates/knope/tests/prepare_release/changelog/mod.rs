@@ -1,4 +1,7 @@
+mod commit_types;
 mod create_missing;
 mod extra_changelog_sections;
 mod header_level_detection;
+mod multiple;
 mod override_default_sections;
+mod section_order;
@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Extra sections can go between built-in sections by listing the built-in sections by name
+#[test]
+fn section_order() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("chore: something\n\nChangelog-Note: A note"),
+            Commit("fix: A fix"),
+            Commit("feat: A feature"),
+            Commit("chore: something\n\nSecurity: A security fix"),
+            Commit("feat!: A breaking change"),
+        ])
+        .run("release");
+}
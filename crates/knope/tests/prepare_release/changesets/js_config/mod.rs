@@ -0,0 +1,9 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// Linked packages, ignored packages, and disabled changelogs from `.changeset/config.json`
+#[test]
+fn js_config() {
+    TestCase::new(file!())
+        .git(&[Commit("chore: Initial commit")])
+        .run("release");
+}
@@ -1,3 +1,4 @@
 mod happy;
 mod invalid_change_file;
+mod js_config;
 mod prerelease;
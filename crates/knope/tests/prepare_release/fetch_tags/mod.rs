@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Shallow, Tag},
+    TestCase,
+};
+
+/// With `fetch_tags`, a shallow clone is fetched before anything else, which fails without a remote.
+#[test]
+fn fetch_tags() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.2.3"),
+            Commit("fix: New fix"),
+            Shallow,
+        ])
+        .run("release");
+}
@@ -0,0 +1,21 @@
+use crate::helpers::{
+    GitCommand::{Commit, CommitFiles, Tag},
+    TestCase,
+};
+
+/// Only commits which change a package's files apply to that package
+#[test]
+fn filter_commits_by_path() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("first/v1.0.0"),
+            Tag("second/v1.0.0"),
+            CommitFiles("feat: A first feature", &["first/src/lib.rs"]),
+            CommitFiles("fix: A second fix", &["second/src/lib.rs"]),
+            CommitFiles("fix: A shared fix", &["shared/notes.txt"]),
+            CommitFiles("feat: Both packages", &["first/a.rs", "second/b.rs"]),
+            Commit("feat!: No files changed"),
+        ])
+        .run("prepare-release");
+}
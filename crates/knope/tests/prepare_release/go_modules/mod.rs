@@ -3,3 +3,4 @@ mod major_version_directories;
 mod major_versions;
 mod subdirectories;
 mod version_determination;
+mod workspace;
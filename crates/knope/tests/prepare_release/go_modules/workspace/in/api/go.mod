@@ -0,0 +1,3 @@
+module github.com/knope-dev/example/api
+
+go 1.22
@@ -0,0 +1,3 @@
+module github.com/knope-dev/example/tools/cli // v0.2.0
+
+go 1.22
@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Each module in `go.work` is its own package, tagged the way Go expects
+#[test]
+fn workspace() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("api/v1.0.0"),
+            Tag("tools/cli/v0.2.0"),
+            Commit("feat(api): New feature"),
+            Commit("fix(tools/cli): A fix"),
+        ])
+        .expected_tags(&["api/v1.1.0", "tools/cli/v0.2.1"])
+        .run("release");
+}
@@ -0,0 +1,3 @@
+module github.com/knope-dev/example/api // v1.1.0
+
+go 1.22
@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Commits by any of `ignore_authors` aren't changes
+#[test]
+fn authors() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing"),
+            Tag("v1.0.0"),
+            Commit("feat!: Ignored because of the author"),
+        ])
+        .run("prepare-release");
+}
@@ -0,0 +1,15 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Commits matching any of `ignore_commit_patterns` aren't changes
+#[test]
+fn patterns() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing"),
+            Tag("v1.0.0"),
+            Commit("fix(deps): Update a dependency"),
+            Commit("feat: WIP new thing"),
+            Commit("fix: A real fix"),
+        ])
+        .run("prepare-release");
+}
@@ -1,11 +1,17 @@
 mod allow_empty;
 mod branching_history;
 mod cargo_workspace;
+mod change_file_metadata;
+mod change_templates;
 mod changelog;
 mod changesets;
+mod custom_file;
 mod enable_prerelease;
+mod fetch_tags;
+mod filter_commits_by_path;
 mod go_modules;
 mod handle_pre_versions_that_are_too_new;
+mod ignore_commits;
 mod ignore_conventional_commits;
 mod inconsistent_versions;
 mod invalid_versioned_files;
@@ -13,15 +19,32 @@ mod missing_versioned_files;
 mod multiple_packages;
 mod no_version_change;
 mod no_versioned_files;
+mod npm_workspaces;
 mod override_prerelease_label;
+mod override_rule;
 mod override_version;
 mod override_version_multiple_packages;
+mod package_discovery;
 mod package_selection;
+mod parallel;
+mod pkgbuild;
+mod pnpm_and_yarn_lockfiles;
 mod prerelease_after_release;
 mod pubspec_yaml;
 mod pyproject_toml;
+mod regex_file;
 mod release_after_prerelease;
+mod release_json;
+mod release_notes_template;
+mod release_title;
 mod scopes;
 mod second_prerelease;
+mod select_packages;
+mod shallow_clone;
+mod snapcraft_yaml;
+mod split_squash_merges;
 mod unknown_versioned_file_format;
+mod unreleased;
 mod verbose;
+mod version_group;
+mod zero_major_breaking;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn test() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("monorepo/v1.0.0"),
+            Tag("first/v0.1.0"),
+            Commit("feat(monorepo): New feature"),
+            Commit("feat(first)!: Breaking change"),
+        ])
+        .run("release");
+}
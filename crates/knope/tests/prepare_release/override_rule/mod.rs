@@ -0,0 +1,22 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+const TEST_CASE: TestCase = TestCase::new(file!()).git(&[
+    Commit("Initial commit"),
+    Tag("v1.1.0"),
+    Commit("fix: A fix which is actually a big deal"),
+]);
+
+/// Force a major bump with `--rule`, even though the commits only imply a patch.
+#[test]
+fn with_option() {
+    TEST_CASE.run("release --rule=major");
+}
+
+/// Force a major bump with the `KNOPE_RULE` environment variable.
+#[test]
+fn with_env() {
+    TEST_CASE.env(&[("KNOPE_RULE", "major")]).run("release");
+}
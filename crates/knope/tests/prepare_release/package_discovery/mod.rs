@@ -0,0 +1,19 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Find packages with `packages.discovery.globs`, naming each after its directory. Packages which
+/// are also defined in `[packages]` use their definition instead.
+#[test]
+fn package_discovery() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.0.0"),
+            Tag("second/v0.1.0"),
+            Tag("third/v2.0.0"),
+            Commit("feat: A new feature"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Finding changes in parallel gives the same results as one package at a time.
+#[test]
+fn parallel() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("first/v1.2.3"),
+            Tag("second/v0.4.6"),
+            Commit("feat!: New breaking feature"),
+        ])
+        .run("release");
+}
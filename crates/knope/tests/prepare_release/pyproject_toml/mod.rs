@@ -1,3 +1,5 @@
 mod mixed;
+mod pep440;
 mod pep621;
 mod poetry;
+mod uv_lock;
@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Pre-releases are written to `pyproject.toml` in PEP 440 form, but tags are still Semantic Versions
+#[test]
+fn prepare_release_pep440() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.1.0"),
+            Tag("v1.2.0-rc.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&["v1.2.0-rc.1"])
+        .run("release");
+}
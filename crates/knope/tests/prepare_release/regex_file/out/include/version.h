@@ -0,0 +1,4 @@
+#pragma once
+
+#define NEWTIFY_VERSION "2.0.0"
+#define NEWTIFY_ABI_VERSION "3.0.0"
@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// With `require_scope = "ignore"`, unscoped commits don't apply to any package
+#[test]
+fn ignore_unscoped_commits() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("fix(first): Fix for first only"),
+            Commit("feat: No-scope feat"),
+            Commit("feat(second)!: Breaking change for second only"),
+        ])
+        .run("release");
+}
@@ -1,4 +1,7 @@
+mod ignore_unscoped_commits;
 mod no_scopes;
+mod patterns;
+mod require_scope;
 mod shared_commits;
 mod skip_unchanged_packages;
 mod unscoped_commits;
@@ -0,0 +1,16 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// Scopes can be glob patterns or regexes, and a commit applies to every package with a
+/// matching scope
+#[test]
+fn patterns() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat(api-users): Glob matched feature"),
+            Commit("fix(cli-v2): Regex matched fix"),
+            Commit("fix(cli-next): Not matched"),
+            Commit("fix(api-internal): Matched by both"),
+            Commit("feat(both): Shared feature"),
+        ])
+        .run("release");
+}
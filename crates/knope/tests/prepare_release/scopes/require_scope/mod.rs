@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+/// With `require_scope = true`, unscoped commits are an error instead of applying to every package
+#[test]
+fn require_scope() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("fix(first): Fix for first only"),
+            Commit("feat: No-scope feat"),
+            Commit("feat(second)!: Breaking change for second only"),
+        ])
+        .run("release");
+}
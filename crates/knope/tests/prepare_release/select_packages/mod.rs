@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Only release the packages selected with `--package`, leaving the rest alone.
+#[test]
+fn select_packages() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("first/v0.1.0"),
+            Tag("second/v1.2.3"),
+            Tag("third/v4.5.5"),
+            Commit("fix: A bug fix"),
+        ])
+        .run("release --package=first --package=third");
+}
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Shallow, Tag},
+    TestCase,
+};
+
+/// The last tag isn't in a shallow clone, so every commit would look new.
+#[test]
+fn shallow_clone() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.2.3"),
+            Commit("fix: New fix"),
+            Shallow,
+        ])
+        .run("release");
+}
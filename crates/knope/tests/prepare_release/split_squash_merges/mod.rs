@@ -0,0 +1,16 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Each bulleted conventional commit in a squash-merge body is a separate change
+#[test]
+fn split_squash_merges() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing"),
+            Tag("v1.0.0"),
+            Commit(
+                "Combined PR (#2)\n\n* feat: A feature\n\n* fix: A fix\n\n* chore: Not a change",
+            ),
+            Commit("fix: A regular fix"),
+        ])
+        .run("prepare-release");
+}
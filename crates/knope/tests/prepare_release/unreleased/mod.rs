@@ -0,0 +1,2 @@
+mod release;
+mod write_section;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// A regular release replaces the "Unreleased" section with the new version.
+#[test]
+fn release() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: Old feature"),
+            Commit("feat: New feature"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,18 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// The "Unreleased" section is regenerated from every change since the last release, without
+/// bumping the version or consuming changesets.
+#[test]
+fn write_section() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: Old feature"),
+            Commit("feat: New feature"),
+        ])
+        .run("unreleased");
+}
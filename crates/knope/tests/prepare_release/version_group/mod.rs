@@ -0,0 +1,20 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Packages in the same `version_group` are all released with the largest version any of them
+/// would get from the changes, even if some of them have no changes. Other packages are unaffected.
+#[test]
+fn version_group() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.0.0"),
+            Tag("second/v1.2.0"),
+            Tag("third/v0.3.0"),
+            Commit("feat(first): A new feature"),
+            Commit("fix(third): A fix"),
+        ])
+        .run("release");
+}
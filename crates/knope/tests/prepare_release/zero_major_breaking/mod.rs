@@ -0,0 +1,15 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn breaking_change_releases_1_0_0() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v0.3.1"),
+            Commit("feat!: A breaking feature"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Prompts are skipped in dry runs, and fail without a terminal to ask in.
+#[test]
+fn prompt() {
+    TestCase::new(file!()).run("deploy");
+}
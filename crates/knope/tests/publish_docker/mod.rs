@@ -0,0 +1,2 @@
+mod tags;
+mod too_many_releases;
@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// When more than one package is released, `package` must say which to build.
+#[test]
+fn too_many_releases() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("first/v1.0.0"),
+            Tag("second/v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .run("release --dry-run");
+}
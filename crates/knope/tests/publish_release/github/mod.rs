@@ -0,0 +1,11 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+#[test]
+fn publish_release_dry_run() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: New feature"), Tag("v1.1.0")])
+        .run("publish --dry-run");
+}
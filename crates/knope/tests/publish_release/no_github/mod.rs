@@ -0,0 +1,12 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `PublishRelease` only works with GitHub, so it must be configured.
+#[test]
+fn github_not_configured() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: New feature"), Tag("v1.1.0")])
+        .run("publish");
+}
@@ -0,0 +1,2 @@
+mod no_remote;
+mod remote;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Dry runs only print the push, real runs fail without anywhere to push to.
+#[test]
+fn no_remote() {
+    TestCase::new(file!()).run("push");
+}
@@ -0,0 +1,12 @@
+use crate::helpers::{GitCommand::Remote, TestCase};
+
+/// The `remote` from the forge config is pushed to instead of the upstream of the branch.
+#[test]
+fn remote() {
+    TestCase::new(file!())
+        .git(&[
+            Remote("origin", "https://github.com/someone/knope.git"),
+            Remote("upstream", "https://github.com/knope-dev/knope.git"),
+        ])
+        .run("push --dry-run");
+}
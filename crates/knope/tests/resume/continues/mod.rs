@@ -0,0 +1,24 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// `--resume` retries the step that failed, without redoing the ones that finished before it
+#[test]
+fn continues() {
+    let test = TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&["v1.1.0"]);
+    let working_dir = test.arrange();
+    let knope_dir = working_dir.path().join(".knope");
+    let asserts = test.act(working_dir, "release --resume");
+    assert!(
+        !knope_dir.exists(),
+        "The saved progress should be removed once the workflow succeeds"
+    );
+    test.assert(asserts);
+}
@@ -0,0 +1,4 @@
+mod continues;
+mod nothing_saved_on_success;
+mod nothing_to_resume;
+mod saves_progress;
@@ -0,0 +1,24 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// Progress is only saved when a step fails, so a successful run leaves no `.knope` directory
+#[test]
+fn nothing_saved_on_success() {
+    let test = TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&["v1.1.0"]);
+    let working_dir = test.arrange();
+    let knope_dir = working_dir.path().join(".knope");
+    let asserts = test.act(working_dir, "release");
+    assert!(
+        !knope_dir.exists(),
+        "Nothing should be saved when no step fails"
+    );
+    test.assert(asserts);
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// `--resume` fails if no earlier run saved any progress
+#[test]
+fn nothing_to_resume() {
+    TestCase::new(file!()).run("release --resume");
+}
@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn nested() {
+    TestCase::new(file!()).run("release");
+}
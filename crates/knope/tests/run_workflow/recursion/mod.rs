@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn recursion() {
+    TestCase::new(file!()).run("--validate");
+}
@@ -0,0 +1,15 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Tags and commits are signed with the configured SSH key, which Git can verify.
+#[cfg(not(windows))]
+#[test]
+fn ssh() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("feat: New feature"),
+        ])
+        .expected_tags(&["v1.1.0"])
+        .run("release");
+}
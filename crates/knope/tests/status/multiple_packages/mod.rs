@@ -0,0 +1,14 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Each package reports its own pending changes, and nothing is modified
+#[test]
+fn multiple_packages() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.2.3"),
+            Tag("second/v0.4.6"),
+            Commit("feat(first): A feature"),
+        ])
+        .run("status");
+}
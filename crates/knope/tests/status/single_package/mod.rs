@@ -0,0 +1,14 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// The built-in command works without a `knope.toml` file
+#[test]
+fn single_package() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("v0.1.0"),
+            Commit("fix: A fix"),
+            Commit("feat!: A breaking feature"),
+        ])
+        .run("status");
+}
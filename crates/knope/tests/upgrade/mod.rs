@@ -1 +1,3 @@
 mod nothing;
+mod packages_array;
+mod variables;
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// A single package in the old `[[packages]]` array becomes `[package]`, keeping comments
+#[test]
+fn packages_array() {
+    TestCase::new(file!()).run("--upgrade");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// Old spellings of variables are replaced with the current ones
+#[test]
+fn variables() {
+    TestCase::new(file!()).run("--upgrade");
+}
@@ -0,0 +1,9 @@
+use crate::helpers::TestCase;
+
+/// `--check-remote` reports a missing token instead of prompting for one.
+#[test]
+fn check_remote_without_token() {
+    TestCase::new(file!())
+        .env(&[("GITHUB_TOKEN", "")])
+        .run("--validate --check-remote");
+}
@@ -0,0 +1,7 @@
+use crate::helpers::TestCase;
+
+/// The Jira project can't be checked without credentials, and validation shouldn't prompt for them.
+#[test]
+fn jira_without_credentials() {
+    TestCase::new(file!()).run("--validate");
+}
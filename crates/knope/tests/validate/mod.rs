@@ -1,3 +1,5 @@
+mod check_remote_without_token;
+mod jira_without_credentials;
 mod kitchen_sink;
 mod multiple_package_formats;
 mod unsupported_dependency;
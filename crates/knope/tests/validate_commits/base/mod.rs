@@ -0,0 +1,14 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Only commits after `base` are checked
+#[test]
+fn base() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Not conventional, but before base"),
+            Commit("Also before base"),
+            Commit("feat: A feature"),
+            Commit("fix: A fix"),
+        ])
+        .run("validate");
+}
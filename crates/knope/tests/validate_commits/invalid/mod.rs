@@ -0,0 +1,15 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Every invalid commit is reported
+#[test]
+fn invalid() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat: Existing feature"),
+            Tag("v1.0.0"),
+            Commit("Not conventional"),
+            Commit("feat: A feature"),
+            Commit("WIP"),
+        ])
+        .run("validate");
+}
@@ -0,0 +1,4 @@
+mod base;
+mod invalid;
+mod unscoped;
+mod valid;
@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// If any package has `require_scope = true`, every conventional commit needs a scope
+#[test]
+fn unscoped() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("feat(first): A scoped feature"),
+            Commit("Merge branch 'fix' into main"),
+            Commit("fix: An unscoped fix"),
+        ])
+        .run("validate");
+}
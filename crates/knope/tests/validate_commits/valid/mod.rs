@@ -0,0 +1,15 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+/// Only commits since the last release are checked
+#[test]
+fn valid() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Not conventional, but already released"),
+            Tag("v1.0.0"),
+            Commit("feat: A feature"),
+            Commit("Merge branch 'fix' into main"),
+            Commit("fix(scope)!: A breaking fix"),
+        ])
+        .run("validate");
+}
@@ -0,0 +1,8 @@
+use crate::helpers::{GitCommand::Commit, TestCase};
+
+#[test]
+fn wait_for_checks_dry_run() {
+    TestCase::new(file!())
+        .git(&[Commit("feat: New feature")])
+        .run("wait --dry-run");
+}
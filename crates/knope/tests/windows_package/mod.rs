@@ -0,0 +1,3 @@
+mod scoop;
+mod too_many_assets;
+mod winget;
@@ -0,0 +1,8 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn env() {
+    TestCase::new(file!())
+        .env(&[("KNOPE_CONDITION_CI", "true")])
+        .run("check");
+}
@@ -0,0 +1,2 @@
+mod env;
+mod package_released;
@@ -0,0 +1,13 @@
+use crate::helpers::{GitCommand::*, TestCase};
+
+#[test]
+fn package_released() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v1.2.3"),
+            Tag("second/v0.4.6"),
+            Commit("feat(first): A new feature"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,2 @@
+mod step;
+mod unknown_package;
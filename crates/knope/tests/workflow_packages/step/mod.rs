@@ -0,0 +1,17 @@
+use crate::helpers::{
+    GitCommand::{Commit, Tag},
+    TestCase,
+};
+
+/// A step with `packages` only sees those packages, so it can use `Version` in a multi-package repo.
+#[test]
+fn step() {
+    TestCase::new(file!())
+        .git(&[
+            Commit("Initial commit"),
+            Tag("first/v0.1.0"),
+            Tag("second/v4.5.5"),
+            Commit("fix: A bug fix"),
+        ])
+        .run("release");
+}
@@ -0,0 +1,6 @@
+use crate::helpers::TestCase;
+
+#[test]
+fn unknown_package() {
+    TestCase::new(file!()).run("--validate");
+}